@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use subtle::ConstantTimeEq;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::Settings;
+use crate::service::WorkerService;
+use crate::types::BackfillRequest;
+
+/// Admin routes answer 401 to every call until `ADMIN_API_TOKEN` is set.
+pub fn router(service: Arc<WorkerService>) -> Router {
+    if service.settings().admin_api_token.is_none() {
+        warn!("ADMIN_API_TOKEN is not set; admin routes are disabled");
+    }
+    Router::new()
+        .route("/admin/backfill", post(start_backfill))
+        .route("/admin/reload", post(reload_config))
+        .route("/admin/queues/:brand/peek", get(peek_queue))
+        .route("/status", get(worker_status))
+        .with_state(service)
+}
+
+async fn start_backfill(
+    State(service): State<Arc<WorkerService>>,
+    headers: HeaderMap,
+    Json(request): Json<BackfillRequest>,
+) -> Response {
+    if !authorized(&service, &headers) {
+        return unauthorized();
+    }
+    if request.brand.trim().is_empty() || request.from > request.to {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "brand is required and from must not be after to" })),
+        )
+            .into_response();
+    }
+
+    let Some(slot) = service.try_begin_backfill(&request.brand) else {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({
+                "error": "a backfill for this brand is already running or BACKFILL_MAX_CONCURRENT jobs are running",
+            })),
+        )
+            .into_response();
+    };
+
+    let job_id = Uuid::new_v4().to_string();
+    info!(job_id, brand = %request.brand, from = %request.from, to = %request.to, "Backfill requested");
+
+    let task_service = service.clone();
+    let task_job_id = job_id.clone();
+    tokio::spawn(async move {
+        let _slot = slot;
+        if let Err(err) = task_service.backfill(&request).await {
+            warn!(job_id = %task_job_id, error = %err, "Backfill job failed");
+        }
+    });
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "status": "accepted",
+            "jobId": job_id,
+            "resultPrefix": service.settings().backfill_result_prefix,
+        })),
+    )
+        .into_response()
+}
+
+async fn reload_config(State(service): State<Arc<WorkerService>>, headers: HeaderMap) -> Response {
+    if !authorized(&service, &headers) {
+        return unauthorized();
+    }
+    let outcome = Settings::reload_from_env(service.settings())
+        .map_err(|err| err.to_string())
+        .and_then(|settings| service.reload(settings).map_err(|err| err.to_string()));
+    match outcome {
+        Ok(generation) => (StatusCode::OK, Json(json!({ "status": "reloaded", "generation": generation }))).into_response(),
+        Err(err) => {
+            warn!(error = %err, "Config reload failed; keeping current adapters");
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({ "error": err }))).into_response()
+        }
+    }
+}
+
+/// Queue-loop state and resource pressure. Holds no customer data, so like `/health` it
+/// needs no token.
+async fn worker_status(State(service): State<Arc<WorkerService>>) -> Response {
+    let status = service.status().await;
+    let pressure = service.throttle().last_sample();
+    Json(json!({
+        "workerId": status.worker_id,
+        "paused": status.paused,
+        "throttled": status.throttled.is_some(),
+        "throttleReason": status.throttled,
+        "waitingSeconds": status.waiting_seconds,
+        "processedTotal": status.processed_total,
+        "pressure": pressure,
+    }))
+    .into_response()
+}
+
+const DEFAULT_PEEK_COUNT: usize = 10;
+const MAX_PEEK_COUNT: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct PeekQuery {
+    count: Option<usize>,
+}
+
+async fn peek_queue(
+    State(service): State<Arc<WorkerService>>,
+    headers: HeaderMap,
+    Path(brand): Path<String>,
+    Query(query): Query<PeekQuery>,
+) -> Response {
+    if !authorized(&service, &headers) {
+        return unauthorized();
+    }
+    let count = query.count.unwrap_or(DEFAULT_PEEK_COUNT).clamp(1, MAX_PEEK_COUNT);
+    match service.peek_queue(&brand, count).await {
+        Ok(payloads) => {
+            let invalid = payloads.iter().filter(|payload| !payload.valid).count();
+            (
+                StatusCode::OK,
+                Json(json!({ "brand": brand, "count": payloads.len(), "invalid": invalid, "payloads": payloads })),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            warn!(brand, error = %err, "Queue peek failed");
+            (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "error": err.to_string() }))).into_response()
+        }
+    }
+}
+
+fn authorized(service: &WorkerService, headers: &HeaderMap) -> bool {
+    let Some(expected) = service.settings().admin_api_token.as_deref() else {
+        return false;
+    };
+    headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| bearer_matches(value, expected))
+}
+
+/// Whether an `authorization` value is `Bearer <expected>`, compared in constant time.
+pub(crate) fn bearer_matches(value: &str, expected: &str) -> bool {
+    value
+        .strip_prefix("Bearer ")
+        .is_some_and(|token| bool::from(token.as_bytes().ct_eq(expected.as_bytes())))
+}
+
+fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, Json(json!({ "error": "unauthorized" }))).into_response()
+}
@@ -0,0 +1,499 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::http::HttpClient;
+use crate::metrics::WORKER_ALERTS_SENT_TOTAL;
+use crate::redis_client::RedisClient;
+use crate::types::{ChunkResult, ClusterResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn for_cluster(cluster: &ClusterResult) -> Self {
+        // Spikes inside a registered event window are expected, not incidents.
+        if cluster.known_event.as_ref().is_some_and(|event| event.downgrade) {
+            return Self::Low;
+        }
+        let score = cluster.sentiment_score();
+        match (cluster.spike, score) {
+            (true, score) if score <= -0.5 => Self::Critical,
+            (true, score) if score < 0.0 => Self::High,
+            (true, _) => Self::Medium,
+            (false, score) if score <= -0.5 => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(default)]
+    pub when: AlertConditions,
+    pub destinations: Vec<AlertDestination>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertConditions {
+    #[serde(default)]
+    pub brands: Vec<String>,
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+    #[serde(default)]
+    pub sentiment_below: Option<f32>,
+    #[serde(default)]
+    pub summary_keywords: Vec<String>,
+    #[serde(default)]
+    pub spike_only: bool,
+    /// Makes the rule brand-level: it is checked once per result instead of per cluster,
+    /// and the cluster conditions above apart from `brands` do not apply.
+    #[serde(default)]
+    pub sentiment_drop: Option<SentimentDropCondition>,
+}
+
+/// `{ "amount": 0.3, "windowSec": 3600 }` fires when a result's sentiment score sits at
+/// least `amount` below the highest score pushed for the brand in the last `windowSec`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentimentDropCondition {
+    pub amount: f32,
+    #[serde(default = "default_drop_window_sec")]
+    pub window_sec: u64,
+}
+
+fn default_drop_window_sec() -> u64 {
+    3600
+}
+
+impl AlertConditions {
+    fn matches_brand(&self, brand: &str) -> bool {
+        self.brands.is_empty() || self.brands.iter().any(|item| item == "*" || item.eq_ignore_ascii_case(brand))
+    }
+
+    fn matches(&self, brand: &str, cluster: &ClusterResult, severity: Severity) -> bool {
+        if self.sentiment_drop.is_some() || !self.matches_brand(brand) {
+            return false;
+        }
+        if self.spike_only && !cluster.spike {
+            return false;
+        }
+        if self.min_severity.is_some_and(|min| severity < min) {
+            return false;
+        }
+        if self
+            .sentiment_below
+            .is_some_and(|threshold| cluster.sentiment_score() >= threshold)
+        {
+            return false;
+        }
+        if !self.summary_keywords.is_empty() {
+            let summary = cluster.summary.as_deref().unwrap_or_default().to_lowercase();
+            if !self
+                .summary_keywords
+                .iter()
+                .any(|keyword| summary.contains(&keyword.to_lowercase()))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AlertDestination {
+    Webhook {
+        url: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Slack {
+        webhook_url: String,
+        #[serde(default)]
+        channel: Option<String>,
+        #[serde(default)]
+        format: SlackFormat,
+    },
+    Redis {
+        channel: String,
+    },
+}
+
+impl AlertDestination {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Webhook { .. } => "webhook",
+            Self::Slack { .. } => "slack",
+            Self::Redis { .. } => "redis",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlackFormat {
+    #[default]
+    Text,
+    Blocks,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEvent {
+    pub rule: String,
+    pub worker_id: String,
+    pub brand: String,
+    pub chunk_id: String,
+    pub cluster_id: i32,
+    pub severity: Severity,
+    pub sentiment_score: f32,
+    pub spike: bool,
+    pub mention_count: usize,
+    pub summary: Option<String>,
+    pub examples: Vec<String>,
+    pub known_event: Option<String>,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentimentDropEvent {
+    pub rule: String,
+    pub worker_id: String,
+    pub brand: String,
+    pub chunk_id: String,
+    pub sentiment_score: f32,
+    /// Score of the brand's previous result push.
+    pub previous_score: f32,
+    /// Highest score pushed in the window; `drop` is measured from here.
+    pub peak_score: f32,
+    pub drop: f32,
+    pub window_sec: u64,
+    pub samples: usize,
+    pub timestamp: String,
+}
+
+pub struct AlertRouter {
+    rules: Vec<AlertRule>,
+    redis: RedisClient,
+    http: HttpClient,
+    settings: Arc<Settings>,
+}
+
+impl AlertRouter {
+    pub fn from_settings(settings: Arc<Settings>, redis: RedisClient, http: HttpClient) -> anyhow::Result<Self> {
+        let rules = match &settings.alert_rules_file {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path).with_context(|| format!("read alert rules from {path}"))?;
+                serde_json::from_str::<Vec<AlertRule>>(&raw).with_context(|| format!("parse alert rules in {path}"))?
+            }
+            None => Vec::new(),
+        };
+        if !rules.is_empty() {
+            info!(worker_id = %settings.worker_id, rules = rules.len(), "Alert routing rules loaded");
+        }
+        Ok(Self {
+            rules,
+            redis,
+            http,
+            settings,
+        })
+    }
+
+    pub async fn evaluate(&self, result: &ChunkResult) {
+        if self.rules.is_empty() {
+            return;
+        }
+        if let Err(err) = self.evaluate_sentiment_drop(result).await {
+            warn!(brand = %result.brand, chunk_id = %result.chunk_id, error = %err, "Failed to evaluate sentiment drop");
+        }
+        for cluster in &result.clusters {
+            let severity = Severity::for_cluster(cluster);
+            for rule in &self.rules {
+                if !rule.when.matches(&result.brand, cluster, severity) {
+                    continue;
+                }
+                let event = AlertEvent {
+                    rule: rule.name.clone(),
+                    worker_id: self.settings.worker_id.clone(),
+                    brand: result.brand.clone(),
+                    chunk_id: result.chunk_id.clone(),
+                    cluster_id: cluster.cluster_id,
+                    severity,
+                    sentiment_score: cluster.sentiment_score(),
+                    spike: cluster.spike,
+                    mention_count: cluster.count,
+                    summary: cluster.summary.clone(),
+                    examples: cluster.examples.clone(),
+                    known_event: cluster.known_event.as_ref().map(|event| event.name.clone()),
+                    timestamp: Utc::now().to_rfc3339(),
+                };
+                let payload = match serde_json::to_value(&event) {
+                    Ok(payload) => payload,
+                    Err(err) => {
+                        warn!(rule = %rule.name, brand = %event.brand, error = %err, "Failed to serialise alert event");
+                        continue;
+                    }
+                };
+                let blocks = slack_blocks(&event, self.settings.dashboard_base_url.as_deref());
+                self.deliver(&rule.name, &rule.destinations, &payload, &slack_text(&event), Some(&blocks))
+                    .await;
+            }
+        }
+    }
+
+    /// Keeps each brand's scores by push time at `{REDIS_TREND_PREFIX}:{brand}:pushes`, so a
+    /// sharp drop alerts even when mention volume stays flat and no cluster spikes.
+    async fn evaluate_sentiment_drop(&self, result: &ChunkResult) -> anyhow::Result<()> {
+        let rules: Vec<(&AlertRule, SentimentDropCondition)> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.when.matches_brand(&result.brand))
+            .filter_map(|rule| Some((rule, rule.when.sentiment_drop?)))
+            .collect();
+        let Some(window_sec) = rules.iter().map(|(_, condition)| condition.window_sec).max() else {
+            return Ok(());
+        };
+        let Some(score) = result.sentiment_score() else {
+            return Ok(());
+        };
+
+        let brand = &result.brand;
+        let key = format!("{}:{brand}:pushes", self.settings.redis_trend_prefix);
+        let now = Utc::now().timestamp_millis();
+        let window_ms = (window_sec * 1000) as i64;
+        let history: Vec<(i64, f32)> = self
+            .redis
+            .zrange_by_score_with_scores(&key, now - window_ms, now)
+            .await?
+            .iter()
+            .filter_map(|(member, at)| Some((*at, member.rsplit(':').next()?.parse::<f32>().ok()?)))
+            .collect();
+        self.redis
+            .zadd_with_ttl(&key, now, &format!("{}:{score}", result.chunk_id), Duration::from_secs(window_sec * 2))
+            .await?;
+        self.redis.zrem_range_by_score(&key, 0, now - window_ms - 1).await?;
+
+        for (rule, condition) in rules {
+            let since = now - (condition.window_sec * 1000) as i64;
+            let scores: Vec<f32> = history.iter().filter(|(at, _)| *at >= since).map(|(_, score)| *score).collect();
+            let (Some(&previous_score), Some(peak_score)) = (scores.last(), scores.iter().copied().reduce(f32::max))
+            else {
+                continue;
+            };
+            let drop = peak_score - score;
+            if drop < condition.amount {
+                continue;
+            }
+            // One alert per drop: the brand stays low for a while after it.
+            let cooldown = format!("{key}:alerted:{}", rule.name);
+            if !self
+                .redis
+                .set_nx_with_ttl(&cooldown, &result.chunk_id, Duration::from_secs(condition.window_sec))
+                .await?
+            {
+                continue;
+            }
+
+            let event = SentimentDropEvent {
+                rule: rule.name.clone(),
+                worker_id: self.settings.worker_id.clone(),
+                brand: brand.clone(),
+                chunk_id: result.chunk_id.clone(),
+                sentiment_score: score,
+                previous_score,
+                peak_score,
+                drop,
+                window_sec: condition.window_sec,
+                samples: scores.len(),
+                timestamp: Utc::now().to_rfc3339(),
+            };
+            let payload = serde_json::to_value(&event).context("serialise sentiment drop event")?;
+            let text = format!(
+                "{brand} sentiment dropped {drop:.2} within {}m: {peak_score:+.2} -> {score:+.2} (previous push {previous_score:+.2})",
+                condition.window_sec / 60
+            );
+            self.deliver(&rule.name, &rule.destinations, &payload, &text, None).await;
+        }
+        Ok(())
+    }
+
+    /// Sends one alert to each destination: `payload` as the webhook body and Redis message,
+    /// `text` (plus `blocks` when the destination asks for them) to Slack.
+    pub async fn deliver(
+        &self,
+        rule: &str,
+        destinations: &[AlertDestination],
+        payload: &serde_json::Value,
+        text: &str,
+        blocks: Option<&serde_json::Value>,
+    ) {
+        for destination in destinations {
+            let outcome = match self.dispatch(destination, payload, text, blocks).await {
+                Ok(()) => "ok",
+                Err(err) => {
+                    warn!(rule, destination = destination.label(), error = %err, "Alert delivery failed");
+                    "error"
+                }
+            };
+            WORKER_ALERTS_SENT_TOTAL
+                .with_label_values(&[&self.settings.worker_id, rule, destination.label(), outcome])
+                .inc();
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        destination: &AlertDestination,
+        payload: &serde_json::Value,
+        text: &str,
+        blocks: Option<&serde_json::Value>,
+    ) -> anyhow::Result<()> {
+        match destination {
+            AlertDestination::Webhook { url } => self.post(url, payload).await,
+            AlertDestination::Slack {
+                webhook_url,
+                channel,
+                format,
+            } => {
+                let mut body = json!({ "text": text });
+                if let (SlackFormat::Blocks, Some(blocks)) = (format, blocks) {
+                    body["blocks"] = blocks.clone();
+                }
+                if let Some(channel) = channel {
+                    body["channel"] = json!(channel);
+                }
+                self.post(webhook_url, &body).await
+            }
+            AlertDestination::Redis { channel } => {
+                let payload = serde_json::to_string(payload).context("serialise alert event")?;
+                self.redis.publish(channel, &payload).await
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, body: &serde_json::Value) -> anyhow::Result<()> {
+        let request = self.http.post(url).timeout(self.settings.alert_timeout).json(body);
+        self.http.send(request, "alert request").await?;
+        Ok(())
+    }
+}
+
+fn slack_text(event: &AlertEvent) -> String {
+    format!(
+        "[{}] {} cluster {}: {} ({} mentions, sentiment {:.2}{}{})",
+        event.severity.label(),
+        event.brand,
+        event.cluster_id,
+        event.summary.as_deref().unwrap_or("no summary"),
+        event.mention_count,
+        event.sentiment_score,
+        if event.spike { ", spike" } else { "" },
+        event
+            .known_event
+            .as_deref()
+            .map(|name| format!(", during {name}"))
+            .unwrap_or_default()
+    )
+}
+
+fn slack_blocks(event: &AlertEvent, dashboard_base_url: Option<&str>) -> serde_json::Value {
+    let label = event
+        .summary
+        .as_deref()
+        .filter(|summary| !summary.trim().is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Cluster {}", event.cluster_id));
+    let headline = if event.spike { "Mention spike" } else { "Brand alert" };
+
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": {
+                "type": "plain_text",
+                "text": format!("{headline}: {} ({})", event.brand, event.severity.label()),
+            }
+        }),
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("*{}*", escape_mrkdwn(&label)) },
+            "fields": [
+                { "type": "mrkdwn", "text": format!("*Mentions*\n{}", event.mention_count) },
+                { "type": "mrkdwn", "text": format!("*Sentiment*\n{:+.2}", event.sentiment_score) },
+                { "type": "mrkdwn", "text": format!("*Spike*\n{}", if event.spike { "yes" } else { "no" }) },
+                { "type": "mrkdwn", "text": format!("*Rule*\n{}", escape_mrkdwn(&event.rule)) },
+            ]
+        }),
+    ];
+
+    if let Some(name) = &event.known_event {
+        blocks.push(json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": format!("Known event: *{}*", escape_mrkdwn(name)) }]
+        }));
+    }
+
+    if !event.examples.is_empty() {
+        let quotes = event
+            .examples
+            .iter()
+            .take(3)
+            .map(|example| format!("> {}", escape_mrkdwn(example)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("*Example mentions*\n{quotes}") }
+        }));
+    }
+
+    if let Some(base) = dashboard_base_url {
+        blocks.push(json!({
+            "type": "actions",
+            "elements": [{
+                "type": "button",
+                "text": { "type": "plain_text", "text": "Open dashboard" },
+                "url": format!("{base}/brands/{}/dashboard", event.brand),
+            }]
+        }));
+    }
+
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{
+            "type": "mrkdwn",
+            "text": format!("chunk `{}` · worker `{}` · {}", event.chunk_id, event.worker_id, event.timestamp),
+        }]
+    }));
+
+    serde_json::Value::Array(blocks)
+}
+
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::prompts::PromptTemplates;
+use crate::redis_client::RedisClient;
+use crate::types::{ClusterEntities, Confidence};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub summary: Option<String>,
+    pub sentiment: HashMap<String, f32>,
+    #[serde(default)]
+    pub emotions: Option<HashMap<String, f32>>,
+    #[serde(default)]
+    pub toxicity: Option<f32>,
+    #[serde(default)]
+    pub entities: Option<ClusterEntities>,
+    #[serde(default)]
+    pub intent: Option<String>,
+    #[serde(default)]
+    pub quotes: Option<Vec<String>>,
+    #[serde(default)]
+    pub confidence: Option<Confidence>,
+}
+
+/// Summaries and sentiment keyed by a hash of the exact LLM input, the model that
+/// analysed it and the prompts it was analysed with, so replays of unchanged mentions can
+/// skip provider calls while backfills under new prompts or models cannot.
+pub struct AnalysisCache {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+    prompts: Arc<PromptTemplates>,
+}
+
+impl AnalysisCache {
+    pub fn new(
+        redis: RedisClient,
+        settings: Arc<Settings>,
+        cipher: Arc<PayloadCipher>,
+        prompts: Arc<PromptTemplates>,
+    ) -> Self {
+        Self {
+            redis,
+            settings,
+            cipher,
+            prompts,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.analysis_cache_enabled
+    }
+
+    pub fn prompts(&self) -> &PromptTemplates {
+        &self.prompts
+    }
+
+    /// `model` is the provider and model the brand's calls resolve to, overrides included.
+    pub async fn get(&self, brand: &str, model: &str, llm_input: &[String]) -> anyhow::Result<Option<CachedAnalysis>> {
+        let Some(stored) = self.redis.get_string(&self.key(brand, model, llm_input)).await? else {
+            return Ok(None);
+        };
+        let raw = self.cipher.decrypt(&stored)?;
+        let cached = serde_json::from_str(&raw).context("decode cached analysis")?;
+        Ok(Some(cached))
+    }
+
+    pub async fn put(
+        &self,
+        brand: &str,
+        model: &str,
+        llm_input: &[String],
+        analysis: &CachedAnalysis,
+    ) -> anyhow::Result<()> {
+        let raw = serde_json::to_string(analysis).context("serialise cached analysis")?;
+        let stored = self.cipher.encrypt(&raw)?;
+        self.redis
+            .set_with_ttl(&self.key(brand, model, llm_input), &stored, self.settings.analysis_cache_ttl)
+            .await
+    }
+
+    fn key(&self, brand: &str, model: &str, llm_input: &[String]) -> String {
+        // Combined and batch analysis use their own prompts and parse a different reply.
+        let mode = if !self.prompts.whole_cluster_prompts(brand) {
+            "separate"
+        } else if self.settings.llm_max_clusters_per_request > 1 {
+            "batch"
+        } else if self.settings.llm_combined_analysis {
+            "combined"
+        } else {
+            "separate"
+        };
+        let mut hasher = Sha256::new();
+        for part in [model, mode, &self.prompts.fingerprint(brand)] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        for text in llm_input {
+            hasher.update((text.len() as u64).to_le_bytes());
+            hasher.update(text.as_bytes());
+        }
+        let digest = hasher.finalize();
+        let hash: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!("{}:{}:{}", self.settings.redis_analysis_cache_prefix, brand, hash)
+    }
+}
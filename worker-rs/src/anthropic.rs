@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Settings;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::key_pool::ApiKeyPool;
+use crate::llm::{
+    batch_sentiment_prompt, confidence_prompt, emotion_prompt, entity_prompt, intent_prompt, json_repair_prompt,
+    parse_analysis, parse_batch_analysis, parse_batch_sentiment, parse_confidence, parse_emotions, parse_entities,
+    parse_intent, parse_quotes, parse_relevance, parse_sentiment, parse_severity, parse_toxicity, quote_prompt,
+    relevance_max_tokens, relevance_prompt, severity_prompt, toxicity_prompt, ClusterAnalysis, LlmAdapter,
+    SeveritySignals, ANALYSIS_JSON_TOKENS, CAPTION_MAX_TOKENS, CAPTION_PROMPT, CONFIDENCE_MAX_TOKENS,
+    EMOTION_MAX_TOKENS, ENTITY_MAX_TOKENS, INTENT_MAX_TOKENS, QUOTE_MAX_TOKENS, SEVERITY_MAX_TOKENS,
+    TOXICITY_MAX_TOKENS,
+};
+use crate::metrics::record_unparseable_response;
+use crate::prompts::PromptTemplates;
+use crate::types::{ClusterEntities, Confidence};
+
+/// Summaries and sentiment from the Anthropic Messages API.
+pub struct AnthropicLlmAdapter {
+    http: HttpClient,
+    url: String,
+    api_keys: ApiKeyPool,
+    version: String,
+    model: String,
+    max_tokens: u32,
+    timeout: Duration,
+    prompts: Arc<PromptTemplates>,
+    worker_id: String,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    text: Option<String>,
+}
+
+impl AnthropicLlmAdapter {
+    pub fn new(settings: &Settings, http: HttpClient, prompts: Arc<PromptTemplates>) -> anyhow::Result<Self> {
+        let api_keys = ApiKeyPool::load(
+            "anthropic",
+            settings.anthropic_api_key.as_deref(),
+            settings.llm_api_keys_file.as_deref(),
+            &settings.worker_id,
+        )?
+        .context("ANTHROPIC_API_KEY or LLM_API_KEYS_FILE is required for LLM_PROVIDER=anthropic")?;
+        Ok(Self {
+            http,
+            url: format!("{}/v1/messages", settings.anthropic_base_url),
+            api_keys,
+            version: settings.anthropic_version.clone(),
+            model: settings.anthropic_model.clone(),
+            max_tokens: settings.anthropic_max_tokens,
+            timeout: settings.llm_timeout,
+            prompts,
+            worker_id: settings.worker_id.clone(),
+        })
+    }
+
+    /// Asks for a JSON response and validates it with `parse`. An invalid response gets one
+    /// retry with the rejected output quoted back before the call fails.
+    async fn message_json<T: Send>(
+        &self,
+        prompt: String,
+        max_tokens: u32,
+        operation: &str,
+        parse: impl Fn(&str) -> Option<T> + Send,
+    ) -> WorkerResult<T> {
+        let raw = self.message(prompt.as_str(), max_tokens).await.map_err(WorkerError::Llm)?;
+        if let Some(parsed) = parse(&raw) {
+            return Ok(parsed);
+        }
+        let retried = self.message(json_repair_prompt(&prompt, &raw), max_tokens).await.map_err(WorkerError::Llm)?;
+        let parsed = parse(&retried);
+        record_unparseable_response(&self.worker_id, "anthropic", operation, parsed.is_some());
+        parsed.with_context(|| format!("unparseable Anthropic {operation} response: {retried}"))
+            .map_err(WorkerError::Llm)
+    }
+
+    /// `content` is the prompt text, or content blocks for multimodal input.
+    async fn message(&self, content: impl Into<serde_json::Value>, max_tokens: u32) -> anyhow::Result<String> {
+        let body = json!({
+            "model": self.model,
+            "max_tokens": max_tokens,
+            "temperature": 0,
+            "messages": [{ "role": "user", "content": content.into() }],
+        });
+        let request = |key: &str| {
+            self.http
+                .post(&self.url)
+                .header("x-api-key", key)
+                .header("anthropic-version", &self.version)
+                .timeout(self.timeout)
+                .json(&body)
+        };
+        let response: MessagesResponse = self
+            .http
+            .send_with_key(&self.api_keys, "Anthropic message", request)
+            .await?
+            .json()
+            .await
+            .context("decode Anthropic message response")?;
+        let text: String = response
+            .content
+            .into_iter()
+            .filter(|block| block.kind == "text")
+            .filter_map(|block| block.text)
+            .collect();
+        Ok(text.trim().to_string())
+    }
+}
+
+#[async_trait]
+impl LlmAdapter for AnthropicLlmAdapter {
+    async fn summarize(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<String>> {
+        let summary = self
+            .message(self.prompts.summary(brand, texts, self.max_tokens), self.max_tokens)
+            .await
+            .map_err(WorkerError::Llm)?;
+        Ok(Some(summary).filter(|summary| !summary.is_empty()))
+    }
+
+    async fn sentiment(&self, brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        self.message_json(self.prompts.sentiment(brand, texts), 64, "sentiment", parse_sentiment).await
+    }
+
+    async fn sentiment_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        let max_tokens = 64 * groups.len() as u32;
+        self.message_json(batch_sentiment_prompt(groups), max_tokens, "sentiment_batch", |raw| {
+            parse_batch_sentiment(raw, groups.len())
+        })
+        .await
+        .map(Some)
+    }
+
+    async fn analyze(&self, texts: &[String]) -> WorkerResult<Option<ClusterAnalysis>> {
+        let prompt = self.prompts.analysis(texts, self.max_tokens);
+        self.message_json(prompt, self.max_tokens + ANALYSIS_JSON_TOKENS, "analysis", parse_analysis)
+            .await
+            .map(Some)
+    }
+
+    async fn analyze_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<ClusterAnalysis>>> {
+        let prompt = self.prompts.batch_analysis(groups, self.max_tokens);
+        let max_tokens = (self.max_tokens + ANALYSIS_JSON_TOKENS) * groups.len() as u32;
+        self.message_json(prompt, max_tokens, "analysis_batch", |raw| parse_batch_analysis(raw, groups.len()))
+            .await
+            .map(Some)
+    }
+
+    async fn emotions(&self, texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        let prompt = emotion_prompt(texts);
+        self.message_json(prompt, EMOTION_MAX_TOKENS, "emotion", parse_emotions).await.map(Some)
+    }
+
+    async fn severity(&self, signals: &SeveritySignals, texts: &[String]) -> WorkerResult<Option<u8>> {
+        let prompt = severity_prompt(signals, texts);
+        self.message_json(prompt, SEVERITY_MAX_TOKENS, "severity", parse_severity).await.map(Some)
+    }
+
+    async fn confidence(
+        &self,
+        texts: &[String],
+        summary: Option<&str>,
+        sentiment: &HashMap<String, f32>,
+    ) -> WorkerResult<Option<Confidence>> {
+        let prompt = confidence_prompt(texts, summary, sentiment);
+        self.message_json(prompt, CONFIDENCE_MAX_TOKENS, "confidence", parse_confidence).await.map(Some)
+    }
+
+    async fn quotes(&self, texts: &[String]) -> WorkerResult<Option<Vec<usize>>> {
+        self.message_json(quote_prompt(texts), QUOTE_MAX_TOKENS, "quote", |raw| parse_quotes(raw, texts.len()))
+            .await
+            .map(Some)
+    }
+
+    async fn intent(&self, texts: &[String]) -> WorkerResult<Option<String>> {
+        let prompt = intent_prompt(texts);
+        self.message_json(prompt, INTENT_MAX_TOKENS, "intent", parse_intent).await.map(Some)
+    }
+
+    async fn relevance(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<Vec<f32>>> {
+        let prompt = relevance_prompt(brand, texts);
+        let max_tokens = relevance_max_tokens(texts.len());
+        self.message_json(prompt, max_tokens, "relevance", |raw| parse_relevance(raw, texts.len()))
+            .await
+            .map(Some)
+    }
+
+    async fn entities(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<ClusterEntities>> {
+        let prompt = entity_prompt(brand, texts);
+        self.message_json(prompt, ENTITY_MAX_TOKENS, "entity", parse_entities).await.map(Some)
+    }
+
+    async fn toxicity(&self, texts: &[String]) -> WorkerResult<Option<f32>> {
+        let prompt = toxicity_prompt(texts);
+        self.message_json(prompt, TOXICITY_MAX_TOKENS, "toxicity", parse_toxicity).await.map(Some)
+    }
+
+    async fn caption(&self, image_url: &str) -> WorkerResult<Option<String>> {
+        let content = json!([
+            { "type": "image", "source": { "type": "url", "url": image_url } },
+            { "type": "text", "text": CAPTION_PROMPT },
+        ]);
+        let caption = self.message(content, CAPTION_MAX_TOKENS).await.map_err(WorkerError::Llm)?;
+        Ok(Some(caption).filter(|caption| !caption.is_empty()))
+    }
+}
@@ -4,15 +4,28 @@ use std::sync::Arc;
 use anyhow::Result;
 use axum::{routing::get, Json, Router};
 use tokio::signal;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Notify};
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
+use crate::admin;
 use crate::config::Settings;
+use crate::control::FleetControl;
+use crate::crypto::PayloadCipher;
+use crate::digest::DailyDigestJob;
+use crate::embeddings::build_embedding_adapter;
+use crate::error::WorkerResult;
+use crate::grpc;
+use crate::http::HttpClient;
+use crate::memory_monitor::RedisMemoryMonitor;
 use crate::metrics::gather_metrics;
 use crate::queue_consumer::QueueConsumer;
+use crate::recluster::ReclusterJob;
 use crate::redis_client::RedisClient;
+use crate::retention::RetentionJob;
 use crate::service::WorkerService;
+use crate::slo::SloMonitor;
+use crate::throttle::ResourceThrottle;
 
 pub async fn run(settings: Settings) -> Result<()> {
     let settings = Arc::new(settings);
@@ -20,16 +33,49 @@ pub async fn run(settings: Settings) -> Result<()> {
     redis.ensure_connection().await?;
 
     let consumer = QueueConsumer::new(redis.clone(), settings.worker_id.clone(), settings.blpop_timeout);
-    let service = WorkerService::new(settings.clone(), redis.clone(), consumer);
+    let service = WorkerService::new(settings.clone(), redis.clone(), consumer)?;
     let service = Arc::new(service);
 
     let (shutdown_tx, _) = broadcast::channel::<()>(1);
 
-    let mut worker_loop = spawn_worker_loop(service.clone(), shutdown_tx.subscribe());
+    let worker_loop = spawn_worker_loop(service.clone(), shutdown_tx.subscribe());
     tokio::pin!(worker_loop);
     let heartbeat_loop = spawn_heartbeat_loop(service.clone(), shutdown_tx.subscribe());
-    let http_server = serve_http(settings.clone(), shutdown_tx.subscribe());
+    let memory_monitor = spawn_memory_monitor(
+        RedisMemoryMonitor::new(redis.clone(), settings.clone()),
+        shutdown_tx.subscribe(),
+    );
+    let cipher = Arc::new(PayloadCipher::from_settings(&settings)?);
+    let http = HttpClient::from_settings(&settings)?;
+    let digest_job = spawn_digest_job(
+        DailyDigestJob::new(redis.clone(), settings.clone(), cipher.clone(), http.clone()),
+        shutdown_tx.subscribe(),
+    );
+    let recluster_job = spawn_recluster_job(
+        ReclusterJob::new(
+            redis.clone(),
+            settings.clone(),
+            build_embedding_adapter(&settings, &redis, &http)?,
+            cipher,
+        )?,
+        shutdown_tx.subscribe(),
+    );
+    let slo_monitor = spawn_slo_monitor(service.slo(), shutdown_tx.subscribe());
+    let resource_throttle = spawn_resource_throttle(service.throttle(), shutdown_tx.subscribe());
+    let retention_job = spawn_retention_job(
+        RetentionJob::new(redis.clone(), settings.clone()),
+        shutdown_tx.subscribe(),
+    );
+    let http_server = serve_http(settings.clone(), service.clone(), shutdown_tx.subscribe());
     let metrics_server = serve_metrics(settings.clone(), shutdown_tx.subscribe());
+    let grpc_server = serve_grpc(service.clone(), shutdown_tx.subscribe());
+    let reload_listener = spawn_reload_listener(service.clone(), shutdown_tx.subscribe());
+    let fleet_stop = Arc::new(Notify::new());
+    let fleet_control = spawn_fleet_control(
+        settings.clone(),
+        FleetControl::new(service.clone(), redis.clone(), fleet_stop.clone()),
+        shutdown_tx.subscribe(),
+    );
 
     info!(
         http_port = settings.http_port,
@@ -42,6 +88,9 @@ pub async fn run(settings: Settings) -> Result<()> {
         _ = signal::ctrl_c() => {
             info!("Shutdown signal received");
         }
+        _ = fleet_stop.notified() => {
+            info!("Fleet shutdown command received");
+        }
         res = &mut worker_loop => {
             worker_finished = true;
             if let Err(err) = res {
@@ -55,15 +104,25 @@ pub async fn run(settings: Settings) -> Result<()> {
     if !worker_finished {
         worker_loop.await.ok();
     }
+    service.shutdown().await;
     heartbeat_loop.await.ok();
+    memory_monitor.await.ok();
+    slo_monitor.await.ok();
+    resource_throttle.await.ok();
+    digest_job.await.ok();
+    recluster_job.await.ok();
+    retention_job.await.ok();
     http_server.await.ok();
     metrics_server.await.ok();
+    grpc_server.await.ok();
+    reload_listener.await.ok();
+    fleet_control.await.ok();
 
     info!("Rust worker shutdown complete");
     Ok(())
 }
 
-fn spawn_worker_loop(service: Arc<WorkerService>, shutdown: broadcast::Receiver<()>) -> JoinHandle<Result<()>> {
+fn spawn_worker_loop(service: Arc<WorkerService>, shutdown: broadcast::Receiver<()>) -> JoinHandle<WorkerResult<()>> {
     tokio::spawn(async move { service.run(shutdown).await })
 }
 
@@ -85,19 +144,108 @@ fn spawn_heartbeat_loop(service: Arc<WorkerService>, mut shutdown: broadcast::Re
     })
 }
 
-fn serve_http(settings: Arc<Settings>, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+/// SIGHUP re-reads the environment and swaps in freshly built pipeline adapters.
+#[cfg(unix)]
+fn spawn_reload_listener(service: Arc<WorkerService>, mut shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(err) => {
+                warn!(error = %err, "Failed to install SIGHUP handler; config reload via signal disabled");
+                return;
+            }
+        };
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => break,
+                _ = hangup.recv() => {
+                    info!("SIGHUP received; reloading config");
+                    match Settings::reload_from_env(service.settings()) {
+                        Ok(settings) => {
+                            if let Err(err) = service.reload(settings) {
+                                warn!(error = %err, "Config reload failed; keeping current adapters");
+                            }
+                        }
+                        Err(err) => warn!(error = %err, "Config reload failed; keeping current adapters"),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(not(unix))]
+fn spawn_reload_listener(_service: Arc<WorkerService>, _shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async {})
+}
+
+fn spawn_fleet_control(
+    settings: Arc<Settings>,
+    control: FleetControl,
+    shutdown: broadcast::Receiver<()>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if !settings.fleet_control_enabled {
+            return;
+        }
+        control.run(shutdown).await
+    })
+}
+
+fn spawn_memory_monitor(monitor: RedisMemoryMonitor, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move { monitor.run(shutdown).await })
+}
+
+fn spawn_digest_job(job: DailyDigestJob, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move { job.run(shutdown).await })
+}
+
+fn spawn_recluster_job(job: ReclusterJob, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move { job.run(shutdown).await })
+}
+
+fn spawn_slo_monitor(monitor: Arc<SloMonitor>, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move { monitor.run(shutdown).await })
+}
+
+fn spawn_resource_throttle(throttle: Arc<ResourceThrottle>, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move { throttle.run(shutdown).await })
+}
+
+fn spawn_retention_job(job: RetentionJob, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move { job.run(shutdown).await })
+}
+
+fn serve_http(
+    settings: Arc<Settings>,
+    service: Arc<WorkerService>,
+    shutdown: broadcast::Receiver<()>,
+) -> JoinHandle<()> {
     let http_port = settings.http_port;
     let router_settings = settings.clone();
-    let router = Router::new().route(
-        "/health",
-        get(move || {
-            let worker_id = router_settings.worker_id.clone();
-            async move { Json(serde_json::json!({ "status": "ok", "workerId": worker_id })) }
-        }),
-    );
+    let router = Router::new()
+        .route(
+            "/health",
+            get(move || {
+                let worker_id = router_settings.worker_id.clone();
+                async move { Json(serde_json::json!({ "status": "ok", "workerId": worker_id })) }
+            }),
+        )
+        .merge(admin::router(service));
     spawn_server(router, http_port, shutdown)
 }
 
+fn serve_grpc(service: Arc<WorkerService>, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if !service.settings().grpc_enabled {
+            return;
+        }
+        if let Err(err) = grpc::serve(service, shutdown).await {
+            error!(error = %err, "gRPC server failed");
+        }
+    })
+}
+
 fn serve_metrics(settings: Arc<Settings>, shutdown: broadcast::Receiver<()>) -> JoinHandle<()> {
     let router = Router::new().route(
         "/metrics",
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::error::{WorkerError, WorkerResult};
+use crate::redis_client::RedisClient;
+
+pub struct ChunkArchive {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+}
+
+impl ChunkArchive {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, cipher: Arc<PayloadCipher>) -> Self {
+        Self {
+            redis,
+            settings,
+            cipher,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.archive_enabled
+    }
+
+    /// Archives `payload` under `chunk_id`, replacing any earlier copy. Entries created more
+    /// than `ARCHIVE_TTL_SEC` ago are dropped on write, however active the brand.
+    pub async fn store(
+        &self,
+        brand: &str,
+        chunk_id: &str,
+        created_at: DateTime<Utc>,
+        payload: &str,
+    ) -> WorkerResult<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        let (index, payloads) = (self.index_key(brand), self.payload_key(brand));
+        let stored = self.cipher.encrypt(payload).map_err(WorkerError::Storage)?;
+        self.redis
+            .zadd_hash_trimmed(&index, &payloads, chunk_id, created_at.timestamp(), &stored, self.settings.archive_ttl)
+            .await
+            .context("archive chunk payload")
+            .map_err(WorkerError::Storage)?;
+        debug!(worker_id = %self.settings.worker_id, brand, chunk_id, key = index, "Chunk payload archived");
+        Ok(())
+    }
+
+    pub async fn load_range(
+        &self,
+        brand: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> WorkerResult<Vec<String>> {
+        let chunk_ids = self
+            .redis
+            .zrange_by_score(&self.index_key(brand), from.timestamp(), to.timestamp())
+            .await
+            .context("load archived chunk ids")
+            .map_err(WorkerError::Storage)?;
+        let stored = self
+            .redis
+            .hmget(&self.payload_key(brand), &chunk_ids)
+            .await
+            .context("load archived chunks")
+            .map_err(WorkerError::Storage)?;
+        Ok(stored
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| match self.cipher.decrypt(&entry) {
+                Ok(payload) => Some(payload),
+                Err(err) => {
+                    warn!(brand, error = %err, "Skipping archived chunk that could not be decrypted");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// The archived payload for `chunk_id`, if it is still retained.
+    pub async fn find(&self, brand: &str, chunk_id: &str) -> WorkerResult<Option<String>> {
+        let stored = self
+            .redis
+            .hget(&self.payload_key(brand), chunk_id)
+            .await
+            .context("load archived chunk")
+            .map_err(WorkerError::Storage)?;
+        stored
+            .map(|entry| self.cipher.decrypt(&entry).map_err(WorkerError::Storage))
+            .transpose()
+    }
+
+    /// Chunk IDs scored by creation time, for range loads and retention.
+    fn index_key(&self, brand: &str) -> String {
+        format!("{}:{}:chunks", self.settings.redis_archive_prefix, brand)
+    }
+
+    /// Sealed chunk payloads by chunk ID.
+    fn payload_key(&self, brand: &str) -> String {
+        format!("{}:{}:payloads", self.settings.redis_archive_prefix, brand)
+    }
+}
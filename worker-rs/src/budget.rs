@@ -0,0 +1,180 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::json;
+use tracing::warn;
+
+use crate::config::Settings;
+use crate::metrics::{WORKER_BUDGET_EXCEEDED_TOTAL, WORKER_PROVIDER_SPEND_USD_TOTAL};
+use crate::redis_client::RedisClient;
+
+const SPEND_KEY_TTL: Duration = Duration::from_secs(2 * 86_400);
+
+#[derive(Debug, Clone, Copy)]
+pub enum BudgetKind {
+    Llm,
+    Embedding,
+}
+
+impl BudgetKind {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Llm => "llm",
+            Self::Embedding => "embedding",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BudgetGuard {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    kind: BudgetKind,
+}
+
+impl BudgetGuard {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, kind: BudgetKind) -> Self {
+        Self { redis, settings, kind }
+    }
+
+    pub async fn allow(&self, provider: &str, brand: &str) -> bool {
+        let (provider_limit, brand_limit) = self.limits();
+        let day = today();
+
+        if let Some(limit) = provider_limit {
+            let key = self.provider_key(provider, &day);
+            if !self.within(&key, limit, provider, brand, "provider").await {
+                return false;
+            }
+        }
+        if let Some(limit) = brand_limit {
+            let key = self.brand_key(provider, brand, &day);
+            if !self.within(&key, limit, provider, brand, "brand").await {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub async fn record(&self, provider: &str, brand: &str, input_tokens: u64, output_tokens: u64) {
+        let cost = self.estimate_cost(input_tokens, output_tokens);
+        if cost <= 0.0 {
+            return;
+        }
+        WORKER_PROVIDER_SPEND_USD_TOTAL
+            .with_label_values(&[&self.settings.worker_id, provider, brand])
+            .inc_by(cost);
+
+        let day = today();
+        for key in [self.provider_key(provider, &day), self.brand_key(provider, brand, &day)] {
+            if let Err(err) = self.redis.incr_by_float_with_ttl(&key, cost, SPEND_KEY_TTL).await {
+                warn!(provider, brand, key, error = %err, "Failed to record provider spend");
+            }
+        }
+    }
+
+    pub fn estimate_cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        match self.kind {
+            BudgetKind::Llm => {
+                input_tokens as f64 / 1000.0 * self.settings.llm_price_input_per_1k
+                    + output_tokens as f64 / 1000.0 * self.settings.llm_price_output_per_1k
+            }
+            BudgetKind::Embedding => input_tokens as f64 / 1000.0 * self.settings.embedding_price_per_1k,
+        }
+    }
+
+    fn limits(&self) -> (Option<f64>, Option<f64>) {
+        match self.kind {
+            BudgetKind::Llm => (
+                self.settings.llm_daily_budget_usd,
+                self.settings.llm_brand_daily_budget_usd,
+            ),
+            BudgetKind::Embedding => (
+                self.settings.embedding_daily_budget_usd,
+                self.settings.embedding_brand_daily_budget_usd,
+            ),
+        }
+    }
+
+    async fn within(&self, key: &str, limit: f64, provider: &str, brand: &str, scope: &str) -> bool {
+        let spent = match self.redis.get_f64(key).await {
+            Ok(value) => value.unwrap_or_default(),
+            Err(err) => {
+                warn!(provider, brand, key, error = %err, "Failed to read provider spend; allowing call");
+                return true;
+            }
+        };
+        if spent < limit {
+            return true;
+        }
+
+        WORKER_BUDGET_EXCEEDED_TOTAL
+            .with_label_values(&[&self.settings.worker_id, provider, scope])
+            .inc();
+        self.alert(key, provider, brand, scope, spent, limit).await;
+        false
+    }
+
+    async fn alert(&self, key: &str, provider: &str, brand: &str, scope: &str, spent: f64, limit: f64) {
+        let marker = format!("{key}:alerted");
+        match self.redis.set_nx_with_ttl(&marker, "1", SPEND_KEY_TTL).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(err) => {
+                warn!(provider, brand, error = %err, "Failed to set budget alert marker");
+                return;
+            }
+        }
+
+        warn!(
+            worker_id = %self.settings.worker_id,
+            kind = self.kind.label(),
+            provider,
+            brand,
+            scope,
+            spent_usd = spent,
+            budget_usd = limit,
+            "Daily provider budget exceeded; downgrading to local adapter"
+        );
+
+        let event = json!({
+            "type": "budget_exceeded",
+            "workerId": self.settings.worker_id,
+            "kind": self.kind.label(),
+            "provider": provider,
+            "brand": brand,
+            "scope": scope,
+            "spentUsd": spent,
+            "budgetUsd": limit,
+            "timestamp": Utc::now().to_rfc3339(),
+        });
+        if let Err(err) = self.redis.publish(&self.settings.alert_channel, &event.to_string()).await {
+            warn!(provider, brand, error = %err, "Failed to publish budget alert");
+        }
+    }
+
+    fn provider_key(&self, provider: &str, day: &str) -> String {
+        format!("{}:{}:{}:{}", self.settings.redis_budget_prefix, self.kind.label(), provider, day)
+    }
+
+    fn brand_key(&self, provider: &str, brand: &str, day: &str) -> String {
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.settings.redis_budget_prefix,
+            self.kind.label(),
+            provider,
+            brand,
+            day
+        )
+    }
+}
+
+pub fn estimate_tokens<S: AsRef<str>>(texts: &[S]) -> u64 {
+    let chars: usize = texts.iter().map(|text| text.as_ref().chars().count()).sum();
+    chars.div_ceil(4) as u64
+}
+
+fn today() -> String {
+    Utc::now().format("%Y%m%d").to_string()
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use tracing::info;
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::pipeline::build_processor;
+use crate::processor::Processor;
+use crate::redis_client::RedisClient;
+use crate::types::Chunk;
+
+/// Conditions a chunk has to meet for a route; empty lists match anything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteMatch {
+    /// Matched against the chunk's most common mention source.
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub brands: Vec<String>,
+    /// Tier names from the file's `brandTiers` map.
+    #[serde(default)]
+    pub brand_tiers: Vec<String>,
+    #[serde(default)]
+    pub min_mentions: Option<usize>,
+    #[serde(default)]
+    pub max_mentions: Option<usize>,
+}
+
+/// A named pipeline variant and the chunks it takes. Anything left out runs as configured
+/// for the worker.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteRule {
+    pub name: String,
+    #[serde(default)]
+    pub when: RouteMatch,
+    #[serde(default)]
+    pub stages: Option<Vec<String>>,
+    #[serde(default)]
+    pub llm_provider: Option<String>,
+    /// Model for the route's LLM provider.
+    #[serde(default)]
+    pub llm_model: Option<String>,
+    #[serde(default)]
+    pub embeddings_provider: Option<String>,
+    #[serde(default)]
+    pub entities: Option<bool>,
+    #[serde(default)]
+    pub llm_tiering: Option<bool>,
+}
+
+impl RouteRule {
+    fn settings(&self, base: &Settings) -> Settings {
+        let mut settings = base.clone();
+        if let Some(stages) = &self.stages {
+            settings.pipeline_stages = stages.iter().map(|stage| stage.trim().to_lowercase()).collect();
+        }
+        if let Some(provider) = &self.llm_provider {
+            settings.llm_provider = provider.trim().to_lowercase();
+            // A fixed provider replaces health routing, as for the shadow pipeline.
+            settings.llm_providers = Vec::new();
+        }
+        if let Some(model) = &self.llm_model {
+            match settings.llm_provider.as_str() {
+                "openai" => settings.openai_model = model.clone(),
+                "gemini" => settings.gemini_model = model.clone(),
+                "anthropic" => settings.anthropic_model = model.clone(),
+                _ => {}
+            }
+        }
+        if let Some(provider) = &self.embeddings_provider {
+            settings.embeddings_provider = provider.trim().to_lowercase();
+        }
+        if let Some(entities) = self.entities {
+            settings.entities_enabled = entities;
+        }
+        if let Some(tiering) = self.llm_tiering {
+            settings.llm_tiering_enabled = tiering;
+        }
+        settings
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoutesFile {
+    #[serde(default)]
+    brand_tiers: HashMap<String, String>,
+    routes: Vec<RouteRule>,
+}
+
+/// Sends chunks to pipeline variants by source, brand, brand tier and size, from the JSON
+/// file at `PIPELINE_ROUTES_FILE`, e.g. news chunks to a premium LLM with entity extraction
+/// and social chunks to a shorter stage list. The first matching route wins; chunks no
+/// route matches run on the live pipeline. The route taken is reported in provenance.
+#[derive(Default)]
+pub struct ChunkRouter {
+    brand_tiers: HashMap<String, String>,
+    routes: Vec<(RouteRule, Processor)>,
+}
+
+impl ChunkRouter {
+    pub fn from_settings(
+        settings: &Arc<Settings>,
+        redis: &RedisClient,
+        cipher: &Arc<PayloadCipher>,
+        http: &HttpClient,
+    ) -> WorkerResult<Self> {
+        let Some(path) = &settings.pipeline_routes_file else {
+            return Ok(Self::default());
+        };
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("read pipeline routes from {path}"))
+            .and_then(|raw| {
+                serde_json::from_str::<RoutesFile>(&raw).with_context(|| format!("parse pipeline routes in {path}"))
+            })
+            .map_err(WorkerError::Config)?;
+
+        let mut routes = Vec::with_capacity(file.routes.len());
+        for rule in file.routes {
+            if routes.iter().any(|(existing, _): &(RouteRule, _)| existing.name == rule.name) {
+                return Err(WorkerError::Config(anyhow!("duplicate pipeline route '{}'", rule.name)));
+            }
+            let route_settings = Arc::new(rule.settings(settings));
+            let processor = build_processor(&route_settings, redis, cipher, http, &[])?;
+            routes.push((rule, processor));
+        }
+        if !routes.is_empty() {
+            info!(worker_id = %settings.worker_id, routes = routes.len(), "Pipeline routes loaded");
+        }
+        Ok(Self {
+            brand_tiers: file
+                .brand_tiers
+                .into_iter()
+                .map(|(brand, tier)| (brand.to_lowercase(), tier.to_lowercase()))
+                .collect(),
+            routes,
+        })
+    }
+
+    /// The first route whose conditions `chunk` meets, with its processor.
+    pub fn route(&self, brand: &str, chunk: &Chunk) -> Option<(&str, &Processor)> {
+        if self.routes.is_empty() {
+            return None;
+        }
+        let brand = brand.to_lowercase();
+        let tier = self.brand_tiers.get(&brand);
+        let source = dominant_source(chunk);
+        let size = chunk.mentions.len();
+        self.routes
+            .iter()
+            .find(|(rule, _)| {
+                let when = &rule.when;
+                (when.sources.is_empty()
+                    || source.is_some_and(|source| when.sources.iter().any(|s| s.eq_ignore_ascii_case(source))))
+                    && (when.brands.is_empty() || when.brands.iter().any(|b| b.eq_ignore_ascii_case(&brand)))
+                    && (when.brand_tiers.is_empty()
+                        || tier.is_some_and(|tier| when.brand_tiers.iter().any(|t| t.eq_ignore_ascii_case(tier))))
+                    && when.min_mentions.is_none_or(|min| size >= min)
+                    && when.max_mentions.is_none_or(|max| size <= max)
+            })
+            .map(|(rule, processor)| (rule.name.as_str(), processor))
+    }
+}
+
+fn dominant_source(chunk: &Chunk) -> Option<&str> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for mention in &chunk.mentions {
+        *counts.entry(mention.source.as_str()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(source, _)| source)
+}
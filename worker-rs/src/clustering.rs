@@ -1,7 +1,10 @@
+use std::cmp::Reverse;
 use std::time::Instant;
 
 use tracing::info;
 
+use crate::config::Settings;
+use crate::context::ProcessingContext;
 use crate::metrics::WORKER_CLUSTERING_TIME_SECONDS;
 
 #[derive(Debug, Clone)]
@@ -16,45 +19,188 @@ pub struct ClusteringOutput {
     pub duration_ms: f64,
 }
 
+/// Groups a chunk's mentions by `CLUSTERING_ALGORITHM`.
 pub struct Clusterer {
     worker_id: String,
+    threshold: Option<f32>,
+    metric: DistanceMetric,
 }
 
 impl Clusterer {
-    pub fn new(worker_id: String) -> Self {
-        Self { worker_id }
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            worker_id: settings.worker_id.clone(),
+            threshold: (settings.clustering_algorithm == "threshold").then_some(settings.clustering_similarity_threshold),
+            metric: DistanceMetric::from_settings(settings),
+        }
     }
 
-    pub async fn cluster(
-        &self,
-        embeddings: &[Vec<f32>],
-        brand: &str,
-        chunk_id: &str,
-    ) -> ClusteringOutput {
+    /// Name recorded in result provenance; changes whenever the grouping strategy does.
+    pub fn algorithm(&self) -> &'static str {
+        match self.threshold {
+            Some(_) => "threshold",
+            None => "single-cluster",
+        }
+    }
+
+    /// Tunables of [`Clusterer::algorithm`], if it has any.
+    pub fn params(&self) -> Option<serde_json::Value> {
+        self.threshold
+            .map(|threshold| serde_json::json!({ "threshold": threshold, "metric": self.metric.name() }))
+    }
+
+    pub async fn cluster(&self, embeddings: &[Vec<f32>], job: &ProcessingContext) -> ClusteringOutput {
         let start = Instant::now();
-        let indices: Vec<usize> = (0..embeddings.len()).collect();
-        let cluster = ClusterGroup {
-            cluster_id: 1,
-            indices,
+        let clusters = match self.threshold {
+            Some(threshold) => threshold_groups(embeddings, threshold, self.metric),
+            None => vec![ClusterGroup {
+                cluster_id: 1,
+                indices: (0..embeddings.len()).collect(),
+            }],
         };
-        self.finish(vec![cluster], start, brand, chunk_id)
+        self.finish(clusters, start, job)
     }
 
     fn finish(
         &self,
         clusters: Vec<ClusterGroup>,
         start: Instant,
-        brand: &str,
-        chunk_id: &str,
+        job: &ProcessingContext,
     ) -> ClusteringOutput {
         let duration = start.elapsed();
         WORKER_CLUSTERING_TIME_SECONDS
-            .with_label_values(&[&self.worker_id, brand])
+            .with_label_values(&[&self.worker_id, &job.brand])
             .observe(duration.as_secs_f64());
-        info!(worker_id = %self.worker_id, brand, chunk_id, clusters = clusters.len(), "Clustering completed");
+        info!(
+            worker_id = %self.worker_id,
+            brand = %job.brand,
+            chunk_id = %job.chunk_id,
+            correlation_id = %job.correlation_id,
+            clusters = clusters.len(),
+            "Clustering completed"
+        );
         ClusteringOutput {
             clusters,
             duration_ms: duration.as_secs_f64() * 1000.0,
         }
     }
 }
+
+pub fn centroid(embeddings: &[Vec<f32>], indices: &[usize]) -> Vec<f32> {
+    let vectors: Vec<&Vec<f32>> = indices.iter().filter_map(|&idx| embeddings.get(idx)).collect();
+    let Some(dim) = vectors.first().map(|vector| vector.len()) else {
+        return Vec::new();
+    };
+    let mut sum = vec![0.0_f32; dim];
+    for vector in &vectors {
+        for (acc, value) in sum.iter_mut().zip(vector.iter()) {
+            *acc += value;
+        }
+    }
+    sum.iter_mut().for_each(|value| *value /= vectors.len() as f32);
+    sum
+}
+
+/// How embedding closeness is measured, from `DISTANCE_METRIC`. Every metric is expressed as
+/// a similarity where larger means closer, so thresholds read the same way whichever is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    Cosine,
+    Euclidean,
+    Dot,
+}
+
+impl DistanceMetric {
+    pub fn from_settings(settings: &Settings) -> Self {
+        match settings.distance_metric.as_str() {
+            "euclidean" => Self::Euclidean,
+            "dot" => Self::Dot,
+            _ => Self::Cosine,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cosine => "cosine",
+            Self::Euclidean => "euclidean",
+            Self::Dot => "dot",
+        }
+    }
+
+    /// Cosine similarity, the dot product, or `1 / (1 + distance)` for euclidean.
+    /// Vectors of different lengths are unrelated.
+    pub fn similarity(self, a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() || a.is_empty() {
+            return 0.0;
+        }
+        match self {
+            Self::Cosine => cosine_similarity(a, b),
+            Self::Euclidean => 1.0 / (1.0 + euclidean_distance(a, b)),
+            Self::Dot => a.iter().zip(b).map(|(x, y)| x * y).sum(),
+        }
+    }
+}
+
+/// Scales `vector` to unit length; zero vectors are left as they are.
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+}
+
+/// Single-pass grouping: each vector joins the cluster whose running-mean centroid is most
+/// similar under `metric`, if at least `threshold`, and otherwise starts a new cluster.
+/// Clusters are numbered from 1, largest first.
+pub fn threshold_groups(embeddings: &[Vec<f32>], threshold: f32, metric: DistanceMetric) -> Vec<ClusterGroup> {
+    let mut centroids: Vec<Vec<f32>> = Vec::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for (idx, vector) in embeddings.iter().enumerate() {
+        let best = centroids
+            .iter()
+            .map(|centroid| metric.similarity(centroid, vector))
+            .enumerate()
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((cluster, _)) => {
+                members[cluster].push(idx);
+                let count = members[cluster].len() as f32;
+                for (acc, value) in centroids[cluster].iter_mut().zip(vector) {
+                    *acc += (value - *acc) / count;
+                }
+            }
+            None => {
+                centroids.push(vector.clone());
+                members.push(vec![idx]);
+            }
+        }
+    }
+    members.sort_by_key(|indices| Reverse(indices.len()));
+    members
+        .into_iter()
+        .enumerate()
+        .map(|(position, indices)| ClusterGroup {
+            cluster_id: position as i32 + 1,
+            indices,
+        })
+        .collect()
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+pub fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
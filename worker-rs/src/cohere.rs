@@ -0,0 +1,90 @@
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Settings;
+use crate::embeddings::EmbeddingAdapter;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::key_pool::ApiKeyPool;
+
+/// Cohere's `/v2/embed` endpoint accepts at most this many texts per request.
+const COHERE_MAX_BATCH: usize = 96;
+
+/// Embeddings from Cohere's `/v2/embed` endpoint, requested with the `clustering` input type
+/// since the vectors only feed mention clustering.
+pub struct CohereEmbeddingAdapter {
+    http: HttpClient,
+    url: String,
+    model: String,
+    api_keys: ApiKeyPool,
+    batch_size: usize,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: EmbeddingsByType,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsByType {
+    #[serde(default)]
+    float: Vec<Vec<f32>>,
+}
+
+impl CohereEmbeddingAdapter {
+    pub fn new(settings: &Settings, http: HttpClient) -> anyhow::Result<Self> {
+        let keys = settings
+            .cohere_api_key
+            .as_deref()
+            .or(settings.embedding_api_key.as_deref());
+        let file = settings.embedding_api_keys_file.as_deref();
+        let api_keys = ApiKeyPool::load("cohere", keys, file, &settings.worker_id)?
+            .context("COHERE_API_KEY or EMBEDDING_API_KEY is required for EMBEDDINGS_PROVIDER=cohere")?;
+        Ok(Self {
+            http,
+            url: format!("{}/v2/embed", settings.cohere_base_url),
+            model: settings.cohere_embed_model.clone(),
+            api_keys,
+            batch_size: settings.embeddings_batch_size.min(COHERE_MAX_BATCH),
+        })
+    }
+
+    async fn embed_batches(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            let body = json!({
+                "model": self.model,
+                "texts": batch,
+                "input_type": "clustering",
+                "embedding_types": ["float"],
+                "truncate": "END",
+            });
+            let request = |key: &str| self.http.post(&self.url).bearer_auth(key).json(&body);
+            let response: EmbedResponse = self
+                .http
+                .send_with_key(&self.api_keys, "Cohere embed", request)
+                .await?
+                .json()
+                .await
+                .context("decode Cohere embed response")?;
+            if response.embeddings.float.len() != batch.len() {
+                bail!(
+                    "Cohere returned {} embeddings for {} texts",
+                    response.embeddings.float.len(),
+                    batch.len()
+                );
+            }
+            vectors.extend(response.embeddings.float);
+        }
+        Ok(vectors)
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for CohereEmbeddingAdapter {
+    async fn embed(&self, texts: &[String], _brand: &str, _chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>> {
+        self.embed_batches(texts).await.map_err(WorkerError::Embedding)
+    }
+}
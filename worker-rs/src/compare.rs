@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde_json::Value;
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::redis_client::RedisClient;
+
+const USAGE: &str = "usage: worker-rs compare-results <baseline> <candidate> [brand]\n\
+                     Each side is a result prefix in Redis (e.g. the live and shadow prefixes) or a \
+                     JSON Lines file of result payloads.";
+
+/// Orchestrator payloads per brand, keyed by chunk ID.
+type ResultSet = BTreeMap<String, HashMap<String, Value>>;
+
+#[derive(Debug, Default)]
+struct BrandStats {
+    matched: usize,
+    baseline_only: usize,
+    candidate_only: usize,
+    cluster_count_equal: usize,
+    cluster_count_delta: f64,
+    sentiment_delta: f64,
+    sentiment_sign_equal: usize,
+    topic_overlap: f64,
+    spike_equal: usize,
+}
+
+/// `worker-rs compare-results`: pairs the results of two pipelines by chunk ID and prints
+/// per-brand agreement on cluster count, sentiment, topics and spike detection, as evidence
+/// for or against rolling out a clustering or LLM change.
+pub async fn run(args: &[String]) -> anyhow::Result<()> {
+    let (baseline, candidate) = match args {
+        [baseline, candidate, ..] => (baseline, candidate),
+        _ => bail!(USAGE),
+    };
+    let brand = args.get(2).map(String::as_str);
+    let settings = Settings::from_env()?;
+    let cipher = PayloadCipher::from_settings(&settings)?;
+    let mut redis = None;
+
+    let mut sets = Vec::with_capacity(2);
+    for source in [baseline, candidate] {
+        let payloads = if Path::new(source).is_file() {
+            let raw = std::fs::read_to_string(source).with_context(|| format!("read results from {source}"))?;
+            raw.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        } else {
+            if redis.is_none() {
+                redis = Some(RedisClient::new(&settings.redis_url).await?);
+            }
+            let redis = redis.as_ref().expect("redis client created above");
+            load_prefix(redis, source, brand).await?
+        };
+        sets.push(index(&cipher, payloads, brand).with_context(|| format!("decode results from {source}"))?);
+    }
+    let candidate = sets.pop().expect("two result sets");
+    let baseline = sets.pop().expect("two result sets");
+
+    let brands: BTreeSet<&String> = baseline.keys().chain(candidate.keys()).collect();
+    if brands.is_empty() {
+        println!("no results found");
+        return Ok(());
+    }
+    let empty = HashMap::new();
+    for brand in brands {
+        let stats = compare(
+            baseline.get(brand).unwrap_or(&empty),
+            candidate.get(brand).unwrap_or(&empty),
+        );
+        print_stats(brand, &stats);
+    }
+    Ok(())
+}
+
+async fn load_prefix(redis: &RedisClient, prefix: &str, brand: Option<&str>) -> anyhow::Result<Vec<String>> {
+    let keys = match brand {
+        Some(brand) => vec![format!("{prefix}:{brand}:chunks")],
+        None => redis.scan_keys(&format!("{prefix}:*:chunks")).await?,
+    };
+    let mut payloads = Vec::new();
+    for key in keys {
+        payloads.extend(redis.lrange(&key, 0, -1).await?);
+    }
+    Ok(payloads)
+}
+
+fn index(cipher: &PayloadCipher, payloads: Vec<String>, brand: Option<&str>) -> anyhow::Result<ResultSet> {
+    let mut set = ResultSet::new();
+    for payload in payloads {
+        let value: Value = serde_json::from_str(&cipher.decrypt(&payload)?)?;
+        let (Some(result_brand), Some(chunk_id)) = (
+            value.get("brand").and_then(Value::as_str),
+            value.get("chunkId").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        if brand.is_some_and(|brand| brand != result_brand) {
+            continue;
+        }
+        // Reprocessed chunks appear more than once; the latest result wins.
+        set.entry(result_brand.to_string())
+            .or_default()
+            .insert(chunk_id.to_string(), value);
+    }
+    Ok(set)
+}
+
+fn compare(baseline: &HashMap<String, Value>, candidate: &HashMap<String, Value>) -> BrandStats {
+    let mut stats = BrandStats {
+        baseline_only: baseline.keys().filter(|id| !candidate.contains_key(*id)).count(),
+        candidate_only: candidate.keys().filter(|id| !baseline.contains_key(*id)).count(),
+        ..Default::default()
+    };
+    for (chunk_id, a) in baseline {
+        let Some(b) = candidate.get(chunk_id) else {
+            continue;
+        };
+        stats.matched += 1;
+
+        let clusters = |value: &Value| value.get("clusters").and_then(Value::as_array).map_or(0, Vec::len);
+        let (a_clusters, b_clusters) = (clusters(a), clusters(b));
+        stats.cluster_count_equal += usize::from(a_clusters == b_clusters);
+        stats.cluster_count_delta += a_clusters.abs_diff(b_clusters) as f64;
+
+        let score = |value: &Value| value.pointer("/sentiment/score").and_then(Value::as_f64).unwrap_or_default();
+        let (a_score, b_score) = (score(a), score(b));
+        stats.sentiment_delta += (a_score - b_score).abs();
+        stats.sentiment_sign_equal += usize::from(sign(a_score) == sign(b_score));
+
+        stats.topic_overlap += jaccard(&topics(a), &topics(b));
+
+        let spike = |value: &Value| value.get("spikeDetected").and_then(Value::as_bool).unwrap_or_default();
+        stats.spike_equal += usize::from(spike(a) == spike(b));
+    }
+    stats
+}
+
+/// -1, 0 or 1, with scores within ±0.05 counting as neutral.
+fn sign(score: f64) -> i8 {
+    if score > 0.05 {
+        1
+    } else if score < -0.05 {
+        -1
+    } else {
+        0
+    }
+}
+
+fn topics(value: &Value) -> HashSet<String> {
+    value
+        .get("topics")
+        .and_then(Value::as_array)
+        .map(|topics| {
+            topics
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|topic| topic.trim().to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / a.union(b).count() as f64
+}
+
+fn print_stats(brand: &str, stats: &BrandStats) {
+    println!(
+        "{brand}: {} chunk(s) compared, {} only in baseline, {} only in candidate",
+        stats.matched, stats.baseline_only, stats.candidate_only
+    );
+    if stats.matched == 0 {
+        return;
+    }
+    let matched = stats.matched as f64;
+    let share = |count: usize| 100.0 * count as f64 / matched;
+    println!(
+        "  cluster count: {:.1}% equal, mean difference {:.2}",
+        share(stats.cluster_count_equal),
+        stats.cluster_count_delta / matched
+    );
+    println!(
+        "  sentiment:     {:.1}% same direction, mean score difference {:.3}",
+        share(stats.sentiment_sign_equal),
+        stats.sentiment_delta / matched
+    );
+    println!("  topics:        mean overlap {:.1}%", 100.0 * stats.topic_overlap / matched);
+    println!("  spikes:        {:.1}% agree", share(stats.spike_equal));
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::config::Settings;
+use crate::context::ProcessingContext;
+use crate::llm::InstrumentedLlmAdapter;
+use crate::types::{Confidence, Provenance};
+
+/// Estimates how far a cluster's summary and sentiment can be trusted, so low-confidence
+/// insights can be suppressed downstream. `CONFIDENCE_MODE=reported` asks the LLM provider
+/// to rate its reading of the cluster; `agreement` scores sentiment on
+/// `CONFIDENCE_SAMPLES` interleaved subsets of the mentions and measures how close each
+/// comes to the cluster's sentiment, leaving summary confidence unset.
+pub struct ConfidenceEstimator {
+    agreement: bool,
+    samples: usize,
+}
+
+impl ConfidenceEstimator {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Option<Self>> {
+        let agreement = match settings.confidence_mode.as_deref() {
+            None => return Ok(None),
+            Some("reported") => false,
+            Some("agreement") => true,
+            Some(other) => bail!("unknown CONFIDENCE_MODE: {other}"),
+        };
+        Ok(Some(Self {
+            agreement,
+            samples: settings.confidence_samples,
+        }))
+    }
+
+    pub async fn estimate(
+        &self,
+        llm: &InstrumentedLlmAdapter,
+        job: &ProcessingContext,
+        texts: &[String],
+        summary: Option<&str>,
+        sentiment: &HashMap<String, f32>,
+        provenance: &mut Provenance,
+    ) -> Option<Confidence> {
+        if !self.agreement {
+            return llm.confidence(job, texts, summary, sentiment, provenance).await;
+        }
+        // Every subset needs at least one mention to disagree with.
+        let samples = self.samples.min(texts.len());
+        if samples < 2 {
+            return None;
+        }
+        let mut distance = 0.0;
+        for offset in 0..samples {
+            let subset: Vec<String> = texts.iter().skip(offset).step_by(samples).cloned().collect();
+            let sampled = llm.sentiment(job, &subset, provenance).await;
+            distance += total_variation(sentiment, &sampled);
+        }
+        Some(Confidence {
+            summary: None,
+            sentiment: Some((1.0 - distance / samples as f32).clamp(0.0, 1.0)),
+        })
+    }
+}
+
+/// Half the summed absolute difference between two sentiment distributions, from 0 for
+/// identical ones to 1 for disjoint ones.
+fn total_variation(a: &HashMap<String, f32>, b: &HashMap<String, f32>) -> f32 {
+    ["positive", "neutral", "negative"]
+        .iter()
+        .map(|label| {
+            let share = |sentiment: &HashMap<String, f32>| sentiment.get(*label).copied().unwrap_or_default();
+            (share(a) - share(b)).abs()
+        })
+        .sum::<f32>()
+        / 2.0
+}
@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use serde::Deserialize;
@@ -67,6 +68,458 @@ struct RawSettings {
     llm_max_concurrency: usize,
     #[serde(rename = "SPIKE_HISTORY_TTL_SEC", default = "default_spike_history_ttl_sec")]
     spike_history_ttl_sec: u64,
+    #[serde(rename = "ARCHIVE_ENABLED", default)]
+    archive_enabled: bool,
+    #[serde(rename = "REDIS_ARCHIVE_PREFIX", default = "default_archive_prefix")]
+    redis_archive_prefix: String,
+    #[serde(rename = "ARCHIVE_TTL_SEC", default = "default_archive_ttl_sec")]
+    archive_ttl_sec: u64,
+    #[serde(rename = "BACKFILL_RESULT_PREFIX", default = "default_backfill_result_prefix")]
+    backfill_result_prefix: String,
+    #[serde(rename = "BACKFILL_MAX_CONCURRENT", default = "default_backfill_max_concurrent")]
+    backfill_max_concurrent: usize,
+    #[serde(rename = "ADMIN_API_TOKEN")]
+    admin_api_token: Option<String>,
+    #[serde(rename = "SHADOW_ENABLED", default)]
+    shadow_enabled: bool,
+    #[serde(rename = "REDIS_SHADOW_PREFIX", default = "default_shadow_prefix")]
+    redis_shadow_prefix: String,
+    #[serde(rename = "SHADOW_EMBEDDINGS_PROVIDER")]
+    shadow_embeddings_provider: Option<String>,
+    #[serde(rename = "SHADOW_LLM_PROVIDER")]
+    shadow_llm_provider: Option<String>,
+    #[serde(rename = "SHADOW_CLUSTERING_ALGORITHM")]
+    shadow_clustering_algorithm: Option<String>,
+    #[serde(rename = "SHADOW_MAX_CONCURRENT", default = "default_shadow_max_concurrent")]
+    shadow_max_concurrent: usize,
+    #[serde(rename = "SHADOW_TIMEOUT_SEC", default = "default_shadow_timeout_sec")]
+    shadow_timeout_sec: u64,
+    #[serde(rename = "REDIS_BUDGET_PREFIX", default = "default_budget_prefix")]
+    redis_budget_prefix: String,
+    #[serde(rename = "ALERT_CHANNEL", default = "default_alert_channel")]
+    alert_channel: String,
+    #[serde(rename = "LLM_DAILY_BUDGET_USD")]
+    llm_daily_budget_usd: Option<f64>,
+    #[serde(rename = "LLM_BRAND_DAILY_BUDGET_USD")]
+    llm_brand_daily_budget_usd: Option<f64>,
+    #[serde(rename = "LLM_PRICE_INPUT_PER_1K", default = "default_llm_price_input_per_1k")]
+    llm_price_input_per_1k: f64,
+    #[serde(rename = "LLM_PRICE_OUTPUT_PER_1K", default = "default_llm_price_output_per_1k")]
+    llm_price_output_per_1k: f64,
+    #[serde(rename = "EMBEDDING_DAILY_BUDGET_USD")]
+    embedding_daily_budget_usd: Option<f64>,
+    #[serde(rename = "EMBEDDING_BRAND_DAILY_BUDGET_USD")]
+    embedding_brand_daily_budget_usd: Option<f64>,
+    #[serde(rename = "EMBEDDING_PRICE_PER_1K", default = "default_embedding_price_per_1k")]
+    embedding_price_per_1k: f64,
+    #[serde(rename = "LLM_BATCH_SENTIMENT", default = "default_true")]
+    llm_batch_sentiment: bool,
+    #[serde(rename = "PAYLOAD_ENCRYPTION_KEY")]
+    payload_encryption_key: Option<String>,
+    #[serde(rename = "PAYLOAD_ENCRYPTION_KEY_FILE")]
+    payload_encryption_key_file: Option<String>,
+    #[serde(rename = "PAYLOAD_ENCRYPTION_REQUIRED", default)]
+    payload_encryption_required: bool,
+    #[serde(rename = "REDIS_TREND_PREFIX", default = "default_trend_prefix")]
+    redis_trend_prefix: String,
+    #[serde(rename = "SENTIMENT_TREND_WINDOW_DAYS", default = "default_sentiment_trend_window_days")]
+    sentiment_trend_window_days: u32,
+    #[serde(rename = "SENTIMENT_TREND_FLAT_THRESHOLD", default = "default_sentiment_trend_flat_threshold")]
+    sentiment_trend_flat_threshold: f32,
+    #[serde(rename = "ALERT_RULES_FILE")]
+    alert_rules_file: Option<String>,
+    #[serde(rename = "ALERT_TIMEOUT_SEC", default = "default_alert_timeout_sec")]
+    alert_timeout_sec: u64,
+    #[serde(rename = "DASHBOARD_BASE_URL")]
+    dashboard_base_url: Option<String>,
+    #[serde(rename = "CLUSTER_RECURRENCE_ENABLED", default)]
+    cluster_recurrence_enabled: bool,
+    #[serde(rename = "CLUSTER_RECURRENCE_THRESHOLD", default = "default_cluster_recurrence_threshold")]
+    cluster_recurrence_threshold: f32,
+    #[serde(rename = "CLUSTER_RECURRENCE_GROWTH", default = "default_cluster_recurrence_growth")]
+    cluster_recurrence_growth: f32,
+    #[serde(rename = "CLUSTER_RECURRENCE_SUPPRESS_LLM", default = "default_true")]
+    cluster_recurrence_suppress_llm: bool,
+    #[serde(rename = "CLUSTER_RECURRENCE_CACHE_SIZE", default = "default_cluster_recurrence_cache_size")]
+    cluster_recurrence_cache_size: usize,
+    #[serde(rename = "REDIS_CENTROID_PREFIX", default = "default_centroid_prefix")]
+    redis_centroid_prefix: String,
+    #[serde(rename = "CENTROID_TTL_SEC", default = "default_centroid_ttl_sec")]
+    centroid_ttl_sec: u64,
+    #[serde(rename = "QUEUE_PRIORITIZATION", default = "default_queue_prioritization")]
+    queue_prioritization: String,
+    #[serde(rename = "QUEUE_STALENESS_SAMPLE_SEC", default = "default_queue_staleness_sample_sec")]
+    queue_staleness_sample_sec: f64,
+    #[serde(rename = "CLUSTER_SAMPLE_THRESHOLD", default = "default_cluster_sample_threshold")]
+    cluster_sample_threshold: usize,
+    #[serde(rename = "CLUSTER_SAMPLE_SIZE", default = "default_cluster_sample_size")]
+    cluster_sample_size: usize,
+    #[serde(rename = "SINK_HTTP_URL")]
+    sink_http_url: Option<String>,
+    #[serde(rename = "SINK_HTTP_NDJSON", default)]
+    sink_http_ndjson: bool,
+    #[serde(rename = "SINK_BATCH_SIZE", default = "default_sink_batch_size")]
+    sink_batch_size: usize,
+    #[serde(rename = "SINK_FLUSH_INTERVAL_SEC", default = "default_sink_flush_interval_sec")]
+    sink_flush_interval_sec: u64,
+    #[serde(rename = "SINK_BUFFER_CAPACITY", default = "default_sink_buffer_capacity")]
+    sink_buffer_capacity: usize,
+    #[serde(rename = "SINK_TIMEOUT_SEC", default = "default_sink_timeout_sec")]
+    sink_timeout_sec: u64,
+    #[serde(rename = "SINK_SPILL_DIR", default = "default_sink_spill_dir")]
+    sink_spill_dir: String,
+    #[serde(rename = "REDIS_MEMORY_SAMPLE_INTERVAL_SEC", default = "default_redis_memory_sample_interval_sec")]
+    redis_memory_sample_interval_sec: u64,
+    #[serde(rename = "REDIS_MEMORY_SAMPLE_KEYS", default = "default_redis_memory_sample_keys")]
+    redis_memory_sample_keys: usize,
+    #[serde(rename = "REDIS_MEMORY_WARN_BYTES", default = "default_redis_memory_warn_bytes")]
+    redis_memory_warn_bytes: u64,
+    #[serde(rename = "REDIS_LENGTH_WARN", default = "default_redis_length_warn")]
+    redis_length_warn: u64,
+    #[serde(rename = "ANALYSIS_CACHE_ENABLED", default = "default_true")]
+    analysis_cache_enabled: bool,
+    #[serde(rename = "REDIS_ANALYSIS_CACHE_PREFIX", default = "default_redis_analysis_cache_prefix")]
+    redis_analysis_cache_prefix: String,
+    #[serde(rename = "ANALYSIS_CACHE_TTL_SEC", default = "default_analysis_cache_ttl_sec")]
+    analysis_cache_ttl_sec: u64,
+    #[serde(rename = "EMBEDDING_CACHE_ENABLED", default = "default_true")]
+    embedding_cache_enabled: bool,
+    #[serde(rename = "REDIS_EMBEDDING_CACHE_PREFIX", default = "default_redis_embedding_cache_prefix")]
+    redis_embedding_cache_prefix: String,
+    #[serde(rename = "EMBEDDING_CACHE_TTL_SEC", default = "default_embedding_cache_ttl_sec")]
+    embedding_cache_ttl_sec: u64,
+    #[serde(rename = "PIPELINE_STAGES", default = "default_pipeline_stages")]
+    pipeline_stages: String,
+    #[serde(rename = "STAGE_TIMEOUTS_MS", default = "default_stage_timeouts_ms")]
+    stage_timeouts_ms: String,
+    #[serde(rename = "STAGE_TIMEOUT_POLICY", default)]
+    stage_timeout_policy: String,
+    #[serde(rename = "BRAND_ONBOARDING_ENABLED", default = "default_true")]
+    brand_onboarding_enabled: bool,
+    #[serde(rename = "REDIS_BRAND_STATE_PREFIX", default = "default_brand_state_prefix")]
+    redis_brand_state_prefix: String,
+    #[serde(rename = "ONBOARDING_CHANNEL", default = "default_onboarding_channel")]
+    onboarding_channel: String,
+    #[serde(rename = "SPIKE_BASELINE_MENTIONS", default = "default_spike_baseline_mentions")]
+    spike_baseline_mentions: u64,
+    #[serde(rename = "SPIKE_WARMUP_CHUNKS", default = "default_spike_warmup_chunks")]
+    spike_warmup_chunks: usize,
+    #[serde(rename = "SPIKE_WARMUP_HOURS", default)]
+    spike_warmup_hours: u64,
+    #[serde(rename = "EVENT_WINDOWS_FILE")]
+    event_windows_file: Option<String>,
+    #[serde(rename = "REDIS_EVENT_WINDOWS_PREFIX", default = "default_event_windows_prefix")]
+    redis_event_windows_prefix: String,
+    #[serde(rename = "RETENTION_ENABLED", default)]
+    retention_enabled: bool,
+    #[serde(rename = "RETENTION_INTERVAL_SEC", default = "default_retention_interval_sec")]
+    retention_interval_sec: u64,
+    #[serde(rename = "REDIS_RETENTION_PREFIX", default = "default_retention_prefix")]
+    redis_retention_prefix: String,
+    #[serde(rename = "RESULT_RETENTION_ENTRIES", default = "default_result_retention_entries")]
+    result_retention_entries: usize,
+    #[serde(rename = "TREND_ROLLUP_RETENTION_DAYS", default = "default_trend_rollup_retention_days")]
+    trend_rollup_retention_days: u32,
+    #[serde(rename = "SPIKE_HISTORY_RETENTION_DAYS", default = "default_spike_history_retention_days")]
+    spike_history_retention_days: u64,
+    #[serde(rename = "FAILURE_PAYLOAD_PREVIEW_CHARS", default = "default_failure_payload_preview_chars")]
+    failure_payload_preview_chars: usize,
+    #[serde(rename = "HTTP_TIMEOUT_SEC", default = "default_http_timeout_sec")]
+    http_timeout_sec: u64,
+    #[serde(rename = "HTTP_CONNECT_TIMEOUT_SEC", default = "default_http_connect_timeout_sec")]
+    http_connect_timeout_sec: u64,
+    #[serde(rename = "HTTP_POOL_MAX_IDLE_PER_HOST", default = "default_http_pool_max_idle_per_host")]
+    http_pool_max_idle_per_host: usize,
+    #[serde(rename = "HTTP_POOL_IDLE_TIMEOUT_SEC", default = "default_http_pool_idle_timeout_sec")]
+    http_pool_idle_timeout_sec: u64,
+    #[serde(rename = "HTTP_PROXY_URL")]
+    http_proxy_url: Option<String>,
+    #[serde(rename = "HTTP_USER_AGENT", default = "default_http_user_agent")]
+    http_user_agent: String,
+    #[serde(rename = "HTTPS_PROXY")]
+    https_proxy: Option<String>,
+    #[serde(rename = "NO_PROXY")]
+    no_proxy: Option<String>,
+    #[serde(rename = "EGRESS_ALLOWED_HOSTS", default)]
+    egress_allowed_hosts: String,
+    #[serde(rename = "LLM_RATE_LIMIT_RPS")]
+    llm_rate_limit_rps: Option<f64>,
+    #[serde(rename = "LLM_RATE_LIMIT_BURST", default = "default_rate_limit_burst")]
+    llm_rate_limit_burst: u32,
+    #[serde(rename = "EMBEDDING_RATE_LIMIT_RPS")]
+    embedding_rate_limit_rps: Option<f64>,
+    #[serde(rename = "EMBEDDING_RATE_LIMIT_BURST", default = "default_rate_limit_burst")]
+    embedding_rate_limit_burst: u32,
+    #[serde(rename = "RATE_LIMIT_FLEET_WIDE", default)]
+    rate_limit_fleet_wide: bool,
+    #[serde(rename = "REDIS_RATE_LIMIT_PREFIX", default = "default_rate_limit_prefix")]
+    redis_rate_limit_prefix: String,
+    #[serde(rename = "EMBEDDING_MODEL")]
+    embedding_model: Option<String>,
+    #[serde(rename = "OPENAI_BASE_URL", default = "default_openai_base_url")]
+    openai_base_url: String,
+    #[serde(rename = "GEMINI_BASE_URL", default = "default_gemini_base_url")]
+    gemini_base_url: String,
+    #[serde(rename = "ANTHROPIC_API_KEY")]
+    anthropic_api_key: Option<String>,
+    #[serde(rename = "ANTHROPIC_MODEL", default = "default_anthropic_model")]
+    anthropic_model: String,
+    #[serde(rename = "ANTHROPIC_MAX_TOKENS", default = "default_anthropic_max_tokens")]
+    anthropic_max_tokens: u32,
+    #[serde(rename = "ANTHROPIC_BASE_URL", default = "default_anthropic_base_url")]
+    anthropic_base_url: String,
+    #[serde(rename = "ANTHROPIC_VERSION", default = "default_anthropic_version")]
+    anthropic_version: String,
+    #[serde(rename = "LLM_PROVIDERS", default)]
+    llm_providers: String,
+    #[serde(rename = "PROVIDER_PROBE_INTERVAL_SEC", default = "default_provider_probe_interval_sec")]
+    provider_probe_interval_sec: u64,
+    #[serde(rename = "GRPC_ENABLED", default)]
+    grpc_enabled: bool,
+    #[serde(rename = "GRPC_PORT", default = "default_grpc_port")]
+    grpc_port: u16,
+    #[serde(rename = "LLM_BASE_URL")]
+    llm_base_url: Option<String>,
+    #[serde(rename = "EXAMPLE_REDACTION", default = "default_example_redaction")]
+    example_redaction: String,
+    #[serde(rename = "NOISE_LISTS_FILE")]
+    noise_lists_file: Option<String>,
+    #[serde(rename = "REDIS_NOISE_LISTS_PREFIX", default = "default_noise_lists_prefix")]
+    redis_noise_lists_prefix: String,
+    #[serde(rename = "CLUSTER_LABEL_CACHE_ENABLED", default)]
+    cluster_label_cache_enabled: bool,
+    #[serde(rename = "REDIS_CLUSTER_LABEL_PREFIX", default = "default_cluster_label_prefix")]
+    redis_cluster_label_prefix: String,
+    #[serde(rename = "CLUSTER_LABEL_TTL_SEC", default = "default_cluster_label_ttl_sec")]
+    cluster_label_ttl_sec: u64,
+    #[serde(rename = "LLM_COMBINED_ANALYSIS", default = "default_true")]
+    llm_combined_analysis: bool,
+    #[serde(rename = "LLM_TIERING_ENABLED", default)]
+    llm_tiering_enabled: bool,
+    #[serde(rename = "LLM_CHEAP_PROVIDER", default = "default_llm_cheap_provider")]
+    llm_cheap_provider: String,
+    #[serde(rename = "LLM_PREMIUM_MIN_CLUSTER_SIZE", default = "default_llm_premium_min_cluster_size")]
+    llm_premium_min_cluster_size: usize,
+    #[serde(rename = "LLM_PREMIUM_MIN_NEGATIVITY", default = "default_llm_premium_min_negativity")]
+    llm_premium_min_negativity: f32,
+    #[serde(rename = "LLM_PREMIUM_ON_SPIKE", default = "default_true")]
+    llm_premium_on_spike: bool,
+    #[serde(rename = "PROMPT_TEMPLATES_FILE")]
+    prompt_templates_file: Option<String>,
+    #[serde(rename = "FLEET_CONTROL_ENABLED", default)]
+    fleet_control_enabled: bool,
+    #[serde(rename = "REDIS_CONTROL_CHANNEL", default = "default_control_channel")]
+    redis_control_channel: String,
+    #[serde(rename = "SLO_RULES_FILE")]
+    slo_rules_file: Option<String>,
+    #[serde(rename = "SLO_EVAL_INTERVAL_SEC", default = "default_slo_eval_interval_sec")]
+    slo_eval_interval_sec: u64,
+    #[serde(rename = "DIGEST_ENABLED", default)]
+    digest_enabled: bool,
+    #[serde(rename = "DIGEST_CHECK_INTERVAL_SEC", default = "default_digest_check_interval_sec")]
+    digest_check_interval_sec: u64,
+    #[serde(rename = "REDIS_DIGEST_PREFIX", default = "default_digest_prefix")]
+    redis_digest_prefix: String,
+    #[serde(rename = "DIGEST_TTL_DAYS", default = "default_digest_ttl_days")]
+    digest_ttl_days: u64,
+    #[serde(rename = "DIGEST_TOP_CLUSTERS", default = "default_digest_top_clusters")]
+    digest_top_clusters: usize,
+    #[serde(rename = "DIGEST_WEBHOOK_URL")]
+    digest_webhook_url: Option<String>,
+    #[serde(rename = "MENTION_DEDUP_ENABLED", default)]
+    mention_dedup_enabled: bool,
+    #[serde(rename = "MENTION_DEDUP_TTL_SEC", default = "default_mention_dedup_ttl_sec")]
+    mention_dedup_ttl_sec: u64,
+    #[serde(rename = "REDIS_MENTION_DEDUP_PREFIX", default = "default_mention_dedup_prefix")]
+    redis_mention_dedup_prefix: String,
+    #[serde(rename = "EMOTIONS_ENABLED", default)]
+    emotions_enabled: bool,
+    #[serde(rename = "RATING_METADATA_KEY", default = "default_rating_metadata_key")]
+    rating_metadata_key: String,
+    #[serde(rename = "RATING_SENTIMENT_WEIGHT", default = "default_rating_sentiment_weight")]
+    rating_sentiment_weight: f32,
+    #[serde(rename = "MEDIA_METADATA_KEY", default = "default_media_metadata_key")]
+    media_metadata_key: String,
+    #[serde(rename = "MEDIA_CAPTION_PROVIDER")]
+    media_caption_provider: Option<String>,
+    #[serde(rename = "MEDIA_CAPTION_MAX_PER_CHUNK", default = "default_media_caption_max_per_chunk")]
+    media_caption_max_per_chunk: usize,
+    #[serde(rename = "TOXICITY_SCORING")]
+    toxicity_scoring: Option<String>,
+    #[serde(rename = "TOXICITY_LEXICON_FILE")]
+    toxicity_lexicon_file: Option<String>,
+    #[serde(rename = "TOXICITY_THRESHOLD", default = "default_toxicity_threshold")]
+    toxicity_threshold: f32,
+    #[serde(rename = "QA_SAMPLE_PERCENT", default)]
+    qa_sample_percent: f64,
+    #[serde(rename = "REDIS_QA_PREFIX", default = "default_qa_prefix")]
+    redis_qa_prefix: String,
+    #[serde(rename = "QA_SAMPLE_MAX_ENTRIES", default = "default_qa_sample_max_entries")]
+    qa_sample_max_entries: usize,
+    #[serde(rename = "QA_SAMPLE_TTL_DAYS", default = "default_qa_sample_ttl_days")]
+    qa_sample_ttl_days: u64,
+    #[serde(rename = "ENTITIES_ENABLED", default)]
+    entities_enabled: bool,
+    #[serde(rename = "RELEVANCE_FILTER_ENABLED", default)]
+    relevance_filter_enabled: bool,
+    #[serde(rename = "RELEVANCE_MODE", default = "default_relevance_mode")]
+    relevance_mode: String,
+    #[serde(rename = "RELEVANCE_ACTION", default = "default_relevance_action")]
+    relevance_action: String,
+    #[serde(rename = "RELEVANCE_THRESHOLD", default = "default_relevance_threshold")]
+    relevance_threshold: f32,
+    #[serde(rename = "RELEVANCE_TERMS_FILE")]
+    relevance_terms_file: Option<String>,
+    #[serde(rename = "RELEVANCE_BATCH_SIZE", default = "default_relevance_batch_size")]
+    relevance_batch_size: usize,
+    #[serde(rename = "INTENT_ENABLED", default)]
+    intent_enabled: bool,
+    #[serde(rename = "QUOTES_ENABLED", default)]
+    quotes_enabled: bool,
+    #[serde(rename = "PREPROCESS_WASM_HOOK")]
+    preprocess_wasm_hook: Option<String>,
+    #[serde(rename = "PREPROCESS_WASM_FUEL", default = "default_preprocess_wasm_fuel")]
+    preprocess_wasm_fuel: u64,
+    #[serde(rename = "SEVERITY_SCORING")]
+    severity_scoring: Option<String>,
+    #[serde(rename = "SEVERITY_VOLUME_WEIGHT", default = "default_severity_volume_weight")]
+    severity_volume_weight: f32,
+    #[serde(rename = "SEVERITY_NEGATIVITY_WEIGHT", default = "default_severity_negativity_weight")]
+    severity_negativity_weight: f32,
+    #[serde(rename = "SEVERITY_SPIKE_WEIGHT", default = "default_severity_spike_weight")]
+    severity_spike_weight: f32,
+    #[serde(rename = "SEVERITY_VOLUME_SATURATION", default = "default_severity_volume_saturation")]
+    severity_volume_saturation: usize,
+    #[serde(rename = "BRAND_TAXONOMY_FILE")]
+    brand_taxonomy_file: Option<String>,
+    #[serde(rename = "CONFIDENCE_MODE")]
+    confidence_mode: Option<String>,
+    #[serde(rename = "CONFIDENCE_SAMPLES", default = "default_confidence_samples")]
+    confidence_samples: usize,
+    #[serde(rename = "CONFIDENCE_THRESHOLD", default = "default_confidence_threshold")]
+    confidence_threshold: f32,
+    #[serde(rename = "LLM_MODEL_OVERRIDES_ENABLED", default)]
+    llm_model_overrides_enabled: bool,
+    #[serde(rename = "LLM_MODEL_OVERRIDES_FILE")]
+    llm_model_overrides_file: Option<String>,
+    #[serde(rename = "REDIS_LLM_OVERRIDE_PREFIX", default = "default_redis_llm_override_prefix")]
+    redis_llm_override_prefix: String,
+    #[serde(rename = "LLM_MODEL_OVERRIDE_REFRESH_SECS", default = "default_llm_model_override_refresh_secs")]
+    llm_model_override_refresh_secs: u64,
+    #[serde(rename = "DEADLINE_ACTION", default = "default_deadline_action")]
+    deadline_action: String,
+    #[serde(rename = "SUMMARY_LANGUAGE")]
+    summary_language: Option<String>,
+    #[serde(rename = "LLM_MAX_CLUSTERS_PER_REQUEST", default = "default_llm_max_clusters_per_request")]
+    llm_max_clusters_per_request: usize,
+    #[serde(rename = "TOPIC_TRENDS_ENABLED", default)]
+    topic_trends_enabled: bool,
+    #[serde(rename = "TOPIC_TREND_CHANGE_THRESHOLD", default = "default_topic_trend_change_threshold")]
+    topic_trend_change_threshold: f64,
+    #[serde(rename = "TOPIC_TREND_MIN_COUNT", default = "default_topic_trend_min_count")]
+    topic_trend_min_count: f64,
+    #[serde(rename = "TOPIC_TREND_LIMIT", default = "default_topic_trend_limit")]
+    topic_trend_limit: usize,
+    #[serde(rename = "LLM_API_KEYS_FILE")]
+    llm_api_keys_file: Option<String>,
+    #[serde(rename = "EMBEDDING_API_KEYS_FILE")]
+    embedding_api_keys_file: Option<String>,
+    #[serde(rename = "SPIKE_HISTORY_KEY", default = "default_spike_history_key")]
+    spike_history_key: String,
+    #[serde(rename = "SPIKE_HISTORY_MIGRATE", default = "default_true")]
+    spike_history_migrate: bool,
+    #[serde(rename = "JOURNAL_SINK", default = "default_journal_sink")]
+    journal_sink: String,
+    #[serde(rename = "JOURNAL_REDIS_STREAM", default = "default_journal_redis_stream")]
+    journal_redis_stream: String,
+    #[serde(rename = "JOURNAL_DIR", default = "default_journal_dir")]
+    journal_dir: String,
+    #[serde(rename = "JOURNAL_RETENTION_DAYS", default = "default_journal_retention_days")]
+    journal_retention_days: u64,
+    #[serde(rename = "PROMPT_SANITIZATION", default = "default_true")]
+    prompt_sanitization: bool,
+    #[serde(rename = "NOVELTY_DETECTION_ENABLED", default)]
+    novelty_detection_enabled: bool,
+    #[serde(rename = "NOVELTY_WINDOW_DAYS", default = "default_novelty_window_days")]
+    novelty_window_days: u64,
+    #[serde(rename = "NOVELTY_THRESHOLD", default = "default_novelty_threshold")]
+    novelty_threshold: f32,
+    #[serde(rename = "NOVELTY_MIN_MENTIONS", default = "default_novelty_min_mentions")]
+    novelty_min_mentions: usize,
+    #[serde(rename = "PIPELINE_ROUTES_FILE")]
+    pipeline_routes_file: Option<String>,
+    #[serde(rename = "EMBEDDINGS_ONNX_MODEL")]
+    embeddings_onnx_model: Option<String>,
+    #[serde(rename = "EMBEDDINGS_ONNX_TOKENIZER")]
+    embeddings_onnx_tokenizer: Option<String>,
+    #[serde(rename = "EMBEDDINGS_ONNX_MAX_TOKENS", default = "default_embeddings_onnx_max_tokens")]
+    embeddings_onnx_max_tokens: usize,
+    #[serde(rename = "TEI_URL")]
+    tei_url: Option<String>,
+    #[serde(rename = "TEI_MAX_BATCH_SIZE")]
+    tei_max_batch_size: Option<usize>,
+    #[serde(rename = "COHERE_API_KEY")]
+    cohere_api_key: Option<String>,
+    #[serde(rename = "COHERE_EMBED_MODEL", default = "default_cohere_embed_model")]
+    cohere_embed_model: String,
+    #[serde(rename = "COHERE_BASE_URL", default = "default_cohere_base_url")]
+    cohere_base_url: String,
+    #[serde(rename = "THROTTLE_MAX_RSS_MB")]
+    throttle_max_rss_mb: Option<u64>,
+    #[serde(rename = "THROTTLE_MAX_TASK_BACKLOG")]
+    throttle_max_task_backlog: Option<usize>,
+    #[serde(rename = "THROTTLE_RESUME_RATIO", default = "default_throttle_resume_ratio")]
+    throttle_resume_ratio: f64,
+    #[serde(rename = "THROTTLE_SAMPLE_INTERVAL_SEC", default = "default_throttle_sample_interval_sec")]
+    throttle_sample_interval_sec: u64,
+    #[serde(rename = "THROTTLE_FETCH_DELAY_MS", default = "default_throttle_fetch_delay_ms")]
+    throttle_fetch_delay_ms: u64,
+    #[serde(rename = "SIMULATED_LATENCY_MS", default = "default_simulated_latency_ms")]
+    simulated_latency_ms: u64,
+    #[serde(rename = "SIMULATED_LATENCY_JITTER", default = "default_simulated_latency_jitter")]
+    simulated_latency_jitter: f64,
+    #[serde(rename = "SIMULATED_LATENCY_PER_TEXT_MS", default)]
+    simulated_latency_per_text_ms: u64,
+    #[serde(rename = "SIMULATED_ERROR_RATE", default)]
+    simulated_error_rate: f64,
+    #[serde(rename = "SIMULATED_STALL_RATE", default)]
+    simulated_stall_rate: f64,
+    #[serde(rename = "SIMULATED_STALL_MS", default = "default_simulated_stall_ms")]
+    simulated_stall_ms: u64,
+    #[serde(rename = "VECTOR_STORE", default = "default_vector_store")]
+    vector_store: String,
+    #[serde(rename = "VECTOR_STORE_URL")]
+    vector_store_url: Option<String>,
+    #[serde(rename = "VECTOR_STORE_API_KEY")]
+    vector_store_api_key: Option<String>,
+    #[serde(rename = "VECTOR_STORE_COLLECTION_PREFIX", default = "default_vector_store_collection_prefix")]
+    vector_store_collection_prefix: String,
+    #[serde(rename = "VECTOR_STORE_EXPORT_MENTIONS", default = "default_true")]
+    vector_store_export_mentions: bool,
+    #[serde(rename = "CLUSTERING_ALGORITHM", default = "default_clustering_algorithm")]
+    clustering_algorithm: String,
+    #[serde(rename = "CLUSTERING_SIMILARITY_THRESHOLD", default = "default_clustering_similarity_threshold")]
+    clustering_similarity_threshold: f32,
+    #[serde(rename = "DISTANCE_METRIC", default = "default_distance_metric")]
+    distance_metric: String,
+    #[serde(rename = "EMBEDDING_NORMALIZE", default = "default_true")]
+    embedding_normalize: bool,
+    #[serde(rename = "EMBEDDING_TIMEOUT_SEC", default = "default_embedding_timeout_sec")]
+    embedding_timeout_sec: u64,
+    #[serde(rename = "RECLUSTER_ENABLED", default)]
+    recluster_enabled: bool,
+    #[serde(rename = "RECLUSTER_INTERVAL_SEC", default = "default_recluster_interval_sec")]
+    recluster_interval_sec: u64,
+    #[serde(rename = "RECLUSTER_WINDOW_HOURS", default = "default_recluster_window_hours")]
+    recluster_window_hours: u64,
+    #[serde(rename = "RECLUSTER_SIMILARITY_THRESHOLD", default = "default_recluster_similarity_threshold")]
+    recluster_similarity_threshold: f32,
+    #[serde(rename = "RECLUSTER_MAX_MENTIONS", default = "default_recluster_max_mentions")]
+    recluster_max_mentions: usize,
+    #[serde(rename = "REDIS_RECLUSTER_PREFIX", default = "default_recluster_prefix")]
+    redis_recluster_prefix: String,
+    #[serde(rename = "RECLUSTER_CHANNEL", default = "default_recluster_channel")]
+    recluster_channel: String,
 }
 
 #[derive(Debug, Clone)]
@@ -102,6 +555,315 @@ pub struct Settings {
     pub embeddings_batch_size: usize,
     pub llm_max_concurrency: usize,
     pub spike_history_ttl: Duration,
+    pub archive_enabled: bool,
+    pub redis_archive_prefix: String,
+    /// How long archived chunks are kept, counted from their creation time.
+    pub archive_ttl: Duration,
+    pub backfill_result_prefix: String,
+    /// Admin backfills allowed to run at once, each for a different brand.
+    pub backfill_max_concurrent: usize,
+    /// Bearer token for the admin HTTP routes and gRPC control plane. Without it the routes
+    /// answer 401 to every call and gRPC does not start.
+    pub admin_api_token: Option<String>,
+    pub shadow_enabled: bool,
+    pub redis_shadow_prefix: String,
+    pub shadow_embeddings_provider: Option<String>,
+    pub shadow_llm_provider: Option<String>,
+    pub shadow_clustering_algorithm: Option<String>,
+    /// Shadow runs in flight at once; chunks that arrive while all are busy go unshadowed.
+    pub shadow_max_concurrent: usize,
+    /// Shadow runs still going after this are abandoned and counted as errors.
+    pub shadow_timeout: Duration,
+    pub redis_budget_prefix: String,
+    pub alert_channel: String,
+    pub llm_daily_budget_usd: Option<f64>,
+    pub llm_brand_daily_budget_usd: Option<f64>,
+    pub llm_price_input_per_1k: f64,
+    pub llm_price_output_per_1k: f64,
+    pub embedding_daily_budget_usd: Option<f64>,
+    pub embedding_brand_daily_budget_usd: Option<f64>,
+    pub embedding_price_per_1k: f64,
+    pub llm_batch_sentiment: bool,
+    pub payload_encryption_key: Option<String>,
+    pub payload_encryption_key_file: Option<String>,
+    pub payload_encryption_required: bool,
+    pub redis_trend_prefix: String,
+    pub sentiment_trend_window_days: u32,
+    pub sentiment_trend_flat_threshold: f32,
+    pub alert_rules_file: Option<String>,
+    pub alert_timeout: Duration,
+    pub dashboard_base_url: Option<String>,
+    pub cluster_recurrence_enabled: bool,
+    pub cluster_recurrence_threshold: f32,
+    pub cluster_recurrence_growth: f32,
+    pub cluster_recurrence_suppress_llm: bool,
+    pub cluster_recurrence_cache_size: usize,
+    pub redis_centroid_prefix: String,
+    pub centroid_ttl: Duration,
+    pub queue_prioritization: String,
+    /// How long `staleness` prioritization reuses the queue head timestamps it last read,
+    /// rather than peeking and decrypting every queue head on each fetch.
+    pub queue_staleness_sample: Duration,
+    pub cluster_sample_threshold: usize,
+    pub cluster_sample_size: usize,
+    pub sink_http_url: Option<String>,
+    pub sink_http_ndjson: bool,
+    pub sink_batch_size: usize,
+    pub sink_flush_interval: Duration,
+    pub sink_buffer_capacity: usize,
+    pub sink_timeout: Duration,
+    /// Where sinks spill records that overflow their buffer or fail to deliver.
+    pub sink_spill_dir: String,
+    pub redis_memory_sample_interval: Duration,
+    pub redis_memory_sample_keys: usize,
+    pub redis_memory_warn_bytes: u64,
+    pub redis_length_warn: u64,
+    pub analysis_cache_enabled: bool,
+    pub redis_analysis_cache_prefix: String,
+    pub analysis_cache_ttl: Duration,
+    /// Caches remote and ONNX embedding vectors by a hash of the cleaned text.
+    pub embedding_cache_enabled: bool,
+    pub redis_embedding_cache_prefix: String,
+    pub embedding_cache_ttl: Duration,
+    /// Stage names in run order. Stages a configured feature needs, e.g. `vector_export` when
+    /// `VECTOR_STORE` is set, `relevance` with `RELEVANCE_FILTER_ENABLED` or `taxonomy` with
+    /// `BRAND_TAXONOMY_FILE`, are added when the list leaves them out.
+    pub pipeline_stages: Vec<String>,
+    pub stage_timeouts: HashMap<String, Duration>,
+    /// Stages whose timeout fails the chunk; all others are skipped with default output.
+    pub stage_timeout_fail: HashSet<String>,
+    pub brand_onboarding_enabled: bool,
+    pub redis_brand_state_prefix: String,
+    pub onboarding_channel: String,
+    pub spike_baseline_mentions: u64,
+    pub spike_warmup_chunks: usize,
+    pub spike_warmup: Duration,
+    pub event_windows_file: Option<String>,
+    pub redis_event_windows_prefix: String,
+    pub retention_enabled: bool,
+    pub retention_interval: Duration,
+    pub redis_retention_prefix: String,
+    pub result_retention_entries: usize,
+    pub trend_rollup_retention_days: u32,
+    pub spike_history_retention: Duration,
+    pub failure_payload_preview_chars: usize,
+    pub http_timeout: Duration,
+    pub http_connect_timeout: Duration,
+    pub http_pool_max_idle_per_host: usize,
+    pub http_pool_idle_timeout: Duration,
+    pub http_proxy_url: Option<String>,
+    pub http_user_agent: String,
+    pub http_no_proxy: Option<String>,
+    pub egress_allowed_hosts: Vec<String>,
+    pub llm_rate_limit_rps: Option<f64>,
+    pub llm_rate_limit_burst: u32,
+    pub embedding_rate_limit_rps: Option<f64>,
+    pub embedding_rate_limit_burst: u32,
+    pub rate_limit_fleet_wide: bool,
+    pub redis_rate_limit_prefix: String,
+    pub embedding_model: Option<String>,
+    pub openai_base_url: String,
+    pub gemini_base_url: String,
+    pub anthropic_api_key: Option<String>,
+    pub anthropic_model: String,
+    pub anthropic_max_tokens: u32,
+    pub anthropic_base_url: String,
+    pub anthropic_version: String,
+    pub llm_providers: Vec<String>,
+    pub provider_probe_interval: Duration,
+    pub grpc_enabled: bool,
+    pub grpc_port: u16,
+    /// OpenAI-compatible endpoint (vLLM, LM Studio, ...) used by `LLM_PROVIDER=openai`
+    /// instead of `OPENAI_BASE_URL`; an API key is optional there.
+    pub llm_base_url: Option<String>,
+    /// `none`, `ids` (mention IDs) or `hash` (text digests) for mention text kept in results.
+    pub example_redaction: String,
+    pub noise_lists_file: Option<String>,
+    pub redis_noise_lists_prefix: String,
+    pub cluster_label_cache_enabled: bool,
+    pub redis_cluster_label_prefix: String,
+    pub cluster_label_ttl: Duration,
+    /// One structured call per cluster for summary, sentiment and topics; when false, or
+    /// for providers without it, summary and sentiment are requested separately.
+    pub llm_combined_analysis: bool,
+    pub llm_tiering_enabled: bool,
+    pub llm_cheap_provider: String,
+    pub llm_premium_min_cluster_size: usize,
+    pub llm_premium_min_negativity: f32,
+    pub llm_premium_on_spike: bool,
+    pub prompt_templates_file: Option<String>,
+    pub fleet_control_enabled: bool,
+    pub redis_control_channel: String,
+    pub slo_rules_file: Option<String>,
+    pub slo_eval_interval: Duration,
+    pub digest_enabled: bool,
+    pub digest_check_interval: Duration,
+    pub redis_digest_prefix: String,
+    pub digest_ttl: Duration,
+    pub digest_top_clusters: usize,
+    pub digest_webhook_url: Option<String>,
+    pub mention_dedup_enabled: bool,
+    pub mention_dedup_ttl: Duration,
+    pub redis_mention_dedup_prefix: String,
+    pub emotions_enabled: bool,
+    pub rating_metadata_key: String,
+    pub rating_sentiment_weight: f32,
+    pub media_metadata_key: String,
+    pub media_caption_provider: Option<String>,
+    pub media_caption_max_per_chunk: usize,
+    pub toxicity_scoring: Option<String>,
+    pub toxicity_lexicon_file: Option<String>,
+    pub toxicity_threshold: f32,
+    /// Share of chunks, 0–100, whose full result and intermediate artifacts are copied
+    /// to `{REDIS_QA_PREFIX}:{brand}`.
+    pub qa_sample_percent: f64,
+    pub redis_qa_prefix: String,
+    pub qa_sample_max_entries: usize,
+    pub qa_sample_ttl: Duration,
+    pub entities_enabled: bool,
+    /// Adds the `relevance` stage ahead of `embed` when `PIPELINE_STAGES` leaves it out.
+    pub relevance_filter_enabled: bool,
+    /// `keyword` or `llm`, for the `relevance` pipeline stage.
+    pub relevance_mode: String,
+    /// `drop` or `downweight` for mentions below `relevance_threshold`.
+    pub relevance_action: String,
+    pub relevance_threshold: f32,
+    pub relevance_terms_file: Option<String>,
+    pub relevance_batch_size: usize,
+    pub intent_enabled: bool,
+    pub quotes_enabled: bool,
+    pub preprocess_wasm_hook: Option<String>,
+    pub preprocess_wasm_fuel: u64,
+    /// `formula` or `llm`; severity isn't scored when unset or `off`.
+    pub severity_scoring: Option<String>,
+    pub severity_volume_weight: f32,
+    pub severity_negativity_weight: f32,
+    pub severity_spike_weight: f32,
+    /// Cluster size at which the volume component of severity maxes out.
+    pub severity_volume_saturation: usize,
+    /// JSON object keyed by brand with aliases, sub-brands and products, for the
+    /// `taxonomy` pipeline stage, which is added ahead of `embed` when this is set.
+    pub brand_taxonomy_file: Option<String>,
+    /// `reported` or `agreement`; confidence isn't estimated when unset or `off`.
+    pub confidence_mode: Option<String>,
+    pub confidence_samples: usize,
+    /// Clusters whose lowest confidence falls below this are flagged `lowConfidence`.
+    pub confidence_threshold: f32,
+    pub llm_model_overrides_enabled: bool,
+    pub llm_model_overrides_file: Option<String>,
+    pub redis_llm_override_prefix: String,
+    /// How long a brand's override lookup is reused before Redis is asked again.
+    pub llm_model_override_refresh: Duration,
+    /// `fast_path` answers chunks past their deadline with heuristics instead of LLM calls;
+    /// `flag` processes them normally. Both mark the result expired.
+    pub deadline_action: String,
+    /// Language summaries are written in regardless of the mentions' language, e.g. `English`.
+    pub summary_language: Option<String>,
+    /// Above 1, uncached clusters are analysed in combined requests of up to this many
+    /// clusters instead of one request each.
+    pub llm_max_clusters_per_request: usize,
+    /// Keep per-day topic counts per brand and report week-over-week topic movement.
+    pub topic_trends_enabled: bool,
+    /// Relative week-over-week change a topic needs to count as rising or falling.
+    pub topic_trend_change_threshold: f64,
+    /// Weighted count a topic needs in either week to be reported.
+    pub topic_trend_min_count: f64,
+    pub topic_trend_limit: usize,
+    /// One API key per line, pooled with the provider's comma-separated `*_API_KEY` keys.
+    pub llm_api_keys_file: Option<String>,
+    pub embedding_api_keys_file: Option<String>,
+    /// `cluster_id` keys spike history by the chunk-local cluster number; `fingerprint` by a
+    /// hash of the cluster's top terms, so unrelated clusters sharing a number don't share a
+    /// baseline.
+    pub spike_history_key: String,
+    /// With fingerprint keys, a cluster with no fingerprint history yet falls back to the
+    /// history under its cluster number while the new keys fill up.
+    pub spike_history_migrate: bool,
+    /// `redis` or `file`; chunk lifecycle events aren't journaled when `off`.
+    pub journal_sink: String,
+    pub journal_redis_stream: String,
+    /// Directory of daily `journal-YYYY-MM-DD.jsonl` files for the `file` sink.
+    pub journal_dir: String,
+    /// How long journal entries are kept before they are trimmed or their files deleted.
+    pub journal_retention: Duration,
+    /// Filter instruction-like phrases out of mention text and wrap each mention in
+    /// `<mention>` delimiters before it reaches a remote LLM.
+    pub prompt_sanitization: bool,
+    /// Keep per-day n-gram counts per brand and flag clusters whose vocabulary is mostly new.
+    pub novelty_detection_enabled: bool,
+    /// Days of n-gram history a term must be absent from to count as new.
+    pub novelty_window_days: u64,
+    /// Share of a cluster's top n-grams that must be new for it to count as emerging.
+    pub novelty_threshold: f32,
+    pub novelty_min_mentions: usize,
+    /// JSON routing rules that send chunks to pipeline variants by source, brand tier and size.
+    pub pipeline_routes_file: Option<String>,
+    /// Sentence-transformer model for `EMBEDDINGS_PROVIDER=onnx`.
+    pub embeddings_onnx_model: Option<String>,
+    /// `tokenizer.json` for the ONNX model; defaults to the one beside it.
+    pub embeddings_onnx_tokenizer: Option<String>,
+    /// Mention text is truncated to this many tokens before ONNX inference.
+    pub embeddings_onnx_max_tokens: usize,
+    /// Base URL of the text-embeddings-inference server for `EMBEDDINGS_PROVIDER=tei`.
+    pub tei_url: Option<String>,
+    /// Overrides the `max_client_batch_size` the TEI server reports.
+    pub tei_max_batch_size: Option<usize>,
+    pub cohere_api_key: Option<String>,
+    pub cohere_embed_model: String,
+    pub cohere_base_url: String,
+    /// Process RSS above which the worker throttles itself; unset disables the check.
+    pub throttle_max_rss_mb: Option<u64>,
+    /// Runtime global-queue depth above which the worker throttles itself.
+    pub throttle_max_task_backlog: Option<usize>,
+    /// Share of each limit both signals must fall below before throttling lifts.
+    pub throttle_resume_ratio: f64,
+    pub throttle_sample_interval: Duration,
+    /// Pause before each queue fetch while throttled.
+    pub throttle_fetch_delay: Duration,
+    /// Base latency of a `simulated` provider call.
+    pub simulated_latency: Duration,
+    /// Largest fraction either way a simulated call's base latency varies by.
+    pub simulated_latency_jitter: f64,
+    pub simulated_latency_per_text: Duration,
+    /// Share of simulated calls that fail after their latency.
+    pub simulated_error_rate: f64,
+    /// Share of simulated calls that hang for `simulated_stall` and then fail.
+    pub simulated_stall_rate: f64,
+    pub simulated_stall: Duration,
+    /// `qdrant` or `pgvector`, which adds the `vector_export` stage; `off` exports nothing.
+    pub vector_store: String,
+    /// Qdrant base URL, or the Postgres connection string for `pgvector`.
+    pub vector_store_url: Option<String>,
+    pub vector_store_api_key: Option<String>,
+    /// Each brand gets its own `<prefix>_<brand>` collection or table.
+    pub vector_store_collection_prefix: String,
+    /// Export every mention embedding alongside the cluster centroids.
+    pub vector_store_export_mentions: bool,
+    /// `single-cluster` puts a chunk's mentions in one cluster; `threshold` groups them by
+    /// `CLUSTERING_SIMILARITY_THRESHOLD` under `DISTANCE_METRIC`.
+    pub clustering_algorithm: String,
+    pub clustering_similarity_threshold: f32,
+    /// `cosine`, `euclidean` or `dot`, for clustering, recurrence matching, sampling and the
+    /// vector store. Similarity thresholds apply to whichever is chosen.
+    pub distance_metric: String,
+    /// L2-normalise every embedding, so providers returning unnormalised vectors compare
+    /// consistently under `euclidean` and `dot`.
+    pub embedding_normalize: bool,
+    /// Limit on one chunk's provider embedding call, batches included; past it the chunk
+    /// is embedded with local n-gram vectors instead.
+    pub embedding_timeout: Duration,
+    /// Periodically re-cluster each brand's archived mentions across chunks; needs `ARCHIVE_ENABLED`.
+    pub recluster_enabled: bool,
+    pub recluster_interval: Duration,
+    /// How far back each re-clustering run reads the chunk archive.
+    pub recluster_window: Duration,
+    /// Similarity under `DISTANCE_METRIC` a mention needs to a cluster's centroid to join it.
+    pub recluster_similarity_threshold: f32,
+    /// Most recent mentions per brand a run clusters; older ones in the window are left out.
+    pub recluster_max_mentions: usize,
+    pub redis_recluster_prefix: String,
+    /// Pub/sub channel each brand's re-clustering report is published on.
+    pub recluster_channel: String,
 }
 
 impl Settings {
@@ -110,12 +872,103 @@ impl Settings {
         Ok(Self::from_raw(raw))
     }
 
+    /// Defaults for everything but the Redis URL, for unit tests.
+    #[cfg(test)]
+    pub(crate) fn for_tests() -> Self {
+        let raw: RawSettings =
+            serde_json::from_value(serde_json::json!({ "REDIS_URL": "redis://localhost" })).expect("test settings");
+        Self::from_raw(raw)
+    }
+
+    /// Parses settings as at startup, with `.env` read over the process environment so edited
+    /// values win. The environment itself is left untouched, and the worker keeps `current`'s
+    /// ID so metrics, locks and heartbeats don't change owner mid-process.
+    pub fn reload_from_env(current: &Settings) -> Result<Self, envy::Error> {
+        let mut vars: HashMap<String, String> = std::env::vars().collect();
+        if let Ok(entries) = dotenvy::dotenv_iter() {
+            vars.extend(entries.flatten());
+        }
+        let raw: RawSettings = envy::from_iter(vars)?;
+        Ok(Self {
+            worker_id: current.worker_id.clone(),
+            ..Self::from_raw(raw)
+        })
+    }
+
+    /// Settings for a pipeline that must not touch live per-brand state, such as the shadow
+    /// and backfill pipelines: results and every store the stages write (spikes, trends, topic
+    /// and n-gram windows, centroids, labels, dedup, onboarding, QA samples and vector
+    /// collections) move under `namespace`. The analysis and embedding caches stay shared;
+    /// they're keyed by model, prompts and input, so an entry holds for any pipeline.
+    pub fn namespaced(&self, namespace: &str) -> Self {
+        Self {
+            redis_result_prefix: namespace.to_string(),
+            redis_spike_prefix: format!("{namespace}:spike"),
+            redis_trend_prefix: format!("{namespace}:trend"),
+            redis_centroid_prefix: format!("{namespace}:centroids"),
+            redis_cluster_label_prefix: format!("{namespace}:labels"),
+            redis_mention_dedup_prefix: format!("{namespace}:dedup"),
+            redis_brand_state_prefix: format!("{namespace}:brand_state"),
+            onboarding_channel: format!("{namespace}:{}", self.onboarding_channel),
+            redis_qa_prefix: format!("{namespace}:qa"),
+            vector_store_collection_prefix: format!("{namespace}_{}", self.vector_store_collection_prefix),
+            ..self.clone()
+        }
+    }
+
+    /// Model behind the configured LLM provider, if it has one. Health-routed
+    /// providers have no single model.
+    pub fn llm_model(&self) -> Option<&str> {
+        if self.llm_providers.len() > 1 {
+            return None;
+        }
+        match self.llm_provider.as_str() {
+            "openai" => Some(&self.openai_model),
+            "gemini" => Some(&self.gemini_model),
+            "anthropic" => Some(&self.anthropic_model),
+            "simulated" => Some("simulated"),
+            _ => None,
+        }
+    }
+
+    /// Model behind the configured embedding provider; local embeddings are hashed n-grams.
+    pub fn embedding_model(&self) -> Option<&str> {
+        match self.embeddings_provider.as_str() {
+            "local" => Some("char-ngram"),
+            "onnx" => self.embedding_model.as_deref().or_else(|| {
+                self.embeddings_onnx_model
+                    .as_deref()
+                    .and_then(|path| std::path::Path::new(path).file_stem())
+                    .and_then(|stem| stem.to_str())
+            }),
+            "gemini" => Some(self.embedding_model.as_deref().unwrap_or("text-embedding-004")),
+            "cohere" => Some(&self.cohere_embed_model),
+            "simulated" => Some("simulated"),
+            _ => self.embedding_model.as_deref(),
+        }
+    }
+
     fn from_raw(raw: RawSettings) -> Self {
         let worker_id = raw
             .worker_id
             .filter(|id| !id.trim().is_empty())
             .unwrap_or_else(|| format!("worker-{}", Uuid::new_v4()))
             .to_lowercase();
+        let mut pipeline_stages: Vec<String> = raw
+            .pipeline_stages
+            .split(',')
+            .map(|stage| stage.trim().to_lowercase().replace('-', "_"))
+            .filter(|stage| !stage.is_empty())
+            .collect();
+        if matches!(raw.vector_store.trim().to_lowercase().as_str(), "qdrant" | "pgvector") {
+            ensure_stage(&mut pipeline_stages, "vector_export", None);
+        }
+        if raw.relevance_filter_enabled {
+            ensure_stage(&mut pipeline_stages, "relevance", Some("embed"));
+        }
+        if raw.brand_taxonomy_file.as_deref().is_some_and(|path| !path.trim().is_empty()) {
+            ensure_stage(&mut pipeline_stages, "taxonomy", Some("embed"));
+        }
 
         Self {
             redis_url: raw.redis_url,
@@ -149,6 +1002,306 @@ impl Settings {
             embeddings_batch_size: raw.embeddings_batch_size.max(1),
             llm_max_concurrency: raw.llm_max_concurrency.max(1),
             spike_history_ttl: Duration::from_secs(raw.spike_history_ttl_sec.max(60)),
+            archive_enabled: raw.archive_enabled,
+            redis_archive_prefix: raw.redis_archive_prefix,
+            archive_ttl: Duration::from_secs(raw.archive_ttl_sec.max(60)),
+            backfill_result_prefix: raw.backfill_result_prefix,
+            backfill_max_concurrent: raw.backfill_max_concurrent.max(1),
+            admin_api_token: raw.admin_api_token.filter(|s| !s.trim().is_empty()),
+            shadow_enabled: raw.shadow_enabled,
+            redis_shadow_prefix: raw.redis_shadow_prefix,
+            shadow_embeddings_provider: raw
+                .shadow_embeddings_provider
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_ascii_lowercase()),
+            shadow_llm_provider: raw
+                .shadow_llm_provider
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_ascii_lowercase()),
+            shadow_clustering_algorithm: raw
+                .shadow_clustering_algorithm
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| clustering_algorithm(&s)),
+            shadow_max_concurrent: raw.shadow_max_concurrent.max(1),
+            shadow_timeout: Duration::from_secs(raw.shadow_timeout_sec.max(1)),
+            redis_budget_prefix: raw.redis_budget_prefix,
+            alert_channel: raw.alert_channel,
+            llm_daily_budget_usd: raw.llm_daily_budget_usd.filter(|value| *value > 0.0),
+            llm_brand_daily_budget_usd: raw.llm_brand_daily_budget_usd.filter(|value| *value > 0.0),
+            llm_price_input_per_1k: raw.llm_price_input_per_1k.max(0.0),
+            llm_price_output_per_1k: raw.llm_price_output_per_1k.max(0.0),
+            embedding_daily_budget_usd: raw.embedding_daily_budget_usd.filter(|value| *value > 0.0),
+            embedding_brand_daily_budget_usd: raw.embedding_brand_daily_budget_usd.filter(|value| *value > 0.0),
+            embedding_price_per_1k: raw.embedding_price_per_1k.max(0.0),
+            llm_batch_sentiment: raw.llm_batch_sentiment,
+            payload_encryption_key: raw.payload_encryption_key.filter(|s| !s.trim().is_empty()),
+            payload_encryption_key_file: raw.payload_encryption_key_file.filter(|s| !s.trim().is_empty()),
+            payload_encryption_required: raw.payload_encryption_required,
+            redis_trend_prefix: raw.redis_trend_prefix,
+            sentiment_trend_window_days: raw.sentiment_trend_window_days.max(1),
+            sentiment_trend_flat_threshold: raw.sentiment_trend_flat_threshold.max(0.0),
+            alert_rules_file: raw.alert_rules_file.filter(|s| !s.trim().is_empty()),
+            alert_timeout: Duration::from_secs(raw.alert_timeout_sec.max(1)),
+            dashboard_base_url: raw
+                .dashboard_base_url
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.trim_end_matches('/').to_string()),
+            cluster_recurrence_enabled: raw.cluster_recurrence_enabled,
+            cluster_recurrence_threshold: raw.cluster_recurrence_threshold.clamp(0.0, 1.0),
+            cluster_recurrence_growth: raw.cluster_recurrence_growth.max(0.0),
+            cluster_recurrence_suppress_llm: raw.cluster_recurrence_suppress_llm,
+            cluster_recurrence_cache_size: raw.cluster_recurrence_cache_size.max(1),
+            redis_centroid_prefix: raw.redis_centroid_prefix,
+            centroid_ttl: Duration::from_secs(raw.centroid_ttl_sec.max(60)),
+            queue_prioritization: raw.queue_prioritization.to_ascii_lowercase(),
+            queue_staleness_sample: Duration::from_secs_f64(raw.queue_staleness_sample_sec.max(0.0)),
+            cluster_sample_threshold: raw.cluster_sample_threshold.max(1),
+            cluster_sample_size: raw.cluster_sample_size.max(1),
+            sink_http_url: raw.sink_http_url.filter(|s| !s.trim().is_empty()),
+            sink_http_ndjson: raw.sink_http_ndjson,
+            sink_batch_size: raw.sink_batch_size.max(1),
+            sink_flush_interval: Duration::from_secs(raw.sink_flush_interval_sec.max(1)),
+            sink_buffer_capacity: raw.sink_buffer_capacity.max(1),
+            sink_timeout: Duration::from_secs(raw.sink_timeout_sec.max(1)),
+            sink_spill_dir: raw.sink_spill_dir,
+            redis_memory_sample_interval: Duration::from_secs(raw.redis_memory_sample_interval_sec.max(10)),
+            redis_memory_sample_keys: raw.redis_memory_sample_keys.max(1),
+            redis_memory_warn_bytes: raw.redis_memory_warn_bytes,
+            redis_length_warn: raw.redis_length_warn,
+            analysis_cache_enabled: raw.analysis_cache_enabled,
+            redis_analysis_cache_prefix: raw.redis_analysis_cache_prefix,
+            analysis_cache_ttl: Duration::from_secs(raw.analysis_cache_ttl_sec.max(60)),
+            embedding_cache_enabled: raw.embedding_cache_enabled,
+            redis_embedding_cache_prefix: raw.redis_embedding_cache_prefix,
+            embedding_cache_ttl: Duration::from_secs(raw.embedding_cache_ttl_sec.max(60)),
+            pipeline_stages,
+            stage_timeouts: parse_stage_map(&raw.stage_timeouts_ms)
+                .into_iter()
+                .filter_map(|(stage, ms)| ms.parse::<u64>().ok().map(|ms| (stage, Duration::from_millis(ms))))
+                .collect(),
+            stage_timeout_fail: parse_stage_map(&raw.stage_timeout_policy)
+                .into_iter()
+                .filter(|(_, policy)| policy.eq_ignore_ascii_case("fail"))
+                .map(|(stage, _)| stage)
+                .collect(),
+            brand_onboarding_enabled: raw.brand_onboarding_enabled,
+            redis_brand_state_prefix: raw.redis_brand_state_prefix,
+            onboarding_channel: raw.onboarding_channel,
+            spike_baseline_mentions: raw.spike_baseline_mentions,
+            spike_warmup_chunks: raw.spike_warmup_chunks,
+            spike_warmup: Duration::from_secs(raw.spike_warmup_hours * 3600),
+            event_windows_file: raw.event_windows_file.filter(|path| !path.trim().is_empty()),
+            redis_event_windows_prefix: raw.redis_event_windows_prefix,
+            retention_enabled: raw.retention_enabled,
+            retention_interval: Duration::from_secs(raw.retention_interval_sec.max(60)),
+            redis_retention_prefix: raw.redis_retention_prefix,
+            result_retention_entries: raw.result_retention_entries.max(1),
+            trend_rollup_retention_days: raw.trend_rollup_retention_days,
+            spike_history_retention: Duration::from_secs(raw.spike_history_retention_days * 86_400),
+            failure_payload_preview_chars: raw.failure_payload_preview_chars,
+            http_timeout: Duration::from_secs(raw.http_timeout_sec.max(1)),
+            http_connect_timeout: Duration::from_secs(raw.http_connect_timeout_sec.max(1)),
+            http_pool_max_idle_per_host: raw.http_pool_max_idle_per_host,
+            http_pool_idle_timeout: Duration::from_secs(raw.http_pool_idle_timeout_sec),
+            http_proxy_url: raw
+                .http_proxy_url
+                .or(raw.https_proxy)
+                .filter(|s| !s.trim().is_empty()),
+            http_user_agent: raw.http_user_agent,
+            http_no_proxy: raw.no_proxy.filter(|s| !s.trim().is_empty()),
+            egress_allowed_hosts: raw
+                .egress_allowed_hosts
+                .split(',')
+                .map(|host| host.trim().to_ascii_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            llm_rate_limit_rps: raw.llm_rate_limit_rps.filter(|value| *value > 0.0),
+            llm_rate_limit_burst: raw.llm_rate_limit_burst.max(1),
+            embedding_rate_limit_rps: raw.embedding_rate_limit_rps.filter(|value| *value > 0.0),
+            embedding_rate_limit_burst: raw.embedding_rate_limit_burst.max(1),
+            rate_limit_fleet_wide: raw.rate_limit_fleet_wide,
+            redis_rate_limit_prefix: raw.redis_rate_limit_prefix,
+            embedding_model: raw.embedding_model.filter(|s| !s.trim().is_empty()),
+            openai_base_url: raw.openai_base_url.trim_end_matches('/').to_string(),
+            gemini_base_url: raw.gemini_base_url.trim_end_matches('/').to_string(),
+            anthropic_api_key: raw.anthropic_api_key.filter(|s| !s.trim().is_empty()),
+            anthropic_model: raw.anthropic_model,
+            anthropic_max_tokens: raw.anthropic_max_tokens.max(1),
+            anthropic_base_url: raw.anthropic_base_url.trim_end_matches('/').to_string(),
+            anthropic_version: raw.anthropic_version,
+            llm_providers: raw
+                .llm_providers
+                .split(',')
+                .map(|provider| provider.trim().to_ascii_lowercase())
+                .filter(|provider| !provider.is_empty())
+                .collect(),
+            provider_probe_interval: Duration::from_secs(raw.provider_probe_interval_sec.max(1)),
+            grpc_enabled: raw.grpc_enabled,
+            grpc_port: raw.grpc_port,
+            llm_base_url: raw
+                .llm_base_url
+                .filter(|s| !s.trim().is_empty())
+                .map(|url| url.trim_end_matches('/').to_string()),
+            example_redaction: raw.example_redaction.trim().to_ascii_lowercase(),
+            noise_lists_file: raw.noise_lists_file.filter(|path| !path.trim().is_empty()),
+            redis_noise_lists_prefix: raw.redis_noise_lists_prefix,
+            cluster_label_cache_enabled: raw.cluster_label_cache_enabled,
+            redis_cluster_label_prefix: raw.redis_cluster_label_prefix,
+            cluster_label_ttl: Duration::from_secs(raw.cluster_label_ttl_sec.max(60)),
+            llm_combined_analysis: raw.llm_combined_analysis,
+            llm_tiering_enabled: raw.llm_tiering_enabled,
+            llm_cheap_provider: raw.llm_cheap_provider.trim().to_ascii_lowercase(),
+            llm_premium_min_cluster_size: raw.llm_premium_min_cluster_size.max(1),
+            llm_premium_min_negativity: raw.llm_premium_min_negativity.clamp(0.0, 1.0),
+            llm_premium_on_spike: raw.llm_premium_on_spike,
+            prompt_templates_file: raw.prompt_templates_file.filter(|path| !path.trim().is_empty()),
+            fleet_control_enabled: raw.fleet_control_enabled,
+            redis_control_channel: raw.redis_control_channel,
+            slo_rules_file: raw.slo_rules_file.filter(|path| !path.trim().is_empty()),
+            slo_eval_interval: Duration::from_secs(raw.slo_eval_interval_sec.max(1)),
+            digest_enabled: raw.digest_enabled,
+            digest_check_interval: Duration::from_secs(raw.digest_check_interval_sec.max(60)),
+            redis_digest_prefix: raw.redis_digest_prefix,
+            // Kept at least two days so the next digest can compare against it.
+            digest_ttl: Duration::from_secs(raw.digest_ttl_days.max(2) * 86_400),
+            digest_top_clusters: raw.digest_top_clusters.max(1),
+            digest_webhook_url: raw.digest_webhook_url.filter(|url| !url.trim().is_empty()),
+            mention_dedup_enabled: raw.mention_dedup_enabled,
+            mention_dedup_ttl: Duration::from_secs(raw.mention_dedup_ttl_sec.max(60)),
+            redis_mention_dedup_prefix: raw.redis_mention_dedup_prefix,
+            emotions_enabled: raw.emotions_enabled,
+            rating_metadata_key: raw.rating_metadata_key,
+            rating_sentiment_weight: raw.rating_sentiment_weight.clamp(0.0, 1.0),
+            media_metadata_key: raw.media_metadata_key,
+            media_caption_provider: raw
+                .media_caption_provider
+                .map(|provider| provider.trim().to_lowercase())
+                .filter(|provider| !provider.is_empty() && provider != "none"),
+            media_caption_max_per_chunk: raw.media_caption_max_per_chunk,
+            toxicity_scoring: raw
+                .toxicity_scoring
+                .map(|mode| mode.trim().to_lowercase())
+                .filter(|mode| !mode.is_empty() && mode != "off"),
+            toxicity_lexicon_file: raw.toxicity_lexicon_file.filter(|path| !path.trim().is_empty()),
+            toxicity_threshold: raw.toxicity_threshold.clamp(0.0, 1.0),
+            qa_sample_percent: raw.qa_sample_percent.clamp(0.0, 100.0),
+            redis_qa_prefix: raw.redis_qa_prefix,
+            qa_sample_max_entries: raw.qa_sample_max_entries.max(1),
+            qa_sample_ttl: Duration::from_secs(raw.qa_sample_ttl_days.max(1) * 86_400),
+            entities_enabled: raw.entities_enabled,
+            relevance_filter_enabled: raw.relevance_filter_enabled,
+            relevance_mode: raw.relevance_mode.trim().to_lowercase(),
+            relevance_action: match raw.relevance_action.trim().to_lowercase().as_str() {
+                "downweight" => "downweight".to_string(),
+                _ => "drop".to_string(),
+            },
+            relevance_threshold: raw.relevance_threshold.clamp(0.0, 1.0),
+            relevance_terms_file: raw.relevance_terms_file.filter(|path| !path.trim().is_empty()),
+            relevance_batch_size: raw.relevance_batch_size.max(1),
+            intent_enabled: raw.intent_enabled,
+            quotes_enabled: raw.quotes_enabled,
+            preprocess_wasm_hook: raw.preprocess_wasm_hook.filter(|path| !path.trim().is_empty()),
+            preprocess_wasm_fuel: raw.preprocess_wasm_fuel.max(1),
+            severity_scoring: raw
+                .severity_scoring
+                .map(|mode| mode.trim().to_lowercase())
+                .filter(|mode| !mode.is_empty() && mode != "off"),
+            severity_volume_weight: raw.severity_volume_weight.max(0.0),
+            severity_negativity_weight: raw.severity_negativity_weight.max(0.0),
+            severity_spike_weight: raw.severity_spike_weight.max(0.0),
+            severity_volume_saturation: raw.severity_volume_saturation.max(1),
+            brand_taxonomy_file: raw.brand_taxonomy_file.filter(|path| !path.trim().is_empty()),
+            confidence_mode: raw
+                .confidence_mode
+                .map(|mode| mode.trim().to_lowercase())
+                .filter(|mode| !mode.is_empty() && mode != "off"),
+            confidence_samples: raw.confidence_samples.max(2),
+            confidence_threshold: raw.confidence_threshold.clamp(0.0, 1.0),
+            llm_model_overrides_enabled: raw.llm_model_overrides_enabled,
+            llm_model_overrides_file: raw.llm_model_overrides_file.filter(|path| !path.trim().is_empty()),
+            redis_llm_override_prefix: raw.redis_llm_override_prefix,
+            llm_model_override_refresh: Duration::from_secs(raw.llm_model_override_refresh_secs),
+            deadline_action: match raw.deadline_action.trim().to_lowercase().as_str() {
+                "flag" => "flag".to_string(),
+                _ => "fast_path".to_string(),
+            },
+            summary_language: raw
+                .summary_language
+                .map(|language| language.trim().to_string())
+                .filter(|language| !language.is_empty()),
+            llm_max_clusters_per_request: raw.llm_max_clusters_per_request.max(1),
+            topic_trends_enabled: raw.topic_trends_enabled,
+            topic_trend_change_threshold: raw.topic_trend_change_threshold.max(0.0),
+            topic_trend_min_count: raw.topic_trend_min_count.max(0.0),
+            topic_trend_limit: raw.topic_trend_limit.max(1),
+            llm_api_keys_file: raw.llm_api_keys_file.filter(|path| !path.trim().is_empty()),
+            embedding_api_keys_file: raw.embedding_api_keys_file.filter(|path| !path.trim().is_empty()),
+            spike_history_key: match raw.spike_history_key.trim().to_lowercase().as_str() {
+                "fingerprint" => "fingerprint".to_string(),
+                _ => "cluster_id".to_string(),
+            },
+            spike_history_migrate: raw.spike_history_migrate,
+            journal_sink: match raw.journal_sink.trim().to_lowercase().as_str() {
+                "redis" => "redis".to_string(),
+                "file" => "file".to_string(),
+                _ => "off".to_string(),
+            },
+            journal_redis_stream: raw.journal_redis_stream,
+            journal_dir: raw.journal_dir,
+            journal_retention: Duration::from_secs(raw.journal_retention_days.max(1) * 86_400),
+            prompt_sanitization: raw.prompt_sanitization,
+            novelty_detection_enabled: raw.novelty_detection_enabled,
+            novelty_window_days: raw.novelty_window_days.clamp(1, 30),
+            novelty_threshold: raw.novelty_threshold.clamp(0.0, 1.0),
+            novelty_min_mentions: raw.novelty_min_mentions.max(1),
+            pipeline_routes_file: raw.pipeline_routes_file.filter(|path| !path.trim().is_empty()),
+            embeddings_onnx_model: raw.embeddings_onnx_model.filter(|path| !path.trim().is_empty()),
+            embeddings_onnx_tokenizer: raw.embeddings_onnx_tokenizer.filter(|path| !path.trim().is_empty()),
+            embeddings_onnx_max_tokens: raw.embeddings_onnx_max_tokens.clamp(8, 8192),
+            tei_url: raw.tei_url.filter(|url| !url.trim().is_empty()),
+            tei_max_batch_size: raw.tei_max_batch_size.filter(|size| *size > 0),
+            cohere_api_key: raw.cohere_api_key.filter(|s| !s.trim().is_empty()),
+            cohere_embed_model: raw.cohere_embed_model,
+            cohere_base_url: raw.cohere_base_url.trim_end_matches('/').to_string(),
+            throttle_max_rss_mb: raw.throttle_max_rss_mb.filter(|mb| *mb > 0),
+            throttle_max_task_backlog: raw.throttle_max_task_backlog.filter(|max| *max > 0),
+            throttle_resume_ratio: raw.throttle_resume_ratio.clamp(0.1, 1.0),
+            throttle_sample_interval: Duration::from_secs(raw.throttle_sample_interval_sec.max(1)),
+            throttle_fetch_delay: Duration::from_millis(raw.throttle_fetch_delay_ms),
+            simulated_latency: Duration::from_millis(raw.simulated_latency_ms),
+            simulated_latency_jitter: raw.simulated_latency_jitter.clamp(0.0, 1.0),
+            simulated_latency_per_text: Duration::from_millis(raw.simulated_latency_per_text_ms),
+            simulated_error_rate: raw.simulated_error_rate.clamp(0.0, 1.0),
+            simulated_stall_rate: raw.simulated_stall_rate.clamp(0.0, 1.0),
+            simulated_stall: Duration::from_millis(raw.simulated_stall_ms),
+            vector_store: match raw.vector_store.trim().to_lowercase().as_str() {
+                "qdrant" => "qdrant".to_string(),
+                "pgvector" => "pgvector".to_string(),
+                _ => "off".to_string(),
+            },
+            vector_store_url: raw
+                .vector_store_url
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty()),
+            vector_store_api_key: raw.vector_store_api_key.filter(|key| !key.trim().is_empty()),
+            vector_store_collection_prefix: raw.vector_store_collection_prefix,
+            vector_store_export_mentions: raw.vector_store_export_mentions,
+            clustering_algorithm: clustering_algorithm(&raw.clustering_algorithm),
+            clustering_similarity_threshold: raw.clustering_similarity_threshold.clamp(0.0, 1.0),
+            distance_metric: match raw.distance_metric.trim().to_lowercase().as_str() {
+                "euclidean" => "euclidean".to_string(),
+                "dot" => "dot".to_string(),
+                _ => "cosine".to_string(),
+            },
+            embedding_normalize: raw.embedding_normalize,
+            embedding_timeout: Duration::from_secs(raw.embedding_timeout_sec.max(1)),
+            recluster_enabled: raw.recluster_enabled,
+            recluster_interval: Duration::from_secs(raw.recluster_interval_sec.max(300)),
+            recluster_window: Duration::from_secs(raw.recluster_window_hours.clamp(1, 24 * 30) * 3600),
+            recluster_similarity_threshold: raw.recluster_similarity_threshold.clamp(0.0, 1.0),
+            recluster_max_mentions: raw.recluster_max_mentions.max(2),
+            redis_recluster_prefix: raw.redis_recluster_prefix,
+            recluster_channel: raw.recluster_channel,
         }
     }
 }
@@ -245,6 +1398,14 @@ fn default_embeddings_batch_size() -> usize {
     32
 }
 
+fn default_embedding_timeout_sec() -> u64 {
+    60
+}
+
+fn default_embeddings_onnx_max_tokens() -> usize {
+    256
+}
+
 fn default_llm_max_concurrency() -> usize {
     4
 }
@@ -252,3 +1413,570 @@ fn default_llm_max_concurrency() -> usize {
 fn default_spike_history_ttl_sec() -> u64 {
     86_400
 }
+
+
+fn default_archive_prefix() -> String {
+    "archive:brand".to_string()
+}
+
+fn default_archive_ttl_sec() -> u64 {
+    30 * 86_400
+}
+
+fn default_backfill_max_concurrent() -> usize {
+    1
+}
+
+fn default_backfill_result_prefix() -> String {
+    "backfill:brand".to_string()
+}
+
+fn default_clustering_algorithm() -> String {
+    "single-cluster".to_string()
+}
+
+fn default_clustering_similarity_threshold() -> f32 {
+    0.6
+}
+
+fn clustering_algorithm(raw: &str) -> String {
+    match raw.trim().to_lowercase().as_str() {
+        "threshold" => "threshold".to_string(),
+        _ => "single-cluster".to_string(),
+    }
+}
+
+fn default_shadow_max_concurrent() -> usize {
+    2
+}
+
+fn default_shadow_timeout_sec() -> u64 {
+    120
+}
+
+fn default_shadow_prefix() -> String {
+    "shadow:brand".to_string()
+}
+
+fn default_budget_prefix() -> String {
+    "budget".to_string()
+}
+
+fn default_alert_channel() -> String {
+    "alerts:worker".to_string()
+}
+
+fn default_llm_price_input_per_1k() -> f64 {
+    0.000_15
+}
+
+fn default_llm_price_output_per_1k() -> f64 {
+    0.000_6
+}
+
+fn default_embedding_price_per_1k() -> f64 {
+    0.000_02
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_trend_prefix() -> String {
+    "trend:brand".to_string()
+}
+
+fn default_sentiment_trend_window_days() -> u32 {
+    7
+}
+
+fn default_sentiment_trend_flat_threshold() -> f32 {
+    0.05
+}
+
+fn default_alert_timeout_sec() -> u64 {
+    10
+}
+
+fn default_cluster_recurrence_threshold() -> f32 {
+    0.95
+}
+
+fn default_cluster_recurrence_growth() -> f32 {
+    0.2
+}
+
+fn default_cluster_recurrence_cache_size() -> usize {
+    50
+}
+
+fn default_centroid_prefix() -> String {
+    "centroids:brand".to_string()
+}
+
+fn default_centroid_ttl_sec() -> u64 {
+    86_400
+}
+
+fn default_queue_prioritization() -> String {
+    "staleness".to_string()
+}
+
+fn default_queue_staleness_sample_sec() -> f64 {
+    5.0
+}
+
+fn default_cluster_sample_threshold() -> usize {
+    50
+}
+
+fn default_cluster_sample_size() -> usize {
+    30
+}
+
+fn default_sink_batch_size() -> usize {
+    100
+}
+
+fn default_sink_flush_interval_sec() -> u64 {
+    5
+}
+
+fn default_sink_buffer_capacity() -> usize {
+    1_000
+}
+
+fn default_sink_timeout_sec() -> u64 {
+    15
+}
+
+fn default_sink_spill_dir() -> String {
+    "sink-spill".to_string()
+}
+
+fn default_redis_memory_sample_interval_sec() -> u64 {
+    300
+}
+
+fn default_redis_memory_sample_keys() -> usize {
+    200
+}
+
+fn default_redis_memory_warn_bytes() -> u64 {
+    256 * 1024 * 1024
+}
+
+fn default_redis_length_warn() -> u64 {
+    10_000
+}
+
+fn default_redis_analysis_cache_prefix() -> String {
+    "analysis:cache".to_string()
+}
+
+fn default_analysis_cache_ttl_sec() -> u64 {
+    30 * 86_400
+}
+
+fn default_redis_embedding_cache_prefix() -> String {
+    "embedding:cache".to_string()
+}
+
+fn default_embedding_cache_ttl_sec() -> u64 {
+    7 * 86_400
+}
+
+fn default_pipeline_stages() -> String {
+    "preprocess,embed,cluster,analyze,spike".to_string()
+}
+
+/// Adds `stage` ahead of `before`, or last when `before` isn't listed, unless it's already there.
+fn ensure_stage(stages: &mut Vec<String>, stage: &str, before: Option<&str>) {
+    if stages.iter().any(|listed| listed == stage) {
+        return;
+    }
+    let at = before
+        .and_then(|before| stages.iter().position(|listed| listed == before))
+        .unwrap_or(stages.len());
+    stages.insert(at, stage.to_string());
+}
+
+fn default_stage_timeouts_ms() -> String {
+    "spike=2000".to_string()
+}
+
+fn parse_stage_map(raw: &str) -> Vec<(String, String)> {
+    raw.split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(stage, value)| (stage.trim().to_lowercase().replace('-', "_"), value.trim().to_string()))
+        .filter(|(stage, _)| !stage.is_empty())
+        .collect()
+}
+
+fn default_brand_state_prefix() -> String {
+    "state:brand".to_string()
+}
+
+fn default_onboarding_channel() -> String {
+    "brand:onboarding".to_string()
+}
+
+fn default_spike_baseline_mentions() -> u64 {
+    25
+}
+
+fn default_spike_warmup_chunks() -> usize {
+    3
+}
+
+fn default_event_windows_prefix() -> String {
+    "events:brand".to_string()
+}
+
+fn default_retention_interval_sec() -> u64 {
+    3600
+}
+
+fn default_retention_prefix() -> String {
+    "retention".to_string()
+}
+
+fn default_result_retention_entries() -> usize {
+    1000
+}
+
+fn default_trend_rollup_retention_days() -> u32 {
+    365
+}
+
+fn default_spike_history_retention_days() -> u64 {
+    14
+}
+
+fn default_failure_payload_preview_chars() -> usize {
+    256
+}
+
+fn default_http_timeout_sec() -> u64 {
+    30
+}
+
+fn default_http_connect_timeout_sec() -> u64 {
+    5
+}
+
+fn default_http_pool_max_idle_per_host() -> usize {
+    8
+}
+
+fn default_http_pool_idle_timeout_sec() -> u64 {
+    90
+}
+
+fn default_http_user_agent() -> String {
+    format!("brand-tracker-worker-rs/{}", env!("CARGO_PKG_VERSION"))
+}
+
+fn default_rate_limit_burst() -> u32 {
+    5
+}
+
+fn default_rate_limit_prefix() -> String {
+    "ratelimit".to_string()
+}
+
+fn default_openai_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_gemini_base_url() -> String {
+    "https://generativelanguage.googleapis.com".to_string()
+}
+
+fn default_simulated_latency_ms() -> u64 {
+    800
+}
+
+fn default_simulated_latency_jitter() -> f64 {
+    0.25
+}
+
+fn default_simulated_stall_ms() -> u64 {
+    60_000
+}
+
+fn default_throttle_resume_ratio() -> f64 {
+    0.8
+}
+
+fn default_throttle_sample_interval_sec() -> u64 {
+    5
+}
+
+fn default_throttle_fetch_delay_ms() -> u64 {
+    1_000
+}
+
+fn default_cohere_embed_model() -> String {
+    "embed-multilingual-v3.0".to_string()
+}
+
+fn default_cohere_base_url() -> String {
+    "https://api.cohere.com".to_string()
+}
+
+fn default_anthropic_model() -> String {
+    "claude-3-5-haiku-latest".to_string()
+}
+
+fn default_anthropic_max_tokens() -> u32 {
+    512
+}
+
+fn default_anthropic_base_url() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+fn default_anthropic_version() -> String {
+    "2023-06-01".to_string()
+}
+
+fn default_provider_probe_interval_sec() -> u64 {
+    30
+}
+
+fn default_grpc_port() -> u16 {
+    50051
+}
+
+fn default_example_redaction() -> String {
+    "none".to_string()
+}
+
+fn default_noise_lists_prefix() -> String {
+    "noise:brand".to_string()
+}
+
+fn default_cluster_label_prefix() -> String {
+    "labels:brand".to_string()
+}
+
+fn default_cluster_label_ttl_sec() -> u64 {
+    7 * 24 * 3600
+}
+
+fn default_llm_cheap_provider() -> String {
+    "mock".to_string()
+}
+
+fn default_llm_premium_min_cluster_size() -> usize {
+    20
+}
+
+fn default_llm_premium_min_negativity() -> f32 {
+    0.5
+}
+
+fn default_control_channel() -> String {
+    "workers:control".to_string()
+}
+
+fn default_slo_eval_interval_sec() -> u64 {
+    30
+}
+
+fn default_digest_check_interval_sec() -> u64 {
+    600
+}
+
+fn default_digest_prefix() -> String {
+    "digest".to_string()
+}
+
+fn default_digest_ttl_days() -> u64 {
+    30
+}
+
+fn default_digest_top_clusters() -> usize {
+    5
+}
+
+fn default_mention_dedup_ttl_sec() -> u64 {
+    6 * 3600
+}
+
+fn default_mention_dedup_prefix() -> String {
+    "dedup:brand".to_string()
+}
+
+fn default_rating_metadata_key() -> String {
+    "rating".to_string()
+}
+
+fn default_rating_sentiment_weight() -> f32 {
+    1.0
+}
+
+fn default_media_metadata_key() -> String {
+    "media".to_string()
+}
+
+fn default_media_caption_max_per_chunk() -> usize {
+    20
+}
+
+fn default_toxicity_threshold() -> f32 {
+    0.6
+}
+
+fn default_qa_prefix() -> String {
+    "qa:results".to_string()
+}
+
+fn default_qa_sample_max_entries() -> usize {
+    1000
+}
+
+fn default_qa_sample_ttl_days() -> u64 {
+    14
+}
+
+fn default_relevance_mode() -> String {
+    "keyword".to_string()
+}
+
+fn default_relevance_action() -> String {
+    "drop".to_string()
+}
+
+fn default_relevance_threshold() -> f32 {
+    0.5
+}
+
+fn default_relevance_batch_size() -> usize {
+    25
+}
+
+fn default_preprocess_wasm_fuel() -> u64 {
+    10_000_000
+}
+
+fn default_severity_volume_weight() -> f32 {
+    0.3
+}
+
+fn default_severity_negativity_weight() -> f32 {
+    0.4
+}
+
+fn default_severity_spike_weight() -> f32 {
+    0.3
+}
+
+fn default_severity_volume_saturation() -> usize {
+    200
+}
+
+fn default_confidence_samples() -> usize {
+    3
+}
+
+fn default_confidence_threshold() -> f32 {
+    0.5
+}
+
+fn default_redis_llm_override_prefix() -> String {
+    "llm:overrides".to_string()
+}
+
+fn default_llm_model_override_refresh_secs() -> u64 {
+    30
+}
+
+fn default_deadline_action() -> String {
+    "fast_path".to_string()
+}
+
+fn default_llm_max_clusters_per_request() -> usize {
+    1
+}
+
+fn default_topic_trend_change_threshold() -> f64 {
+    0.25
+}
+
+fn default_topic_trend_min_count() -> f64 {
+    3.0
+}
+
+fn default_topic_trend_limit() -> usize {
+    5
+}
+
+fn default_spike_history_key() -> String {
+    "cluster_id".to_string()
+}
+
+fn default_journal_sink() -> String {
+    "off".to_string()
+}
+
+fn default_journal_redis_stream() -> String {
+    "journal:chunks".to_string()
+}
+
+fn default_journal_dir() -> String {
+    "journal".to_string()
+}
+
+fn default_journal_retention_days() -> u64 {
+    90
+}
+
+fn default_novelty_window_days() -> u64 {
+    7
+}
+
+fn default_novelty_threshold() -> f32 {
+    0.6
+}
+
+fn default_novelty_min_mentions() -> usize {
+    3
+}
+
+fn default_vector_store() -> String {
+    "off".to_string()
+}
+
+fn default_vector_store_collection_prefix() -> String {
+    "mentions".to_string()
+}
+
+fn default_distance_metric() -> String {
+    "cosine".to_string()
+}
+
+fn default_recluster_interval_sec() -> u64 {
+    3600
+}
+
+fn default_recluster_window_hours() -> u64 {
+    24
+}
+
+fn default_recluster_similarity_threshold() -> f32 {
+    0.6
+}
+
+fn default_recluster_max_mentions() -> usize {
+    5000
+}
+
+fn default_recluster_prefix() -> String {
+    "recluster:brand".to_string()
+}
+
+fn default_recluster_channel() -> String {
+    "worker:recluster".to_string()
+}
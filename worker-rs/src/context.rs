@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+
+use crate::model_overrides::ModelOverride;
+use crate::types::Chunk;
+
+/// Which LLM a chunk's calls go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LlmSelection {
+    /// The configured provider, or the brand's or chunk's model override.
+    #[default]
+    Configured,
+    /// The built-in heuristics, for a chunk that can't wait for a provider.
+    Heuristic,
+}
+
+/// Who a chunk belongs to and how it should be processed, handed to every stage and to the
+/// LLM, embedding and clustering adapters. New per-chunk knobs go here rather than into
+/// each adapter's signature.
+#[derive(Debug, Clone)]
+pub struct ProcessingContext {
+    pub brand: String,
+    pub chunk_id: String,
+    /// Joins this chunk's logs with the caller's; the chunk ID unless the chunk meta sets one.
+    pub correlation_id: String,
+    pub deadline: Option<DateTime<Utc>>,
+    pub priority: Option<String>,
+    /// Serve clusters from the analysis cache when their LLM input is unchanged.
+    pub reuse_cached: bool,
+    pub llm: LlmSelection,
+    /// Provider and model from the chunk meta, taking precedence over the brand's override.
+    /// Only honoured with `LLM_MODEL_OVERRIDES_ENABLED`.
+    pub llm_override: Option<ModelOverride>,
+    /// Preview run: stages read per-brand state but leave spike history, dedup claims,
+    /// novelty n-grams, recurrence memory, labels, the analysis cache and vector exports
+    /// as they were, so later runs see the same state.
+    pub dry_run: bool,
+}
+
+impl ProcessingContext {
+    /// `fallback_brand` stands in when the chunk names no brand.
+    pub fn new(chunk: &Chunk, fallback_brand: &str, reuse_cached: bool) -> Self {
+        let brand = if chunk.brand.trim().is_empty() {
+            fallback_brand.to_string()
+        } else {
+            chunk.brand.clone()
+        };
+        let meta = chunk.meta.clone().unwrap_or_default();
+        Self {
+            brand,
+            chunk_id: chunk.chunk_id.clone(),
+            correlation_id: meta.correlation_id.unwrap_or_else(|| chunk.chunk_id.clone()),
+            deadline: meta.deadline,
+            priority: meta.priority,
+            reuse_cached,
+            llm: LlmSelection::Configured,
+            llm_override: meta.llm,
+            dry_run: false,
+        }
+    }
+
+    pub fn deadline_passed(&self) -> bool {
+        self.deadline.is_some_and(|deadline| deadline <= Utc::now())
+    }
+
+    pub fn heuristic_only(&self) -> bool {
+        self.llm == LlmSelection::Heuristic
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::Deserialize;
+use tokio::sync::{broadcast, Notify};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::redis_client::RedisClient;
+use crate::service::WorkerService;
+
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Fleet command published on `REDIS_CONTROL_CHANNEL`, e.g.
+/// `{"command": "pause", "brand": "acme", "workers": ["worker-3"]}`. Without `workers`
+/// every subscribed worker acts on it.
+#[derive(Debug, Deserialize)]
+struct FleetMessage {
+    #[serde(flatten)]
+    command: FleetCommand,
+    #[serde(default)]
+    workers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum FleetCommand {
+    /// Finish the chunk in flight, then shut down.
+    Drain,
+    Shutdown,
+    /// Pauses one brand's queue, or the whole queue loop without a brand.
+    Pause {
+        #[serde(default)]
+        brand: Option<String>,
+    },
+    Resume {
+        #[serde(default)]
+        brand: Option<String>,
+    },
+    Reload,
+}
+
+/// Listens on the fleet control channel so operators can drain, stop, pause or reload
+/// every worker at once. Shutdown requests are signalled through `stop`.
+pub struct FleetControl {
+    service: Arc<WorkerService>,
+    redis: RedisClient,
+    stop: Arc<Notify>,
+}
+
+impl FleetControl {
+    pub fn new(service: Arc<WorkerService>, redis: RedisClient, stop: Arc<Notify>) -> Self {
+        Self { service, redis, stop }
+    }
+
+    pub async fn run(self, mut shutdown: broadcast::Receiver<()>) {
+        let channel = self.service.settings().redis_control_channel.clone();
+        loop {
+            let mut pubsub = tokio::select! {
+                _ = shutdown.recv() => return,
+                subscribed = self.redis.subscribe(&channel) => match subscribed {
+                    Ok(pubsub) => pubsub,
+                    Err(err) => {
+                        warn!(channel, error = %err, "Fleet control subscription failed; retrying");
+                        sleep(RESUBSCRIBE_DELAY).await;
+                        continue;
+                    }
+                },
+            };
+            info!(channel, "Listening for fleet commands");
+            let mut messages = pubsub.on_message();
+            loop {
+                tokio::select! {
+                    _ = shutdown.recv() => return,
+                    message = messages.next() => match message {
+                        Some(message) => match message.get_payload::<String>() {
+                            Ok(payload) => self.handle(&payload).await,
+                            Err(err) => warn!(channel, error = %err, "Ignoring unreadable fleet command"),
+                        },
+                        None => break,
+                    },
+                }
+            }
+            warn!(channel, "Fleet control subscription closed; resubscribing");
+            sleep(RESUBSCRIBE_DELAY).await;
+        }
+    }
+
+    async fn handle(&self, payload: &str) {
+        let message: FleetMessage = match serde_json::from_str(payload) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!(error = %err, "Ignoring malformed fleet command");
+                return;
+            }
+        };
+        let worker_id = &self.service.settings().worker_id;
+        if !message.workers.is_empty() && !message.workers.contains(worker_id) {
+            return;
+        }
+        info!(worker_id = %worker_id, command = ?message.command, "Fleet command received");
+
+        match message.command {
+            FleetCommand::Drain => {
+                self.service.drain().await;
+                self.stop.notify_one();
+            }
+            FleetCommand::Shutdown => self.stop.notify_one(),
+            FleetCommand::Pause { brand: Some(brand) } => self.service.pause_brand(&brand),
+            FleetCommand::Pause { brand: None } => self.service.pause(),
+            FleetCommand::Resume { brand: Some(brand) } => self.service.resume_brand(&brand),
+            FleetCommand::Resume { brand: None } => self.service.resume(),
+            FleetCommand::Reload => {
+                let outcome = Settings::reload_from_env(self.service.settings())
+                    .map_err(|err| err.to_string())
+                    .and_then(|settings| self.service.reload(settings).map_err(|err| err.to_string()));
+                if let Err(err) = outcome {
+                    warn!(error = %err, "Config reload failed; keeping current adapters");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::config::Settings;
+
+const ENVELOPE_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+pub struct PayloadCipher {
+    cipher: Option<Aes256Gcm>,
+    required: bool,
+}
+
+impl PayloadCipher {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Self> {
+        let key = match (&settings.payload_encryption_key, &settings.payload_encryption_key_file) {
+            (Some(key), _) => Some(key.trim().to_string()),
+            (None, Some(path)) => Some(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("read payload encryption key from {path}"))?
+                    .trim()
+                    .to_string(),
+            ),
+            (None, None) => None,
+        };
+
+        let cipher = match key {
+            Some(encoded) => {
+                let bytes = STANDARD
+                    .decode(encoded)
+                    .context("payload encryption key must be base64")?;
+                Some(
+                    Aes256Gcm::new_from_slice(&bytes)
+                        .map_err(|_| anyhow!("payload encryption key must be 32 bytes"))?,
+                )
+            }
+            None => None,
+        };
+
+        if settings.payload_encryption_required && cipher.is_none() {
+            bail!("PAYLOAD_ENCRYPTION_REQUIRED is set but no encryption key was provided");
+        }
+
+        Ok(Self {
+            cipher,
+            required: settings.payload_encryption_required,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> anyhow::Result<String> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(plaintext.to_string());
+        };
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!("payload encryption failed"))?;
+        let mut envelope = nonce.to_vec();
+        envelope.extend_from_slice(&ciphertext);
+        Ok(format!("{ENVELOPE_PREFIX}{}", STANDARD.encode(envelope)))
+    }
+
+    pub fn decrypt(&self, payload: &str) -> anyhow::Result<String> {
+        let Some(encoded) = payload.strip_prefix(ENVELOPE_PREFIX) else {
+            if self.required {
+                bail!("plaintext payload rejected because encryption is required");
+            }
+            return Ok(payload.to_string());
+        };
+        let Some(cipher) = &self.cipher else {
+            bail!("encrypted payload received but no encryption key is configured");
+        };
+        let bytes = STANDARD.decode(encoded).context("decode encrypted payload")?;
+        if bytes.len() <= NONCE_LEN {
+            bail!("encrypted payload is truncated");
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("payload decryption failed"))?;
+        String::from_utf8(plaintext).context("decrypted payload is not valid UTF-8")
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::config::Settings;
+use crate::redis_client::RedisClient;
+
+/// Mention IDs seen per brand over the last `MENTION_DEDUP_TTL_SEC`, so overlapping
+/// orchestrator windows and reposts don't inflate volumes and spike baselines. Each ID is
+/// claimed by the first chunk that carries it; retries of that chunk still see it as new.
+pub struct MentionDedup {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl MentionDedup {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Self {
+        Self { redis, settings }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.mention_dedup_enabled
+    }
+
+    /// Positions in `ids` already claimed by another chunk. Empty IDs are never duplicates.
+    /// Unless `claim` is set, the rest are only checked, not claimed for `chunk_id`.
+    pub async fn duplicates(
+        &self,
+        brand: &str,
+        chunk_id: &str,
+        ids: &[&str],
+        claim: bool,
+    ) -> anyhow::Result<HashSet<usize>> {
+        let positions: Vec<usize> = (0..ids.len()).filter(|&index| !ids[index].trim().is_empty()).collect();
+        if positions.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let keys: Vec<String> = positions
+            .iter()
+            .map(|&index| format!("{}:{}:{}", self.settings.redis_mention_dedup_prefix, brand, ids[index]))
+            .collect();
+        if !claim {
+            let holders = self.redis.get_many(&keys).await?;
+            return Ok(holders
+                .into_iter()
+                .zip(positions)
+                .filter(|(holder, _)| holder.as_deref().is_some_and(|holder| holder != chunk_id))
+                .map(|(_, position)| position)
+                .collect());
+        }
+        let claimed = self
+            .redis
+            .claim_keys(&keys, chunk_id, self.settings.mention_dedup_ttl)
+            .await?;
+        Ok(claimed.into_iter().map(|index| positions[index]).collect())
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::http::HttpClient;
+use crate::metrics::WORKER_DIGESTS_TOTAL;
+use crate::redis_client::RedisClient;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrandDigest {
+    pub brand: String,
+    pub date: String,
+    pub generated_at: String,
+    pub worker_id: String,
+    pub chunks: usize,
+    pub mentions: usize,
+    pub mean_sentiment_score: f64,
+    /// Change against the previous day's digest, when there is one.
+    pub sentiment_delta: Option<f64>,
+    pub hourly_sentiment: Vec<HourlySentiment>,
+    pub top_clusters: Vec<DigestCluster>,
+    pub spikes: Vec<DigestCluster>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourlySentiment {
+    pub hour: u32,
+    pub chunks: usize,
+    pub mean_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestCluster {
+    pub label: String,
+    pub stable_id: Option<String>,
+    pub mention_count: usize,
+    pub sentiment_score: f64,
+    pub chunks: usize,
+}
+
+#[derive(Default)]
+struct ClusterTally {
+    label: String,
+    stable_id: Option<String>,
+    mention_count: usize,
+    weighted_score: f64,
+    chunks: usize,
+    spike: bool,
+}
+
+impl ClusterTally {
+    fn finish(&self) -> DigestCluster {
+        DigestCluster {
+            label: self.label.clone(),
+            stable_id: self.stable_id.clone(),
+            mention_count: self.mention_count,
+            sentiment_score: self.weighted_score / self.mention_count.max(1) as f64,
+            chunks: self.chunks,
+        }
+    }
+}
+
+/// Once a UTC day closes, the leader worker aggregates each brand's stored results for that
+/// day into a digest at `{REDIS_DIGEST_PREFIX}:{brand}:{date}` and, with
+/// `DIGEST_WEBHOOK_URL`, posts it as well. Digests only see results still in the result
+/// lists, so `RESULT_RETENTION_ENTRIES` must cover a day of chunks.
+pub struct DailyDigestJob {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+    http: HttpClient,
+}
+
+impl DailyDigestJob {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, cipher: Arc<PayloadCipher>, http: HttpClient) -> Self {
+        Self {
+            redis,
+            settings,
+            cipher,
+            http,
+        }
+    }
+
+    pub async fn run(&self, mut shutdown: broadcast::Receiver<()>) {
+        if !self.settings.digest_enabled {
+            return;
+        }
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => {
+                    info!("Daily digest job stopping");
+                    break;
+                }
+                _ = tokio::time::sleep(self.settings.digest_check_interval) => {
+                    let key = format!("{}:leader", self.settings.redis_digest_prefix);
+                    let lease = self.settings.digest_check_interval * 2;
+                    match self.redis.acquire_lease(&key, &self.settings.worker_id, lease).await {
+                        Ok(true) => {
+                            if let Err(err) = self.run_once(Utc::now()).await {
+                                warn!(error = %err, "Daily digest run failed");
+                            }
+                        }
+                        Ok(false) => debug!(worker_id = %self.settings.worker_id, "Digest leader is another worker"),
+                        Err(err) => warn!(error = %err, "Digest leader election failed"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes any missing digests for the day before `now`.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> anyhow::Result<usize> {
+        let Some(day) = now.date_naive().pred_opt() else {
+            return Ok(0);
+        };
+        let suffix = ":chunks";
+        let keys = self
+            .redis
+            .scan_keys(&format!("{}:*{suffix}", self.settings.redis_result_prefix))
+            .await?;
+        let mut written = 0;
+        for key in keys {
+            let Some(brand) = key
+                .strip_prefix(&format!("{}:", self.settings.redis_result_prefix))
+                .and_then(|rest| rest.strip_suffix(suffix))
+            else {
+                continue;
+            };
+            let digest_key = self.key(brand, day);
+            if self.redis.get_string(&digest_key).await?.is_some() {
+                continue;
+            }
+            match self.build(brand, day, &key).await {
+                Ok(Some(digest)) => {
+                    self.publish(&digest_key, &digest).await?;
+                    written += 1;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(brand, date = %day, error = %err, "Failed to build daily digest");
+                    WORKER_DIGESTS_TOTAL
+                        .with_label_values(&[&self.settings.worker_id, brand, "error"])
+                        .inc();
+                }
+            }
+        }
+        Ok(written)
+    }
+
+    async fn build(&self, brand: &str, day: NaiveDate, results_key: &str) -> anyhow::Result<Option<BrandDigest>> {
+        let mut chunks = 0;
+        let mut mentions = 0;
+        let mut score_total = 0.0;
+        let mut hourly: HashMap<u32, (usize, f64)> = HashMap::new();
+        let mut clusters: HashMap<String, ClusterTally> = HashMap::new();
+
+        for entry in self.redis.lrange(results_key, 0, -1).await? {
+            let Some(result) = self
+                .cipher
+                .decrypt(&entry)
+                .ok()
+                .and_then(|plaintext| serde_json::from_str::<Value>(&plaintext).ok())
+            else {
+                continue;
+            };
+            let Some(processed_at) = result
+                .get("processedAt")
+                .and_then(Value::as_str)
+                .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .filter(|timestamp| timestamp.date_naive() == day)
+            else {
+                continue;
+            };
+
+            let score = result.pointer("/sentiment/score").and_then(Value::as_f64).unwrap_or_default();
+            chunks += 1;
+            score_total += score;
+            mentions += result.pointer("/meta/mentionCount").and_then(Value::as_u64).unwrap_or_default() as usize;
+            let hour = hourly.entry(processed_at.hour()).or_default();
+            hour.0 += 1;
+            hour.1 += score;
+
+            for cluster in result.get("clusters").and_then(Value::as_array).into_iter().flatten() {
+                let label = cluster.get("label").and_then(Value::as_str).unwrap_or_default().to_string();
+                let stable_id = cluster.get("stableId").and_then(Value::as_str).map(str::to_string);
+                let count = cluster.get("mentionCount").and_then(Value::as_u64).unwrap_or_default() as usize;
+                let tally = clusters
+                    .entry(stable_id.clone().unwrap_or_else(|| label.to_lowercase()))
+                    .or_insert_with(|| ClusterTally {
+                        label,
+                        stable_id,
+                        ..Default::default()
+                    });
+                tally.mention_count += count;
+                tally.weighted_score +=
+                    cluster.get("sentimentScore").and_then(Value::as_f64).unwrap_or_default() * count as f64;
+                tally.chunks += 1;
+                tally.spike |= cluster.get("spike").and_then(Value::as_bool).unwrap_or_default();
+            }
+        }
+        if chunks == 0 {
+            return Ok(None);
+        }
+
+        let mean_sentiment_score = score_total / chunks as f64;
+        let previous = match day.pred_opt() {
+            Some(previous_day) => self.redis.get_string(&self.key(brand, previous_day)).await?,
+            None => None,
+        };
+        let sentiment_delta = previous
+            .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+            .and_then(|digest| digest.get("meanSentimentScore").and_then(Value::as_f64))
+            .map(|previous_score| mean_sentiment_score - previous_score);
+
+        let mut hourly_sentiment: Vec<HourlySentiment> = hourly
+            .into_iter()
+            .map(|(hour, (chunks, total))| HourlySentiment {
+                hour,
+                chunks,
+                mean_score: total / chunks as f64,
+            })
+            .collect();
+        hourly_sentiment.sort_by_key(|entry| entry.hour);
+
+        let mut tallies: Vec<ClusterTally> = clusters.into_values().collect();
+        tallies.sort_by_key(|tally| std::cmp::Reverse(tally.mention_count));
+        let top_clusters = tallies
+            .iter()
+            .take(self.settings.digest_top_clusters)
+            .map(ClusterTally::finish)
+            .collect();
+        let spikes = tallies.iter().filter(|tally| tally.spike).map(ClusterTally::finish).collect();
+
+        Ok(Some(BrandDigest {
+            brand: brand.to_string(),
+            date: day.format("%Y-%m-%d").to_string(),
+            generated_at: Utc::now().to_rfc3339(),
+            worker_id: self.settings.worker_id.clone(),
+            chunks,
+            mentions,
+            mean_sentiment_score,
+            sentiment_delta,
+            hourly_sentiment,
+            top_clusters,
+            spikes,
+        }))
+    }
+
+    async fn publish(&self, key: &str, digest: &BrandDigest) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(digest).context("serialise daily digest")?;
+        self.redis.set_with_ttl(key, &payload, self.settings.digest_ttl).await?;
+        let outcome = match &self.settings.digest_webhook_url {
+            Some(url) => {
+                let request = self.http.post(url).timeout(self.settings.alert_timeout).json(digest);
+                match self.http.send(request, "digest webhook").await {
+                    Ok(_) => "ok",
+                    Err(err) => {
+                        warn!(brand = %digest.brand, date = %digest.date, error = %err, "Digest webhook delivery failed");
+                        "webhook_error"
+                    }
+                }
+            }
+            None => "ok",
+        };
+        WORKER_DIGESTS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, &digest.brand, outcome])
+            .inc();
+        info!(
+            worker_id = %self.settings.worker_id,
+            brand = %digest.brand,
+            date = %digest.date,
+            chunks = digest.chunks,
+            "Daily digest written"
+        );
+        Ok(())
+    }
+
+    fn key(&self, brand: &str, day: NaiveDate) -> String {
+        format!("{}:{}:{}", self.settings.redis_digest_prefix, brand, day.format("%Y-%m-%d"))
+    }
+}
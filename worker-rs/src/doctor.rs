@@ -0,0 +1,179 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use tokio::net::TcpListener;
+use tokio::time::timeout;
+
+use crate::config::Settings;
+use crate::embeddings::embedding_provider_adapter;
+use crate::http::HttpClient;
+use crate::llm::provider_adapter;
+use crate::prompts::PromptTemplates;
+use crate::queue_consumer::QueueConsumer;
+use crate::redis_client::RedisClient;
+use crate::service::WorkerService;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(20);
+const PROBE_TTL: Duration = Duration::from_secs(60);
+
+struct Report {
+    failed: usize,
+}
+
+impl Report {
+    fn record(&mut self, name: &str, outcome: anyhow::Result<String>) {
+        match outcome {
+            Ok(detail) => println!("[PASS] {name}: {detail}"),
+            Err(err) => {
+                self.failed += 1;
+                println!("[FAIL] {name}: {err:#}");
+            }
+        }
+    }
+}
+
+/// `worker-rs doctor`: checks the configuration, Redis access, provider credentials and
+/// listen ports the worker will use, and prints a pass/fail line for each. Returns whether
+/// every check passed. Provider checks make one small real call each.
+pub async fn run() -> bool {
+    let mut report = Report { failed: 0 };
+    let settings = match Settings::from_env() {
+        Ok(settings) => Arc::new(settings),
+        Err(err) => {
+            report.record("config", Err(err.into()));
+            return false;
+        }
+    };
+    report.record("config", Ok(format!("loaded for worker {}", settings.worker_id)));
+
+    let redis = check_redis(&settings, &mut report).await;
+    match &redis {
+        Some(redis) => {
+            let consumer = QueueConsumer::new(redis.clone(), settings.worker_id.clone(), settings.blpop_timeout);
+            let outcome = WorkerService::new(settings.clone(), redis.clone(), consumer)
+                .map(|_| "pipelines, alerts, SLO rules and sinks built".to_string())
+                .map_err(|err| anyhow!(err));
+            report.record("pipeline config", outcome);
+        }
+        None => report.record("pipeline config", Err(anyhow!("skipped without Redis"))),
+    }
+
+    match HttpClient::from_settings(&settings) {
+        Ok(http) => {
+            for provider in llm_providers(&settings) {
+                let outcome = timeout(CHECK_TIMEOUT, check_llm(&provider, &settings, &http)).await;
+                report.record(&format!("llm provider {provider}"), flatten(outcome));
+            }
+            let outcome = timeout(CHECK_TIMEOUT, check_embeddings(&settings, &http)).await;
+            report.record(&format!("embeddings provider {}", settings.embeddings_provider), flatten(outcome));
+        }
+        Err(err) => report.record("http client", Err(err)),
+    }
+
+    for (name, port) in [
+        ("http port", settings.http_port),
+        ("metrics port", settings.prometheus_port),
+        ("grpc port", settings.grpc_port),
+    ] {
+        report.record(name, check_port(port).await);
+    }
+
+    println!("{} check(s) failed", report.failed);
+    report.failed == 0
+}
+
+async fn check_redis(settings: &Settings, report: &mut Report) -> Option<RedisClient> {
+    let redis = match timeout(CHECK_TIMEOUT, RedisClient::new(&settings.redis_url)).await {
+        Ok(Ok(redis)) => redis,
+        Ok(Err(err)) => {
+            report.record("redis connection", Err(err));
+            return None;
+        }
+        Err(_) => {
+            report.record("redis connection", Err(anyhow!("timed out after {CHECK_TIMEOUT:?}")));
+            return None;
+        }
+    };
+    let connected = redis.ensure_connection().await.map(|_| "PING answered".to_string());
+    let reachable = connected.is_ok();
+    report.record("redis connection", connected);
+    if !reachable {
+        return None;
+    }
+    report.record("redis permissions", check_redis_permissions(&redis, settings).await);
+    Some(redis)
+}
+
+/// Exercises the command families the worker relies on against throwaway keys.
+async fn check_redis_permissions(redis: &RedisClient, settings: &Settings) -> anyhow::Result<String> {
+    let key = format!("{}:doctor:{}", settings.redis_result_prefix, settings.worker_id);
+    redis.set_with_ttl(&key, "ok", PROBE_TTL).await.context("SET")?;
+    if redis.get_string(&key).await.context("GET")?.as_deref() != Some("ok") {
+        bail!("GET returned a different value than was SET");
+    }
+    redis.delete(&key).await.context("DEL")?;
+
+    let list = format!("{key}:list");
+    redis.lpush_capped(&list, "ok", 1, PROBE_TTL).await.context("LPUSH/LTRIM/EXPIRE")?;
+    redis.lrange(&list, 0, -1).await.context("LRANGE")?;
+    redis.delete(&list).await.context("DEL")?;
+
+    redis
+        .claim_keys(&[format!("{key}:claim")], &settings.worker_id, PROBE_TTL)
+        .await
+        .context("EVAL")?;
+    redis.delete(&format!("{key}:claim")).await.context("DEL")?;
+    redis
+        .scan_keys(&format!("{}:*", settings.redis_queue_prefix))
+        .await
+        .context("SCAN")?;
+    Ok("read, write, list, script and scan commands allowed".to_string())
+}
+
+/// Every provider the configuration can send LLM calls to.
+fn llm_providers(settings: &Settings) -> Vec<String> {
+    let mut providers = if settings.llm_providers.len() > 1 {
+        settings.llm_providers.clone()
+    } else {
+        vec![settings.llm_provider.clone()]
+    };
+    let extra = [
+        settings.llm_tiering_enabled.then(|| settings.llm_cheap_provider.clone()),
+        settings.shadow_llm_provider.clone(),
+        settings.media_caption_provider.clone(),
+    ];
+    for provider in extra.into_iter().flatten() {
+        if !providers.contains(&provider) {
+            providers.push(provider);
+        }
+    }
+    providers
+}
+
+async fn check_llm(provider: &str, settings: &Arc<Settings>, http: &HttpClient) -> anyhow::Result<String> {
+    let prompts = PromptTemplates::from_settings(settings)?;
+    let adapter = provider_adapter(provider, settings, http, &prompts)?;
+    adapter.sentiment("doctor", &["Works as expected.".to_string()]).await?;
+    Ok("test sentiment call succeeded".to_string())
+}
+
+async fn check_embeddings(settings: &Settings, http: &HttpClient) -> anyhow::Result<String> {
+    let adapter = embedding_provider_adapter(settings, http)?;
+    let vectors = adapter.embed(&["doctor".to_string()], "doctor", "doctor").await?;
+    let dimensions = vectors.first().map(Vec::len).unwrap_or_default();
+    Ok(format!("test embedding returned {dimensions} dimensions"))
+}
+
+async fn check_port(port: u16) -> anyhow::Result<String> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("cannot bind {addr}"))?;
+    Ok(format!("{addr} is free"))
+}
+
+fn flatten(outcome: Result<anyhow::Result<String>, tokio::time::error::Elapsed>) -> anyhow::Result<String> {
+    outcome.unwrap_or_else(|_| Err(anyhow!("timed out after {CHECK_TIMEOUT:?}")))
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::config::Settings;
+use crate::metrics::WORKER_EMBEDDING_CACHE_TOTAL;
+use crate::redis_client::RedisClient;
+
+/// Embedding vectors keyed by a hash of the cleaned mention text, so mentions repeated
+/// across chunks are embedded once per `EMBEDDING_CACHE_TTL_SEC`. Keys include the provider
+/// and model, so switching either never mixes vector spaces. Vectors are stored as base64
+/// little-endian `f32`s. Cache failures are logged and the texts embedded as usual.
+pub struct EmbeddingCache {
+    redis: RedisClient,
+    namespace: String,
+    settings: Arc<Settings>,
+}
+
+impl EmbeddingCache {
+    /// `None` when disabled, or for local n-gram vectors that cost less to compute than fetch.
+    pub fn from_settings(settings: &Arc<Settings>, redis: &RedisClient) -> Option<Self> {
+        let provider = settings.embeddings_provider.as_str();
+        if !settings.embedding_cache_enabled || provider == "local" {
+            return None;
+        }
+        let model = settings.embedding_model().unwrap_or("default");
+        Some(Self {
+            redis: redis.clone(),
+            namespace: format!("{}:{provider}:{model}", settings.redis_embedding_cache_prefix),
+            settings: settings.clone(),
+        })
+    }
+
+    /// Cached vectors in `texts` order, `None` for each miss.
+    pub async fn get_many(&self, texts: &[String]) -> Vec<Option<Vec<f32>>> {
+        let keys: Vec<String> = texts.iter().map(|text| self.key(text)).collect();
+        let stored = match self.redis.get_many(&keys).await {
+            Ok(stored) => stored,
+            Err(err) => {
+                warn!(worker_id = %self.settings.worker_id, error = %err, "Embedding cache lookup failed");
+                vec![None; texts.len()]
+            }
+        };
+        let vectors: Vec<Option<Vec<f32>>> = stored.iter().map(|value| value.as_deref().and_then(decode)).collect();
+        let hits = vectors.iter().filter(|vector| vector.is_some()).count();
+        WORKER_EMBEDDING_CACHE_TOTAL
+            .with_label_values(&[&self.settings.worker_id, "hit"])
+            .inc_by(hits as u64);
+        WORKER_EMBEDDING_CACHE_TOTAL
+            .with_label_values(&[&self.settings.worker_id, "miss"])
+            .inc_by((texts.len() - hits) as u64);
+        vectors
+    }
+
+    pub async fn put_many(&self, texts: &[String], vectors: &[Vec<f32>]) {
+        let entries: Vec<(String, String)> = texts
+            .iter()
+            .zip(vectors)
+            .map(|(text, vector)| (self.key(text), encode(vector)))
+            .collect();
+        if let Err(err) = self
+            .redis
+            .set_many_with_ttl(&entries, self.settings.embedding_cache_ttl)
+            .await
+        {
+            warn!(worker_id = %self.settings.worker_id, error = %err, "Embedding cache write failed");
+        }
+    }
+
+    fn key(&self, text: &str) -> String {
+        let digest = Sha256::digest(text.as_bytes());
+        let hash: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!("{}:{hash}", self.namespace)
+    }
+}
+
+fn encode(vector: &[f32]) -> String {
+    let bytes: Vec<u8> = vector.iter().flat_map(|value| value.to_le_bytes()).collect();
+    STANDARD.encode(bytes)
+}
+
+fn decode(stored: &str) -> Option<Vec<f32>> {
+    let bytes = STANDARD.decode(stored).ok()?;
+    if bytes.is_empty() || bytes.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+    )
+}
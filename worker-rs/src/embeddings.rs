@@ -1,38 +1,84 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use sha2::{Digest, Sha256};
 use tracing::warn;
 
+use crate::budget::{estimate_tokens, BudgetGuard, BudgetKind};
+use crate::clustering::l2_normalize;
+use crate::cohere::CohereEmbeddingAdapter;
 use crate::config::Settings;
-use crate::metrics::WORKER_EMBEDDING_TIME_SECONDS;
+use crate::context::ProcessingContext;
+use crate::embedding_cache::EmbeddingCache;
+use crate::error::{WorkerError, WorkerResult};
+use crate::gemini::GeminiEmbeddingAdapter;
+use crate::http::HttpClient;
+use crate::metrics::{record_provider_call, WORKER_EMBEDDING_FALLBACK_TOTAL, WORKER_EMBEDDING_TIME_SECONDS};
+use crate::onnx::OnnxEmbeddingAdapter;
+use crate::ratelimit::RateLimiter;
+use crate::redis_client::RedisClient;
+use crate::simulated::SimulatedEmbeddingAdapter;
+use crate::tei::TeiEmbeddingAdapter;
+use crate::types::Provenance;
 
-const FALLBACK_DIM: usize = 128;
+/// Buckets in a local n-gram vector.
+const NGRAM_DIM: usize = 512;
+/// Character n-gram lengths hashed alongside whole words.
+const NGRAM_SIZES: [usize; 2] = [3, 4];
 
 #[async_trait]
 pub trait EmbeddingAdapter: Send + Sync {
-    async fn embed(&self, texts: &[String], brand: &str, chunk_id: &str) -> Vec<Vec<f32>>;
+    async fn embed(&self, texts: &[String], brand: &str, chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>>;
 }
 
-pub struct HashEmbeddingAdapter;
+/// The `local` provider, also standing in for a remote provider that fails or is over
+/// budget. Each text becomes a hashed bag of its words and their character 3- and 4-grams,
+/// so texts sharing vocabulary or word stems land close together. Lexical only, but good
+/// enough for offline clustering.
+pub struct NgramEmbeddingAdapter;
 
 #[async_trait]
-impl EmbeddingAdapter for HashEmbeddingAdapter {
-    async fn embed(&self, texts: &[String], _brand: &str, _chunk_id: &str) -> Vec<Vec<f32>> {
-        texts.iter().map(|text| hash_vector(text)).collect()
+impl EmbeddingAdapter for NgramEmbeddingAdapter {
+    async fn embed(&self, texts: &[String], _brand: &str, _chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| ngram_vector(text)).collect())
     }
 }
 
-fn hash_vector(text: &str) -> Vec<f32> {
-    let mut hasher = Sha256::new();
-    hasher.update(text.as_bytes());
-    let digest = hasher.finalize();
-    let mut result = vec![0.0_f32; FALLBACK_DIM];
-    for (idx, chunk) in digest.iter().cycle().take(FALLBACK_DIM).enumerate() {
-        result[idx] = (*chunk as f32) / 255.0;
+/// Features are hashed into `NGRAM_DIM` signed buckets with sublinear term frequency and
+/// the vector is L2-normalised. There is no corpus-wide IDF, so vectors from different chunks
+/// and worker restarts stay comparable (stored centroids are matched across chunks).
+fn ngram_vector(text: &str) -> Vec<f32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for word in text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        *counts.entry(format!("w:{word}")).or_default() += 1;
+        let padded: Vec<char> = format!(" {word} ").chars().collect();
+        for size in NGRAM_SIZES {
+            for gram in padded.windows(size) {
+                *counts.entry(gram.iter().collect()).or_default() += 1;
+            }
+        }
     }
-    result
+
+    let mut vector = vec![0.0_f32; NGRAM_DIM];
+    for (feature, count) in counts {
+        let hash = fnv1a(feature.as_bytes());
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[(hash % NGRAM_DIM as u64) as usize] += sign * (1.0 + (count as f32).ln());
+    }
+    l2_normalize(&mut vector);
+    vector
+}
+
+/// Stable across builds and platforms, unlike `std`'s hasher.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 pub struct RemoteEmbeddingAdapter {
@@ -41,41 +87,219 @@ pub struct RemoteEmbeddingAdapter {
 
 #[async_trait]
 impl EmbeddingAdapter for RemoteEmbeddingAdapter {
-    async fn embed(&self, texts: &[String], brand: &str, chunk_id: &str) -> Vec<Vec<f32>> {
-        warn!(provider = %self.provider, count = texts.len(), brand, chunk_id, "Remote embedding provider not yet implemented; returning hashed vectors");
-        texts.iter().map(|text| hash_vector(text)).collect()
+    async fn embed(&self, _texts: &[String], _brand: &str, _chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>> {
+        Err(WorkerError::Config(anyhow::anyhow!(
+            "embedding provider '{}' is not implemented",
+            self.provider
+        )))
     }
 }
 
 pub struct InstrumentedEmbeddingAdapter {
     delegate: Arc<dyn EmbeddingAdapter>,
+    fallback: Arc<dyn EmbeddingAdapter>,
+    provider: String,
+    budget: Option<BudgetGuard>,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<EmbeddingCache>,
+    normalize: bool,
+    timeout: Option<Duration>,
     worker_id: String,
 }
 
 impl InstrumentedEmbeddingAdapter {
-    pub fn new(delegate: Arc<dyn EmbeddingAdapter>, worker_id: String) -> Self {
-        Self { delegate, worker_id }
+    pub fn new(
+        delegate: Arc<dyn EmbeddingAdapter>,
+        provider: String,
+        budget: Option<BudgetGuard>,
+        worker_id: String,
+    ) -> Self {
+        Self {
+            delegate,
+            fallback: Arc::new(NgramEmbeddingAdapter),
+            provider,
+            budget,
+            rate_limiter: None,
+            cache: None,
+            normalize: false,
+            timeout: None,
+            worker_id,
+        }
+    }
+
+    /// Throttles calls to the provider; budget downgrades to the fallback are not limited.
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Serves repeated texts from `cache` and embeds only the rest.
+    pub fn with_cache(mut self, cache: Option<EmbeddingCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// L2-normalises every vector returned, cached or fresh.
+    pub fn with_normalization(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Bounds each provider call; a call that runs past `timeout` is abandoned for local vectors.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
     }
 
-    pub async fn embed(&self, texts: &[String], brand: &str, chunk_id: &str) -> Vec<Vec<f32>> {
+    pub async fn embed(&self, texts: &[String], job: &ProcessingContext, provenance: &mut Provenance) -> Vec<Vec<f32>> {
         let start = Instant::now();
-        let vectors = self.delegate.embed(texts, brand, chunk_id).await;
-        let duration = start.elapsed();
+        let mut vectors = match &self.cache {
+            Some(cache) => self.embed_cached(cache, texts, job, provenance).await,
+            None => self.embed_uncached(texts, job, provenance).await.0,
+        };
+        if self.normalize {
+            vectors.iter_mut().for_each(|vector| l2_normalize(vector));
+        }
         WORKER_EMBEDDING_TIME_SECONDS
-            .with_label_values(&[&self.worker_id, brand])
-            .observe(duration.as_secs_f64());
+            .with_label_values(&[&self.worker_id, &job.brand])
+            .observe(start.elapsed().as_secs_f64());
         vectors
     }
+
+    async fn embed_cached(
+        &self,
+        cache: &EmbeddingCache,
+        texts: &[String],
+        job: &ProcessingContext,
+        provenance: &mut Provenance,
+    ) -> Vec<Vec<f32>> {
+        let mut vectors = cache.get_many(texts).await;
+        // Each distinct missing text is embedded once, however often it repeats in the chunk.
+        let mut missing: Vec<String> = Vec::new();
+        for (text, vector) in texts.iter().zip(&vectors) {
+            if vector.is_none() && !missing.contains(text) {
+                missing.push(text.clone());
+            }
+        }
+        if !missing.is_empty() {
+            let (fresh, from_provider) = self.embed_uncached(&missing, job, provenance).await;
+            // Fallback vectors stand in for this chunk only and are never cached. They live in a
+            // different space from the cached provider vectors, so the whole chunk goes local.
+            if !from_provider {
+                return texts.iter().map(|text| ngram_vector(text)).collect();
+            }
+            cache.put_many(&missing, &fresh).await;
+            let fresh: HashMap<&String, Vec<f32>> = missing.iter().zip(fresh).collect();
+            for (text, vector) in texts.iter().zip(vectors.iter_mut()) {
+                if vector.is_none() {
+                    *vector = fresh.get(text).cloned();
+                }
+            }
+        }
+        vectors.into_iter().map(Option::unwrap_or_default).collect()
+    }
+
+    /// Vectors for `texts`, and whether they came from the provider rather than the fallback.
+    async fn embed_uncached(
+        &self,
+        texts: &[String],
+        job: &ProcessingContext,
+        provenance: &mut Provenance,
+    ) -> (Vec<Vec<f32>>, bool) {
+        let (brand, chunk_id) = (job.brand.as_str(), job.chunk_id.as_str());
+        // Budget downgrades are recorded against the local fallback so they do not skew provider ratios.
+        let (adapter, provider, metered) = match &self.budget {
+            Some(budget) if !budget.allow(&self.provider, brand).await => (&self.fallback, "local", false),
+            Some(_) => (&self.delegate, self.provider.as_str(), true),
+            None => (&self.delegate, self.provider.as_str(), false),
+        };
+        let local = Arc::ptr_eq(adapter, &self.fallback);
+        if local {
+            provenance.record_fallback("embed", "budget");
+            self.record_fallback("budget");
+        } else if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+        let outcome = match self.timeout.filter(|_| !local) {
+            // Dropping the timed-out future abandons whichever provider batch was in flight.
+            Some(limit) => tokio::time::timeout(limit, adapter.embed(texts, brand, chunk_id))
+                .await
+                .unwrap_or_else(|_| Err(WorkerError::Timeout("embed".to_string()))),
+            None => adapter.embed(texts, brand, chunk_id).await,
+        };
+        record_provider_call(&self.worker_id, provider, "embed", outcome.as_ref().err());
+        let from_provider = outcome.is_ok() && !local;
+        let vectors = match outcome {
+            Ok(vectors) => {
+                if let (true, Some(budget)) = (metered, &self.budget) {
+                    budget.record(&self.provider, brand, estimate_tokens(texts), 0).await;
+                }
+                vectors
+            }
+            Err(err) => {
+                let reason = if matches!(err, WorkerError::Timeout(_)) { "timeout" } else { "error" };
+                provenance.record_fallback("embed", reason);
+                self.record_fallback(reason);
+                warn!(
+                    provider,
+                    count = texts.len(),
+                    brand,
+                    chunk_id,
+                    correlation_id = %job.correlation_id,
+                    error = %err,
+                    "Embedding request failed; returning local n-gram vectors"
+                );
+                texts.iter().map(|text| ngram_vector(text)).collect()
+            }
+        };
+        (vectors, from_provider)
+    }
+
+    fn record_fallback(&self, reason: &str) {
+        WORKER_EMBEDDING_FALLBACK_TOTAL
+            .with_label_values(&[&self.worker_id, &self.provider, reason])
+            .inc();
+    }
 }
 
-pub fn build_embedding_adapter(settings: &Arc<Settings>) -> InstrumentedEmbeddingAdapter {
+pub fn build_embedding_adapter(
+    settings: &Arc<Settings>,
+    redis: &RedisClient,
+    http: &HttpClient,
+) -> anyhow::Result<InstrumentedEmbeddingAdapter> {
     let provider = settings.embeddings_provider.as_str();
-    let delegate: Arc<dyn EmbeddingAdapter> = match provider {
-        "local" => Arc::new(HashEmbeddingAdapter),
+    let delegate = embedding_provider_adapter(settings, http)?;
+
+    // Local n-grams and in-process ONNX inference cost nothing and call no provider.
+    let remote = !matches!(provider, "local" | "onnx");
+    let budget = remote.then(|| BudgetGuard::new(redis.clone(), settings.clone(), BudgetKind::Embedding));
+    let rate_limiter = remote
+        .then(|| RateLimiter::new(redis.clone(), settings, BudgetKind::Embedding, provider))
+        .flatten();
+
+    Ok(
+        InstrumentedEmbeddingAdapter::new(delegate, provider.to_string(), budget, settings.worker_id.clone())
+            .with_rate_limiter(rate_limiter)
+            .with_cache(EmbeddingCache::from_settings(settings, redis))
+            .with_normalization(settings.embedding_normalize)
+            .with_timeout(remote.then_some(settings.embedding_timeout)),
+    )
+}
+
+/// Remote providers pool `EMBEDDING_API_KEY` (comma-separated) with `EMBEDDING_API_KEYS_FILE`.
+pub(crate) fn embedding_provider_adapter(
+    settings: &Settings,
+    http: &HttpClient,
+) -> anyhow::Result<Arc<dyn EmbeddingAdapter>> {
+    Ok(match settings.embeddings_provider.as_str() {
+        "local" => Arc::new(NgramEmbeddingAdapter),
+        "gemini" => Arc::new(GeminiEmbeddingAdapter::new(settings, http.clone())?),
+        "onnx" => Arc::new(OnnxEmbeddingAdapter::new(settings)?),
+        "tei" => Arc::new(TeiEmbeddingAdapter::new(settings, http.clone())?),
+        "cohere" => Arc::new(CohereEmbeddingAdapter::new(settings, http.clone())?),
+        "simulated" => Arc::new(SimulatedEmbeddingAdapter::new(settings)),
         other => Arc::new(RemoteEmbeddingAdapter {
             provider: other.to_string(),
         }),
-    };
-
-    InstrumentedEmbeddingAdapter::new(delegate, settings.worker_id.clone())
+    })
 }
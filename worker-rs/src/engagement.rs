@@ -0,0 +1,25 @@
+use crate::types::{Engagement, Mention};
+
+/// The `likes`, `shares` and `comments` counts in the mention metadata, as numbers or
+/// numeric strings; `None` when the mention carries none of them.
+pub fn mention_engagement(mention: &Mention) -> Option<Engagement> {
+    let metadata = mention.metadata.as_ref()?;
+    let count = |key: &str| -> Option<u64> {
+        match metadata.get(key)? {
+            serde_json::Value::Number(number) => number
+                .as_u64()
+                .or_else(|| number.as_f64().map(|value| value.max(0.0) as u64)),
+            serde_json::Value::String(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    };
+    let (likes, shares, comments) = (count("likes"), count("shares"), count("comments"));
+    if likes.is_none() && shares.is_none() && comments.is_none() {
+        return None;
+    }
+    Some(Engagement::new(
+        likes.unwrap_or_default(),
+        shares.unwrap_or_default(),
+        comments.unwrap_or_default(),
+    ))
+}
@@ -0,0 +1,128 @@
+use thiserror::Error;
+
+/// Error surface of the worker library. Each variant names the pipeline stage that
+/// failed so callers can pick a retry policy or failure label without parsing messages.
+/// Provider and store adapters report their own failures as `Embedding`, `Llm` and `Storage`.
+#[derive(Debug, Error)]
+pub enum WorkerError {
+    #[error("configuration error: {0:#}")]
+    Config(anyhow::Error),
+    #[error("queue error: {0:#}")]
+    Queue(anyhow::Error),
+    #[error("decode error: {0:#}")]
+    Decode(anyhow::Error),
+    #[error("embedding error: {0:#}")]
+    Embedding(anyhow::Error),
+    #[error("llm error: {0:#}")]
+    Llm(anyhow::Error),
+    #[error("storage error: {0:#}")]
+    Storage(anyhow::Error),
+    #[error("spike detection error: {0:#}")]
+    Spike(anyhow::Error),
+    #[error("pipeline stage '{0}' timed out")]
+    Timeout(String),
+    #[error("pipeline stage '{stage}' failed: {source}")]
+    Stage { stage: String, source: Box<WorkerError> },
+}
+
+pub type WorkerResult<T> = Result<T, WorkerError>;
+
+impl WorkerError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::Queue(_) => "queue",
+            Self::Decode(_) => "decode",
+            Self::Embedding(_) => "embedding",
+            Self::Llm(_) => "llm",
+            Self::Storage(_) => "storage",
+            Self::Spike(_) => "spike",
+            Self::Timeout(_) => "timeout",
+            Self::Stage { source, .. } => source.kind(),
+        }
+    }
+
+    /// Attributes the error to a pipeline stage unless it already names one.
+    pub fn in_stage(self, stage: &str) -> Self {
+        match self {
+            Self::Timeout(_) | Self::Stage { .. } => self,
+            other => Self::Stage {
+                stage: stage.to_string(),
+                source: Box::new(other),
+            },
+        }
+    }
+
+    pub fn stage(&self) -> Option<&str> {
+        match self {
+            Self::Timeout(stage) | Self::Stage { stage, .. } => Some(stage),
+            _ => None,
+        }
+    }
+
+    /// Messages of the underlying error and each of its sources, outermost first.
+    pub fn chain(&self) -> Vec<String> {
+        match self {
+            Self::Config(err)
+            | Self::Queue(err)
+            | Self::Decode(err)
+            | Self::Embedding(err)
+            | Self::Llm(err)
+            | Self::Storage(err)
+            | Self::Spike(err) => err.chain().map(|cause| cause.to_string()).collect(),
+            Self::Timeout(_) => vec![self.to_string()],
+            Self::Stage { source, .. } => source.chain(),
+        }
+    }
+
+    /// Infrastructure and provider failures may succeed on a later attempt;
+    /// malformed payloads and bad configuration never will.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Config(_) | Self::Decode(_) => false,
+            Self::Stage { source, .. } => source.is_retryable(),
+            _ => true,
+        }
+    }
+
+    /// [`provider_error_kind`] of the underlying error; timeouts raised by the worker's own
+    /// deadline count as `timeout`.
+    pub fn provider_kind(&self) -> &'static str {
+        match self {
+            Self::Config(err)
+            | Self::Queue(err)
+            | Self::Decode(err)
+            | Self::Embedding(err)
+            | Self::Llm(err)
+            | Self::Storage(err)
+            | Self::Spike(err) => provider_error_kind(err),
+            Self::Timeout(_) => "timeout",
+            Self::Stage { source, .. } => source.provider_kind(),
+        }
+    }
+}
+
+/// Coarse classification of a provider call failure for metrics labels.
+pub fn provider_error_kind(err: &anyhow::Error) -> &'static str {
+    if err.chain().any(|cause| cause.is::<tokio::time::error::Elapsed>()) {
+        return "timeout";
+    }
+    let Some(http) = err.chain().find_map(|cause| cause.downcast_ref::<reqwest::Error>()) else {
+        return "other";
+    };
+    if http.is_timeout() {
+        return "timeout";
+    }
+    if http.is_connect() {
+        return "connect";
+    }
+    if http.is_decode() {
+        return "decode";
+    }
+    match http.status().map(|status| status.as_u16()) {
+        Some(429) => "rate_limited",
+        Some(code) if code >= 500 => "server",
+        Some(_) => "client",
+        None => "other",
+    }
+}
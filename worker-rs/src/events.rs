@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::redis_client::RedisClient;
+use crate::types::KnownEvent;
+
+/// A scheduled brand event (launch, campaign) during which spikes are expected.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventWindow {
+    #[serde(default)]
+    pub brand: Option<String>,
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// When false the spike keeps its severity and is only annotated.
+    #[serde(default = "default_downgrade")]
+    pub downgrade: bool,
+}
+
+impl EventWindow {
+    fn covers(&self, brand: &str, at: DateTime<Utc>) -> bool {
+        let brand_matches = self
+            .brand
+            .as_deref()
+            .is_none_or(|item| item == "*" || item.eq_ignore_ascii_case(brand));
+        brand_matches && self.start <= at && at < self.end
+    }
+}
+
+fn default_downgrade() -> bool {
+    true
+}
+
+/// Event windows from `EVENT_WINDOWS_FILE` plus per-brand JSON arrays stored at
+/// `{REDIS_EVENT_WINDOWS_PREFIX}:{brand}`, so operators can add windows without a redeploy.
+pub struct EventCalendar {
+    static_windows: Vec<EventWindow>,
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl EventCalendar {
+    pub fn from_settings(settings: Arc<Settings>, redis: RedisClient) -> anyhow::Result<Self> {
+        let static_windows = match &settings.event_windows_file {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path).with_context(|| format!("read event windows from {path}"))?;
+                serde_json::from_str::<Vec<EventWindow>>(&raw).with_context(|| format!("parse event windows in {path}"))?
+            }
+            None => Vec::new(),
+        };
+        if !static_windows.is_empty() {
+            info!(worker_id = %settings.worker_id, windows = static_windows.len(), "Event windows loaded");
+        }
+        Ok(Self {
+            static_windows,
+            redis,
+            settings,
+        })
+    }
+
+    pub async fn active(&self, brand: &str, at: DateTime<Utc>) -> Option<KnownEvent> {
+        let key = format!("{}:{}", self.settings.redis_event_windows_prefix, brand);
+        let dynamic = match self.redis.get_string(&key).await {
+            Ok(Some(raw)) => serde_json::from_str::<Vec<EventWindow>>(&raw).unwrap_or_else(|err| {
+                warn!(brand, key, error = %err, "Ignoring malformed event windows");
+                Vec::new()
+            }),
+            Ok(None) => Vec::new(),
+            Err(err) => {
+                warn!(brand, error = %err, "Failed to load event windows");
+                Vec::new()
+            }
+        };
+
+        self.static_windows
+            .iter()
+            .chain(dynamic.iter())
+            .find(|window| window.covers(brand, at))
+            .map(|window| KnownEvent {
+                name: window.name.clone(),
+                downgrade: window.downgrade,
+            })
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Settings;
+use crate::embeddings::EmbeddingAdapter;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::key_pool::ApiKeyPool;
+use crate::llm::{
+    batch_sentiment_prompt, confidence_prompt, emotion_prompt, entity_prompt, intent_prompt, json_repair_prompt,
+    parse_analysis, parse_batch_analysis, parse_batch_sentiment, parse_confidence, parse_emotions, parse_entities,
+    parse_intent, parse_quotes, parse_relevance, parse_sentiment, parse_severity, parse_toxicity, quote_prompt,
+    relevance_max_tokens, relevance_prompt, severity_prompt, toxicity_prompt, ClusterAnalysis, LlmAdapter,
+    SeveritySignals, ANALYSIS_JSON_TOKENS, CONFIDENCE_MAX_TOKENS, EMOTION_MAX_TOKENS, ENTITY_MAX_TOKENS,
+    INTENT_MAX_TOKENS, QUOTE_MAX_TOKENS, SEVERITY_MAX_TOKENS, TOXICITY_MAX_TOKENS,
+};
+use crate::metrics::record_unparseable_response;
+use crate::prompts::PromptTemplates;
+use crate::types::{ClusterEntities, Confidence};
+
+/// Summaries and sentiment from the Gemini `generateContent` endpoint of the Generative Language API.
+pub struct GeminiLlmAdapter {
+    http: HttpClient,
+    url: String,
+    api_keys: ApiKeyPool,
+    max_tokens: u32,
+    timeout: Duration,
+    prompts: Arc<PromptTemplates>,
+    worker_id: String,
+}
+
+#[derive(Deserialize)]
+struct GenerateContentResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Deserialize)]
+struct Candidate {
+    content: Option<Content>,
+}
+
+#[derive(Deserialize)]
+struct Content {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Deserialize)]
+struct Part {
+    text: Option<String>,
+}
+
+impl GeminiLlmAdapter {
+    pub fn new(settings: &Settings, http: HttpClient, prompts: Arc<PromptTemplates>) -> anyhow::Result<Self> {
+        let api_keys = ApiKeyPool::load(
+            "gemini",
+            settings.gemini_api_key.as_deref().or(settings.llm_api_key.as_deref()),
+            settings.llm_api_keys_file.as_deref(),
+            &settings.worker_id,
+        )?
+        .context("GEMINI_API_KEY, LLM_API_KEY or LLM_API_KEYS_FILE is required for LLM_PROVIDER=gemini")?;
+        Ok(Self {
+            http,
+            url: format!(
+                "{}/{}/models/{}:generateContent",
+                settings.gemini_base_url, settings.gemini_api_version, settings.gemini_model
+            ),
+            api_keys,
+            max_tokens: settings.llm_summary_max_tokens,
+            timeout: settings.llm_timeout,
+            prompts,
+            worker_id: settings.worker_id.clone(),
+        })
+    }
+
+    /// Asks for a JSON response and validates it with `parse`. An invalid response gets one
+    /// retry with the rejected output quoted back before the call fails.
+    async fn generate_json<T: Send>(
+        &self,
+        prompt: String,
+        max_tokens: u32,
+        operation: &str,
+        parse: impl Fn(&str) -> Option<T> + Send,
+    ) -> WorkerResult<T> {
+        let raw = self.generate(prompt.clone(), max_tokens).await.map_err(WorkerError::Llm)?;
+        if let Some(parsed) = parse(&raw) {
+            return Ok(parsed);
+        }
+        let retried = self.generate(json_repair_prompt(&prompt, &raw), max_tokens).await.map_err(WorkerError::Llm)?;
+        let parsed = parse(&retried);
+        record_unparseable_response(&self.worker_id, "gemini", operation, parsed.is_some());
+        parsed.with_context(|| format!("unparseable Gemini {operation} response: {retried}"))
+            .map_err(WorkerError::Llm)
+    }
+
+    async fn generate(&self, prompt: String, max_tokens: u32) -> anyhow::Result<String> {
+        let body = json!({
+            "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+            "generationConfig": { "maxOutputTokens": max_tokens, "temperature": 0 },
+        });
+        // The key goes in a header rather than the `key` query parameter so it never appears in logged URLs.
+        let request = |key: &str| {
+            self.http
+                .post(&self.url)
+                .header("x-goog-api-key", key)
+                .timeout(self.timeout)
+                .json(&body)
+        };
+        let response: GenerateContentResponse = self
+            .http
+            .send_with_key(&self.api_keys, "Gemini generateContent", request)
+            .await?
+            .json()
+            .await
+            .context("decode Gemini generateContent response")?;
+        let text = response
+            .candidates
+            .into_iter()
+            .next()
+            .and_then(|candidate| candidate.content)
+            .map(|content| {
+                content
+                    .parts
+                    .into_iter()
+                    .filter_map(|part| part.text)
+                    .collect::<String>()
+            })
+            .context("Gemini response had no candidates")?;
+        Ok(text.trim().to_string())
+    }
+}
+
+#[async_trait]
+impl LlmAdapter for GeminiLlmAdapter {
+    async fn summarize(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<String>> {
+        let summary = self
+            .generate(self.prompts.summary(brand, texts, self.max_tokens), self.max_tokens)
+            .await
+            .map_err(WorkerError::Llm)?;
+        Ok(Some(summary).filter(|summary| !summary.is_empty()))
+    }
+
+    async fn sentiment(&self, brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        self.generate_json(self.prompts.sentiment(brand, texts), 64, "sentiment", parse_sentiment).await
+    }
+
+    async fn sentiment_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        let max_tokens = 64 * groups.len() as u32;
+        self.generate_json(batch_sentiment_prompt(groups), max_tokens, "sentiment_batch", |raw| {
+            parse_batch_sentiment(raw, groups.len())
+        })
+        .await
+        .map(Some)
+    }
+
+    async fn analyze(&self, texts: &[String]) -> WorkerResult<Option<ClusterAnalysis>> {
+        let prompt = self.prompts.analysis(texts, self.max_tokens);
+        self.generate_json(prompt, self.max_tokens + ANALYSIS_JSON_TOKENS, "analysis", parse_analysis)
+            .await
+            .map(Some)
+    }
+
+    async fn analyze_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<ClusterAnalysis>>> {
+        let prompt = self.prompts.batch_analysis(groups, self.max_tokens);
+        let max_tokens = (self.max_tokens + ANALYSIS_JSON_TOKENS) * groups.len() as u32;
+        self.generate_json(prompt, max_tokens, "analysis_batch", |raw| parse_batch_analysis(raw, groups.len()))
+            .await
+            .map(Some)
+    }
+
+    async fn emotions(&self, texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        let prompt = emotion_prompt(texts);
+        self.generate_json(prompt, EMOTION_MAX_TOKENS, "emotion", parse_emotions).await.map(Some)
+    }
+
+    async fn severity(&self, signals: &SeveritySignals, texts: &[String]) -> WorkerResult<Option<u8>> {
+        let prompt = severity_prompt(signals, texts);
+        self.generate_json(prompt, SEVERITY_MAX_TOKENS, "severity", parse_severity).await.map(Some)
+    }
+
+    async fn confidence(
+        &self,
+        texts: &[String],
+        summary: Option<&str>,
+        sentiment: &HashMap<String, f32>,
+    ) -> WorkerResult<Option<Confidence>> {
+        let prompt = confidence_prompt(texts, summary, sentiment);
+        self.generate_json(prompt, CONFIDENCE_MAX_TOKENS, "confidence", parse_confidence).await.map(Some)
+    }
+
+    async fn quotes(&self, texts: &[String]) -> WorkerResult<Option<Vec<usize>>> {
+        self.generate_json(quote_prompt(texts), QUOTE_MAX_TOKENS, "quote", |raw| parse_quotes(raw, texts.len()))
+            .await
+            .map(Some)
+    }
+
+    async fn intent(&self, texts: &[String]) -> WorkerResult<Option<String>> {
+        let prompt = intent_prompt(texts);
+        self.generate_json(prompt, INTENT_MAX_TOKENS, "intent", parse_intent).await.map(Some)
+    }
+
+    async fn relevance(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<Vec<f32>>> {
+        let prompt = relevance_prompt(brand, texts);
+        let max_tokens = relevance_max_tokens(texts.len());
+        self.generate_json(prompt, max_tokens, "relevance", |raw| parse_relevance(raw, texts.len()))
+            .await
+            .map(Some)
+    }
+
+    async fn entities(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<ClusterEntities>> {
+        let prompt = entity_prompt(brand, texts);
+        self.generate_json(prompt, ENTITY_MAX_TOKENS, "entity", parse_entities).await.map(Some)
+    }
+
+    async fn toxicity(&self, texts: &[String]) -> WorkerResult<Option<f32>> {
+        let prompt = toxicity_prompt(texts);
+        self.generate_json(prompt, TOXICITY_MAX_TOKENS, "toxicity", parse_toxicity).await.map(Some)
+    }
+}
+
+/// Vectors from the Gemini `batchEmbedContents` endpoint, with the Gemini base URL and API
+/// version the LLM adapter uses. `EMBEDDING_API_KEY` wins over the Gemini LLM keys, so
+/// embeddings can be billed to their own project.
+pub struct GeminiEmbeddingAdapter {
+    http: HttpClient,
+    url: String,
+    model: String,
+    api_keys: ApiKeyPool,
+    batch_size: usize,
+}
+
+#[derive(Deserialize)]
+struct BatchEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<ContentEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct ContentEmbedding {
+    values: Vec<f32>,
+}
+
+impl GeminiEmbeddingAdapter {
+    pub fn new(settings: &Settings, http: HttpClient) -> anyhow::Result<Self> {
+        let keys = settings
+            .embedding_api_key
+            .as_deref()
+            .or(settings.gemini_api_key.as_deref())
+            .or(settings.llm_api_key.as_deref());
+        let file = settings
+            .embedding_api_keys_file
+            .as_deref()
+            .or(settings.llm_api_keys_file.as_deref());
+        let api_keys = ApiKeyPool::load("gemini", keys, file, &settings.worker_id)?
+            .context("EMBEDDING_API_KEY, GEMINI_API_KEY or LLM_API_KEY is required for EMBEDDINGS_PROVIDER=gemini")?;
+        let model = settings.embedding_model().unwrap_or("text-embedding-004").to_string();
+        Ok(Self {
+            http,
+            url: format!(
+                "{}/{}/models/{model}:batchEmbedContents",
+                settings.gemini_base_url, settings.gemini_api_version
+            ),
+            model,
+            api_keys,
+            batch_size: settings.embeddings_batch_size,
+        })
+    }
+
+    async fn embed_batches(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            let requests: Vec<serde_json::Value> = batch
+                .iter()
+                .map(|text| {
+                    json!({
+                        "model": format!("models/{}", self.model),
+                        "content": { "parts": [{ "text": text }] },
+                    })
+                })
+                .collect();
+            let body = json!({ "requests": requests });
+            let request = |key: &str| self.http.post(&self.url).header("x-goog-api-key", key).json(&body);
+            let response: BatchEmbedResponse = self
+                .http
+                .send_with_key(&self.api_keys, "Gemini batchEmbedContents", request)
+                .await?
+                .json()
+                .await
+                .context("decode Gemini batchEmbedContents response")?;
+            if response.embeddings.len() != batch.len() {
+                bail!(
+                    "Gemini returned {} embeddings for {} texts",
+                    response.embeddings.len(),
+                    batch.len()
+                );
+            }
+            vectors.extend(response.embeddings.into_iter().map(|embedding| embedding.values));
+        }
+        Ok(vectors)
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for GeminiEmbeddingAdapter {
+    async fn embed(&self, texts: &[String], _brand: &str, _chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>> {
+        self.embed_batches(texts).await.map_err(WorkerError::Embedding)
+    }
+}
@@ -0,0 +1,157 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::bail;
+use futures::Stream;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::admin::bearer_matches;
+use crate::error::WorkerError;
+use crate::service::{WorkerService, WorkerStatus};
+use crate::types::{Chunk, ChunkResult};
+
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("worker.v1");
+}
+
+use proto::worker_control_server::{WorkerControl, WorkerControlServer};
+
+pub struct ControlPlane {
+    service: Arc<WorkerService>,
+}
+
+impl ControlPlane {
+    pub fn new(service: Arc<WorkerService>) -> Self {
+        Self { service }
+    }
+}
+
+type ResultStream = Pin<Box<dyn Stream<Item = Result<proto::ResultNotification, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl WorkerControl for ControlPlane {
+    async fn process_chunk(
+        &self,
+        request: Request<proto::ProcessChunkRequest>,
+    ) -> Result<Response<proto::ProcessChunkResponse>, Status> {
+        let request = request.into_inner();
+        let chunk: Chunk = serde_json::from_str(&request.chunk_json)
+            .map_err(|err| Status::invalid_argument(format!("invalid chunk JSON: {err}")))?;
+        let fallback_brand = if request.brand.trim().is_empty() {
+            "unknown"
+        } else {
+            request.brand.as_str()
+        };
+        let result = self
+            .service
+            .process_chunk(chunk, fallback_brand, request.persist)
+            .await
+            .map_err(status_for)?;
+        Ok(Response::new(proto::ProcessChunkResponse {
+            result: Some(notification(&result)),
+        }))
+    }
+
+    async fn get_status(&self, _request: Request<proto::GetStatusRequest>) -> Result<Response<proto::WorkerStatus>, Status> {
+        Ok(Response::new(status_message(self.service.status().await)))
+    }
+
+    async fn pause(&self, _request: Request<proto::PauseRequest>) -> Result<Response<proto::WorkerStatus>, Status> {
+        self.service.pause();
+        Ok(Response::new(status_message(self.service.status().await)))
+    }
+
+    async fn resume(&self, _request: Request<proto::ResumeRequest>) -> Result<Response<proto::WorkerStatus>, Status> {
+        self.service.resume();
+        Ok(Response::new(status_message(self.service.status().await)))
+    }
+
+    type StreamResultsStream = ResultStream;
+
+    async fn stream_results(
+        &self,
+        request: Request<proto::StreamResultsRequest>,
+    ) -> Result<Response<Self::StreamResultsStream>, Status> {
+        let brand = request.into_inner().brand;
+        let stream = BroadcastStream::new(self.service.subscribe_results()).filter_map(move |item| match item {
+            Ok(result) if brand.is_empty() || result.brand == brand => Some(Ok(notification(&result))),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                warn!(skipped, "Result stream subscriber lagged; notifications dropped");
+                None
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn notification(result: &ChunkResult) -> proto::ResultNotification {
+    proto::ResultNotification {
+        chunk_id: result.chunk_id.clone(),
+        brand: result.brand.clone(),
+        timestamp: result.timestamp,
+        cluster_count: result.clusters.len() as u32,
+        spike_detected: result.clusters.iter().any(|cluster| cluster.spike),
+        degraded: result.provenance.degraded(),
+        result_json: serde_json::to_string(result).unwrap_or_default(),
+    }
+}
+
+fn status_message(status: WorkerStatus) -> proto::WorkerStatus {
+    proto::WorkerStatus {
+        worker_id: status.worker_id,
+        paused: status.paused,
+        waiting_seconds: status.waiting_seconds,
+        processed_total: status.processed_total,
+        throttled: status.throttled.unwrap_or_default().to_string(),
+    }
+}
+
+fn status_for(err: WorkerError) -> Status {
+    match err {
+        WorkerError::Decode(_) | WorkerError::Config(_) => Status::invalid_argument(err.to_string()),
+        WorkerError::Timeout(_) => Status::deadline_exceeded(err.to_string()),
+        other if other.is_retryable() => Status::unavailable(other.to_string()),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+/// Serves the control plane until shutdown. Every call needs the admin bearer token, like the
+/// HTTP admin routes; without `ADMIN_API_TOKEN` the server refuses to start.
+// tonic fixes the interceptor's error type to `Status`.
+#[allow(clippy::result_large_err)]
+pub async fn serve(service: Arc<WorkerService>, mut shutdown: broadcast::Receiver<()>) -> anyhow::Result<()> {
+    let settings = service.settings().clone();
+    let Some(expected) = settings.admin_api_token.clone() else {
+        bail!("GRPC_ENABLED requires ADMIN_API_TOKEN");
+    };
+    let addr = SocketAddr::from(([0, 0, 0, 0], settings.grpc_port));
+    let server = WorkerControlServer::with_interceptor(ControlPlane::new(service), move |request: Request<()>| {
+        let authorized = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| bearer_matches(value, &expected));
+        if authorized {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("unauthorized"))
+        }
+    });
+
+    info!(grpc_port = settings.grpc_port, "gRPC control plane listening");
+    tonic::transport::Server::builder()
+        .add_service(server)
+        .serve_with_shutdown(addr, async move {
+            let _ = shutdown.recv().await;
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use rand::Rng;
+use reqwest::redirect::Policy;
+use reqwest::{NoProxy, Request, RequestBuilder, Response, StatusCode};
+use tracing::warn;
+
+use crate::config::Settings;
+use crate::error::provider_error_kind;
+use crate::key_pool::ApiKeyPool;
+use crate::metrics::WORKER_EGRESS_BLOCKED_TOTAL;
+
+/// Shared outbound HTTP client for provider, sink and alert traffic. Cloning is cheap and
+/// every clone draws from the same connection pool.
+///
+/// `HTTP_PROXY_URL` (or `HTTPS_PROXY`) routes every request through one proxy, except hosts
+/// listed in `NO_PROXY`. When `EGRESS_ALLOWED_HOSTS` is set, requests to any other host are
+/// refused before a connection is made, and so are redirects to one.
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: reqwest::Client,
+    max_retries: u32,
+    backoff_base: Duration,
+    /// Longest `Retry-After` delay honoured before a retry.
+    max_retry_delay: Duration,
+    allowed_hosts: Vec<String>,
+    worker_id: String,
+}
+
+impl HttpClient {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&settings.http_user_agent)
+            .timeout(settings.http_timeout)
+            .connect_timeout(settings.http_connect_timeout)
+            .pool_max_idle_per_host(settings.http_pool_max_idle_per_host)
+            .pool_idle_timeout(settings.http_pool_idle_timeout);
+        if !settings.egress_allowed_hosts.is_empty() {
+            builder = builder.redirect(egress_redirect_policy(
+                settings.egress_allowed_hosts.clone(),
+                settings.worker_id.clone(),
+            ));
+        }
+        if let Some(proxy) = &settings.http_proxy_url {
+            let no_proxy = settings.http_no_proxy.as_deref().and_then(NoProxy::from_string);
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy)
+                    .context("parse HTTP_PROXY_URL")?
+                    .no_proxy(no_proxy),
+            );
+        }
+        let inner = builder.build().context("build HTTP client")?;
+        Ok(Self {
+            inner,
+            max_retries: settings.max_retries,
+            backoff_base: Duration::from_secs_f64(settings.retry_backoff_base),
+            max_retry_delay: settings.http_timeout,
+            allowed_hosts: settings.egress_allowed_hosts.clone(),
+            worker_id: settings.worker_id.clone(),
+        })
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.inner.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.inner.post(url)
+    }
+
+    pub fn put(&self, url: &str) -> RequestBuilder {
+        self.inner.put(url)
+    }
+
+    /// Sends the request, retrying connection failures, timeouts, 429s and 5xx responses
+    /// up to `MAX_RETRIES` times with jittered exponential backoff from `RETRY_BACKOFF_BASE`,
+    /// or after the response's `Retry-After` delay, capped at `HTTP_TIMEOUT_SEC`.
+    /// Non-success responses are returned as errors.
+    pub async fn send(&self, request: RequestBuilder, what: &str) -> anyhow::Result<Response> {
+        self.execute(request, what, true).await
+    }
+
+    /// Like `send`, with the request built around a key from `keys`. A 429 rotates the pool
+    /// and tries the next key straight away; only once every key has been tried does a 429
+    /// get retried with backoff.
+    pub async fn send_with_key(
+        &self,
+        keys: &ApiKeyPool,
+        what: &str,
+        build: impl Fn(&str) -> RequestBuilder,
+    ) -> anyhow::Result<Response> {
+        for _ in 1..keys.key_count() {
+            let (index, key) = keys.current();
+            match self.execute(build(key), what, false).await {
+                Err(err) if provider_error_kind(&err) == "rate_limited" => keys.rotate(index),
+                outcome => return outcome,
+            }
+        }
+        self.execute(build(keys.current().1), what, true).await
+    }
+
+    async fn execute(&self, request: RequestBuilder, what: &str, retry_rate_limited: bool) -> anyhow::Result<Response> {
+        let request = request.build().with_context(|| format!("build {what}"))?;
+        self.check_egress(&request)?;
+
+        let mut attempt = 0;
+        loop {
+            // Streaming bodies cannot be replayed, so those requests get a single attempt.
+            let Some(current) = (attempt < self.max_retries).then(|| request.try_clone()).flatten() else {
+                return finish(self.inner.execute(request).await, what);
+            };
+
+            match self.inner.execute(current).await {
+                Ok(response)
+                    if !is_transient_status(response.status())
+                        || (!retry_rate_limited && response.status() == StatusCode::TOO_MANY_REQUESTS) =>
+                {
+                    return finish(Ok(response), what);
+                }
+                Ok(response) => {
+                    let delay = retry_after(&response)
+                        .map(|delay| delay.min(self.max_retry_delay))
+                        .unwrap_or_else(|| self.backoff(attempt));
+                    warn!(what, status = %response.status(), attempt, delay_ms = delay.as_millis() as u64, "HTTP request failed; retrying");
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) if err.is_timeout() || err.is_connect() => {
+                    let delay = self.backoff(attempt);
+                    warn!(what, error = %err, attempt, delay_ms = delay.as_millis() as u64, "HTTP request failed; retrying");
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => return finish(Err(err), what),
+            }
+            attempt += 1;
+        }
+    }
+
+    fn check_egress(&self, request: &Request) -> anyhow::Result<()> {
+        if self.allowed_hosts.is_empty() {
+            return Ok(());
+        }
+        let host = request.url().host_str().unwrap_or_default().to_ascii_lowercase();
+        if host_allowed(&self.allowed_hosts, &host, &self.worker_id) {
+            return Ok(());
+        }
+        bail!("outbound host '{host}' is not in EGRESS_ALLOWED_HOSTS")
+    }
+
+    /// Half of the exponential delay is fixed and half random, so workers that failed
+    /// together do not retry in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.backoff_base.saturating_mul(2u32.saturating_pow(attempt));
+        delay.mul_f64(0.5 + rand::thread_rng().gen_range(0.0..0.5))
+    }
+}
+
+fn finish(outcome: reqwest::Result<Response>, what: &str) -> anyhow::Result<Response> {
+    outcome
+        .with_context(|| format!("send {what}"))?
+        .error_for_status()
+        .with_context(|| format!("{what} returned an error"))
+}
+
+/// Follows redirects, up to reqwest's default of 10 hops, only while each hop stays on an
+/// allowed host.
+fn egress_redirect_policy(allowed_hosts: Vec<String>, worker_id: String) -> Policy {
+    Policy::custom(move |attempt| {
+        let host = attempt.url().host_str().unwrap_or_default().to_ascii_lowercase();
+        if !host_allowed(&allowed_hosts, &host, &worker_id) {
+            let message = format!("redirect to host '{host}' is not in EGRESS_ALLOWED_HOSTS");
+            attempt.error(message)
+        } else if attempt.previous().len() >= 10 {
+            attempt.error("too many redirects")
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// Counts the host in `worker_egress_blocked_total` when it is refused.
+fn host_allowed(allowed_hosts: &[String], host: &str, worker_id: &str) -> bool {
+    if allowed_hosts.iter().any(|pattern| host_matches(pattern, host)) {
+        return true;
+    }
+    WORKER_EGRESS_BLOCKED_TOTAL.with_label_values(&[worker_id, host]).inc();
+    false
+}
+
+/// `*.example.com` matches any subdomain of example.com; other patterns must match exactly.
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::config::Settings;
+use crate::error::WorkerError;
+use crate::redis_client::RedisClient;
+use crate::types::{Chunk, ChunkResult, Provenance};
+
+/// One step in a chunk's lifecycle: `received`, `completed` or `failed`. Carries IDs,
+/// stage timings and providers only, never mention text, so the journal can outlive the
+/// results it describes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub event: &'static str,
+    pub at: DateTime<Utc>,
+    pub worker_id: String,
+    pub brand: String,
+    pub chunk_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reprocess: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mentions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clusters: Option<usize>,
+    /// Stages that completed, with their durations.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub stage_times_ms: BTreeMap<String, f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_stages: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+}
+
+impl JournalEntry {
+    fn new(event: &'static str, worker_id: &str, brand: &str, chunk_id: &str) -> Self {
+        Self {
+            event,
+            at: Utc::now(),
+            worker_id: worker_id.to_string(),
+            brand: brand.to_string(),
+            chunk_id: chunk_id.to_string(),
+            correlation_id: None,
+            attempt: None,
+            reprocess: None,
+            mentions: None,
+            clusters: None,
+            stage_times_ms: BTreeMap::new(),
+            skipped_stages: Vec::new(),
+            provenance: None,
+            total_ms: None,
+            reason: None,
+            error_kind: None,
+            stage: None,
+        }
+    }
+
+    pub fn received(worker_id: &str, brand: &str, chunk: &Chunk, reprocess: bool) -> Self {
+        let meta = chunk.meta.as_ref();
+        Self {
+            correlation_id: meta.and_then(|meta| meta.correlation_id.clone()),
+            attempt: Some(meta.and_then(|meta| meta.attempt).unwrap_or(1)),
+            reprocess: Some(reprocess),
+            mentions: Some(chunk.mentions.len()),
+            ..Self::new("received", worker_id, brand, &chunk.chunk_id)
+        }
+    }
+
+    pub fn completed(worker_id: &str, result: &ChunkResult) -> Self {
+        Self {
+            clusters: Some(result.clusters.len()),
+            stage_times_ms: result.metrics.stage_times_ms.clone(),
+            skipped_stages: result.metrics.skipped_stages.clone(),
+            provenance: Some(result.provenance.clone()),
+            total_ms: Some(result.metrics.total_task_time_ms),
+            ..Self::new("completed", worker_id, &result.brand, &result.chunk_id)
+        }
+    }
+
+    pub fn failed(worker_id: &str, brand: &str, chunk_id: &str, reason: &'static str, err: &WorkerError) -> Self {
+        Self {
+            reason: Some(reason),
+            error_kind: Some(err.kind()),
+            stage: err.stage().map(str::to_string),
+            ..Self::new("failed", worker_id, brand, chunk_id)
+        }
+    }
+}
+
+enum JournalSink {
+    Off,
+    /// Entries go to `JOURNAL_REDIS_STREAM` as an `entry` JSON field.
+    Redis,
+    /// Appends to the day's file; holds the day last written so old files are pruned once
+    /// per day.
+    File(Mutex<Option<NaiveDate>>),
+}
+
+/// Append-only audit trail of every chunk the worker takes in, from `JOURNAL_SINK`, so an
+/// audit can reconstruct what happened to a customer's chunk after its results have expired.
+/// Entries older than `JOURNAL_RETENTION_DAYS` are trimmed from the stream or their daily
+/// files deleted. Journal writes never fail the chunk.
+pub struct ProcessingJournal {
+    sink: JournalSink,
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl ProcessingJournal {
+    pub fn from_settings(settings: Arc<Settings>, redis: RedisClient) -> anyhow::Result<Self> {
+        let sink = match settings.journal_sink.as_str() {
+            "redis" => JournalSink::Redis,
+            "file" => {
+                std::fs::create_dir_all(&settings.journal_dir)
+                    .with_context(|| format!("create journal directory {}", settings.journal_dir))?;
+                JournalSink::File(Mutex::new(None))
+            }
+            _ => JournalSink::Off,
+        };
+        Ok(Self { sink, redis, settings })
+    }
+
+    pub async fn record(&self, entry: JournalEntry) {
+        if let Err(err) = self.write(&entry).await {
+            warn!(
+                worker_id = %self.settings.worker_id,
+                brand = %entry.brand,
+                chunk_id = %entry.chunk_id,
+                event = entry.event,
+                error = %err,
+                "Failed to write processing journal entry"
+            );
+        }
+    }
+
+    async fn write(&self, entry: &JournalEntry) -> anyhow::Result<()> {
+        if matches!(self.sink, JournalSink::Off) {
+            return Ok(());
+        }
+        let line = serde_json::to_string(entry).context("serialise journal entry")?;
+        match &self.sink {
+            JournalSink::Off => Ok(()),
+            JournalSink::Redis => {
+                let retention = chrono::Duration::from_std(self.settings.journal_retention)?;
+                let min_id = (entry.at - retention).timestamp_millis().max(0);
+                self.redis
+                    .xadd_trimmed(&self.settings.journal_redis_stream, min_id, &[("entry", line)])
+                    .await
+            }
+            JournalSink::File(last_day) => {
+                let day = entry.at.date_naive();
+                // Held across the append so concurrent writers don't interleave lines.
+                let mut last_day = last_day.lock().await;
+                if *last_day != Some(day) {
+                    self.prune(day);
+                    *last_day = Some(day);
+                }
+                let path = self.day_path(day);
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .await
+                    .with_context(|| format!("open journal file {}", path.display()))?;
+                file.write_all(format!("{line}\n").as_bytes())
+                    .await
+                    .with_context(|| format!("append to journal file {}", path.display()))
+            }
+        }
+    }
+
+    fn day_path(&self, day: NaiveDate) -> PathBuf {
+        PathBuf::from(&self.settings.journal_dir).join(format!("journal-{}.jsonl", day.format("%Y-%m-%d")))
+    }
+
+    /// Deletes daily files that fell out of the retention window.
+    fn prune(&self, today: NaiveDate) {
+        let days = self.settings.journal_retention.as_secs() / 86_400;
+        let Some(cutoff) = today.checked_sub_days(Days::new(days)) else {
+            return;
+        };
+        let entries = match std::fs::read_dir(&self.settings.journal_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                warn!(dir = %self.settings.journal_dir, error = %err, "Failed to list journal files");
+                return;
+            }
+        };
+        for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            let day = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("journal-")?.strip_suffix(".jsonl"))
+                .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok());
+            if day.is_some_and(|day| day < cutoff) {
+                if let Err(err) = std::fs::remove_file(&path) {
+                    warn!(path = %path.display(), error = %err, "Failed to delete expired journal file");
+                }
+            }
+        }
+    }
+}
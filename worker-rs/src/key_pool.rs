@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+use tracing::warn;
+
+use crate::metrics::WORKER_API_KEY_ROTATIONS_TOTAL;
+
+/// API keys for one provider. Every call uses the current key, and a 429 on it moves the
+/// pool to the next, so one rate-limited key doesn't stall every brand. Keys come from a
+/// comma-separated setting plus an optional file with one key per line.
+pub struct ApiKeyPool {
+    keys: Vec<String>,
+    current: AtomicUsize,
+    provider: String,
+    worker_id: String,
+}
+
+impl ApiKeyPool {
+    /// `None` when neither source holds a key. Blank lines and `#` comments in the file are
+    /// skipped and repeated keys kept once.
+    pub fn load(
+        provider: &str,
+        keys: Option<&str>,
+        file: Option<&str>,
+        worker_id: &str,
+    ) -> anyhow::Result<Option<Self>> {
+        let mut pooled: Vec<String> = Vec::new();
+        let from_file = match file {
+            Some(path) => std::fs::read_to_string(path).with_context(|| format!("read API keys from {path}"))?,
+            None => String::new(),
+        };
+        let listed = keys.into_iter().flat_map(|keys| keys.split(','));
+        let filed = from_file.lines().filter(|line| !line.trim_start().starts_with('#'));
+        for key in listed.chain(filed).map(str::trim).filter(|key| !key.is_empty()) {
+            if !pooled.iter().any(|pooled| pooled == key) {
+                pooled.push(key.to_string());
+            }
+        }
+        if pooled.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            keys: pooled,
+            current: AtomicUsize::new(0),
+            provider: provider.to_string(),
+            worker_id: worker_id.to_string(),
+        }))
+    }
+
+    /// The key to use, with its position to hand back to `rotate` if it gets rate-limited.
+    pub fn current(&self) -> (usize, &str) {
+        let index = self.current.load(Ordering::Acquire) % self.keys.len();
+        (index, &self.keys[index])
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Moves on from the key at `index`, unless a concurrent call already has.
+    pub fn rotate(&self, index: usize) {
+        let next = (index + 1) % self.keys.len();
+        if self
+            .current
+            .compare_exchange(index, next, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            WORKER_API_KEY_ROTATIONS_TOTAL
+                .with_label_values(&[&self.worker_id, &self.provider])
+                .inc();
+            warn!(provider = %self.provider, key = index, next, "API key rate-limited; rotating to the next key");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::redis_client::RedisClient;
+
+/// First label given to a recurring narrative, kept per stable cluster ID so later chunks
+/// reuse it instead of the LLM's fresh wording. Stable IDs come from recurrence matching,
+/// so this needs `CLUSTER_RECURRENCE_ENABLED` as well. Labels are stored sealed.
+pub struct ClusterLabels {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+}
+
+impl ClusterLabels {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, cipher: Arc<PayloadCipher>) -> Self {
+        Self {
+            redis,
+            settings,
+            cipher,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.cluster_label_cache_enabled && self.settings.cluster_recurrence_enabled
+    }
+
+    /// Returns the cached label for `stable_id`, refreshing its TTL, or stores `candidate`
+    /// as the label when there is none yet. Without `record` nothing is written.
+    pub async fn resolve(
+        &self,
+        brand: &str,
+        stable_id: &str,
+        candidate: Option<&str>,
+        record: bool,
+    ) -> anyhow::Result<Option<String>> {
+        let key = self.key(brand, stable_id);
+        let ttl = self.settings.cluster_label_ttl;
+        if let Some(stored) = self.redis.get_string(&key).await? {
+            if record {
+                self.redis.set_with_ttl(&key, &stored, ttl).await?;
+            }
+            return Ok(Some(self.cipher.decrypt(&stored)?));
+        }
+        let Some(candidate) = candidate.map(str::trim).filter(|label| !label.is_empty()) else {
+            return Ok(None);
+        };
+        if !record {
+            return Ok(Some(candidate.to_string()));
+        }
+        if self.redis.set_nx_with_ttl(&key, &self.cipher.encrypt(candidate)?, ttl).await? {
+            return Ok(Some(candidate.to_string()));
+        }
+        // Another worker labelled the narrative first.
+        match self.redis.get_string(&key).await? {
+            Some(stored) => Ok(Some(self.cipher.decrypt(&stored)?)),
+            None => Ok(Some(candidate.to_string())),
+        }
+    }
+
+    fn key(&self, brand: &str, stable_id: &str) -> String {
+        format!("{}:{}:{}", self.settings.redis_cluster_label_prefix, brand, stable_id)
+    }
+}
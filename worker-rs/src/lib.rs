@@ -1,14 +1,81 @@
+pub mod admin;
+pub mod alerts;
+pub mod analysis_cache;
+pub mod anthropic;
 pub mod app;
+pub mod archive;
+pub mod budget;
+pub mod chunk_router;
+pub mod cohere;
+pub mod compare;
+pub mod confidence;
+pub mod context;
 pub mod config;
+pub mod control;
+pub mod crypto;
+pub mod dedup;
+pub mod digest;
+pub mod doctor;
+pub mod error;
+pub mod events;
+pub mod gemini;
+pub mod grpc;
+pub mod http;
+pub mod journal;
+pub mod key_pool;
 pub mod logging;
+pub mod media;
+pub mod memory_monitor;
 pub mod metrics;
+pub mod model_overrides;
+pub mod noise;
+pub mod novelty;
+pub mod onboarding;
+pub mod onnx;
+pub mod openai;
+pub mod embedding_cache;
 pub mod embeddings;
+pub mod engagement;
 pub mod clustering;
+pub mod labels;
 pub mod llm;
+pub mod pipeline;
 pub mod spike;
 pub mod processor;
+pub mod prompts;
+pub mod qa;
 pub mod queue_consumer;
+pub mod ratelimit;
+pub mod ratings;
+pub mod recluster;
+pub mod recurrence;
 pub mod redis_client;
+pub mod relevance;
+pub mod retention;
+pub mod routing;
+pub mod sampling;
+pub mod sanitize;
 pub mod service;
+pub mod severity;
+pub mod simulated;
+pub mod sinks;
+pub mod slo;
+pub mod stages;
 pub mod storage;
+pub mod taxonomy;
+pub mod tei;
+pub mod throttle;
+pub mod tiering;
+pub mod topic_trend;
+pub mod toxicity;
+pub mod trend;
 pub mod types;
+pub mod vector_store;
+pub mod wasm_hook;
+
+pub use config::Settings;
+pub use error::{WorkerError, WorkerResult};
+pub use pipeline::Pipeline;
+pub use stages::{PipelineStage, StageContext};
+pub use redis_client::RedisClient;
+pub use types::{Chunk, ChunkResult};
@@ -1,29 +1,193 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
 use tracing::{info, warn};
 
+use crate::anthropic::AnthropicLlmAdapter;
+use crate::budget::{estimate_tokens, BudgetGuard, BudgetKind};
 use crate::config::Settings;
-use crate::metrics::WORKER_LLM_LATENCY_SECONDS;
+use crate::context::ProcessingContext;
+use crate::error::{WorkerError, WorkerResult};
+use crate::gemini::GeminiLlmAdapter;
+use crate::http::HttpClient;
+use crate::metrics::{record_llm_usage, record_provider_call, WORKER_LLM_LATENCY_SECONDS, WORKER_LLM_TIMEOUTS_TOTAL};
+use crate::model_overrides::ModelOverrides;
+use crate::openai::OpenAiLlmAdapter;
+use crate::prompts::PromptTemplates;
+use crate::ratelimit::RateLimiter;
+use crate::simulated::SimulatedLlmAdapter;
+use crate::redis_client::RedisClient;
+use crate::routing::HealthRoutedLlmAdapter;
+use crate::sanitize::SanitizingLlmAdapter;
+use crate::types::{ClusterEntities, Confidence, Provenance};
 
 #[async_trait]
 pub trait LlmAdapter: Send + Sync {
-    async fn summarize(&self, texts: &[String]) -> Option<String>;
-    async fn sentiment(&self, texts: &[String]) -> HashMap<String, f32>;
+    async fn summarize(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<String>>;
+    async fn sentiment(&self, brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>>;
+
+    async fn sentiment_batch(&self, _groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        Ok(None)
+    }
+
+    /// `None` when the provider has no combined mode.
+    async fn analyze(&self, _texts: &[String]) -> WorkerResult<Option<ClusterAnalysis>> {
+        Ok(None)
+    }
+
+    /// Combined analysis of several clusters in one request, in `groups` order; `None` when
+    /// the provider has no batch mode.
+    async fn analyze_batch(&self, _groups: &[Vec<String>]) -> WorkerResult<Option<Vec<ClusterAnalysis>>> {
+        Ok(None)
+    }
+
+    /// Strength of each of [`EMOTIONS`] between 0 and 1; `None` when the provider can't classify them.
+    async fn emotions(&self, _texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        Ok(None)
+    }
+
+    /// Crisis severity from 0 to 100 for a cluster described by `signals`; `None` when the
+    /// provider can't score it.
+    async fn severity(&self, _signals: &SeveritySignals, _texts: &[String]) -> WorkerResult<Option<u8>> {
+        Ok(None)
+    }
+
+    /// The model's own confidence in `summary` and `sentiment` as a reading of `texts`;
+    /// `None` when the provider can't report it.
+    async fn confidence(
+        &self,
+        _texts: &[String],
+        _summary: Option<&str>,
+        _sentiment: &HashMap<String, f32>,
+    ) -> WorkerResult<Option<Confidence>> {
+        Ok(None)
+    }
+
+    /// Indices into `texts` of the one to [`QUOTE_LIMIT`] most representative mentions;
+    /// `None` when the provider can't pick them.
+    async fn quotes(&self, _texts: &[String]) -> WorkerResult<Option<Vec<usize>>> {
+        Ok(None)
+    }
+
+    /// One of [`INTENTS`]; `None` when the provider can't classify intent.
+    async fn intent(&self, _texts: &[String]) -> WorkerResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Probability that each text is about `brand` rather than another sense of its name;
+    /// `None` when the provider can't classify relevance.
+    async fn relevance(&self, _brand: &str, _texts: &[String]) -> WorkerResult<Option<Vec<f32>>> {
+        Ok(None)
+    }
+
+    /// People, products, competitors and locations named in the texts; `None` when the
+    /// provider can't extract them.
+    async fn entities(&self, _brand: &str, _texts: &[String]) -> WorkerResult<Option<ClusterEntities>> {
+        Ok(None)
+    }
+
+    /// How abusive the texts are, between 0 and 1; `None` when the provider can't score it.
+    async fn toxicity(&self, _texts: &[String]) -> WorkerResult<Option<f32>> {
+        Ok(None)
+    }
+
+    /// One-sentence description of the image at `image_url`; `None` without multimodal support.
+    async fn caption(&self, _image_url: &str) -> WorkerResult<Option<String>> {
+        Ok(None)
+    }
+}
+
+pub const CAPTION_PROMPT: &str = "Describe this image from a social media post in one sentence, \
+     including any visible text, logos or products.";
+pub const CAPTION_MAX_TOKENS: u32 = 96;
+
+pub const EMOTIONS: [&str; 5] = ["anger", "joy", "fear", "disappointment", "excitement"];
+
+/// What the authors of a cluster want, for routing complaints to support and churn risk to
+/// account managers.
+pub const INTENTS: [&str; 5] = ["complaint", "praise", "question", "churn_risk", "other"];
+
+/// What a severity score is based on, besides the cluster's mentions.
+#[derive(Debug, Clone)]
+pub struct SeveritySignals {
+    pub summary: Option<String>,
+    pub mention_count: usize,
+    pub negative: f32,
+    pub spike: bool,
+    /// A spike explained by a scheduled event, such as a launch.
+    pub known_event: bool,
+}
+
+/// Summary, sentiment and topics for one cluster from a single structured call.
+#[derive(Debug, Clone)]
+pub struct ClusterAnalysis {
+    pub summary: Option<String>,
+    pub sentiment: HashMap<String, f32>,
+    pub topics: Vec<String>,
 }
 
+const SENTIMENT_OUTPUT_TOKENS: u64 = 24;
+const EMOTION_OUTPUT_TOKENS: u64 = 40;
+/// Output budget for an emotion classification call.
+pub const EMOTION_MAX_TOKENS: u32 = 64;
+const TOXICITY_OUTPUT_TOKENS: u64 = 8;
+const ENTITY_OUTPUT_TOKENS: u64 = 80;
+/// Output budget for an entity extraction call.
+pub const ENTITY_MAX_TOKENS: u32 = 160;
+const ENTITY_LIMIT: usize = 5;
+const RELEVANCE_OUTPUT_TOKENS_PER_TEXT: u64 = 8;
+const INTENT_OUTPUT_TOKENS: u64 = 4;
+const QUOTE_OUTPUT_TOKENS: u64 = 12;
+const SEVERITY_OUTPUT_TOKENS: u64 = 8;
+const CONFIDENCE_OUTPUT_TOKENS: u64 = 16;
+/// Output budget for a confidence call.
+pub const CONFIDENCE_MAX_TOKENS: u32 = 32;
+/// Output budget for a severity scoring call.
+pub const SEVERITY_MAX_TOKENS: u32 = 16;
+/// Output budget for a quote selection call.
+pub const QUOTE_MAX_TOKENS: u32 = 32;
+/// Most quotes kept per cluster.
+pub const QUOTE_LIMIT: usize = 3;
+/// Output budget for an intent classification call.
+pub const INTENT_MAX_TOKENS: u32 = 16;
+
+/// Output budget for classifying the relevance of `texts` mentions.
+pub fn relevance_max_tokens(texts: usize) -> u32 {
+    16 + RELEVANCE_OUTPUT_TOKENS_PER_TEXT as u32 * texts as u32
+}
+/// Output budget for a toxicity scoring call.
+pub const TOXICITY_MAX_TOKENS: u32 = 16;
+const ANALYSIS_TOPIC_LIMIT: usize = 5;
+/// Output headroom for the JSON wrapper, sentiment and topics around a combined summary.
+pub const ANALYSIS_JSON_TOKENS: u32 = 128;
+
 pub struct MockLlmAdapter;
 
 #[async_trait]
 impl LlmAdapter for MockLlmAdapter {
-    async fn summarize(&self, texts: &[String]) -> Option<String> {
-        texts.first().cloned()
+    async fn summarize(&self, _brand: &str, texts: &[String]) -> WorkerResult<Option<String>> {
+        Ok(texts.first().cloned())
+    }
+
+    async fn sentiment(&self, _brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        Ok(simple_sentiment(texts))
+    }
+
+    async fn sentiment_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        Ok(Some(groups.iter().map(|texts| simple_sentiment(texts)).collect()))
     }
 
-    async fn sentiment(&self, texts: &[String]) -> HashMap<String, f32> {
-        simple_sentiment(texts)
+    async fn emotions(&self, texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        Ok(Some(simple_emotions(texts)))
+    }
+
+    async fn intent(&self, texts: &[String]) -> WorkerResult<Option<String>> {
+        Ok(Some(simple_intent(texts)))
     }
 }
 
@@ -33,62 +197,1125 @@ pub struct RemoteLlmAdapter {
 
 #[async_trait]
 impl LlmAdapter for RemoteLlmAdapter {
-    async fn summarize(&self, texts: &[String]) -> Option<String> {
-        warn!(provider = %self.provider, "Remote LLM summarize not implemented; using heuristic fallback");
-        texts.first().cloned()
+    async fn summarize(&self, _brand: &str, _texts: &[String]) -> WorkerResult<Option<String>> {
+        Err(not_implemented(&self.provider))
+    }
+
+    async fn sentiment(&self, _brand: &str, _texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        Err(not_implemented(&self.provider))
+    }
+}
+
+fn not_implemented(provider: &str) -> WorkerError {
+    WorkerError::Config(anyhow::anyhow!("LLM provider '{provider}' is not implemented"))
+}
+
+/// Pacers are keyed by provider and shared across pipelines, so live, backfill and shadow
+/// traffic together stay within `LLM_MAX_CONCURRENCY` and `LLM_MIN_DELAY_SEC`.
+static PACERS: Lazy<StdMutex<HashMap<String, Arc<Pacer>>>> = Lazy::new(Default::default);
+
+/// Holds back or releases slots on every pacer, for the resource throttle.
+pub fn throttle_pacers(throttled: bool) {
+    for pacer in PACERS.lock().expect("LLM pacer registry poisoned").values() {
+        pacer.set_throttled(throttled);
     }
+}
+
+/// Caps in-flight provider calls and spaces out their start times.
+pub struct Pacer {
+    permits: Semaphore,
+    max_concurrency: usize,
+    /// Slots taken out of circulation while the worker is throttled.
+    held: StdMutex<usize>,
+    min_delay: Duration,
+    last_start: Mutex<Option<Instant>>,
+}
 
-    async fn sentiment(&self, texts: &[String]) -> HashMap<String, f32> {
-        warn!(provider = %self.provider, "Remote LLM sentiment not implemented; using heuristic fallback");
-        simple_sentiment(texts)
+impl Pacer {
+    /// A pacer registered with other limits, e.g. before a config reload, is replaced; calls
+    /// already holding its slots finish on it.
+    pub fn shared(provider: &str, max_concurrency: usize, min_delay: Duration) -> Arc<Self> {
+        let mut pacers = PACERS.lock().expect("LLM pacer registry poisoned");
+        if let Some(pacer) = pacers.get(provider) {
+            if pacer.max_concurrency == max_concurrency && pacer.min_delay == min_delay {
+                return pacer.clone();
+            }
+        }
+        let pacer = Arc::new(Self {
+            permits: Semaphore::new(max_concurrency),
+            max_concurrency,
+            held: StdMutex::new(0),
+            min_delay,
+            last_start: Mutex::new(None),
+        });
+        if let Some(previous) = pacers.insert(provider.to_string(), pacer.clone()) {
+            if *previous.held.lock().expect("LLM pacer poisoned") > 0 {
+                pacer.set_throttled(true);
+            }
+        }
+        pacer
+    }
+
+    /// While throttled, half the slots (never the last one) are held back; slots busy at the
+    /// time are only taken once released, on a later call.
+    fn set_throttled(&self, throttled: bool) {
+        let mut held = self.held.lock().expect("LLM pacer poisoned");
+        if throttled {
+            let target = self.max_concurrency - self.max_concurrency.div_ceil(2);
+            *held += self.permits.forget_permits(target.saturating_sub(*held));
+        } else if *held > 0 {
+            self.permits.add_permits(*held);
+            *held = 0;
+        }
+    }
+
+    /// Waits for a free slot and the minimum delay since the previous call started.
+    async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self.permits.acquire().await.expect("LLM pacer semaphore closed");
+        if !self.min_delay.is_zero() {
+            let mut last_start = self.last_start.lock().await;
+            if let Some(last_start) = *last_start {
+                tokio::time::sleep(self.min_delay.saturating_sub(last_start.elapsed())).await;
+            }
+            *last_start = Some(Instant::now());
+        }
+        permit
     }
 }
 
 pub struct InstrumentedLlmAdapter {
     delegate: Arc<dyn LlmAdapter>,
+    fallback: Arc<dyn LlmAdapter>,
+    provider: String,
+    model: String,
+    budget: Option<BudgetGuard>,
+    rate_limiter: Option<RateLimiter>,
+    pacer: Option<Arc<Pacer>>,
+    overrides: Option<Arc<ModelOverrides>>,
+    timeout: Option<Duration>,
     worker_id: String,
 }
 
 impl InstrumentedLlmAdapter {
-    pub fn new(delegate: Arc<dyn LlmAdapter>, worker_id: String) -> Self {
-        Self { delegate, worker_id }
+    pub fn new(
+        delegate: Arc<dyn LlmAdapter>,
+        provider: String,
+        budget: Option<BudgetGuard>,
+        worker_id: String,
+    ) -> Self {
+        Self {
+            delegate,
+            fallback: Arc::new(MockLlmAdapter),
+            provider,
+            model: "unknown".to_string(),
+            budget,
+            rate_limiter: None,
+            pacer: None,
+            overrides: None,
+            timeout: None,
+            worker_id,
+        }
     }
 
-    pub async fn summarize(&self, brand: &str, texts: &[String]) -> Option<String> {
-        self.observe(brand, "summary", || self.delegate.summarize(texts)).await
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Throttles calls to the provider; budget downgrades to the fallback are not limited.
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Model label for token and cost metrics.
+    pub fn with_model(mut self, model: Option<&str>) -> Self {
+        if let Some(model) = model {
+            self.model = model.to_string();
+        }
+        self
+    }
+
+    pub fn with_pacer(mut self, pacer: Option<Arc<Pacer>>) -> Self {
+        self.pacer = pacer;
+        self
+    }
+
+    /// Cancels provider calls, including their retries, that run longer than `timeout`; they
+    /// fall back like failed calls.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends calls for brands with a model override to the overridden provider and model.
+    pub fn with_model_overrides(mut self, overrides: Option<Arc<ModelOverrides>>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// `provider/model` that `job`'s calls go to, after any brand or chunk override.
+    pub async fn effective_model(&self, job: &ProcessingContext) -> String {
+        let resolved = match &self.overrides {
+            Some(overrides) => overrides.resolve(&job.brand, job.llm_override.as_ref()).await,
+            None => None,
+        };
+        match resolved {
+            Some(resolved) => format!("{}/{}", resolved.provider, resolved.model.as_deref().unwrap_or("default")),
+            None => format!("{}/{}", self.provider, self.model),
+        }
+    }
+
+    pub async fn summarize(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<String> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "summary", provenance).await;
+        match self.observe(brand, &provider, "summary", || adapter.summarize(brand, texts)).await {
+            Ok(summary) => {
+                if let Some(model) = &metered {
+                    let output = summary.as_deref().map(|text| estimate_tokens(&[text])).unwrap_or_default();
+                    self.record_usage(brand, &provider, model, texts, output).await;
+                }
+                summary
+            }
+            Err(_) => {
+                provenance.record_fallback("summary", "error");
+                self.fallback.summarize(brand, texts).await.ok().flatten()
+            }
+        }
+    }
+
+    pub async fn sentiment(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> HashMap<String, f32> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "sentiment", provenance).await;
+        match self.observe(brand, &provider, "sentiment", || adapter.sentiment(brand, texts)).await {
+            Ok(sentiment) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, SENTIMENT_OUTPUT_TOKENS).await;
+                }
+                sentiment
+            }
+            Err(_) => {
+                provenance.record_fallback("sentiment", "error");
+                simple_sentiment(texts)
+            }
+        }
+    }
+
+    pub async fn sentiment_batch(
+        &self,
+        job: &ProcessingContext,
+        groups: &[Vec<String>],
+        provenance: &mut Provenance,
+    ) -> Option<Vec<HashMap<String, f32>>> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "sentiment_batch", provenance).await;
+        let scores = self
+            .observe(brand, &provider, "sentiment_batch", || adapter.sentiment_batch(groups))
+            .await
+            .ok()
+            .flatten()
+            .filter(|scores| scores.len() == groups.len());
+        if let Some(model) = metered.as_ref().filter(|_| scores.is_some()) {
+            let texts: Vec<String> = groups.iter().flatten().cloned().collect();
+            self.record_usage(brand, &provider, model, &texts, SENTIMENT_OUTPUT_TOKENS * groups.len() as u64)
+                .await;
+        }
+        scores
+    }
+
+    /// Returns `None` when the provider has no combined mode, so the caller can make
+    /// separate summary and sentiment calls instead.
+    pub async fn analyze(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<ClusterAnalysis> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "analysis", provenance).await;
+        match self.observe(brand, &provider, "analysis", || adapter.analyze(texts)).await {
+            Ok(analysis) => {
+                if let Some(model) = metered.as_ref().filter(|_| analysis.is_some()) {
+                    let summary = analysis.as_ref().and_then(|analysis| analysis.summary.as_deref());
+                    let output = summary.map(|text| estimate_tokens(&[text])).unwrap_or_default();
+                    self.record_usage(brand, &provider, model, texts, output + SENTIMENT_OUTPUT_TOKENS).await;
+                }
+                analysis
+            }
+            Err(_) => {
+                provenance.record_fallback("analysis", "error");
+                Some(ClusterAnalysis {
+                    summary: self.fallback.summarize(brand, texts).await.ok().flatten(),
+                    sentiment: simple_sentiment(texts),
+                    topics: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// `None` when the provider call fails, has no batch mode or skips a group, so the
+    /// caller can analyse the clusters one by one instead.
+    pub async fn analyze_batch(
+        &self,
+        job: &ProcessingContext,
+        groups: &[Vec<String>],
+        provenance: &mut Provenance,
+    ) -> Option<Vec<ClusterAnalysis>> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "analysis_batch", provenance).await;
+        let analyses = self
+            .observe(brand, &provider, "analysis_batch", || adapter.analyze_batch(groups))
+            .await
+            .ok()
+            .flatten()
+            .filter(|analyses| analyses.len() == groups.len());
+        if let (Some(analyses), Some(model)) = (&analyses, &metered) {
+            let texts: Vec<String> = groups.iter().flatten().cloned().collect();
+            let output: u64 = analyses
+                .iter()
+                .map(|analysis| analysis.summary.as_deref().map(|text| estimate_tokens(&[text])).unwrap_or_default())
+                .sum();
+            self.record_usage(brand, &provider, model, &texts, output + SENTIMENT_OUTPUT_TOKENS * groups.len() as u64)
+                .await;
+        }
+        analyses
+    }
+
+    /// `None` when the provider call fails or the provider can't score severity.
+    pub async fn severity(
+        &self,
+        job: &ProcessingContext,
+        signals: &SeveritySignals,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<u8> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "severity", provenance).await;
+        match self.observe(brand, &provider, "severity", || adapter.severity(signals, texts)).await {
+            Ok(Some(score)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, SEVERITY_OUTPUT_TOKENS).await;
+                }
+                Some(score)
+            }
+            Ok(None) => None,
+            Err(_) => {
+                provenance.record_fallback("severity", "error");
+                None
+            }
+        }
+    }
+
+    /// `None` when the provider call fails or the provider can't report confidence.
+    pub async fn confidence(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        summary: Option<&str>,
+        sentiment: &HashMap<String, f32>,
+        provenance: &mut Provenance,
+    ) -> Option<Confidence> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "confidence", provenance).await;
+        match self
+            .observe(brand, &provider, "confidence", || adapter.confidence(texts, summary, sentiment))
+            .await
+        {
+            Ok(Some(confidence)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, CONFIDENCE_OUTPUT_TOKENS).await;
+                }
+                Some(confidence)
+            }
+            Ok(None) => None,
+            Err(_) => {
+                provenance.record_fallback("confidence", "error");
+                None
+            }
+        }
+    }
+
+    /// Verbatim mentions from `texts`; `None` when the provider call fails or the provider
+    /// can't pick quotes.
+    pub async fn quotes(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<Vec<String>> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "quotes", provenance).await;
+        match self.observe(brand, &provider, "quotes", || adapter.quotes(texts)).await {
+            Ok(Some(indices)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, QUOTE_OUTPUT_TOKENS).await;
+                }
+                Some(indices.into_iter().filter_map(|idx| texts.get(idx).cloned()).collect())
+            }
+            Ok(None) => None,
+            Err(_) => {
+                provenance.record_fallback("quotes", "error");
+                None
+            }
+        }
+    }
+
+    /// Falls back to the keyword heuristic when the provider call fails or the provider
+    /// can't classify intent.
+    pub async fn intent(&self, job: &ProcessingContext, texts: &[String], provenance: &mut Provenance) -> String {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "intent", provenance).await;
+        match self.observe(brand, &provider, "intent", || adapter.intent(texts)).await {
+            Ok(Some(intent)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, INTENT_OUTPUT_TOKENS).await;
+                }
+                intent
+            }
+            Ok(None) => simple_intent(texts),
+            Err(_) => {
+                provenance.record_fallback("intent", "error");
+                simple_intent(texts)
+            }
+        }
+    }
+
+    /// `None` when the provider call fails or the provider can't classify relevance.
+    pub async fn relevance(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<Vec<f32>> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "relevance", provenance).await;
+        match self.observe(brand, &provider, "relevance", || adapter.relevance(brand, texts)).await {
+            Ok(Some(scores)) => {
+                if let Some(model) = &metered {
+                    let output = RELEVANCE_OUTPUT_TOKENS_PER_TEXT * texts.len() as u64;
+                    self.record_usage(brand, &provider, model, texts, output).await;
+                }
+                Some(scores)
+            }
+            Ok(None) => None,
+            Err(_) => {
+                provenance.record_fallback("relevance", "error");
+                None
+            }
+        }
+    }
+
+    /// `None` when the provider call fails or the provider can't extract entities.
+    pub async fn entities(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<ClusterEntities> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "entities", provenance).await;
+        match self.observe(brand, &provider, "entities", || adapter.entities(brand, texts)).await {
+            Ok(Some(entities)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, ENTITY_OUTPUT_TOKENS).await;
+                }
+                Some(entities)
+            }
+            Ok(None) => None,
+            Err(_) => {
+                provenance.record_fallback("entities", "error");
+                None
+            }
+        }
+    }
+
+    /// `None` when the provider call fails or the provider can't score toxicity.
+    pub async fn toxicity(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> Option<f32> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "toxicity", provenance).await;
+        match self.observe(brand, &provider, "toxicity", || adapter.toxicity(texts)).await {
+            Ok(Some(score)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, TOXICITY_OUTPUT_TOKENS).await;
+                }
+                Some(score)
+            }
+            Ok(None) => None,
+            Err(_) => {
+                provenance.record_fallback("toxicity", "error");
+                None
+            }
+        }
+    }
+
+    /// Falls back to the keyword heuristic when the provider call fails or the provider
+    /// can't classify emotions.
+    pub async fn emotions(
+        &self,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> HashMap<String, f32> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "emotions", provenance).await;
+        match self.observe(brand, &provider, "emotions", || adapter.emotions(texts)).await {
+            Ok(Some(emotions)) => {
+                if let Some(model) = &metered {
+                    self.record_usage(brand, &provider, model, texts, EMOTION_OUTPUT_TOKENS).await;
+                }
+                emotions
+            }
+            Ok(None) => simple_emotions(texts),
+            Err(_) => {
+                provenance.record_fallback("emotions", "error");
+                simple_emotions(texts)
+            }
+        }
+    }
+
+    /// `None` when the call fails or the provider has no multimodal support.
+    pub async fn caption(
+        &self,
+        job: &ProcessingContext,
+        image_url: &str,
+        provenance: &mut Provenance,
+    ) -> Option<String> {
+        let brand = job.brand.as_str();
+        let (adapter, provider, metered, _permit) = self.select(job, "caption", provenance).await;
+        match self.observe(brand, &provider, "caption", || adapter.caption(image_url)).await {
+            Ok(caption) => {
+                if let Some(model) = &metered {
+                    let output = caption.as_deref().map(|text| estimate_tokens(&[text])).unwrap_or_default();
+                    self.record_usage(brand, &provider, model, &[CAPTION_PROMPT.to_string()], output).await;
+                }
+                caption
+            }
+            Err(_) => {
+                provenance.record_fallback("caption", "error");
+                None
+            }
+        }
+    }
+
+    /// Picks the adapter for a call along with the provider label its outcome is recorded under;
+    /// heuristic-only chunks and budget downgrades are attributed to the heuristic fallback, not
+    /// the remote provider. Budgeted calls also get the model label their usage is metered
+    /// under; both follow any brand or chunk override.
+    /// Provider calls hold the returned pacer permit until they finish.
+    async fn select(
+        &self,
+        job: &ProcessingContext,
+        operation: &str,
+        provenance: &mut Provenance,
+    ) -> (Arc<dyn LlmAdapter>, Cow<'_, str>, Option<Cow<'_, str>>, Option<SemaphorePermit<'_>>) {
+        if job.heuristic_only() {
+            return (self.fallback.clone(), Cow::Borrowed("mock"), None, None);
+        }
+        let brand = job.brand.as_str();
+        let resolved = match &self.overrides {
+            Some(overrides) => overrides.resolve(brand, job.llm_override.as_ref()).await,
+            None => None,
+        };
+        let (provider, model) = match &resolved {
+            Some(resolved) => (
+                Cow::Owned(resolved.provider.clone()),
+                match &resolved.model {
+                    Some(model) => Cow::Owned(model.clone()),
+                    None if resolved.provider == self.provider => Cow::Borrowed(self.model.as_str()),
+                    None => Cow::Borrowed("default"),
+                },
+            ),
+            None => (Cow::Borrowed(self.provider.as_str()), Cow::Borrowed(self.model.as_str())),
+        };
+        let metered = match &self.budget {
+            Some(budget) if !budget.allow(&provider, brand).await => {
+                provenance.record_fallback(operation, "budget");
+                return (self.fallback.clone(), Cow::Borrowed("mock"), None, None);
+            }
+            Some(_) => Some(model),
+            None => None,
+        };
+        let permit = match &self.pacer {
+            Some(pacer) => Some(pacer.acquire().await),
+            None => None,
+        };
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+        match resolved {
+            Some(resolved) => {
+                provenance.llm_provider = Some(resolved.provider);
+                provenance.llm_model = resolved.model;
+                (resolved.adapter, provider, metered, permit)
+            }
+            None => (self.delegate.clone(), provider, metered, permit),
+        }
     }
 
-    pub async fn sentiment(&self, brand: &str, texts: &[String]) -> HashMap<String, f32> {
-        self.observe(brand, "sentiment", || self.delegate.sentiment(texts)).await
+    async fn record_usage(&self, brand: &str, provider: &str, model: &str, texts: &[String], output_tokens: u64) {
+        let input_tokens = estimate_tokens(texts);
+        let cost = self
+            .budget
+            .as_ref()
+            .map(|budget| budget.estimate_cost(input_tokens, output_tokens))
+            .unwrap_or_default();
+        record_llm_usage(&self.worker_id, provider, model, brand, input_tokens, output_tokens, cost);
+        if let Some(budget) = &self.budget {
+            budget.record(provider, brand, input_tokens, output_tokens).await;
+        }
     }
 
-    async fn observe<T, Fut>(&self, brand: &str, operation: &str, fut: impl FnOnce() -> Fut) -> T
+    async fn observe<T, Fut>(
+        &self,
+        brand: &str,
+        provider: &str,
+        operation: &str,
+        fut: impl FnOnce() -> Fut,
+    ) -> WorkerResult<T>
     where
-        Fut: std::future::Future<Output = T>,
+        Fut: std::future::Future<Output = WorkerResult<T>>,
     {
         let start = Instant::now();
-        let result = fut().await;
+        // Dropping the timed-out future drops the in-flight HTTP request with it, so the
+        // connection is closed rather than left waiting on the provider.
+        let result = match self.timeout {
+            Some(limit) => tokio::time::timeout(limit, fut()).await.unwrap_or_else(|_| {
+                WORKER_LLM_TIMEOUTS_TOTAL
+                    .with_label_values(&[&self.worker_id, provider, operation])
+                    .inc();
+                Err(WorkerError::Timeout(operation.to_string()))
+            }),
+            None => fut().await,
+        };
         let duration = start.elapsed();
         WORKER_LLM_LATENCY_SECONDS
             .with_label_values(&[&self.worker_id, brand, operation])
             .observe(duration.as_secs_f64());
-        info!(worker_id = %self.worker_id, brand, operation, latency_ms = duration.as_secs_f64() * 1000.0, "LLM operation completed");
+        record_provider_call(&self.worker_id, provider, operation, result.as_ref().err());
+        match &result {
+            Ok(_) => {
+                info!(worker_id = %self.worker_id, brand, operation, latency_ms = duration.as_secs_f64() * 1000.0, "LLM operation completed")
+            }
+            Err(err) => {
+                warn!(worker_id = %self.worker_id, provider, brand, operation, error = %err, "LLM operation failed; using heuristic fallback")
+            }
+        }
         result
     }
 }
 
-pub fn build_llm_adapter(settings: &Arc<Settings>) -> InstrumentedLlmAdapter {
-    let delegate: Arc<dyn LlmAdapter> = match settings.llm_provider.as_str() {
-        "mock" => Arc::new(MockLlmAdapter),
+pub fn build_llm_adapter(
+    settings: &Arc<Settings>,
+    redis: &RedisClient,
+    http: &HttpClient,
+) -> anyhow::Result<InstrumentedLlmAdapter> {
+    let prompts = PromptTemplates::from_settings(settings)?;
+    // With several LLM_PROVIDERS, calls are routed by provider health and metered under the joined name.
+    let (provider, delegate): (String, Arc<dyn LlmAdapter>) = if settings.llm_providers.len() > 1 {
+        let providers = settings
+            .llm_providers
+            .iter()
+            .map(|name| Ok((name.clone(), provider_adapter(name, settings, http, &prompts)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let router = HealthRoutedLlmAdapter::new(providers, settings.provider_probe_interval, settings.worker_id.clone());
+        (settings.llm_providers.join("+"), Arc::new(router))
+    } else {
+        let delegate = provider_adapter(&settings.llm_provider, settings, http, &prompts)?;
+        (settings.llm_provider.clone(), delegate)
+    };
+    let budget = (provider != "mock")
+        .then(|| BudgetGuard::new(redis.clone(), settings.clone(), BudgetKind::Llm));
+    let rate_limiter = (provider != "mock")
+        .then(|| RateLimiter::new(redis.clone(), settings, BudgetKind::Llm, &provider))
+        .flatten();
+    let pacer = (provider != "mock")
+        .then(|| Pacer::shared(&provider, settings.llm_max_concurrency, settings.llm_min_delay));
+
+    let overrides = ModelOverrides::from_settings(settings, redis, http, &prompts)?.map(Arc::new);
+
+    Ok(InstrumentedLlmAdapter::new(delegate, provider, budget, settings.worker_id.clone())
+        .with_model(settings.llm_model())
+        .with_rate_limiter(rate_limiter)
+        .with_pacer(pacer)
+        .with_timeout(settings.llm_timeout)
+        .with_model_overrides(overrides))
+}
+
+pub(crate) fn provider_adapter(
+    provider: &str,
+    settings: &Settings,
+    http: &HttpClient,
+    prompts: &Arc<PromptTemplates>,
+) -> anyhow::Result<Arc<dyn LlmAdapter>> {
+    let adapter: Arc<dyn LlmAdapter> = match provider {
+        "mock" => return Ok(Arc::new(MockLlmAdapter)),
+        "openai" => Arc::new(OpenAiLlmAdapter::new(settings, http.clone(), prompts.clone())?),
+        "gemini" => Arc::new(GeminiLlmAdapter::new(settings, http.clone(), prompts.clone())?),
+        "anthropic" => Arc::new(AnthropicLlmAdapter::new(settings, http.clone(), prompts.clone())?),
+        "simulated" => Arc::new(SimulatedLlmAdapter::new(settings)),
         other => Arc::new(RemoteLlmAdapter {
             provider: other.to_string(),
         }),
     };
+    if !settings.prompt_sanitization {
+        return Ok(adapter);
+    }
+    Ok(Arc::new(SanitizingLlmAdapter::new(adapter, provider, &settings.worker_id)))
+}
+
+pub fn summary_prompt(texts: &[String], max_tokens: u32) -> String {
+    format!(
+        "You are an analyst summarizing brand mentions.\n\
+         Summarize the following texts into a concise overview (max {max_tokens} tokens).\n\
+         Texts:\n{}\n",
+        texts.join("\n")
+    )
+}
+
+/// The JSON object in a model response. A response that isn't valid JSON as-is goes
+/// through [`repair_json`] first.
+pub fn json_object(raw: &str) -> Option<serde_json::Value> {
+    let strict = raw
+        .find('{')
+        .zip(raw.rfind('}'))
+        .and_then(|(start, end)| raw.get(start..=end))
+        .and_then(|object| serde_json::from_str(object).ok());
+    strict.or_else(|| serde_json::from_str(&repair_json(raw)?).ok())
+}
+
+/// Fixes the JSON mistakes models commonly make: prose after the object, trailing commas and
+/// output cut off at the token limit, whose open strings and brackets are closed. `None`
+/// when the response has no object or its brackets don't match.
+pub fn repair_json(raw: &str) -> Option<String> {
+    fn trim_trailing_comma(json: &mut String) {
+        json.truncate(json.trim_end().len());
+        if json.ends_with(',') {
+            json.pop();
+        }
+    }
+
+    let mut repaired = String::new();
+    let mut closers = Vec::new();
+    let (mut in_string, mut escaped) = (false, false);
+    for ch in raw[raw.find('{')?..].chars() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            repaired.push(ch);
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' => closers.push('}'),
+            '[' => closers.push(']'),
+            '}' | ']' => {
+                trim_trailing_comma(&mut repaired);
+                if closers.pop() != Some(ch) {
+                    return None;
+                }
+                repaired.push(ch);
+                if closers.is_empty() {
+                    return Some(repaired);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        repaired.push(ch);
+    }
+    if in_string {
+        repaired.push('"');
+    }
+    trim_trailing_comma(&mut repaired);
+    if repaired.ends_with(':') {
+        repaired.push_str("null");
+    }
+    repaired.extend(closers.into_iter().rev());
+    Some(repaired)
+}
+
+/// Retry prompt for a response that failed validation: the original request plus the
+/// rejected output, so the model corrects it rather than starting over.
+pub fn json_repair_prompt(prompt: &str, rejected: &str) -> String {
+    format!(
+        "{prompt}\n\nYour previous response was not a valid JSON object in the requested format:\n{rejected}\n\
+         Respond again with only the corrected JSON object.\n"
+    )
+}
+
+pub fn sentiment_prompt(texts: &[String]) -> String {
+    format!(
+        "You are a sentiment analysis assistant. Analyse the sentiment of the texts below and return \
+         a JSON object with keys positive, negative, neutral whose values are floats between 0 and 1 summing to 1.\n\
+         Texts:\n{}\n",
+        texts.join("\n")
+    )
+}
+
+pub fn parse_sentiment(raw: &str) -> Option<HashMap<String, f32>> {
+    let value = json_object(raw)?;
+    parse_sentiment_distribution(&value)
+}
+
+pub fn analysis_prompt(texts: &[String], max_tokens: u32) -> String {
+    format!(
+        "You are an analyst reviewing brand mentions. Respond with only a JSON object with keys \
+         \"summary\" (a concise overview of the texts, max {max_tokens} tokens), \"sentiment\" (an object \
+         with positive, negative and neutral floats between 0 and 1 summing to 1) and \"topics\" \
+         (up to {ANALYSIS_TOPIC_LIMIT} short topic phrases).\n\
+         Texts:\n{}\n",
+        texts.join("\n")
+    )
+}
+
+pub fn parse_analysis(raw: &str) -> Option<ClusterAnalysis> {
+    parse_analysis_value(&json_object(raw)?)
+}
+
+pub fn batch_analysis_prompt(groups: &[Vec<String>], max_tokens: u32) -> String {
+    let mut prompt = format!(
+        "You are an analyst reviewing numbered groups of brand mentions. Respond with only a JSON object \
+         mapping each group number to an object with keys \"summary\" (a concise overview of that group, max \
+         {max_tokens} tokens), \"sentiment\" (an object with positive, negative and neutral floats between 0 and \
+         1 summing to 1) and \"topics\" (up to {ANALYSIS_TOPIC_LIMIT} short topic phrases).\n\n"
+    );
+    for (idx, texts) in groups.iter().enumerate() {
+        prompt.push_str(&format!("{}.\n", idx + 1));
+        for text in texts {
+            prompt.push_str(&format!("- {text}\n"));
+        }
+    }
+    prompt
+}
+
+/// `None` unless every group has a valid analysis.
+pub fn parse_batch_analysis(raw: &str, expected: usize) -> Option<Vec<ClusterAnalysis>> {
+    let value = json_object(raw)?;
+    let object = value.as_object()?;
+    (1..=expected)
+        .map(|idx| object.get(&idx.to_string()).and_then(parse_analysis_value))
+        .collect()
+}
+
+fn parse_analysis_value(value: &serde_json::Value) -> Option<ClusterAnalysis> {
+    let sentiment = parse_sentiment_distribution(value.get("sentiment")?)?;
+    let summary = value
+        .get("summary")
+        .and_then(|summary| summary.as_str())
+        .map(|summary| summary.trim().to_string())
+        .filter(|summary| !summary.is_empty());
+    let topics = value
+        .get("topics")
+        .and_then(|topics| topics.as_array())
+        .map(|topics| {
+            topics
+                .iter()
+                .filter_map(|topic| topic.as_str())
+                .map(|topic| topic.trim().to_string())
+                .filter(|topic| !topic.is_empty())
+                .take(ANALYSIS_TOPIC_LIMIT)
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(ClusterAnalysis {
+        summary,
+        sentiment,
+        topics,
+    })
+}
+
+pub fn emotion_prompt(texts: &[String]) -> String {
+    format!(
+        "You are an analyst reviewing brand mentions. Rate how strongly each emotion is expressed in the \
+         texts below. Respond with only a JSON object with keys {} whose values are floats between 0 and 1.\n\
+         Texts:\n{}\n",
+        EMOTIONS.join(", "),
+        texts.join("\n")
+    )
+}
+
+pub fn parse_emotions(raw: &str) -> Option<HashMap<String, f32>> {
+    let value = json_object(raw)?;
+    let read = |emotion: &str| value.get(emotion).and_then(|score| score.as_f64());
+    if EMOTIONS.iter().all(|emotion| read(emotion).is_none()) {
+        return None;
+    }
+    // Emotions the model left out were not expressed.
+    Some(
+        EMOTIONS
+            .iter()
+            .map(|emotion| (emotion.to_string(), read(emotion).unwrap_or_default().clamp(0.0, 1.0) as f32))
+            .collect(),
+    )
+}
+
+pub fn severity_prompt(signals: &SeveritySignals, texts: &[String]) -> String {
+    format!(
+        "You are a reputation analyst. Rate how severe a reputation crisis the group of brand mentions below is, \
+         from 0 (no risk) to 100 (critical). Consider volume, negativity, whether it is spiking and how damaging \
+         the claims are.\n\
+         Summary: {}\nMentions in group: {}\nNegative share: {:.2}\nSpiking: {}\nExplained by a scheduled event: {}\n\
+         Respond with only a JSON object {{\"severity\": <integer 0-100>}}.\n\
+         Texts:\n{}\n",
+        signals.summary.as_deref().unwrap_or("none"),
+        signals.mention_count,
+        signals.negative,
+        signals.spike,
+        signals.known_event,
+        texts.join("\n")
+    )
+}
+
+pub fn parse_severity(raw: &str) -> Option<u8> {
+    let value = json_object(raw)?;
+    let score = value.get("severity")?.as_f64()?;
+    Some(score.round().clamp(0.0, 100.0) as u8)
+}
+
+pub fn confidence_prompt(texts: &[String], summary: Option<&str>, sentiment: &HashMap<String, f32>) -> String {
+    let share = |label: &str| sentiment.get(label).copied().unwrap_or_default();
+    format!(
+        "You are an analyst checking another analyst's reading of a group of brand mentions. Rate from 0 to 1 \
+         how confident you are that the summary and the sentiment below accurately reflect the texts. \
+         Respond with only a JSON object {{\"summary\": <float>, \"sentiment\": <float>}}.\n\
+         Summary: {}\nSentiment: positive {:.2}, neutral {:.2}, negative {:.2}\n\
+         Texts:\n{}\n",
+        summary.unwrap_or("none"),
+        share("positive"),
+        share("neutral"),
+        share("negative"),
+        texts.join("\n")
+    )
+}
+
+pub fn parse_confidence(raw: &str) -> Option<Confidence> {
+    let value = json_object(raw)?;
+    let score = |key: &str| value.get(key).and_then(|score| score.as_f64()).map(|score| score.clamp(0.0, 1.0) as f32);
+    let confidence = Confidence {
+        summary: score("summary"),
+        sentiment: score("sentiment"),
+    };
+    confidence.min().map(|_| confidence)
+}
+
+pub fn quote_prompt(texts: &[String]) -> String {
+    let mut prompt = format!(
+        "Pick the 1 to {QUOTE_LIMIT} numbered social media mentions below that best represent what the group is \
+         saying, most representative first. Respond with only a JSON object {{\"quotes\": [<mention numbers>]}}.\n\n"
+    );
+    for (idx, text) in texts.iter().enumerate() {
+        prompt.push_str(&format!("{}. {text}\n", idx + 1));
+    }
+    prompt
+}
+
+/// Zero-based indices of the picked mentions, without duplicates or out-of-range numbers.
+pub fn parse_quotes(raw: &str, expected: usize) -> Option<Vec<usize>> {
+    let value = json_object(raw)?;
+    let mut indices: Vec<usize> = Vec::new();
+    for number in value.get("quotes")?.as_array()?.iter().filter_map(|number| number.as_u64()) {
+        let idx = (number as usize).checked_sub(1)?;
+        if idx < expected && !indices.contains(&idx) {
+            indices.push(idx);
+        }
+    }
+    indices.truncate(QUOTE_LIMIT);
+    (!indices.is_empty()).then_some(indices)
+}
+
+pub fn intent_prompt(texts: &[String]) -> String {
+    format!(
+        "You are an analyst reviewing brand mentions. Classify the main intent of the texts below as one of: {}. \
+         Use churn_risk when authors say they are leaving or switching to a competitor. \
+         Respond with only a JSON object {{\"intent\": \"<label>\"}}.\n\
+         Texts:\n{}\n",
+        INTENTS.join(", "),
+        texts.join("\n")
+    )
+}
+
+pub fn parse_intent(raw: &str) -> Option<String> {
+    let value = json_object(raw)?;
+    let intent = value.get("intent")?.as_str()?.trim().to_lowercase().replace([' ', '-'], "_");
+    INTENTS.contains(&intent.as_str()).then_some(intent)
+}
+
+pub fn relevance_prompt(brand: &str, texts: &[String]) -> String {
+    let mut prompt = format!(
+        "Decide whether each numbered social media mention is about the brand \"{brand}\" rather than another \
+         meaning of the word. Respond with only a JSON object mapping each mention number to the probability, \
+         between 0 and 1, that it is about the brand.\n\n"
+    );
+    for (idx, text) in texts.iter().enumerate() {
+        prompt.push_str(&format!("{}. {text}\n", idx + 1));
+    }
+    prompt
+}
 
-    InstrumentedLlmAdapter::new(delegate, settings.worker_id.clone())
+pub fn parse_relevance(raw: &str, expected: usize) -> Option<Vec<f32>> {
+    let value = json_object(raw)?;
+    let object = value.as_object()?;
+    (1..=expected)
+        .map(|idx| {
+            object
+                .get(&idx.to_string())
+                .and_then(|score| score.as_f64())
+                .map(|score| score.clamp(0.0, 1.0) as f32)
+        })
+        .collect()
 }
 
-fn simple_sentiment(texts: &[String]) -> HashMap<String, f32> {
+pub fn entity_prompt(brand: &str, texts: &[String]) -> String {
+    format!(
+        "You are an analyst reviewing mentions of the brand \"{brand}\". List the named entities in the texts \
+         below. Respond with only a JSON object with keys \"people\", \"products\", \"competitors\" and \
+         \"locations\", each an array of at most {ENTITY_LIMIT} names; competitors are other brands that compete \
+         with {brand}.\n\
+         Texts:\n{}\n",
+        texts.join("\n")
+    )
+}
+
+pub fn parse_entities(raw: &str) -> Option<ClusterEntities> {
+    let mut entities: ClusterEntities = serde_json::from_value(json_object(raw)?).ok()?;
+    for names in [
+        &mut entities.people,
+        &mut entities.products,
+        &mut entities.competitors,
+        &mut entities.locations,
+    ] {
+        names.retain(|name| !name.trim().is_empty());
+        names.truncate(ENTITY_LIMIT);
+    }
+    Some(entities)
+}
+
+pub fn toxicity_prompt(texts: &[String]) -> String {
+    format!(
+        "You are a trust and safety analyst reviewing brand mentions. Rate how abusive the texts below are: \
+         insults, harassment, threats or hate aimed at people, not ordinary complaints about a product. \
+         Respond with only a JSON object {{\"toxicity\": <float between 0 and 1>}}.\n\
+         Texts:\n{}\n",
+        texts.join("\n")
+    )
+}
+
+pub fn parse_toxicity(raw: &str) -> Option<f32> {
+    let value = json_object(raw)?;
+    let score = value.get("toxicity")?.as_f64()?;
+    Some(score.clamp(0.0, 1.0) as f32)
+}
+
+pub fn batch_sentiment_prompt(groups: &[Vec<String>]) -> String {
+    let mut prompt = String::from(
+        "Score the sentiment of each numbered group of social media mentions. \
+         Respond with only a JSON object mapping each group number to an object with \
+         \"positive\", \"negative\" and \"neutral\" probabilities that sum to 1.\n\n",
+    );
+    for (idx, texts) in groups.iter().enumerate() {
+        prompt.push_str(&format!("{}.\n", idx + 1));
+        for text in texts {
+            prompt.push_str(&format!("- {text}\n"));
+        }
+    }
+    prompt
+}
+
+pub fn parse_batch_sentiment(raw: &str, expected: usize) -> Option<Vec<HashMap<String, f32>>> {
+    let value = json_object(raw)?;
+    let object = value.as_object()?;
+    (1..=expected)
+        .map(|idx| object.get(&idx.to_string()).and_then(parse_sentiment_distribution))
+        .collect()
+}
+
+pub fn parse_sentiment_distribution(value: &serde_json::Value) -> Option<HashMap<String, f32>> {
+    let read = |label: &str| value.get(label).and_then(|v| v.as_f64()).map(|v| v.max(0.0) as f32);
+    let positive = read("positive")?;
+    let negative = read("negative")?;
+    let neutral = read("neutral")?;
+    let total = positive + negative + neutral;
+    if total <= 0.0 {
+        return None;
+    }
+    Some(HashMap::from([
+        ("positive".to_string(), positive / total),
+        ("negative".to_string(), negative / total),
+        ("neutral".to_string(), neutral / total),
+    ]))
+}
+
+/// Share of texts that mention each emotion's keywords.
+pub fn simple_emotions(texts: &[String]) -> HashMap<String, f32> {
+    let keywords: [&[&str]; 5] = [
+        &["angry", "furious", "outraged", "hate", "unacceptable"],
+        &["love", "happy", "great", "awesome", "delighted"],
+        &["afraid", "scared", "worried", "unsafe", "risk"],
+        &["disappointed", "let down", "expected better", "broken", "refund"],
+        &["excited", "can't wait", "amazing", "wow", "finally"],
+    ];
+    let lowered: Vec<String> = texts.iter().map(|text| text.to_lowercase()).collect();
+    let total = lowered.len().max(1) as f32;
+    EMOTIONS
+        .iter()
+        .zip(keywords)
+        .map(|(emotion, words)| {
+            let hits = lowered
+                .iter()
+                .filter(|text| words.iter().any(|word| text.contains(word)))
+                .count();
+            (emotion.to_string(), hits as f32 / total)
+        })
+        .collect()
+}
+
+/// Intent whose keywords appear in the most texts; churn signals win ties since they are
+/// the costliest to miss.
+pub fn simple_intent(texts: &[String]) -> String {
+    let keywords: [(&str, &[&str]); 4] = [
+        ("churn_risk", &["cancel", "switching to", "unsubscribe", "leaving", "done with", "moving to"]),
+        ("complaint", &["broken", "worst", "refund", "not working", "terrible", "support"]),
+        ("question", &["?", "how do", "does anyone", "anyone know", "is there"]),
+        ("praise", &["love", "great", "awesome", "thank", "best"]),
+    ];
+    let lowered: Vec<String> = texts.iter().map(|text| text.to_lowercase()).collect();
+    keywords
+        .iter()
+        .map(|(intent, words)| {
+            let hits = lowered
+                .iter()
+                .filter(|text| words.iter().any(|word| text.contains(word)))
+                .count();
+            (*intent, hits)
+        })
+        .filter(|(_, hits)| *hits > 0)
+        .fold(None, |best: Option<(&str, usize)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        })
+        .map_or("other", |(intent, _)| intent)
+        .to_string()
+}
+
+pub fn simple_sentiment(texts: &[String]) -> HashMap<String, f32> {
     let positive_words = ["great", "good", "love", "awesome", "excellent", "improved", "success", "fast"];
     let negative_words = ["bad", "hate", "poor", "slow", "issue", "problem", "bug", "error"];
 
@@ -117,3 +1344,99 @@ fn simple_sentiment(texts: &[String]) -> HashMap<String, f32> {
         ("neutral".to_string(), neutral / total),
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn repaired(raw: &str) -> Option<serde_json::Value> {
+        repair_json(raw).map(|json| serde_json::from_str(&json).expect("repaired JSON parses"))
+    }
+
+    #[test]
+    fn repair_json_closes_truncated_string() {
+        assert_eq!(
+            repaired(r#"{"summary": "Customers report late deliv"#),
+            Some(json!({ "summary": "Customers report late deliv" }))
+        );
+    }
+
+    #[test]
+    fn repair_json_drops_trailing_commas() {
+        assert_eq!(
+            repaired(r#"{"topics": ["delivery", "refunds",], "count": 2,}"#),
+            Some(json!({ "topics": ["delivery", "refunds"], "count": 2 }))
+        );
+        assert_eq!(repaired(r#"{"positive": 0.5,"#), Some(json!({ "positive": 0.5 })));
+    }
+
+    #[test]
+    fn repair_json_fills_dangling_colon() {
+        assert_eq!(
+            repaired(r#"{"positive": 0.2, "negative":"#),
+            Some(json!({ "positive": 0.2, "negative": null }))
+        );
+    }
+
+    #[test]
+    fn repair_json_rejects_mismatched_brackets() {
+        assert_eq!(repair_json(r#"{"topics": ["delivery"}"#), None);
+        assert_eq!(repair_json(r#"{"sentiment": {"positive": 1]"#), None);
+    }
+
+    #[test]
+    fn repair_json_ignores_prose_around_object() {
+        assert_eq!(
+            repaired("Here is the analysis: {\"intent\": \"complaint\"} Let me know if you need more {"),
+            Some(json!({ "intent": "complaint" }))
+        );
+    }
+
+    #[test]
+    fn repair_json_needs_an_object() {
+        assert_eq!(repair_json("I cannot help with that."), None);
+    }
+
+    #[test]
+    fn parse_batch_sentiment_normalizes_each_group() {
+        let raw = concat!(
+            r#"{"1": {"positive": 3, "negative": 1, "neutral": 0},"#,
+            r#" "2": {"positive": 0, "negative": 0, "neutral": 2}}"#
+        );
+        let groups = parse_batch_sentiment(raw, 2).expect("both groups parse");
+        assert_eq!(groups[0]["positive"], 0.75);
+        assert_eq!(groups[0]["negative"], 0.25);
+        assert_eq!(groups[1]["neutral"], 1.0);
+    }
+
+    #[test]
+    fn parse_batch_sentiment_rejects_missing_group() {
+        let raw = r#"{"1": {"positive": 1, "negative": 0, "neutral": 0}}"#;
+        assert!(parse_batch_sentiment(raw, 2).is_none());
+    }
+
+    #[test]
+    fn parse_batch_sentiment_rejects_non_numeric_scores() {
+        let raw = r#"{"1": {"positive": "high", "negative": 0, "neutral": 0}}"#;
+        assert!(parse_batch_sentiment(raw, 1).is_none());
+    }
+
+    #[test]
+    fn parse_batch_sentiment_rejects_zero_totals() {
+        let raw = r#"{"1": {"positive": 0, "negative": -1, "neutral": 0}}"#;
+        assert!(parse_batch_sentiment(raw, 1).is_none());
+    }
+
+    #[test]
+    fn parse_batch_sentiment_ignores_extra_groups() {
+        let raw = concat!(
+            r#"{"1": {"positive": 1, "negative": 1, "neutral": 0},"#,
+            r#" "2": {"positive": 1, "negative": 0, "neutral": 0}}"#
+        );
+        let groups = parse_batch_sentiment(raw, 1).expect("requested group parses");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0]["positive"], 0.5);
+    }
+}
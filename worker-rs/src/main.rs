@@ -4,7 +4,17 @@ use anyhow::Result;
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
 
-    let settings = worker_rs::config::Settings::from_env()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("doctor") => {
+            let passed = worker_rs::doctor::run().await;
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        Some("compare-results") => return worker_rs::compare::run(&args[1..]).await,
+        _ => {}
+    }
+
+    let settings = worker_rs::Settings::from_env()?;
     worker_rs::logging::init(&settings.log_level);
 
     worker_rs::app::run(settings).await
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::config::Settings;
+use crate::context::ProcessingContext;
+use crate::http::HttpClient;
+use crate::llm::{build_llm_adapter, InstrumentedLlmAdapter};
+use crate::redis_client::RedisClient;
+use crate::types::{Mention, Provenance};
+
+/// First media URL under `key` in the mention metadata: a URL string, a list of URLs,
+/// or a list of objects with a `url` field.
+pub fn media_url(mention: &Mention, key: &str) -> Option<String> {
+    let url_of = |value: &Value| match value {
+        Value::String(url) => Some(url.clone()),
+        Value::Object(object) => object.get("url").and_then(Value::as_str).map(str::to_string),
+        _ => None,
+    };
+    let value = mention.metadata.as_ref()?.get(key)?;
+    let url = match value {
+        Value::Array(items) => items.iter().find_map(url_of),
+        other => url_of(other),
+    }?;
+    Some(url).filter(|url| !url.trim().is_empty())
+}
+
+/// Describes media-only mentions with `MEDIA_CAPTION_PROVIDER` so they can be clustered
+/// like text. Providers without multimodal support leave them uncaptioned.
+pub struct MediaCaptioner {
+    llm: InstrumentedLlmAdapter,
+}
+
+impl MediaCaptioner {
+    pub fn from_settings(settings: &Arc<Settings>, redis: &RedisClient, http: &HttpClient) -> anyhow::Result<Option<Self>> {
+        let Some(provider) = &settings.media_caption_provider else {
+            return Ok(None);
+        };
+        let caption_settings = Arc::new(Settings {
+            llm_provider: provider.clone(),
+            llm_providers: Vec::new(),
+            ..(**settings).clone()
+        });
+        Ok(Some(Self {
+            llm: build_llm_adapter(&caption_settings, redis, http)?,
+        }))
+    }
+
+    pub async fn caption(
+        &self,
+        job: &ProcessingContext,
+        image_url: &str,
+        provenance: &mut Provenance,
+    ) -> Option<String> {
+        self.llm.caption(job, image_url, provenance).await
+    }
+}
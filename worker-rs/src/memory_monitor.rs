@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::metrics::{WORKER_REDIS_FAMILY_BYTES, WORKER_REDIS_FAMILY_KEYS, WORKER_REDIS_FAMILY_MAX_LENGTH};
+use crate::redis_client::RedisClient;
+
+#[derive(Debug, Clone, Copy)]
+enum KeyKind {
+    List,
+    SortedSet,
+    Stream,
+}
+
+impl KeyKind {
+    fn length_command(self) -> &'static str {
+        match self {
+            Self::List => "LLEN",
+            Self::SortedSet => "ZCARD",
+            Self::Stream => "XLEN",
+        }
+    }
+}
+
+struct KeyFamily {
+    name: &'static str,
+    pattern: String,
+    kind: KeyKind,
+}
+
+#[derive(Debug, Default)]
+struct FamilySample {
+    keys: usize,
+    estimated_bytes: u64,
+    max_length: u64,
+}
+
+pub struct RedisMemoryMonitor {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl RedisMemoryMonitor {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Self {
+        Self { redis, settings }
+    }
+
+    pub async fn run(&self, mut shutdown: broadcast::Receiver<()>) {
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => {
+                    info!("Redis memory monitor stopping");
+                    break;
+                }
+                _ = tokio::time::sleep(self.settings.redis_memory_sample_interval) => {
+                    self.sample_all().await;
+                }
+            }
+        }
+    }
+
+    async fn sample_all(&self) {
+        for family in self.families() {
+            match self.sample(&family).await {
+                Ok(sample) => self.report(&family, &sample),
+                Err(err) => warn!(family = family.name, error = %err, "Redis memory sampling failed"),
+            }
+        }
+    }
+
+    async fn sample(&self, family: &KeyFamily) -> anyhow::Result<FamilySample> {
+        let keys = self.redis.scan_keys(&family.pattern).await?;
+        if keys.is_empty() {
+            return Ok(FamilySample::default());
+        }
+
+        let sampled: Vec<String> = keys
+            .iter()
+            .take(self.settings.redis_memory_sample_keys)
+            .cloned()
+            .collect();
+        let usage = self.redis.memory_usage(&sampled).await?;
+        let sampled_bytes: u64 = usage.iter().flatten().sum();
+        let estimated_bytes = sampled_bytes * keys.len() as u64 / sampled.len() as u64;
+        let max_length = self
+            .redis
+            .lengths(family.kind.length_command(), &sampled)
+            .await?
+            .into_iter()
+            .max()
+            .unwrap_or_default();
+
+        Ok(FamilySample {
+            keys: keys.len(),
+            estimated_bytes,
+            max_length,
+        })
+    }
+
+    fn report(&self, family: &KeyFamily, sample: &FamilySample) {
+        let labels = [self.settings.worker_id.as_str(), family.name];
+        WORKER_REDIS_FAMILY_KEYS
+            .with_label_values(&labels)
+            .set(sample.keys as f64);
+        WORKER_REDIS_FAMILY_BYTES
+            .with_label_values(&labels)
+            .set(sample.estimated_bytes as f64);
+        WORKER_REDIS_FAMILY_MAX_LENGTH
+            .with_label_values(&labels)
+            .set(sample.max_length as f64);
+
+        if sample.estimated_bytes > self.settings.redis_memory_warn_bytes {
+            warn!(
+                worker_id = %self.settings.worker_id,
+                family = family.name,
+                keys = sample.keys,
+                estimated_bytes = sample.estimated_bytes,
+                threshold_bytes = self.settings.redis_memory_warn_bytes,
+                "Redis key family exceeds memory threshold"
+            );
+        }
+        if sample.max_length > self.settings.redis_length_warn {
+            warn!(
+                worker_id = %self.settings.worker_id,
+                family = family.name,
+                max_length = sample.max_length,
+                threshold = self.settings.redis_length_warn,
+                "Redis key family exceeds length threshold"
+            );
+        }
+    }
+
+    fn families(&self) -> Vec<KeyFamily> {
+        let settings = &self.settings;
+        let mut families = vec![
+            KeyFamily {
+                name: "results",
+                pattern: format!("{}:*:chunks", settings.redis_result_prefix),
+                kind: KeyKind::List,
+            },
+            KeyFamily {
+                name: "failures",
+                pattern: format!("{}:*", settings.redis_failed_prefix),
+                kind: KeyKind::List,
+            },
+            KeyFamily {
+                name: "spike_history",
+                pattern: format!("{}:*", settings.redis_spike_prefix),
+                kind: KeyKind::List,
+            },
+            KeyFamily {
+                name: "centroids",
+                pattern: format!("{}:*", settings.redis_centroid_prefix),
+                kind: KeyKind::List,
+            },
+            KeyFamily {
+                name: "sentiment_trend",
+                pattern: format!("{}:*", settings.redis_trend_prefix),
+                kind: KeyKind::SortedSet,
+            },
+            KeyFamily {
+                name: "archive",
+                pattern: format!("{}:*", settings.redis_archive_prefix),
+                kind: KeyKind::SortedSet,
+            },
+        ];
+        if settings.journal_sink == "redis" {
+            families.push(KeyFamily {
+                name: "journal",
+                pattern: settings.journal_redis_stream.clone(),
+                kind: KeyKind::Stream,
+            });
+        }
+        families
+    }
+}
@@ -1,8 +1,10 @@
 use once_cell::sync::Lazy;
 use prometheus::{
+    register_counter_vec,
     register_gauge_vec,
     register_histogram_vec,
     register_int_counter_vec,
+    CounterVec,
     Encoder,
     GaugeVec,
     HistogramOpts,
@@ -11,6 +13,8 @@ use prometheus::{
     TextEncoder,
 };
 
+use crate::error::WorkerError;
+
 pub static WORKER_CHUNKS_PROCESSED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "worker_chunks_processed_total",
@@ -45,6 +49,15 @@ pub static WORKER_IO_TIME_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(opts, &["worker_id", "brand", "stage"]).expect("register worker_io_time_seconds")
 });
 
+pub static WORKER_ADAPTER_GENERATION: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_adapter_generation",
+        "Generation of the pipeline adapters, incremented on every config reload",
+        &["worker_id"]
+    )
+    .expect("register worker_adapter_generation")
+});
+
 pub static WORKER_WAITING_SECONDS: Lazy<GaugeVec> = Lazy::new(|| {
     register_gauge_vec!(
         "worker_waiting_seconds",
@@ -104,6 +117,591 @@ pub static WORKER_SPIKE_DETECTION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     .expect("register worker_spike_detection_seconds")
 });
 
+pub static WORKER_SHADOW_COMPARISONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_shadow_comparisons_total",
+        "Total number of shadow pipeline comparisons by outcome (ok, error, skipped)",
+        &["worker_id", "brand", "outcome"]
+    )
+    .expect("register worker_shadow_comparisons_total")
+});
+
+pub static WORKER_SHADOW_SENTIMENT_DELTA: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "worker_shadow_sentiment_delta",
+        "Histogram of absolute sentiment score delta between shadow and primary pipelines",
+        &["worker_id", "brand"],
+        vec![0.01, 0.05, 0.1, 0.2, 0.3, 0.5, 1.0, 2.0]
+    )
+    .expect("register worker_shadow_sentiment_delta")
+});
+
+pub static WORKER_PROVIDER_SPEND_USD_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "worker_provider_spend_usd_total",
+        "Estimated provider spend in USD derived from token counts",
+        &["worker_id", "provider", "brand"]
+    )
+    .expect("register worker_provider_spend_usd_total")
+});
+
+pub static WORKER_LLM_TOKENS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_llm_tokens_total",
+        "Estimated LLM tokens sent (prompt) and received (completion)",
+        &["worker_id", "provider", "model", "brand", "kind"]
+    )
+    .expect("register worker_llm_tokens_total")
+});
+
+pub static WORKER_LLM_COST_USD_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "worker_llm_cost_usd_total",
+        "Estimated LLM cost in USD from token counts and LLM_PRICE_*_PER_1K",
+        &["worker_id", "provider", "model", "brand"]
+    )
+    .expect("register worker_llm_cost_usd_total")
+});
+
+pub static WORKER_BUDGET_EXCEEDED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_budget_exceeded_total",
+        "Total number of provider calls downgraded because a daily budget was exceeded",
+        &["worker_id", "provider", "scope"]
+    )
+    .expect("register worker_budget_exceeded_total")
+});
+
+pub static WORKER_ALERTS_SENT_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_alerts_sent_total",
+        "Total number of routed alerts by rule, destination and outcome",
+        &["worker_id", "rule", "destination", "outcome"]
+    )
+    .expect("register worker_alerts_sent_total")
+});
+
+pub static WORKER_HOOK_DROPPED_MENTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_hook_dropped_mentions_total",
+        "Total number of mentions dropped by the WASM preprocessing hook",
+        &["worker_id", "brand"]
+    )
+    .expect("register worker_hook_dropped_mentions_total")
+});
+
+pub static WORKER_IRRELEVANT_MENTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_irrelevant_mentions_total",
+        "Total number of mentions classified as not about the brand, by action taken",
+        &["worker_id", "brand", "action"]
+    )
+    .expect("register worker_irrelevant_mentions_total")
+});
+
+pub static WORKER_LLM_TIER_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_llm_tier_total",
+        "Total number of clusters analysed per LLM cost tier",
+        &["worker_id", "brand", "tier"]
+    )
+    .expect("register worker_llm_tier_total")
+});
+
+pub static WORKER_LLM_TIMEOUTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_llm_timeouts_total",
+        "Total number of LLM calls cancelled after LLM_TIMEOUT_SEC",
+        &["worker_id", "provider", "operation"]
+    )
+    .expect("register worker_llm_timeouts_total")
+});
+
+pub static WORKER_LLM_UNPARSEABLE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_llm_unparseable_total",
+        "Total number of LLM responses that failed JSON validation, by whether the retry fixed them",
+        &["worker_id", "provider", "operation", "outcome"]
+    )
+    .expect("register worker_llm_unparseable_total")
+});
+
+pub static WORKER_SANITIZED_INPUTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_sanitized_inputs_total",
+        "Total number of mention texts filtered for prompt-injection patterns before a remote LLM call",
+        &["worker_id", "provider", "kind"]
+    )
+    .expect("register worker_sanitized_inputs_total")
+});
+
+pub static WORKER_EMERGING_NARRATIVES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_emerging_narratives_total",
+        "Total number of clusters flagged as emerging narratives by n-gram novelty",
+        &["worker_id"]
+    )
+    .expect("register worker_emerging_narratives_total")
+});
+
+pub static WORKER_PRODUCT_MENTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_product_mentions_total",
+        "Total number of mentions resolved to a product, sub-brand or the brand by the brand taxonomy",
+        &["worker_id", "brand", "entity"]
+    )
+    .expect("register worker_product_mentions_total")
+});
+
+pub static WORKER_QA_SAMPLES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_qa_samples_total",
+        "Total number of chunk results copied to the QA sample",
+        &["worker_id", "brand"]
+    )
+    .expect("register worker_qa_samples_total")
+});
+
+pub static WORKER_RECURRING_CLUSTERS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_recurring_clusters_total",
+        "Total number of clusters matched to a recently emitted cluster",
+        &["worker_id", "brand", "suppressed"]
+    )
+    .expect("register worker_recurring_clusters_total")
+});
+
+pub static WORKER_QUEUE_OLDEST_AGE_SECONDS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_queue_oldest_age_seconds",
+        "Age of the oldest entry in each brand queue as seen by the worker",
+        &["worker_id", "brand"]
+    )
+    .expect("register worker_queue_oldest_age_seconds")
+});
+
+pub static WORKER_CROSS_CHUNK_DUPLICATES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_cross_chunk_duplicates_total",
+        "Total number of mentions dropped because an earlier chunk already carried them",
+        &["worker_id", "brand"]
+    )
+    .expect("register worker_cross_chunk_duplicates_total")
+});
+
+pub static WORKER_MEDIA_MENTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_media_mentions_total",
+        "Total number of media-only mentions by whether they were captioned",
+        &["worker_id", "brand", "outcome"]
+    )
+    .expect("register worker_media_mentions_total")
+});
+
+pub static WORKER_NOISE_MENTIONS_DROPPED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_noise_mentions_dropped_total",
+        "Total number of mentions left empty after stripping noise phrases and stopwords",
+        &["worker_id", "brand"]
+    )
+    .expect("register worker_noise_mentions_dropped_total")
+});
+
+pub static WORKER_TOXIC_CLUSTERS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_toxic_clusters_total",
+        "Total number of clusters scored at or above the toxicity threshold",
+        &["worker_id", "brand"]
+    )
+    .expect("register worker_toxic_clusters_total")
+});
+
+pub static WORKER_ROUTED_CHUNKS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_routed_chunks_total",
+        "Total number of chunks sent to a pipeline route instead of the live pipeline",
+        &["worker_id", "route"]
+    )
+    .expect("register worker_routed_chunks_total")
+});
+
+pub static WORKER_TRIVIAL_CHUNKS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_trivial_chunks_total",
+        "Total number of chunks with zero or one mention handled on the fast path",
+        &["worker_id", "brand", "kind"]
+    )
+    .expect("register worker_trivial_chunks_total")
+});
+
+pub static WORKER_QUEUE_WAIT_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "worker_queue_wait_seconds",
+        "Histogram of time chunks spent queued before the worker fetched them",
+        &["worker_id", "brand"],
+        vec![0.1, 0.5, 1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 3600.0]
+    )
+    .expect("register worker_queue_wait_seconds")
+});
+
+pub static WORKER_E2E_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "worker_e2e_latency_seconds",
+        "Histogram of latency from orchestrator enqueue to result push",
+        &["worker_id", "brand"],
+        vec![0.5, 1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 900.0, 3600.0]
+    )
+    .expect("register worker_e2e_latency_seconds")
+});
+
+pub static WORKER_SINK_FLUSH_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "worker_sink_flush_seconds",
+        "Histogram of sink batch flush durations",
+        &["worker_id", "sink", "trigger", "outcome"],
+        vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+    )
+    .expect("register worker_sink_flush_seconds")
+});
+
+pub static WORKER_SINK_BATCH_SIZE: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "worker_sink_batch_size",
+        "Histogram of records per sink batch flush",
+        &["worker_id", "sink"],
+        vec![1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0]
+    )
+    .expect("register worker_sink_batch_size")
+});
+
+pub static WORKER_SINK_DROPPED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_sink_dropped_total",
+        "Total number of records dropped because they could not be buffered or spilled, or the sink rejected them",
+        &["worker_id", "sink"]
+    )
+    .expect("register worker_sink_dropped_total")
+});
+
+pub static WORKER_SINK_BUFFERED_RECORDS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_sink_buffered_records",
+        "Records held in memory by a sink awaiting a batch flush",
+        &["worker_id", "sink"]
+    )
+    .expect("register worker_sink_buffered_records")
+});
+
+pub static WORKER_SINK_SPILLED_RECORDS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_sink_spilled_records",
+        "Records spilled to disk by a sink awaiting replay",
+        &["worker_id", "sink"]
+    )
+    .expect("register worker_sink_spilled_records")
+});
+
+pub static WORKER_SINK_SPILLED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_sink_spilled_total",
+        "Total number of records spilled to disk, by reason (overflow, error)",
+        &["worker_id", "sink", "reason"]
+    )
+    .expect("register worker_sink_spilled_total")
+});
+
+pub static WORKER_THROTTLED: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_throttled",
+        "Whether the worker is throttled by resource pressure (1) or not (0)",
+        &["worker_id"]
+    )
+    .expect("register worker_throttled")
+});
+
+pub static WORKER_PROCESS_RSS_BYTES: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_process_rss_bytes",
+        "Resident set size of the worker process",
+        &["worker_id"]
+    )
+    .expect("register worker_process_rss_bytes")
+});
+
+pub static WORKER_RUNTIME_TASKS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_runtime_tasks",
+        "Tokio runtime tasks, by state (alive, backlog)",
+        &["worker_id", "state"]
+    )
+    .expect("register worker_runtime_tasks")
+});
+
+pub static WORKER_SLO_BREACHED: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_slo_breached",
+        "Whether an SLO rule's threshold is currently breached (1) or not (0)",
+        &["worker_id", "rule"]
+    )
+    .expect("register worker_slo_breached")
+});
+
+pub static WORKER_REDIS_FAMILY_KEYS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_redis_family_keys",
+        "Number of Redis keys in each worker-owned key family",
+        &["worker_id", "family"]
+    )
+    .expect("register worker_redis_family_keys")
+});
+
+pub static WORKER_REDIS_FAMILY_BYTES: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_redis_family_bytes",
+        "Estimated Redis memory used by each worker-owned key family",
+        &["worker_id", "family"]
+    )
+    .expect("register worker_redis_family_bytes")
+});
+
+pub static WORKER_REDIS_FAMILY_MAX_LENGTH: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_redis_family_max_length",
+        "Largest list or sorted set length sampled in each worker-owned key family",
+        &["worker_id", "family"]
+    )
+    .expect("register worker_redis_family_max_length")
+});
+
+pub static WORKER_ANALYSIS_CACHE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_analysis_cache_total",
+        "Total number of cached cluster analysis lookups during reprocessing",
+        &["worker_id", "brand", "outcome"]
+    )
+    .expect("register worker_analysis_cache_total")
+});
+
+pub static WORKER_EMBEDDING_CACHE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_embedding_cache_total",
+        "Total number of embedding cache lookups per text, by outcome (hit, miss)",
+        &["worker_id", "outcome"]
+    )
+    .expect("register worker_embedding_cache_total")
+});
+
+pub static WORKER_EMBEDDING_FALLBACK_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_embedding_fallback_total",
+        "Total number of chunks embedded with local vectors instead of the provider, by reason (error, timeout, budget)",
+        &["worker_id", "provider", "reason"]
+    )
+    .expect("register worker_embedding_fallback_total")
+});
+
+pub static WORKER_VECTOR_EXPORT_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_vector_export_total",
+        "Total number of vectors exported to the vector store, by kind (centroid, mention) and outcome (ok, error)",
+        &["worker_id", "store", "kind", "outcome"]
+    )
+    .expect("register worker_vector_export_total")
+});
+
+pub static WORKER_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_errors_total",
+        "Total number of worker errors by pipeline stage",
+        &["worker_id", "kind"]
+    )
+    .expect("register worker_errors_total")
+});
+
+pub static WORKER_STAGE_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "worker_stage_seconds",
+        "Time spent in each pipeline stage",
+        &["worker_id", "stage"]
+    )
+    .expect("register worker_stage_seconds")
+});
+
+pub static WORKER_STAGE_TIMEOUTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_stage_timeouts_total",
+        "Total number of pipeline stages that exceeded their timeout",
+        &["worker_id", "brand", "stage", "policy"]
+    )
+    .expect("register worker_stage_timeouts_total")
+});
+
+pub static WORKER_BRANDS_ONBOARDED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_brands_onboarded_total",
+        "Total number of brands onboarded by this worker",
+        &["worker_id"]
+    )
+    .expect("register worker_brands_onboarded_total")
+});
+
+pub static WORKER_DIGESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_digests_total",
+        "Total number of daily brand digests generated by outcome",
+        &["worker_id", "brand", "outcome"]
+    )
+    .expect("register worker_digests_total")
+});
+
+pub static WORKER_RECLUSTER_RUNS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_recluster_runs_total",
+        "Total number of per-brand re-clustering runs, by outcome (ok, skipped, error)",
+        &["worker_id", "outcome"]
+    )
+    .expect("register worker_recluster_runs_total")
+});
+
+pub static WORKER_RETENTION_ACTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_retention_actions_total",
+        "Total number of entries or keys removed or compacted by the retention job",
+        &["worker_id", "action"]
+    )
+    .expect("register worker_retention_actions_total")
+});
+
+pub static WORKER_PROVIDER_CALLS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_provider_calls_total",
+        "Total number of LLM and embedding provider calls by outcome",
+        &["worker_id", "provider", "operation", "outcome"]
+    )
+    .expect("register worker_provider_calls_total")
+});
+
+pub static WORKER_PROVIDER_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_provider_errors_total",
+        "Total number of failed provider calls that fell back to heuristics",
+        &["worker_id", "provider", "operation", "kind"]
+    )
+    .expect("register worker_provider_errors_total")
+});
+
+pub static WORKER_PROVIDER_SUCCESS_RATIO: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_provider_success_ratio",
+        "Share of provider calls that succeeded since the worker started",
+        &["worker_id", "provider", "operation"]
+    )
+    .expect("register worker_provider_success_ratio")
+});
+
+pub static WORKER_EGRESS_BLOCKED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_egress_blocked_total",
+        "Total number of outbound HTTP requests refused by the egress allowlist",
+        &["worker_id", "host"]
+    )
+    .expect("register worker_egress_blocked_total")
+});
+
+pub static WORKER_API_KEY_ROTATIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_api_key_rotations_total",
+        "Total number of times a rate-limited provider API key was rotated out for the next pooled key",
+        &["worker_id", "provider"]
+    )
+    .expect("register worker_api_key_rotations_total")
+});
+
+pub static WORKER_RATE_LIMIT_WAIT_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    let opts = HistogramOpts::new(
+        "worker_rate_limit_wait_seconds",
+        "Time provider calls spent waiting on the rate limiter",
+    )
+    .buckets(vec![0.001, 0.01, 0.05, 0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0]);
+    register_histogram_vec!(opts, &["worker_id", "provider"]).expect("register worker_rate_limit_wait_seconds")
+});
+
+pub static WORKER_EXPIRED_CHUNKS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_expired_chunks_total",
+        "Total number of chunks whose deadline passed before processing finished, by action taken",
+        &["worker_id", "brand", "action"]
+    )
+    .expect("register worker_expired_chunks_total")
+});
+
+pub static WORKER_DEGRADED_CHUNKS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "worker_degraded_chunks_total",
+        "Total number of chunks with output served by a fallback, by fallback reason",
+        &["worker_id", "brand", "reason"]
+    )
+    .expect("register worker_degraded_chunks_total")
+});
+
+pub static WORKER_PROVIDER_HEALTH_SCORE: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "worker_provider_health_score",
+        "Rolling success rate divided by one plus rolling latency, used to route LLM calls",
+        &["worker_id", "provider"]
+    )
+    .expect("register worker_provider_health_score")
+});
+
+pub fn record_provider_call(worker_id: &str, provider: &str, operation: &str, error: Option<&WorkerError>) {
+    let outcome = if error.is_some() { "error" } else { "ok" };
+    WORKER_PROVIDER_CALLS_TOTAL
+        .with_label_values(&[worker_id, provider, operation, outcome])
+        .inc();
+    if let Some(err) = error {
+        WORKER_PROVIDER_ERRORS_TOTAL
+            .with_label_values(&[worker_id, provider, operation, err.provider_kind()])
+            .inc();
+    }
+
+    let ok = WORKER_PROVIDER_CALLS_TOTAL
+        .with_label_values(&[worker_id, provider, operation, "ok"])
+        .get();
+    let failed = WORKER_PROVIDER_CALLS_TOTAL
+        .with_label_values(&[worker_id, provider, operation, "error"])
+        .get();
+    WORKER_PROVIDER_SUCCESS_RATIO
+        .with_label_values(&[worker_id, provider, operation])
+        .set(ok as f64 / (ok + failed).max(1) as f64);
+}
+
+pub fn record_unparseable_response(worker_id: &str, provider: &str, operation: &str, repaired: bool) {
+    let outcome = if repaired { "repaired" } else { "failed" };
+    WORKER_LLM_UNPARSEABLE_TOTAL
+        .with_label_values(&[worker_id, provider, operation, outcome])
+        .inc();
+}
+
+pub fn record_llm_usage(
+    worker_id: &str,
+    provider: &str,
+    model: &str,
+    brand: &str,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    cost_usd: f64,
+) {
+    WORKER_LLM_TOKENS_TOTAL
+        .with_label_values(&[worker_id, provider, model, brand, "prompt"])
+        .inc_by(prompt_tokens);
+    WORKER_LLM_TOKENS_TOTAL
+        .with_label_values(&[worker_id, provider, model, brand, "completion"])
+        .inc_by(completion_tokens);
+    WORKER_LLM_COST_USD_TOTAL
+        .with_label_values(&[worker_id, provider, model, brand])
+        .inc_by(cost_usd);
+}
+
 pub fn gather_metrics() -> String {
     let metric_families = prometheus::gather();
     let mut buffer = Vec::new();
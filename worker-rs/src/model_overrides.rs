@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::http::HttpClient;
+use crate::llm::{provider_adapter, LlmAdapter};
+use crate::prompts::PromptTemplates;
+use crate::redis_client::RedisClient;
+
+/// Override adapters built so far, by provider and model.
+type AdapterCache = Mutex<HashMap<(String, Option<String>), Arc<dyn LlmAdapter>>>;
+
+/// Provider and model a brand's LLM calls go to instead of the worker defaults, e.g.
+/// `{"provider": "openai", "model": "gpt-4o"}`. Either may be left out.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ModelOverride {
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// The adapter an override resolved to, with the labels to report it under.
+pub struct ResolvedOverride {
+    pub adapter: Arc<dyn LlmAdapter>,
+    pub provider: String,
+    pub model: Option<String>,
+}
+
+/// Per-brand overrides from `LLM_MODEL_OVERRIDES_FILE` (a JSON object keyed by brand) plus
+/// JSON objects stored at `{REDIS_LLM_OVERRIDE_PREFIX}:{brand}`, which win over the file and
+/// are re-read every `LLM_MODEL_OVERRIDE_REFRESH_SECS`, so a brand can be moved to a larger
+/// model without a restart. Calls are budgeted and their usage metered under the override's
+/// provider and model, while pacing and rate limits stay those of the worker's provider.
+pub struct ModelOverrides {
+    static_overrides: HashMap<String, ModelOverride>,
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    http: HttpClient,
+    prompts: Arc<PromptTemplates>,
+    lookups: Mutex<HashMap<String, (Instant, Option<ModelOverride>)>>,
+    adapters: AdapterCache,
+}
+
+impl ModelOverrides {
+    pub fn from_settings(
+        settings: &Arc<Settings>,
+        redis: &RedisClient,
+        http: &HttpClient,
+        prompts: &Arc<PromptTemplates>,
+    ) -> anyhow::Result<Option<Self>> {
+        if !settings.llm_model_overrides_enabled {
+            return Ok(None);
+        }
+        let static_overrides = match &settings.llm_model_overrides_file {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path).with_context(|| format!("read model overrides from {path}"))?;
+                serde_json::from_str::<HashMap<String, ModelOverride>>(&raw)
+                    .with_context(|| format!("parse model overrides in {path}"))?
+                    .into_iter()
+                    .map(|(brand, entry)| (brand.to_lowercase(), entry))
+                    .collect()
+            }
+            None => HashMap::new(),
+        };
+        if !static_overrides.is_empty() {
+            info!(worker_id = %settings.worker_id, brands = static_overrides.len(), "LLM model overrides loaded");
+        }
+        Ok(Some(Self {
+            static_overrides,
+            redis: redis.clone(),
+            settings: settings.clone(),
+            http: http.clone(),
+            prompts: prompts.clone(),
+            lookups: Mutex::new(HashMap::new()),
+            adapters: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// The adapter `brand` is overridden to, with `chunk_override` winning over the brand's;
+    /// `None` when it uses the worker defaults or its override can't be built.
+    pub async fn resolve(&self, brand: &str, chunk_override: Option<&ModelOverride>) -> Option<ResolvedOverride> {
+        let entry = match chunk_override {
+            Some(entry) => entry.clone(),
+            None => self.lookup(brand).await?,
+        };
+        let provider = entry.provider.unwrap_or_else(|| self.settings.llm_provider.clone());
+        let key = (provider.clone(), entry.model.clone());
+        let cached = self.adapters.lock().expect("model override adapters lock").get(&key).cloned();
+        let adapter = match cached {
+            Some(adapter) => adapter,
+            None => {
+                let mut settings = (*self.settings).clone();
+                if let Some(model) = &entry.model {
+                    match provider.as_str() {
+                        "openai" => settings.openai_model = model.clone(),
+                        "gemini" => settings.gemini_model = model.clone(),
+                        "anthropic" => settings.anthropic_model = model.clone(),
+                        _ => {}
+                    }
+                }
+                let adapter = match provider_adapter(&provider, &settings, &self.http, &self.prompts) {
+                    Ok(adapter) => adapter,
+                    Err(err) => {
+                        warn!(brand, provider, error = %err, "Cannot build overridden LLM adapter; using defaults");
+                        return None;
+                    }
+                };
+                self.adapters
+                    .lock()
+                    .expect("model override adapters lock")
+                    .insert(key, adapter.clone());
+                adapter
+            }
+        };
+        Some(ResolvedOverride {
+            adapter,
+            provider,
+            model: entry.model,
+        })
+    }
+
+    async fn lookup(&self, brand: &str) -> Option<ModelOverride> {
+        let brand = brand.to_lowercase();
+        let refresh = self.settings.llm_model_override_refresh;
+        if let Some((fetched_at, entry)) = self.lookups.lock().expect("model override lookups lock").get(&brand) {
+            if fetched_at.elapsed() < refresh {
+                return entry.clone();
+            }
+        }
+        let key = format!("{}:{}", self.settings.redis_llm_override_prefix, brand);
+        let dynamic = match self.redis.get_string(&key).await {
+            Ok(Some(raw)) => serde_json::from_str::<ModelOverride>(&raw)
+                .map_err(|err| warn!(brand, key, error = %err, "Ignoring malformed model override"))
+                .ok(),
+            Ok(None) => None,
+            Err(err) => {
+                warn!(brand, error = %err, "Failed to load model override");
+                None
+            }
+        };
+        let entry = dynamic.or_else(|| self.static_overrides.get(&brand).cloned());
+        self.lookups
+            .lock()
+            .expect("model override lookups lock")
+            .insert(brand, (Instant::now(), entry.clone()));
+        entry
+    }
+}
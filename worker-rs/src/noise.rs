@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::redis_client::RedisClient;
+
+/// Boilerplate phrases ("use code BRAND10") and stopwords stripped from mention text
+/// before embedding.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoiseList {
+    #[serde(default)]
+    pub phrases: Vec<String>,
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+}
+
+impl NoiseList {
+    fn is_empty(&self) -> bool {
+        self.phrases.is_empty() && self.stopwords.is_empty()
+    }
+
+    fn extend(&mut self, other: &NoiseList) {
+        self.phrases.extend(other.phrases.iter().map(|phrase| phrase.to_lowercase()));
+        self.stopwords.extend(other.stopwords.iter().map(|word| word.to_lowercase()));
+    }
+
+    /// Strips phrases and stopwords from already lowercased text.
+    pub fn strip(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for phrase in self.phrases.iter().filter(|phrase| !phrase.trim().is_empty()) {
+            text = text.replace(phrase.as_str(), " ");
+        }
+        let stopwords: HashSet<&str> = self.stopwords.iter().map(String::as_str).collect();
+        text.split_whitespace()
+            .filter(|word| !stopwords.contains(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Noise lists from `NOISE_LISTS_FILE` (a JSON object keyed by brand, `*` for all brands)
+/// plus a JSON object stored at `{REDIS_NOISE_LISTS_PREFIX}:{brand}`, read per chunk so
+/// operators can change lists without a redeploy.
+pub struct NoiseFilter {
+    static_lists: HashMap<String, NoiseList>,
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl NoiseFilter {
+    pub fn from_settings(settings: Arc<Settings>, redis: RedisClient) -> anyhow::Result<Self> {
+        let static_lists = match &settings.noise_lists_file {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path).with_context(|| format!("read noise lists from {path}"))?;
+                serde_json::from_str::<HashMap<String, NoiseList>>(&raw)
+                    .with_context(|| format!("parse noise lists in {path}"))?
+                    .into_iter()
+                    .map(|(brand, list)| (brand.to_lowercase(), list))
+                    .collect()
+            }
+            None => HashMap::new(),
+        };
+        if !static_lists.is_empty() {
+            info!(worker_id = %settings.worker_id, brands = static_lists.len(), "Noise lists loaded");
+        }
+        Ok(Self {
+            static_lists,
+            redis,
+            settings,
+        })
+    }
+
+    pub async fn list(&self, brand: &str) -> NoiseList {
+        let key = format!("{}:{}", self.settings.redis_noise_lists_prefix, brand);
+        let dynamic = match self.redis.get_string(&key).await {
+            Ok(Some(raw)) => serde_json::from_str::<NoiseList>(&raw).unwrap_or_else(|err| {
+                warn!(brand, key, error = %err, "Ignoring malformed noise list");
+                NoiseList::default()
+            }),
+            Ok(None) => NoiseList::default(),
+            Err(err) => {
+                warn!(brand, error = %err, "Failed to load noise list");
+                NoiseList::default()
+            }
+        };
+
+        let mut list = NoiseList::default();
+        for source in [self.static_lists.get("*"), self.static_lists.get(&brand.to_lowercase()), Some(&dynamic)]
+            .into_iter()
+            .flatten()
+            .filter(|source| !source.is_empty())
+        {
+            list.extend(source);
+        }
+        list
+    }
+}
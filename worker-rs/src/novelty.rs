@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::config::Settings;
+use crate::redis_client::RedisClient;
+use crate::types::{ClusterResult, EmergingNarrative};
+
+/// N-grams per cluster compared against history.
+const TOP_NGRAMS: usize = 10;
+/// Mentions an n-gram must appear in to stand for the cluster rather than one post.
+const MIN_DOCUMENT_FREQUENCY: usize = 2;
+
+/// Per-brand, per-day sorted sets of n-gram hash → mention count under
+/// `{REDIS_TREND_PREFIX}:{brand}:ngrams:{YYYY-MM-DD}`. A cluster whose top unigrams and
+/// bigrams are mostly absent from the previous `NOVELTY_WINDOW_DAYS` is an emerging
+/// narrative, which can surface a new crisis while its volume is still below spike level.
+/// The chunk's own day is not part of the history, so a narrative stays flagged for the
+/// day it first appears on. Only hashes are stored, which is all membership checks need.
+pub struct NoveltyDetector {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl NoveltyDetector {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Option<Self> {
+        settings.novelty_detection_enabled.then(|| Self { redis, settings })
+    }
+
+    /// Sets `emerging` on qualifying clusters, then adds the chunk's n-grams to its day when
+    /// `record` is set. Flags nothing until the brand has some n-gram history.
+    pub async fn detect(
+        &self,
+        brand: &str,
+        at: DateTime<Utc>,
+        clusters: &mut [ClusterResult],
+        record: bool,
+    ) -> anyhow::Result<()> {
+        let day = at.date_naive();
+        let ranked: Vec<Vec<(String, usize)>> = clusters.iter().map(|cluster| ranked_ngrams(&cluster.examples)).collect();
+
+        let history: Vec<String> = (1..=self.settings.novelty_window_days)
+            .filter_map(|back| day.checked_sub_days(Days::new(back)))
+            .map(|day| self.key(brand, day))
+            .collect();
+        let warmed_up = self.redis.lengths("ZCARD", &history).await?.iter().any(|&len| len > 0);
+        if warmed_up {
+            let candidates: Vec<String> = ranked
+                .iter()
+                .zip(clusters.iter())
+                .filter(|(_, cluster)| cluster.count >= self.settings.novelty_min_mentions)
+                .flat_map(|(ngrams, _)| top_terms(ngrams))
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            let seen = self.seen(&history, &candidates).await?;
+            for (cluster, ngrams) in clusters.iter_mut().zip(&ranked) {
+                if cluster.count < self.settings.novelty_min_mentions {
+                    continue;
+                }
+                let terms: Vec<String> = top_terms(ngrams).collect();
+                if terms.is_empty() {
+                    continue;
+                }
+                let new: Vec<String> = terms.iter().filter(|term| !seen.contains(*term)).cloned().collect();
+                let novelty = new.len() as f32 / terms.len() as f32;
+                if novelty >= self.settings.novelty_threshold {
+                    cluster.emerging = Some(EmergingNarrative { novelty, terms: new });
+                }
+            }
+        }
+
+        if !record {
+            return Ok(());
+        }
+        let mut counts: HashMap<String, f64> = HashMap::new();
+        for (ngram, frequency) in ranked.into_iter().flatten() {
+            *counts.entry(ngram_hash(&ngram)).or_default() += frequency as f64;
+        }
+        if !counts.is_empty() {
+            let ttl = Duration::from_secs((self.settings.novelty_window_days + 1) * 86_400);
+            let counts: Vec<(String, f64)> = counts.into_iter().collect();
+            self.redis.zincr_many_with_ttl(&self.key(brand, day), &counts, ttl).await?;
+        }
+        Ok(())
+    }
+
+    /// The candidates present on any day of `history`.
+    async fn seen(&self, history: &[String], candidates: &[String]) -> anyhow::Result<HashSet<String>> {
+        let hashes: Vec<String> = candidates.iter().map(|candidate| ngram_hash(candidate)).collect();
+        let scores = self.redis.zmscore_many(history, &hashes).await?;
+        Ok(candidates
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| scores.iter().any(|day| day.get(*index).copied().flatten().is_some()))
+            .map(|(_, candidate)| candidate.clone())
+            .collect())
+    }
+
+    fn key(&self, brand: &str, day: NaiveDate) -> String {
+        format!("{}:{}:ngrams:{}", self.settings.redis_trend_prefix, brand, day.format("%Y-%m-%d"))
+    }
+}
+
+fn ngram_hash(ngram: &str) -> String {
+    let digest = Sha256::digest(ngram.as_bytes());
+    digest.iter().take(16).map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn top_terms(ngrams: &[(String, usize)]) -> impl Iterator<Item = String> + '_ {
+    ngrams
+        .iter()
+        .filter(|(_, frequency)| *frequency >= MIN_DOCUMENT_FREQUENCY)
+        .take(TOP_NGRAMS)
+        .map(|(ngram, _)| ngram.clone())
+}
+
+/// Unigrams of four or more characters and bigrams of adjacent three-or-more character
+/// words, with the number of texts each appears in, most frequent first.
+fn ranked_ngrams(texts: &[String]) -> Vec<(String, usize)> {
+    let mut frequencies: HashMap<String, usize> = HashMap::new();
+    for text in texts {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let mut ngrams: HashSet<String> = words.iter().filter(|word| word.chars().count() >= 4).cloned().collect();
+        for pair in words.windows(2) {
+            if pair.iter().all(|word| word.chars().count() >= 3) {
+                ngrams.insert(format!("{} {}", pair[0], pair[1]));
+            }
+        }
+        for ngram in ngrams {
+            *frequencies.entry(ngram).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = frequencies.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::config::Settings;
+use crate::metrics::WORKER_BRANDS_ONBOARDED_TOTAL;
+use crate::redis_client::RedisClient;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingEvent {
+    pub brand: String,
+    pub worker_id: String,
+    pub onboarded_at: String,
+    pub spike_baseline: u64,
+}
+
+/// Initializes per-brand state the first time any worker sees a brand queue, so new
+/// brands start with a spike baseline instead of an empty history.
+pub struct BrandOnboarding {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    known: Mutex<HashSet<String>>,
+}
+
+impl BrandOnboarding {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Self {
+        Self {
+            redis,
+            settings,
+            known: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub async fn observe(&self, brand: &str) -> anyhow::Result<()> {
+        if !self.settings.brand_onboarding_enabled || self.known.lock().await.contains(brand) {
+            return Ok(());
+        }
+
+        let key = brand_state_key(&self.settings, brand);
+        let onboarded_at = Utc::now().to_rfc3339();
+        // HSETNX makes exactly one worker the owner of onboarding across the fleet.
+        if self.redis.hset_nx(&key, "onboardedAt", &onboarded_at).await? {
+            let baseline = self.settings.spike_baseline_mentions;
+            self.redis
+                .hset_multiple(
+                    &key,
+                    &[
+                        ("onboardedBy", self.settings.worker_id.clone()),
+                        ("spikeBaseline", baseline.to_string()),
+                        ("embeddingsProvider", self.settings.embeddings_provider.clone()),
+                        ("llmProvider", self.settings.llm_provider.clone()),
+                    ],
+                )
+                .await?;
+
+            let event = OnboardingEvent {
+                brand: brand.to_string(),
+                worker_id: self.settings.worker_id.clone(),
+                onboarded_at,
+                spike_baseline: baseline,
+            };
+            let payload = serde_json::to_string(&event)?;
+            self.redis.publish(&self.settings.onboarding_channel, &payload).await?;
+            WORKER_BRANDS_ONBOARDED_TOTAL
+                .with_label_values(&[&self.settings.worker_id])
+                .inc();
+            info!(worker_id = %self.settings.worker_id, brand, baseline, "Brand onboarded");
+        }
+
+        self.known.lock().await.insert(brand.to_string());
+        Ok(())
+    }
+}
+
+pub fn brand_state_key(settings: &Settings, brand: &str) -> String {
+    format!("{}:{}", settings.redis_brand_state_prefix, brand)
+}
@@ -0,0 +1,182 @@
+use crate::config::Settings;
+
+/// Sentence-transformer embeddings computed in-process with ONNX Runtime, for
+/// `EMBEDDINGS_PROVIDER=onnx`, so mention text never leaves the worker. Needs the
+/// `onnx-embeddings` build feature and the ONNX Runtime shared library, found through
+/// `ORT_DYLIB_PATH` or the system library path.
+///
+/// `EMBEDDINGS_ONNX_MODEL` is an exported model taking `input_ids` and `attention_mask`
+/// (and `token_type_ids` when declared). Its first output is either token states, which are
+/// mean-pooled over the attention mask, or sentence vectors. Vectors are L2-normalised.
+/// The tokenizer is `EMBEDDINGS_ONNX_TOKENIZER`, or `tokenizer.json` beside the model.
+pub struct OnnxEmbeddingAdapter {
+    #[cfg(feature = "onnx-embeddings")]
+    session: std::sync::Arc<std::sync::Mutex<ort::session::Session>>,
+    #[cfg(feature = "onnx-embeddings")]
+    tokenizer: std::sync::Arc<tokenizers::Tokenizer>,
+    #[cfg(feature = "onnx-embeddings")]
+    token_type_ids: bool,
+    #[cfg(feature = "onnx-embeddings")]
+    batch_size: usize,
+    #[cfg(not(feature = "onnx-embeddings"))]
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "onnx-embeddings")]
+impl OnnxEmbeddingAdapter {
+    pub fn new(settings: &Settings) -> anyhow::Result<Self> {
+        use std::path::Path;
+
+        use anyhow::{anyhow, Context};
+        use tokenizers::{PaddingParams, TruncationParams};
+
+        let model = settings
+            .embeddings_onnx_model
+            .as_deref()
+            .context("EMBEDDINGS_ONNX_MODEL is required for EMBEDDINGS_PROVIDER=onnx")?;
+        let tokenizer_path = match &settings.embeddings_onnx_tokenizer {
+            Some(path) => path.clone(),
+            None => Path::new(model)
+                .with_file_name("tokenizer.json")
+                .to_string_lossy()
+                .into_owned(),
+        };
+
+        let mut tokenizer = tokenizers::Tokenizer::from_file(&tokenizer_path)
+            .map_err(|err| anyhow!("load ONNX embedding tokenizer from {tokenizer_path}: {err}"))?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: settings.embeddings_onnx_max_tokens,
+                ..Default::default()
+            }))
+            .map_err(|err| anyhow!("configure ONNX embedding tokenizer: {err}"))?;
+
+        let session = ort::session::Session::builder()
+            .and_then(|builder| builder.commit_from_file(model))
+            .with_context(|| format!("load ONNX embedding model from {model}"))?;
+        let token_type_ids = session.inputs.iter().any(|input| input.name == "token_type_ids");
+        tracing::info!(worker_id = %settings.worker_id, model, tokenizer = %tokenizer_path, "ONNX embedding model loaded");
+
+        Ok(Self {
+            session: std::sync::Arc::new(std::sync::Mutex::new(session)),
+            tokenizer: std::sync::Arc::new(tokenizer),
+            token_type_ids,
+            batch_size: settings.embeddings_batch_size.max(1),
+        })
+    }
+}
+
+#[cfg(feature = "onnx-embeddings")]
+fn embed_batch(
+    session: &std::sync::Mutex<ort::session::Session>,
+    tokenizer: &tokenizers::Tokenizer,
+    token_type_ids: bool,
+    texts: &[String],
+) -> anyhow::Result<Vec<Vec<f32>>> {
+    use anyhow::{anyhow, bail};
+    use ort::value::Tensor;
+
+    let encodings = tokenizer
+        .encode_batch(texts.to_vec(), true)
+        .map_err(|err| anyhow!("tokenize mentions for ONNX embedding: {err}"))?;
+    let batch = encodings.len();
+    let seq_len = encodings.first().map_or(0, |encoding| encoding.len());
+    let flatten = |field: fn(&tokenizers::Encoding) -> &[u32]| -> Vec<i64> {
+        encodings
+            .iter()
+            .flat_map(|encoding| field(encoding).iter().map(|&id| i64::from(id)))
+            .collect()
+    };
+    let mask = flatten(tokenizers::Encoding::get_attention_mask);
+
+    let mut inputs = ort::inputs![
+        "input_ids" => Tensor::from_array(([batch, seq_len], flatten(tokenizers::Encoding::get_ids)))?,
+        "attention_mask" => Tensor::from_array(([batch, seq_len], mask.clone()))?,
+    ];
+    if token_type_ids {
+        inputs.push((
+            "token_type_ids".into(),
+            Tensor::from_array(([batch, seq_len], flatten(tokenizers::Encoding::get_type_ids)))?.into(),
+        ));
+    }
+
+    let mut session = session.lock().map_err(|_| anyhow!("ONNX embedding session poisoned"))?;
+    let outputs = session.run(inputs)?;
+    let (shape, data) = outputs[0].try_extract_tensor::<f32>()?;
+    let mut vectors = match **shape {
+        [rows, hidden] if rows as usize == batch => data.chunks(hidden as usize).map(<[f32]>::to_vec).collect(),
+        [rows, tokens, hidden] if rows as usize == batch && tokens as usize == seq_len => {
+            let hidden = hidden as usize;
+            data.chunks(seq_len * hidden)
+                .zip(mask.chunks(seq_len.max(1)))
+                .map(|(states, mask)| {
+                    let mut pooled = vec![0.0_f32; hidden];
+                    let mut count = 0.0_f32;
+                    for (state, _) in states.chunks(hidden).zip(mask).filter(|(_, &m)| m > 0) {
+                        pooled.iter_mut().zip(state).for_each(|(sum, value)| *sum += value);
+                        count += 1.0;
+                    }
+                    pooled.iter_mut().for_each(|value| *value /= count.max(1.0));
+                    pooled
+                })
+                .collect::<Vec<_>>()
+        }
+        _ => bail!("unexpected ONNX embedding output shape {shape:?}"),
+    };
+    for vector in &mut vectors {
+        let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|value| *value /= norm);
+        }
+    }
+    Ok(vectors)
+}
+
+#[cfg(feature = "onnx-embeddings")]
+#[async_trait::async_trait]
+impl crate::embeddings::EmbeddingAdapter for OnnxEmbeddingAdapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        _brand: &str,
+        _chunk_id: &str,
+    ) -> crate::error::WorkerResult<Vec<Vec<f32>>> {
+        let (session, tokenizer) = (self.session.clone(), self.tokenizer.clone());
+        let (token_type_ids, batch_size) = (self.token_type_ids, self.batch_size);
+        let texts = texts.to_vec();
+        // Inference is CPU-bound; keep it off the async workers.
+        let embedded = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<Vec<f32>>> {
+            let mut vectors = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(batch_size) {
+                vectors.extend(embed_batch(&session, &tokenizer, token_type_ids, batch)?);
+            }
+            Ok(vectors)
+        })
+        .await;
+        match embedded {
+            Ok(outcome) => outcome.map_err(crate::error::WorkerError::Embedding),
+            Err(err) => Err(crate::error::WorkerError::Embedding(err.into())),
+        }
+    }
+}
+
+#[cfg(not(feature = "onnx-embeddings"))]
+impl OnnxEmbeddingAdapter {
+    pub fn new(_settings: &Settings) -> anyhow::Result<Self> {
+        anyhow::bail!("EMBEDDINGS_PROVIDER=onnx needs a worker built with the onnx-embeddings feature")
+    }
+}
+
+#[cfg(not(feature = "onnx-embeddings"))]
+#[async_trait::async_trait]
+impl crate::embeddings::EmbeddingAdapter for OnnxEmbeddingAdapter {
+    async fn embed(
+        &self,
+        _texts: &[String],
+        _brand: &str,
+        _chunk_id: &str,
+    ) -> crate::error::WorkerResult<Vec<Vec<f32>>> {
+        match self.never {}
+    }
+}
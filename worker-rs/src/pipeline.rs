@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::processor::Processor;
+use crate::redis_client::RedisClient;
+use crate::stages::{build_stages, PipelineStage};
+use crate::types::{Chunk, ChunkResult};
+
+/// Chunk processing without the queue loop, for services that want to run the
+/// worker's analysis in-process (orchestrator tests, batch backfill jobs).
+///
+/// Spike history, trends and centroids are still kept in Redis under the prefixes
+/// from `settings`; pass `Settings::namespaced` to keep them apart from live workers.
+pub struct Pipeline {
+    processor: Processor,
+}
+
+impl Pipeline {
+    pub fn new(settings: Arc<Settings>, redis: RedisClient) -> WorkerResult<Self> {
+        Self::with_stages(settings, redis, Vec::new())
+    }
+
+    /// Like [`Pipeline::new`], with extra stages that `PIPELINE_STAGES` can refer to by name.
+    pub fn with_stages(
+        settings: Arc<Settings>,
+        redis: RedisClient,
+        custom: Vec<Arc<dyn PipelineStage>>,
+    ) -> WorkerResult<Self> {
+        let cipher = Arc::new(PayloadCipher::from_settings(&settings).map_err(WorkerError::Config)?);
+        let http = HttpClient::from_settings(&settings).map_err(WorkerError::Config)?;
+        Ok(Self {
+            processor: build_processor(&settings, &redis, &cipher, &http, &custom)?,
+        })
+    }
+
+    pub async fn process(&self, chunk: Chunk) -> WorkerResult<ChunkResult> {
+        self.processor.process_chunk(chunk, "unknown", 0.0).await
+    }
+
+    /// Like [`Pipeline::process`], leaving spike history, dedup, novelty and recurrence state
+    /// untouched.
+    pub async fn preview(&self, chunk: Chunk) -> WorkerResult<ChunkResult> {
+        self.processor.preview_chunk(chunk, "unknown").await
+    }
+
+    pub async fn reprocess(&self, chunk: Chunk, force_refresh: bool) -> WorkerResult<ChunkResult> {
+        self.processor.reprocess_chunk(chunk, "unknown", force_refresh).await
+    }
+}
+
+pub(crate) fn build_processor(
+    settings: &Arc<Settings>,
+    redis: &RedisClient,
+    cipher: &Arc<PayloadCipher>,
+    http: &HttpClient,
+    custom: &[Arc<dyn PipelineStage>],
+) -> WorkerResult<Processor> {
+    let stages = build_stages(settings, redis, cipher, http, custom)?;
+    Ok(Processor::new(settings.clone(), stages))
+}
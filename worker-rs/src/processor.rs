@@ -1,252 +1,275 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
-use anyhow::Result;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
 use tracing::warn;
 
-use crate::clustering::{Clusterer, ClusteringOutput};
 use crate::config::Settings;
-use crate::embeddings::InstrumentedEmbeddingAdapter;
-use crate::llm::InstrumentedLlmAdapter;
+use crate::context::{LlmSelection, ProcessingContext};
+use crate::error::{WorkerError, WorkerResult};
 use crate::metrics::{
-    WORKER_PREPROCESSING_TIME_SECONDS,
+    WORKER_DEGRADED_CHUNKS_TOTAL, WORKER_EXPIRED_CHUNKS_TOTAL, WORKER_QUEUE_WAIT_SECONDS, WORKER_STAGE_SECONDS,
+    WORKER_STAGE_TIMEOUTS_TOTAL,
 };
-use crate::spike::{SpikeDetectionResult, SpikeDetector};
-use crate::types::{Chunk, ChunkMetrics, ChunkResult, ClusterResult, Mention};
-
-static URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://\S+").expect("Invalid URL regex"));
-static WHITESPACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").expect("Invalid whitespace regex"));
-const TOPIC_LIMIT: usize = 10;
+use crate::qa;
+use crate::stages::{PipelineStage, StageContext};
+use crate::types::{Chunk, ChunkMetrics, ChunkResult, Provenance};
 
 pub struct Processor {
     settings: Arc<Settings>,
-    embeddings: InstrumentedEmbeddingAdapter,
-    clusterer: Clusterer,
-    llm: InstrumentedLlmAdapter,
-    spike_detector: SpikeDetector,
+    stages: Vec<Arc<dyn PipelineStage>>,
 }
 
 impl Processor {
-    pub fn new(
-        settings: Arc<Settings>,
-        embeddings: InstrumentedEmbeddingAdapter,
-        clusterer: Clusterer,
-        llm: InstrumentedLlmAdapter,
-        spike_detector: SpikeDetector,
-    ) -> Self {
-        Self {
-            settings,
-            embeddings,
-            clusterer,
-            llm,
-            spike_detector,
-        }
+    pub fn new(settings: Arc<Settings>, stages: Vec<Arc<dyn PipelineStage>>) -> Self {
+        Self { settings, stages }
+    }
+
+    pub async fn process_chunk(&self, chunk: Chunk, fallback_brand: &str, fetch_time_ms: f64) -> WorkerResult<ChunkResult> {
+        self.run(chunk, fallback_brand, fetch_time_ms, false, false).await
     }
 
-    pub async fn process_chunk(&self, chunk: Chunk, fallback_brand: &str, fetch_time_ms: f64) -> Result<ChunkResult> {
+    /// Processes a chunk without changing any per-brand state; see [`ProcessingContext::dry_run`].
+    pub async fn preview_chunk(&self, chunk: Chunk, fallback_brand: &str) -> WorkerResult<ChunkResult> {
+        self.run(chunk, fallback_brand, 0.0, false, true).await
+    }
+
+    /// Reprocesses a previously seen chunk, reusing cached summaries and sentiment
+    /// for clusters whose LLM input is unchanged unless `force_refresh` is set.
+    pub async fn reprocess_chunk(&self, chunk: Chunk, fallback_brand: &str, force_refresh: bool) -> WorkerResult<ChunkResult> {
+        let reuse_cached = self.settings.analysis_cache_enabled && !force_refresh;
+        self.run(chunk, fallback_brand, 0.0, reuse_cached, false).await
+    }
+
+    async fn run(
+        &self,
+        chunk: Chunk,
+        fallback_brand: &str,
+        fetch_time_ms: f64,
+        reuse_cached: bool,
+        dry_run: bool,
+    ) -> WorkerResult<ChunkResult> {
         let total_start = Instant::now();
         let mut metrics = ChunkMetrics {
             io_time_ms: fetch_time_ms,
             ..Default::default()
         };
 
-        let mut brand = chunk.brand.clone();
-        if brand.trim().is_empty() {
-            brand = fallback_brand.to_string();
-        }
-
-        let preprocess_start = Instant::now();
-        let mentions = self.preprocess(&chunk.mentions);
-        let preprocessing_duration = preprocess_start.elapsed();
-        metrics.preprocessing_time_ms = preprocessing_duration.as_secs_f64() * 1000.0;
-        WORKER_PREPROCESSING_TIME_SECONDS
-            .with_label_values(&[&self.settings.worker_id, &brand])
-            .observe(preprocessing_duration.as_secs_f64());
-
-        if mentions.is_empty() {
-            metrics.total_task_time_ms = total_start.elapsed().as_secs_f64() * 1000.0 + metrics.io_time_ms;
-            return Ok(ChunkResult {
-                chunk_id: chunk.chunk_id,
-                brand,
-                timestamp: chunk.created_at.timestamp(),
-                clusters: Vec::new(),
-                metrics,
-            });
+        let job = ProcessingContext {
+            dry_run,
+            ..ProcessingContext::new(&chunk, fallback_brand, reuse_cached)
+        };
+        let enqueued_at = chunk.meta.as_ref().and_then(|meta| meta.enqueued_at);
+        if let Some(enqueued_at) = enqueued_at {
+            let wait_ms = (Utc::now() - enqueued_at).num_milliseconds().max(0) as f64 - fetch_time_ms;
+            metrics.queue_wait_ms = Some(wait_ms.max(0.0));
+            WORKER_QUEUE_WAIT_SECONDS
+                .with_label_values(&[&self.settings.worker_id, &job.brand])
+                .observe(wait_ms.max(0.0) / 1000.0);
         }
 
-        let embed_start = Instant::now();
-        let embeddings = self
-            .embeddings
-            .embed(&mentions, &brand, &chunk.chunk_id)
-            .await;
-        metrics.embedding_time_ms = embed_start.elapsed().as_secs_f64() * 1000.0;
-
-        let clustering_output = self
-            .clusterer
-            .cluster(&embeddings, &brand, &chunk.chunk_id)
-            .await;
-        metrics.clustering_time_ms = clustering_output.duration_ms;
+        let mut ctx = StageContext {
+            job,
+            chunk,
+            mentions: Vec::new(),
+            embeddings: Vec::new(),
+            clusters: Vec::new(),
+            results: Vec::new(),
+            metrics,
+            provenance: Provenance::default(),
+            complete: false,
+        };
 
-        let clusters = self
-            .build_cluster_results(&brand, &chunk.chunk_id, &mentions, clustering_output)
-            .await;
+        let mut expired = false;
+        for stage in &self.stages {
+            if ctx.complete {
+                break;
+            }
+            // Checked per stage so a chunk that runs out of budget midway skips the remaining LLM calls.
+            if !expired && ctx.job.deadline_passed() {
+                expired = true;
+                self.expire(&mut ctx);
+            }
+            self.run_stage(stage.as_ref(), &mut ctx).await?;
+        }
 
-        metrics.llm_time_ms = clusters.iter().map(|cluster| cluster.metrics.llm_ms).sum();
-        metrics.spike_detection_time_ms = clusters
-            .iter()
-            .map(|cluster| cluster.metrics.spike_ms)
-            .sum();
+        let qa = qa::sampled(&ctx.job.chunk_id, self.settings.qa_sample_percent)
+            .then(|| qa::capture(&ctx, self.settings.example_redaction == "none"));
 
-        let cluster_results: Vec<ClusterResult> = clusters.into_iter().map(|wrapper| wrapper.cluster).collect();
+        if self.settings.example_redaction != "none" {
+            redact_examples(&mut ctx, &self.settings.example_redaction);
+        }
 
-        metrics.total_task_time_ms = total_start.elapsed().as_secs_f64() * 1000.0 + metrics.io_time_ms;
+        ctx.metrics.total_task_time_ms = total_start.elapsed().as_secs_f64() * 1000.0 + ctx.metrics.io_time_ms;
+        for reason in &ctx.provenance.fallbacks {
+            WORKER_DEGRADED_CHUNKS_TOTAL
+                .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, reason])
+                .inc();
+        }
 
         Ok(ChunkResult {
-            chunk_id: chunk.chunk_id,
-            brand,
-            timestamp: chunk.created_at.timestamp(),
-            clusters: cluster_results,
-            metrics,
+            chunk_id: ctx.job.chunk_id,
+            brand: ctx.job.brand,
+            timestamp: ctx.chunk.created_at.timestamp(),
+            clusters: ctx.results,
+            metrics: ctx.metrics,
+            provenance: ctx.provenance,
+            enqueued_at,
+            qa,
+            expired,
+            priority: ctx.job.priority,
         })
     }
 
-    fn preprocess(&self, mentions: &[Mention]) -> Vec<String> {
-        let mut seen = HashSet::new();
-        let mut cleaned = Vec::new();
-
-        for mention in mentions {
-            let candidate = self.clean_text(&mention.text);
-            if candidate.is_empty() {
-                continue;
-            }
-            if seen.insert(candidate.clone()) {
-                cleaned.push(candidate);
-            }
+    fn expire(&self, ctx: &mut StageContext) {
+        let action = &self.settings.deadline_action;
+        WORKER_EXPIRED_CHUNKS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, action])
+            .inc();
+        warn!(
+            worker_id = %self.settings.worker_id,
+            brand = %ctx.job.brand,
+            chunk_id = %ctx.job.chunk_id,
+            correlation_id = %ctx.job.correlation_id,
+            action = %action,
+            "Chunk deadline passed"
+        );
+        if action == "fast_path" {
+            ctx.job.llm = LlmSelection::Heuristic;
+            ctx.provenance.record_fallback("deadline", "expired");
         }
-
-        cleaned
     }
 
-    fn clean_text(&self, text: &str) -> String {
-        let without_urls = URL_RE.replace_all(text, "");
-        let normalized = WHITESPACE_RE.replace_all(without_urls.trim(), " ");
-        normalized.to_lowercase()
+    async fn run_stage(&self, stage: &dyn PipelineStage, ctx: &mut StageContext) -> WorkerResult<()> {
+        let name = stage.name().to_string();
+        let start = Instant::now();
+        let outcome = match self.settings.stage_timeouts.get(&name) {
+            Some(limit) => tokio::time::timeout(*limit, stage.run(ctx)).await.ok(),
+            None => Some(stage.run(ctx).await),
+        };
+        let elapsed = start.elapsed();
+        ctx.metrics.stage_times_ms.insert(name.clone(), elapsed.as_secs_f64() * 1000.0);
+        WORKER_STAGE_SECONDS
+            .with_label_values(&[&self.settings.worker_id, &name])
+            .observe(elapsed.as_secs_f64());
+
+        if let Some(result) = outcome {
+            return result.map_err(|err| err.in_stage(&name));
+        }
+
+        let fail = self.settings.stage_timeout_fail.contains(&name);
+        WORKER_STAGE_TIMEOUTS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, &name, if fail { "fail" } else { "skip" }])
+            .inc();
+        warn!(
+            worker_id = %self.settings.worker_id,
+            brand = %ctx.job.brand,
+            chunk_id = %ctx.job.chunk_id,
+            correlation_id = %ctx.job.correlation_id,
+            stage = %name,
+            elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+            "Pipeline stage timed out"
+        );
+        if fail {
+            return Err(WorkerError::Timeout(name));
+        }
+        stage.skip(ctx);
+        ctx.provenance.record_fallback(&name, "timeout");
+        ctx.metrics.skipped_stages.push(name);
+        Ok(())
     }
+}
 
-    async fn build_cluster_results(
-        &self,
-        brand: &str,
-        chunk_id: &str,
-        mentions: &[String],
-        clustering_output: ClusteringOutput,
-    ) -> Vec<ClusterWithMetrics> {
-        let mut results = Vec::new();
-
-        for group in clustering_output.clusters {
-            let cluster_mentions: Vec<String> = group
-                .indices
+/// Replaces mention text kept in cluster results with mention IDs (`ids`) or SHA-256
+/// references (any other mode). Runs after analysis, so the LLM still saw the full text.
+fn redact_examples(ctx: &mut StageContext, mode: &str) {
+    let ids: HashMap<&str, &str> = ctx
+        .mentions
+        .iter()
+        .map(|mention| (mention.text.as_str(), mention.source.id.as_str()))
+        .collect();
+    let redact = |text: &String| match ids.get(text.as_str()) {
+        Some(id) if mode == "ids" => id.to_string(),
+        _ => {
+            let digest = Sha256::digest(text.as_bytes());
+            let hash: String = digest.iter().take(8).map(|byte| format!("{byte:02x}")).collect();
+            format!("sha256:{hash}")
+        }
+    };
+    for cluster in &mut ctx.results {
+        cluster.examples = cluster.examples.iter().map(redact).collect();
+        // Topics are mention text unless the LLM produced them in a combined analysis.
+        if let Some(topics) = cluster.topics.as_mut() {
+            *topics = topics
                 .iter()
-                .filter_map(|&idx| mentions.get(idx).cloned())
+                .map(|topic| if ids.contains_key(topic.as_str()) { redact(topic) } else { topic.clone() })
                 .collect();
+        }
+        if let Some(quotes) = cluster.quotes.as_mut() {
+            *quotes = quotes.iter().map(redact).collect();
+        }
+        // Trivial and fallback clusters use a raw mention as their summary.
+        if cluster.summary.as_deref().is_some_and(|summary| ids.contains_key(summary)) {
+            cluster.summary = cluster.summary.as_ref().map(redact);
+        }
+    }
+}
 
-            if cluster_mentions.is_empty() {
-                continue;
-            }
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
 
-            let examples = cluster_mentions
-                .iter()
-                .take(self.settings.preprocessing_examples)
-                .cloned()
-                .collect::<Vec<_>>();
-
-            let llm_start = Instant::now();
-            let summary = self.llm.summarize(brand, &cluster_mentions).await;
-            let sentiment = self.llm.sentiment(brand, &cluster_mentions).await;
-            let llm_duration_ms = llm_start.elapsed().as_secs_f64() * 1000.0;
-
-            let spike_start = Instant::now();
-            let spike_result = match self
-                .spike_detector
-                .detect(brand, group.cluster_id, cluster_mentions.len())
-                .await
-            {
-                Ok(result) => result,
-                Err(err) => {
-                    warn!(
-                        worker_id = %self.settings.worker_id,
-                        brand,
-                        chunk_id,
-                        cluster_id = group.cluster_id,
-                        error = %err,
-                        "Spike detection failed; marking cluster as non-spike"
-                    );
-                    SpikeDetectionResult::default()
-                }
-            };
-            let spike_duration_ms = spike_start.elapsed().as_secs_f64() * 1000.0;
-
-            let topics = cluster_mentions
-                .iter()
-                .take(TOPIC_LIMIT)
-                .cloned()
-                .collect::<Vec<_>>();
-
-            results.push(ClusterWithMetrics {
-                cluster: ClusterResult {
-                    cluster_id: group.cluster_id,
-                    count: cluster_mentions.len(),
-                    examples: examples.clone(),
-                    summary,
-                    spike: spike_result.is_spike,
-                    sentiment,
-                    topics: Some(topics),
-                },
-                metrics: ClusterStageMetrics {
-                    llm_ms: llm_duration_ms,
-                    spike_ms: spike_duration_ms,
-                },
-            });
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Records whether each chunk it sees runs as a preview.
+    #[derive(Default)]
+    struct DryRunProbe(Mutex<Vec<bool>>);
+
+    #[async_trait]
+    impl PipelineStage for DryRunProbe {
+        fn name(&self) -> &str {
+            "probe"
         }
 
-        if results.is_empty() {
-            // Fallback: treat all mentions as a single cluster.
-            let examples = mentions
-                .iter()
-                .take(self.settings.preprocessing_examples)
-                .cloned()
-                .collect::<Vec<_>>();
-            results.push(ClusterWithMetrics {
-                cluster: ClusterResult {
-                    cluster_id: 1,
-                    count: mentions.len(),
-                    examples: examples.clone(),
-                    summary: examples.first().cloned(),
-                    spike: false,
-                    sentiment: HashMap::from([
-                        ("positive".to_string(), 0.33),
-                        ("negative".to_string(), 0.33),
-                        ("neutral".to_string(), 0.34),
-                    ]),
-                    topics: Some(examples),
-                },
-                metrics: ClusterStageMetrics::default(),
-            });
+        async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+            self.0.lock().expect("probe poisoned").push(ctx.job.dry_run);
+            Ok(())
         }
+    }
 
-        results
+    fn processor(probe: Arc<DryRunProbe>) -> Processor {
+        Processor::new(Arc::new(Settings::for_tests()), vec![probe])
     }
-}
 
-#[derive(Default)]
-struct ClusterStageMetrics {
-    llm_ms: f64,
-    spike_ms: f64,
-}
+    fn chunk() -> Chunk {
+        serde_json::from_value(json!({
+            "brand": "acme",
+            "chunkId": "chunk-1",
+            "createdAt": "2026-01-01T00:00:00Z",
+            "mentions": [],
+        }))
+        .expect("chunk")
+    }
 
-struct ClusterWithMetrics {
-    cluster: ClusterResult,
-    metrics: ClusterStageMetrics,
+    #[tokio::test]
+    async fn preview_runs_stages_as_dry_run() {
+        let probe = Arc::new(DryRunProbe::default());
+        let processor = processor(probe.clone());
+        processor.preview_chunk(chunk(), "unknown").await.expect("preview");
+        assert_eq!(*probe.0.lock().expect("probe poisoned"), vec![true]);
+    }
+
+    #[tokio::test]
+    async fn processing_and_reprocessing_record_state() {
+        let probe = Arc::new(DryRunProbe::default());
+        let processor = processor(probe.clone());
+        processor.process_chunk(chunk(), "unknown", 0.0).await.expect("process");
+        processor.reprocess_chunk(chunk(), "unknown", false).await.expect("reprocess");
+        assert_eq!(*probe.0.lock().expect("probe poisoned"), vec![false, false]);
+    }
 }
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::config::Settings;
+use crate::llm::{analysis_prompt, batch_analysis_prompt, sentiment_prompt, summary_prompt};
+
+/// Bumped whenever a built-in prompt in `llm.rs` changes, so cached analyses produced
+/// under the old wording are not reused.
+pub const BUILTIN_PROMPTS_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct PromptSet {
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    sentiment: Option<String>,
+}
+
+/// Summary and sentiment prompt templates from `PROMPT_TEMPLATES_FILE`:
+///
+/// ```json
+/// { "default": { "summary": "..." }, "brands": { "acme-bank": { "summary": "..." } } }
+/// ```
+///
+/// Templates may use `{brand}`, `{mentions}` (one mention per line) and `{max_tokens}`.
+/// A brand override wins over `default`, which wins over the built-in prompt. Sentiment
+/// templates must still ask for a JSON object with positive, negative and neutral scores.
+/// With `SUMMARY_LANGUAGE` set, every summary prompt also asks for that language.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PromptTemplates {
+    #[serde(default)]
+    default: PromptSet,
+    #[serde(default)]
+    brands: HashMap<String, PromptSet>,
+    #[serde(skip)]
+    summary_language: Option<String>,
+}
+
+impl PromptTemplates {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Arc<Self>> {
+        let Some(path) = &settings.prompt_templates_file else {
+            return Ok(Arc::new(Self {
+                summary_language: settings.summary_language.clone(),
+                ..Self::default()
+            }));
+        };
+        let raw = std::fs::read_to_string(path).with_context(|| format!("read prompt templates from {path}"))?;
+        let mut templates: Self =
+            serde_json::from_str(&raw).with_context(|| format!("parse prompt templates in {path}"))?;
+        templates.brands = templates
+            .brands
+            .into_iter()
+            .map(|(brand, set)| (brand.to_lowercase(), set))
+            .collect();
+        templates.summary_language = settings.summary_language.clone();
+        info!(worker_id = %settings.worker_id, brands = templates.brands.len(), "Prompt templates loaded");
+        Ok(Arc::new(templates))
+    }
+
+    pub fn summary(&self, brand: &str, texts: &[String], max_tokens: u32) -> String {
+        let prompt = match self.template(brand, |set| set.summary.as_deref()) {
+            Some(template) => render(template, brand, texts, max_tokens),
+            None => summary_prompt(texts, max_tokens),
+        };
+        self.in_language(prompt, "summary")
+    }
+
+    /// Combined summary, sentiment and topics prompt; not templated, so not used for brands
+    /// where [`Self::whole_cluster_prompts`] is false.
+    pub fn analysis(&self, texts: &[String], max_tokens: u32) -> String {
+        self.in_language(analysis_prompt(texts, max_tokens), "summary and topics")
+    }
+
+    /// Combined analysis of several clusters in one request; not templated, like `analysis`.
+    pub fn batch_analysis(&self, groups: &[Vec<String>], max_tokens: u32) -> String {
+        self.in_language(batch_analysis_prompt(groups, max_tokens), "summaries and topics")
+    }
+
+    fn in_language(&self, prompt: String, what: &str) -> String {
+        match &self.summary_language {
+            Some(language) => {
+                format!("{prompt}\nWrite the {what} in {language}, whatever language the texts are in.\n")
+            }
+            None => prompt,
+        }
+    }
+
+    pub fn sentiment(&self, brand: &str, texts: &[String]) -> String {
+        match self.template(brand, |set| set.sentiment.as_deref()) {
+            Some(template) => render(template, brand, texts, 0),
+            None => sentiment_prompt(texts),
+        }
+    }
+
+    /// Whether `brand` can use the combined and batch analysis prompts, which carry neither
+    /// a summary nor a sentiment template. Brands with either get separate calls instead.
+    pub fn whole_cluster_prompts(&self, brand: &str) -> bool {
+        self.template(brand, |set| set.summary.as_deref()).is_none() && !self.sentiment_templated(brand)
+    }
+
+    /// Whether `brand` has a sentiment template, which the batch sentiment prompt ignores.
+    pub fn sentiment_templated(&self, brand: &str) -> bool {
+        self.template(brand, |set| set.sentiment.as_deref()).is_some()
+    }
+
+    /// Everything that shapes `brand`'s prompts: its effective templates, the summary
+    /// language and the built-in prompt version.
+    pub fn fingerprint(&self, brand: &str) -> String {
+        format!(
+            "v{BUILTIN_PROMPTS_VERSION}\0{}\0{}\0{}",
+            self.template(brand, |set| set.summary.as_deref()).unwrap_or_default(),
+            self.template(brand, |set| set.sentiment.as_deref()).unwrap_or_default(),
+            self.summary_language.as_deref().unwrap_or_default(),
+        )
+    }
+
+    fn template(&self, brand: &str, pick: impl Fn(&PromptSet) -> Option<&str>) -> Option<&str> {
+        self.brands
+            .get(&brand.to_lowercase())
+            .and_then(&pick)
+            .or_else(|| pick(&self.default))
+            .filter(|template| !template.trim().is_empty())
+    }
+}
+
+fn render(template: &str, brand: &str, texts: &[String], max_tokens: u32) -> String {
+    template
+        .replace("{brand}", brand)
+        .replace("{max_tokens}", &max_tokens.to_string())
+        .replace("{mentions}", &texts.join("\n"))
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::stages::StageContext;
+
+/// Intermediate pipeline state kept for a QA-sampled chunk, so clustering and summaries
+/// can be evaluated offline against production data.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QaArtifacts {
+    pub mentions: Vec<QaMention>,
+    pub clusters: Vec<QaCluster>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QaMention {
+    pub id: String,
+    /// Preprocessed text; omitted when `EXAMPLE_REDACTION` is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QaCluster {
+    pub cluster_id: i32,
+    pub mention_ids: Vec<String>,
+    pub centroid: Vec<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling_rate: Option<f32>,
+}
+
+/// Whether `chunk_id` falls in the `percent` QA sample. Hashing the ID rather than rolling
+/// a die keeps a chunk in or out of the sample when it is reprocessed.
+pub fn sampled(chunk_id: &str, percent: f64) -> bool {
+    if percent <= 0.0 {
+        return false;
+    }
+    let digest = Sha256::digest(chunk_id.as_bytes());
+    let bucket = u16::from_be_bytes([digest[0], digest[1]]) as f64 / (u16::MAX as f64 + 1.0);
+    bucket * 100.0 < percent
+}
+
+pub fn capture(ctx: &StageContext, include_text: bool) -> QaArtifacts {
+    let ids: HashMap<&str, &str> = ctx
+        .mentions
+        .iter()
+        .map(|mention| (mention.text.as_str(), mention.source.id.as_str()))
+        .collect();
+    let mentions = ctx
+        .mentions
+        .iter()
+        .enumerate()
+        .map(|(index, mention)| QaMention {
+            id: mention.source.id.clone(),
+            text: include_text.then(|| mention.text.clone()),
+            embedding: ctx.embeddings.get(index).cloned(),
+        })
+        .collect();
+    let clusters = ctx
+        .clusters
+        .iter()
+        .map(|cluster| QaCluster {
+            cluster_id: cluster.cluster_id,
+            mention_ids: cluster
+                .mentions
+                .iter()
+                .filter_map(|text| ids.get(text.as_str()).map(|id| id.to_string()))
+                .collect(),
+            centroid: cluster.centroid.clone(),
+            sampling_rate: cluster.sampling_rate,
+        })
+        .collect();
+    QaArtifacts { mentions, clusters }
+}
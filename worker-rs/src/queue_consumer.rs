@@ -3,6 +3,7 @@ use std::time::Duration;
 use tokio::time;
 use tracing::info;
 
+use crate::error::{WorkerError, WorkerResult};
 use crate::redis_client::RedisClient;
 
 pub struct QueueConsumer {
@@ -20,14 +21,18 @@ impl QueueConsumer {
         }
     }
 
-    pub async fn fetch(&self, keys: &[String]) -> anyhow::Result<Option<(String, String, f64)>> {
+    pub async fn fetch(&self, keys: &[String]) -> WorkerResult<Option<(String, String, f64)>> {
         let start = std::time::Instant::now();
         if keys.is_empty() {
             time::sleep(self.blpop_timeout).await;
             return Ok(None);
         }
 
-        let result = self.redis.blpop(keys, self.blpop_timeout).await?;
+        let result = self
+            .redis
+            .blpop(keys, self.blpop_timeout)
+            .await
+            .map_err(WorkerError::Queue)?;
         let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
 
         if let Some((queue_key, payload)) = result {
@@ -38,11 +43,15 @@ impl QueueConsumer {
         }
     }
 
-    pub async fn scan_brand_queues(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
-        self.redis.scan_brand_queues(prefix).await
+    pub async fn peek_heads(&self, keys: &[String]) -> WorkerResult<Vec<Option<String>>> {
+        self.redis.list_heads(keys).await.map_err(WorkerError::Queue)
     }
 
-    pub async fn set_heartbeat(&self, worker_id: &str, interval: Duration) -> anyhow::Result<()> {
-        self.redis.set_heartbeat(worker_id, interval).await
+    pub async fn scan_brand_queues(&self, prefix: &str) -> WorkerResult<Vec<String>> {
+        self.redis.scan_brand_queues(prefix).await.map_err(WorkerError::Queue)
+    }
+
+    pub async fn set_heartbeat(&self, worker_id: &str, interval: Duration) -> WorkerResult<()> {
+        self.redis.set_heartbeat(worker_id, interval).await.map_err(WorkerError::Queue)
     }
 }
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+use crate::budget::BudgetKind;
+use crate::config::Settings;
+use crate::metrics::WORKER_RATE_LIMIT_WAIT_SECONDS;
+use crate::redis_client::RedisClient;
+
+/// Buckets are keyed by kind and provider and shared by every adapter in the process,
+/// so live, backfill and shadow pipelines draw from the same allowance.
+static LOCAL_BUCKETS: Lazy<Mutex<HashMap<String, Arc<Mutex<TokenBucket>>>>> = Lazy::new(Default::default);
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Refills, then takes a token or returns the wait until one is available.
+    fn take(&mut self, rate: f64, burst: f64) -> Option<Duration> {
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.updated).as_secs_f64() * rate).min(burst);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+/// Token-bucket limiter for provider calls. With `RATE_LIMIT_FLEET_WIDE` the bucket
+/// lives in Redis and is shared by every worker; if Redis is unavailable the
+/// process-local bucket is used instead.
+#[derive(Clone)]
+pub struct RateLimiter {
+    redis: Option<RedisClient>,
+    key: String,
+    rate: f64,
+    burst: u32,
+    local: Arc<Mutex<TokenBucket>>,
+    provider: String,
+    worker_id: String,
+}
+
+impl RateLimiter {
+    /// Returns `None` when no rate is configured for `kind`.
+    pub fn new(redis: RedisClient, settings: &Settings, kind: BudgetKind, provider: &str) -> Option<Self> {
+        let (rate, burst) = match kind {
+            BudgetKind::Llm => (settings.llm_rate_limit_rps?, settings.llm_rate_limit_burst),
+            BudgetKind::Embedding => (settings.embedding_rate_limit_rps?, settings.embedding_rate_limit_burst),
+        };
+        let key = format!("{}:{}:{}", settings.redis_rate_limit_prefix, kind.label(), provider);
+        let local = LOCAL_BUCKETS
+            .lock()
+            .expect("rate limit registry poisoned")
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(TokenBucket {
+                    tokens: burst as f64,
+                    updated: Instant::now(),
+                }))
+            })
+            .clone();
+        Some(Self {
+            redis: settings.rate_limit_fleet_wide.then_some(redis),
+            key,
+            rate,
+            burst,
+            local,
+            provider: provider.to_string(),
+            worker_id: settings.worker_id.clone(),
+        })
+    }
+
+    /// Waits until a call may proceed.
+    pub async fn acquire(&self) {
+        let start = Instant::now();
+        while let Some(wait) = self.take().await {
+            tokio::time::sleep(wait).await;
+        }
+        WORKER_RATE_LIMIT_WAIT_SECONDS
+            .with_label_values(&[&self.worker_id, &self.provider])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    async fn take(&self) -> Option<Duration> {
+        if let Some(redis) = &self.redis {
+            match redis.take_token(&self.key, self.rate, self.burst).await {
+                Ok(wait) => return (!wait.is_zero()).then_some(wait),
+                Err(err) => warn!(key = %self.key, error = %err, "Fleet rate limiter unavailable; using local bucket"),
+            }
+        }
+        self.local
+            .lock()
+            .expect("rate limit bucket poisoned")
+            .take(self.rate, self.burst as f64)
+    }
+}
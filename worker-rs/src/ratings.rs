@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use crate::types::{Mention, RatingSummary};
+
+/// The 1–5 rating under `key` in the mention metadata, as a number or numeric string.
+pub fn mention_rating(mention: &Mention, key: &str) -> Option<f32> {
+    let value = mention.metadata.as_ref()?.get(key)?;
+    let rating = match value {
+        serde_json::Value::Number(number) => number.as_f64()?,
+        serde_json::Value::String(text) => text.trim().parse().ok()?,
+        _ => return None,
+    };
+    (1.0..=5.0).contains(&rating).then_some(rating as f32)
+}
+
+/// Mixes the sentiment implied by star ratings into `sentiment`. Ratings count for the
+/// rated share of the cluster, scaled by `weight`, so a fully rated cluster with weight 1
+/// takes its sentiment from the stars alone.
+pub fn blend_sentiment(
+    sentiment: &HashMap<String, f32>,
+    rating: &RatingSummary,
+    mentions: usize,
+    weight: f32,
+) -> HashMap<String, f32> {
+    let share = (rating.count as f32 / mentions.max(1) as f32).min(1.0) * weight.clamp(0.0, 1.0);
+    // 1 star is fully negative, 3 neutral, 5 fully positive.
+    let polarity = (rating.average - 3.0) / 2.0;
+    let implied = [
+        ("positive", polarity.max(0.0)),
+        ("negative", (-polarity).max(0.0)),
+        ("neutral", 1.0 - polarity.abs()),
+    ];
+    implied
+        .into_iter()
+        .map(|(label, stars)| {
+            let model = sentiment.get(label).copied().unwrap_or_default();
+            (label.to_string(), model * (1.0 - share) + stars * share)
+        })
+        .collect()
+}
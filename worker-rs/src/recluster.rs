@@ -0,0 +1,297 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::archive::ChunkArchive;
+use crate::clustering::{threshold_groups, DistanceMetric};
+use crate::config::Settings;
+use crate::context::{LlmSelection, ProcessingContext};
+use crate::crypto::PayloadCipher;
+use crate::embeddings::InstrumentedEmbeddingAdapter;
+use crate::metrics::WORKER_RECLUSTER_RUNS_TOTAL;
+use crate::noise::NoiseFilter;
+use crate::redis_client::RedisClient;
+use crate::spike::cluster_fingerprint;
+use crate::stages::{clean_text, redact_pii};
+use crate::types::{Chunk, Provenance};
+use crate::wasm_hook::PreprocessHook;
+
+const LABEL_TERMS: usize = 3;
+const REPORT_TTL: Duration = Duration::from_secs(7 * 86_400);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReclusterReport {
+    pub brand: String,
+    pub generated_at: String,
+    pub worker_id: String,
+    pub window_start: String,
+    pub chunks: usize,
+    pub mentions: usize,
+    pub clusters: Vec<GlobalCluster>,
+}
+
+/// A narrative found across chunks. `id` is the cluster's term fingerprint, the same one
+/// `SPIKE_HISTORY_KEY=fingerprint` keys spike history by.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalCluster {
+    pub id: String,
+    pub label: String,
+    pub mention_count: usize,
+    pub chunk_ids: Vec<String>,
+    pub mention_ids: Vec<String>,
+}
+
+struct ArchivedMention {
+    id: String,
+    chunk_id: String,
+    text: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Per-chunk clustering splits a narrative whenever it spans chunks. Every
+/// `RECLUSTER_INTERVAL_SEC` the leader worker reads each brand's last
+/// `RECLUSTER_WINDOW_HOURS` of archived chunks, embeds their mentions and groups them in
+/// one pass. The corrected assignments are stored at `{REDIS_RECLUSTER_PREFIX}:{brand}:latest`
+/// and published on `RECLUSTER_CHANNEL`, encrypted like results.
+///
+/// Archived mentions are raw, so they go through the pipeline's WASM hook, noise filter and,
+/// when `pii_redact` is a pipeline stage, PII redaction before they are embedded or labelled.
+pub struct ReclusterJob {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    archive: ChunkArchive,
+    embeddings: InstrumentedEmbeddingAdapter,
+    cipher: Arc<PayloadCipher>,
+    noise: NoiseFilter,
+    hook: Option<PreprocessHook>,
+}
+
+impl ReclusterJob {
+    pub fn new(
+        redis: RedisClient,
+        settings: Arc<Settings>,
+        embeddings: InstrumentedEmbeddingAdapter,
+        cipher: Arc<PayloadCipher>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            archive: ChunkArchive::new(redis.clone(), settings.clone(), cipher.clone()),
+            noise: NoiseFilter::from_settings(settings.clone(), redis.clone())?,
+            hook: PreprocessHook::from_settings(&settings)?,
+            redis,
+            settings,
+            embeddings,
+            cipher,
+        })
+    }
+
+    pub async fn run(&self, mut shutdown: broadcast::Receiver<()>) {
+        if !self.settings.recluster_enabled {
+            return;
+        }
+        if !self.archive.enabled() {
+            warn!("RECLUSTER_ENABLED needs ARCHIVE_ENABLED; re-clustering job not started");
+            return;
+        }
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => {
+                    info!("Re-clustering job stopping");
+                    break;
+                }
+                _ = tokio::time::sleep(self.settings.recluster_interval) => {
+                    let key = format!("{}:leader", self.settings.redis_recluster_prefix);
+                    let lease = self.settings.recluster_interval * 2;
+                    match self.redis.acquire_lease(&key, &self.settings.worker_id, lease).await {
+                        Ok(true) => {
+                            if let Err(err) = self.run_once(Utc::now()).await {
+                                warn!(error = %err, "Re-clustering run failed");
+                            }
+                        }
+                        Ok(false) => debug!(worker_id = %self.settings.worker_id, "Re-clustering leader is another worker"),
+                        Err(err) => warn!(error = %err, "Re-clustering leader election failed"),
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn run_once(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<ReclusterReport>> {
+        let pattern = format!("{}:*:chunks", self.settings.redis_archive_prefix);
+        let prefix = format!("{}:", self.settings.redis_archive_prefix);
+        let brands: BTreeSet<String> = self
+            .redis
+            .scan_keys(&pattern)
+            .await?
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix)?.strip_suffix(":chunks").map(str::to_string))
+            .collect();
+
+        let mut reports = Vec::new();
+        for brand in brands {
+            match self.recluster_brand(&brand, now).await {
+                Ok(Some(report)) => {
+                    self.record("ok");
+                    reports.push(report);
+                }
+                Ok(None) => self.record("skipped"),
+                Err(err) => {
+                    self.record("error");
+                    warn!(brand, error = %err, "Re-clustering brand failed");
+                }
+            }
+        }
+        Ok(reports)
+    }
+
+    async fn recluster_brand(&self, brand: &str, now: DateTime<Utc>) -> anyhow::Result<Option<ReclusterReport>> {
+        let window_start = now - chrono::Duration::from_std(self.settings.recluster_window)?;
+        let payloads = self.archive.load_range(brand, window_start, now).await?;
+        let chunks = payloads.len();
+        let mentions = self.mentions(brand, payloads).await?;
+        if mentions.len() < 2 {
+            return Ok(None);
+        }
+
+        let texts: Vec<String> = mentions.iter().map(|mention| mention.text.clone()).collect();
+        let job = ProcessingContext {
+            brand: brand.to_string(),
+            chunk_id: format!("recluster:{}", now.timestamp()),
+            correlation_id: format!("recluster:{brand}:{}", now.timestamp()),
+            deadline: None,
+            priority: None,
+            reuse_cached: false,
+            llm: LlmSelection::Heuristic,
+            llm_override: None,
+            dry_run: false,
+        };
+        let mut provenance = Provenance::default();
+        let embeddings = self.embeddings.embed(&texts, &job, &mut provenance).await;
+        if provenance.degraded() {
+            // Fallback vectors would regroup the brand on a different basis than its chunks.
+            warn!(brand, fallbacks = ?provenance.fallbacks, "Embeddings degraded; skipping re-clustering");
+            return Ok(None);
+        }
+
+        let metric = DistanceMetric::from_settings(&self.settings);
+        let threshold = self.settings.recluster_similarity_threshold;
+        // Up to `RECLUSTER_MAX_MENTIONS` vectors compared pairwise; keep it off the async workers.
+        let groups = tokio::task::spawn_blocking(move || threshold_groups(&embeddings, threshold, metric))
+            .await
+            .context("re-clustering task")?;
+        let clusters: Vec<GlobalCluster> = groups
+            .into_iter()
+            .map(|group| {
+                let members: Vec<&ArchivedMention> = group.indices.iter().map(|&idx| &mentions[idx]).collect();
+                let texts: Vec<String> = members.iter().map(|mention| mention.text.clone()).collect();
+                let chunk_ids: BTreeSet<String> = members.iter().map(|mention| mention.chunk_id.clone()).collect();
+                GlobalCluster {
+                    id: cluster_fingerprint(&texts),
+                    label: cluster_label(&texts),
+                    mention_count: members.len(),
+                    chunk_ids: chunk_ids.into_iter().collect(),
+                    mention_ids: members.iter().map(|mention| mention.id.clone()).collect(),
+                }
+            })
+            .collect();
+
+        let report = ReclusterReport {
+            brand: brand.to_string(),
+            generated_at: now.to_rfc3339(),
+            worker_id: self.settings.worker_id.clone(),
+            window_start: window_start.to_rfc3339(),
+            chunks,
+            mentions: mentions.len(),
+            clusters,
+        };
+        let payload = serde_json::to_string(&report).context("serialise re-clustering report")?;
+        let payload = self.cipher.encrypt(&payload)?;
+        let key = format!("{}:{brand}:latest", self.settings.redis_recluster_prefix);
+        self.redis.set_with_ttl(&key, &payload, REPORT_TTL).await?;
+        self.redis.publish(&self.settings.recluster_channel, &payload).await?;
+        info!(
+            worker_id = %self.settings.worker_id,
+            brand,
+            chunks,
+            mentions = report.mentions,
+            clusters = report.clusters.len(),
+            "Re-clustering run completed"
+        );
+        Ok(Some(report))
+    }
+
+    /// Distinct mentions from the archived chunks, preprocessed as for per-chunk clustering
+    /// and capped at the `RECLUSTER_MAX_MENTIONS` most recent. A failing hook fails the run
+    /// rather than letting unredacted text through.
+    async fn mentions(&self, brand: &str, payloads: Vec<String>) -> anyhow::Result<Vec<ArchivedMention>> {
+        let noise = self.noise.list(brand).await;
+        let mut session = self.hook.as_ref().map(PreprocessHook::session).transpose()?;
+        let redact = self.settings.pipeline_stages.iter().any(|stage| stage == "pii_redact");
+        let mut seen = HashSet::new();
+        let mut mentions = Vec::new();
+        for payload in payloads {
+            let chunk: Chunk = match serde_json::from_str(&payload) {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    warn!(brand, error = %err, "Skipping archived chunk that could not be decoded");
+                    continue;
+                }
+            };
+            for mention in chunk.mentions {
+                let raw = match session.as_mut() {
+                    Some(session) => match session.apply(brand, &mention)? {
+                        Some(text) => text,
+                        None => continue,
+                    },
+                    None => mention.text.clone(),
+                };
+                let text = noise.strip(&clean_text(&raw));
+                if text.is_empty() || !seen.insert(mention.id.clone()) {
+                    continue;
+                }
+                let text = if redact { redact_pii(&text) } else { text };
+                mentions.push(ArchivedMention {
+                    id: mention.id,
+                    chunk_id: chunk.chunk_id.clone(),
+                    text,
+                    created_at: mention.created_at,
+                });
+            }
+        }
+        mentions.sort_by_key(|mention| Reverse(mention.created_at));
+        mentions.truncate(self.settings.recluster_max_mentions);
+        Ok(mentions)
+    }
+
+    fn record(&self, outcome: &str) {
+        WORKER_RECLUSTER_RUNS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, outcome])
+            .inc();
+    }
+}
+
+/// The cluster's most frequent terms of four or more letters.
+fn cluster_label(texts: &[String]) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for text in texts {
+        for term in text.split(|c: char| !c.is_alphanumeric()).filter(|term| term.chars().count() >= 4) {
+            *counts.entry(term).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(&str, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked
+        .into_iter()
+        .take(LABEL_TERMS)
+        .map(|(term, _)| term)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::clustering::DistanceMetric;
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::redis_client::RedisClient;
+use crate::types::ClusterReference;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmittedCluster {
+    pub chunk_id: String,
+    pub cluster_id: i32,
+    pub count: usize,
+    pub summary: Option<String>,
+    pub centroid: Vec<f32>,
+    pub emitted_at: i64,
+    #[serde(default)]
+    pub stable_id: Option<String>,
+}
+
+impl EmittedCluster {
+    pub fn new(chunk_id: &str, cluster_id: i32, count: usize, summary: Option<String>, centroid: Vec<f32>, stable_id: String) -> Self {
+        Self {
+            chunk_id: chunk_id.to_string(),
+            cluster_id,
+            count,
+            summary,
+            centroid,
+            emitted_at: Utc::now().timestamp(),
+            stable_id: Some(stable_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurringMatch {
+    pub reference: ClusterReference,
+    pub summary: Option<String>,
+    pub stable_id: String,
+}
+
+/// Recently emitted clusters per brand, sealed like other payloads since they carry
+/// summaries.
+pub struct RecurrenceDetector {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+}
+
+impl RecurrenceDetector {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, cipher: Arc<PayloadCipher>) -> Self {
+        Self {
+            redis,
+            settings,
+            cipher,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.cluster_recurrence_enabled
+    }
+
+    pub async fn recent(&self, brand: &str) -> anyhow::Result<Vec<EmittedCluster>> {
+        let entries = self.redis.lrange(&self.key(brand), 0, -1).await?;
+        Ok(entries
+            .iter()
+            .filter_map(|entry| self.cipher.decrypt(entry).ok())
+            .filter_map(|entry| serde_json::from_str(&entry).ok())
+            .collect())
+    }
+
+    pub fn find_match(&self, recent: &[EmittedCluster], centroid: &[f32], count: usize) -> Option<RecurringMatch> {
+        let max_count = |prior: usize| prior as f32 * (1.0 + self.settings.cluster_recurrence_growth);
+        let metric = DistanceMetric::from_settings(&self.settings);
+        recent
+            .iter()
+            .map(|prior| (prior, metric.similarity(&prior.centroid, centroid)))
+            .filter(|(prior, similarity)| {
+                *similarity >= self.settings.cluster_recurrence_threshold && count as f32 <= max_count(prior.count)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(prior, similarity)| RecurringMatch {
+                reference: ClusterReference {
+                    chunk_id: prior.chunk_id.clone(),
+                    cluster_id: prior.cluster_id,
+                    similarity,
+                },
+                summary: prior.summary.clone(),
+                // Entries written before stable IDs existed start a narrative at themselves.
+                stable_id: prior
+                    .stable_id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}:{}", prior.chunk_id, prior.cluster_id)),
+            })
+    }
+
+    pub async fn remember(&self, brand: &str, entry: &EmittedCluster) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(entry).context("serialise cluster centroid")?;
+        let payload = self.cipher.encrypt(&payload)?;
+        self.redis
+            .lpush_capped(
+                &self.key(brand),
+                &payload,
+                self.settings.cluster_recurrence_cache_size,
+                self.settings.centroid_ttl,
+            )
+            .await
+    }
+
+    fn key(&self, brand: &str) -> String {
+        format!("{}:{}", self.settings.redis_centroid_prefix, brand)
+    }
+}
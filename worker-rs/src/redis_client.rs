@@ -1,22 +1,25 @@
 use std::time::Duration;
 
 use anyhow::Context;
-use redis::{aio::ConnectionManager, Client};
+use redis::aio::{ConnectionManager, PubSub};
+use redis::Client;
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 #[derive(Clone)]
 pub struct RedisClient {
+    client: Client,
     inner: std::sync::Arc<Mutex<ConnectionManager>>,
 }
 
 impl RedisClient {
     pub async fn new(url: &str) -> anyhow::Result<Self> {
         let client = Client::open(url.to_string()).context("Failed to create Redis client")?;
-        let manager = ConnectionManager::new(client)
+        let manager = ConnectionManager::new(client.clone())
             .await
             .context("Failed to create Redis connection manager")?;
         Ok(Self {
+            client,
             inner: std::sync::Arc::new(Mutex::new(manager)),
         })
     }
@@ -29,6 +32,19 @@ impl RedisClient {
             .context("Redis PING failed")
     }
 
+    /// Opens a dedicated connection subscribed to `channel`; pub/sub cannot share the
+    /// managed connection.
+    pub async fn subscribe(&self, channel: &str) -> anyhow::Result<PubSub> {
+        let mut pubsub = self
+            .client
+            .get_async_connection()
+            .await
+            .context("Failed to open Redis pub/sub connection")?
+            .into_pubsub();
+        pubsub.subscribe(channel).await.context("Redis SUBSCRIBE failed")?;
+        Ok(pubsub)
+    }
+
     pub async fn blpop(&self, keys: &[String], timeout: Duration) -> anyhow::Result<Option<(String, String)>> {
         if keys.is_empty() {
             sleep(timeout).await;
@@ -61,7 +77,13 @@ impl RedisClient {
     }
 
     pub async fn scan_brand_queues(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
-        let pattern = format!("{prefix}:*:chunks");
+        let mut results = self.scan_keys(&format!("{prefix}:*:chunks")).await?;
+        results.sort();
+        results.dedup();
+        Ok(results)
+    }
+
+    pub async fn scan_keys(&self, pattern: &str) -> anyhow::Result<Vec<String>> {
         let mut cursor: u64 = 0;
         let mut results: Vec<String> = Vec::new();
         loop {
@@ -69,7 +91,7 @@ impl RedisClient {
             let (next, chunk): (u64, Vec<String>) = redis::cmd("SCAN")
                 .arg(cursor)
                 .arg("MATCH")
-                .arg(&pattern)
+                .arg(pattern)
                 .arg("COUNT")
                 .arg(100)
                 .query_async(&mut *conn)
@@ -82,8 +104,6 @@ impl RedisClient {
             }
             cursor = next;
         }
-        results.sort();
-        results.dedup();
         Ok(results)
     }
 
@@ -101,41 +121,572 @@ impl RedisClient {
             .context("Redis heartbeat SET failed")
     }
 
-    pub async fn get_spike_history(&self, prefix: &str, brand: &str, cluster_id: i32) -> anyhow::Result<Vec<i64>> {
-        let key = format!("{prefix}:{brand}:{cluster_id}");
-        let mut conn = self.inner.lock().await;
-        let history: Vec<String> = redis::cmd("LRANGE")
-            .arg(&key)
-            .arg(0)
-            .arg(-1)
-            .query_async(&mut *conn)
-            .await
-            .context("Redis LRANGE failed for spike history")?;
+    /// `cluster` is the cluster number or fingerprint the history is kept under.
+    pub async fn spike_history(&self, prefix: &str, brand: &str, cluster: &str) -> anyhow::Result<Vec<i64>> {
+        let history = self.lrange(&format!("{prefix}:{brand}:{cluster}"), 0, -1).await?;
         Ok(history
             .into_iter()
             .filter_map(|value| value.parse::<i64>().ok())
             .collect())
     }
 
-    pub async fn append_spike_history(
+    /// Appends `value` to the spike history and returns the entries that preceded it.
+    /// Read and append run as one script so concurrent workers never see the same
+    /// history for a brand twice or count their own sample in the baseline.
+    pub async fn push_spike_history(
         &self,
         prefix: &str,
         brand: &str,
-        cluster_id: i32,
+        cluster: &str,
         value: i64,
         ttl: Duration,
+    ) -> anyhow::Result<Vec<i64>> {
+        let script = redis::Script::new(
+            r"
+            local history = redis.call('LRANGE', KEYS[1], 0, -1)
+            redis.call('LPUSH', KEYS[1], ARGV[1])
+            redis.call('LTRIM', KEYS[1], 0, 99)
+            redis.call('EXPIRE', KEYS[1], ARGV[2])
+            return history
+            ",
+        );
+        let key = format!("{prefix}:{brand}:{cluster}");
+        let mut conn = self.inner.lock().await;
+        let history: Vec<String> = script
+            .key(&key)
+            .arg(value)
+            .arg(ttl.as_secs())
+            .invoke_async(&mut *conn)
+            .await
+            .context("Redis spike history script failed")?;
+        Ok(history
+            .into_iter()
+            .filter_map(|value| value.parse::<i64>().ok())
+            .collect())
+    }
+
+    /// Claims each key for `owner` with `ttl` unless a different owner holds it, returning
+    /// the positions of keys held by someone else.
+    pub async fn claim_keys(&self, keys: &[String], owner: &str, ttl: Duration) -> anyhow::Result<Vec<usize>> {
+        let script = redis::Script::new(
+            r"
+            local taken = {}
+            for index, key in ipairs(KEYS) do
+                local holder = redis.call('GET', key)
+                if holder and holder ~= ARGV[1] then
+                    taken[#taken + 1] = index - 1
+                else
+                    redis.call('SET', key, ARGV[1], 'EX', ARGV[2])
+                end
+            end
+            return taken
+            ",
+        );
+        let mut invocation = script.prepare_invoke();
+        for key in keys {
+            invocation.key(key);
+        }
+        invocation.arg(owner).arg(ttl.as_secs().max(1));
+        let mut conn = self.inner.lock().await;
+        invocation
+            .invoke_async(&mut *conn)
+            .await
+            .context("Redis claim script failed")
+    }
+
+    pub async fn zadd_with_ttl(&self, key: &str, score: i64, member: &str, ttl: Duration) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        pipe.cmd("ZADD").arg(key).arg(score).arg(member).ignore();
+        pipe.cmd("EXPIRE").arg(key).arg(ttl.as_secs() as usize).ignore();
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis ZADD failed")
+    }
+
+    /// Scores `member` in the `index` sorted set and stores `payload` for it in the `payloads`
+    /// hash, replacing any earlier payload. Members scored before `retention` ago are removed
+    /// from both first; a member already that old is not added.
+    pub async fn zadd_hash_trimmed(
+        &self,
+        index: &str,
+        payloads: &str,
+        member: &str,
+        score: i64,
+        payload: &str,
+        retention: Duration,
     ) -> anyhow::Result<()> {
-        let key = format!("{prefix}:{brand}:{cluster_id}");
+        let script = redis::Script::new(
+            r"
+            local cutoff = tonumber(ARGV[3])
+            local expired = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', '(' .. cutoff)
+            for start = 1, #expired, 500 do
+                local batch = {unpack(expired, start, math.min(start + 499, #expired))}
+                redis.call('ZREM', KEYS[1], unpack(batch))
+                redis.call('HDEL', KEYS[2], unpack(batch))
+            end
+            if tonumber(ARGV[2]) < cutoff then
+                return 0
+            end
+            redis.call('ZADD', KEYS[1], ARGV[2], ARGV[1])
+            redis.call('HSET', KEYS[2], ARGV[1], ARGV[4])
+            redis.call('EXPIRE', KEYS[1], ARGV[5])
+            redis.call('EXPIRE', KEYS[2], ARGV[5])
+            return 1
+            ",
+        );
+        let cutoff = chrono::Utc::now().timestamp() - retention.as_secs() as i64;
+        let mut conn = self.inner.lock().await;
+        script
+            .key(index)
+            .key(payloads)
+            .arg(member)
+            .arg(score)
+            .arg(cutoff)
+            .arg(payload)
+            .arg(retention.as_secs().max(1))
+            .invoke_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis archive script failed")
+    }
+
+    /// Adds each weight to its member's score and refreshes the key's TTL.
+    pub async fn zincr_many_with_ttl(&self, key: &str, members: &[(String, f64)], ttl: Duration) -> anyhow::Result<()> {
         let mut conn = self.inner.lock().await;
         let mut pipe = redis::pipe();
-        pipe.cmd("LPUSH").arg(&key).arg(value).ignore();
-        pipe.cmd("LTRIM").arg(&key).arg(0).arg(99).ignore();
-        pipe.cmd("EXPIRE")
-            .arg(&key)
+        for (member, weight) in members {
+            pipe.cmd("ZINCRBY").arg(key).arg(*weight).arg(member).ignore();
+        }
+        pipe.cmd("EXPIRE").arg(key).arg(ttl.as_secs() as usize).ignore();
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis ZINCRBY failed")
+    }
+
+    /// Sets each field of the hash at `key` and refreshes its TTL.
+    pub async fn hset_many_with_ttl(&self, key: &str, fields: &[(String, String)], ttl: Duration) -> anyhow::Result<()> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        let cmd = pipe.cmd("HSET").arg(key);
+        for (field, value) in fields {
+            cmd.arg(field).arg(value);
+        }
+        cmd.ignore();
+        pipe.cmd("EXPIRE").arg(key).arg(ttl.as_secs() as usize).ignore();
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis HSET failed")
+    }
+
+    /// Values of `fields` in each hash, in `keys` order; absent fields are `None`.
+    pub async fn hmget_many(&self, keys: &[String], fields: &[String]) -> anyhow::Result<Vec<Vec<Option<String>>>> {
+        if keys.is_empty() || fields.is_empty() {
+            return Ok(vec![vec![None; fields.len()]; keys.len()]);
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("HMGET").arg(key).arg(fields);
+        }
+        pipe.query_async(&mut *conn).await.context("Redis HMGET failed")
+    }
+
+    /// Every member and score of each sorted set, in `keys` order; missing keys come back empty.
+    pub async fn zrange_all_with_scores(&self, keys: &[String]) -> anyhow::Result<Vec<Vec<(String, f64)>>> {
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("ZRANGE").arg(key).arg(0).arg(-1).arg("WITHSCORES");
+        }
+        pipe.query_async(&mut *conn).await.context("Redis ZRANGE failed")
+    }
+
+    /// Scores of `members` in each sorted set, in `keys` order; absent members are `None`.
+    pub async fn zmscore_many(&self, keys: &[String], members: &[String]) -> anyhow::Result<Vec<Vec<Option<f64>>>> {
+        if keys.is_empty() || members.is_empty() {
+            return Ok(vec![vec![None; members.len()]; keys.len()]);
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("ZMSCORE").arg(key).arg(members);
+        }
+        pipe.query_async(&mut *conn).await.context("Redis ZMSCORE failed")
+    }
+
+    pub async fn zrange_by_score(&self, key: &str, min: i64, max: i64) -> anyhow::Result<Vec<String>> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("ZRANGEBYSCORE")
+            .arg(key)
+            .arg(min)
+            .arg(max)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis ZRANGEBYSCORE failed")
+    }
+
+    pub async fn incr_by_float_with_ttl(&self, key: &str, amount: f64, ttl: Duration) -> anyhow::Result<f64> {
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        pipe.cmd("INCRBYFLOAT").arg(key).arg(amount);
+        pipe.cmd("EXPIRE").arg(key).arg(ttl.as_secs() as usize).ignore();
+        let (value,): (f64,) = pipe
+            .query_async(&mut *conn)
+            .await
+            .context("Redis INCRBYFLOAT failed")?;
+        Ok(value)
+    }
+
+    pub async fn get_f64(&self, key: &str) -> anyhow::Result<Option<f64>> {
+        let mut conn = self.inner.lock().await;
+        let value: Option<String> = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis GET failed")?;
+        Ok(value.and_then(|raw| raw.parse::<f64>().ok()))
+    }
+
+    pub async fn get_string(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let mut conn = self.inner.lock().await;
+        let value: Option<String> = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis GET failed")?;
+        Ok(value)
+    }
+
+    pub async fn set_with_ttl(&self, key: &str, value: &str, ttl: Duration) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("EX")
             .arg(ttl.as_secs() as usize)
-            .ignore();
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis SET failed")?;
+        Ok(())
+    }
+
+    pub async fn get_many(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.inner.lock().await;
+        redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis MGET failed")
+    }
+
+    pub async fn set_many_with_ttl(&self, entries: &[(String, String)], ttl: Duration) -> anyhow::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for (key, value) in entries {
+            pipe.cmd("SET").arg(key).arg(value).arg("EX").arg(ttl.as_secs() as usize).ignore();
+        }
         pipe.query_async::<_, ()>(&mut *conn)
             .await
-            .context("Redis pipeline failed for spike history")
+            .context("Redis SET pipeline failed")
+    }
+
+    pub async fn set_nx_with_ttl(&self, key: &str, value: &str, ttl: Duration) -> anyhow::Result<bool> {
+        let mut conn = self.inner.lock().await;
+        let result: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs() as usize)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis SET NX failed")?;
+        Ok(result.is_some())
+    }
+
+    /// Takes or renews a lease held under `key`; `false` while another holder owns it.
+    pub async fn acquire_lease(&self, key: &str, holder: &str, ttl: Duration) -> anyhow::Result<bool> {
+        if self.set_nx_with_ttl(key, holder, ttl).await? {
+            return Ok(true);
+        }
+        if self.get_string(key).await?.as_deref() == Some(holder) {
+            self.set_with_ttl(key, holder, ttl).await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    pub async fn hset_nx(&self, key: &str, field: &str, value: &str) -> anyhow::Result<bool> {
+        let mut conn = self.inner.lock().await;
+        let created: i64 = redis::cmd("HSETNX")
+            .arg(key)
+            .arg(field)
+            .arg(value)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis HSETNX failed")?;
+        Ok(created == 1)
+    }
+
+    pub async fn hset_multiple(&self, key: &str, fields: &[(&str, String)]) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        let mut cmd = redis::cmd("HSET");
+        cmd.arg(key);
+        for (field, value) in fields {
+            cmd.arg(*field).arg(value);
+        }
+        cmd.query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis HSET failed")?;
+        Ok(())
+    }
+
+    pub async fn hget(&self, key: &str, field: &str) -> anyhow::Result<Option<String>> {
+        let mut conn = self.inner.lock().await;
+        let value: Option<String> = redis::cmd("HGET")
+            .arg(key)
+            .arg(field)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis HGET failed")?;
+        Ok(value)
+    }
+
+    /// Values of `fields` in order; missing fields come back as `None`.
+    pub async fn hmget(&self, key: &str, fields: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        if fields.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.inner.lock().await;
+        redis::cmd("HMGET")
+            .arg(key)
+            .arg(fields)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis HMGET failed")
+    }
+
+    pub async fn hkeys(&self, key: &str) -> anyhow::Result<Vec<String>> {
+        let mut conn = self.inner.lock().await;
+        let fields: Vec<String> = redis::cmd("HKEYS")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis HKEYS failed")?;
+        Ok(fields)
+    }
+
+    pub async fn hdel(&self, key: &str, fields: &[String]) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("HDEL")
+            .arg(key)
+            .arg(fields)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis HDEL failed")?;
+        Ok(())
+    }
+
+    pub async fn ltrim(&self, key: &str, start: isize, stop: isize) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("LTRIM")
+            .arg(key)
+            .arg(start)
+            .arg(stop)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis LTRIM failed")?;
+        Ok(())
+    }
+
+    pub async fn zrange_by_score_with_scores(&self, key: &str, min: i64, max: i64) -> anyhow::Result<Vec<(String, i64)>> {
+        let mut conn = self.inner.lock().await;
+        let entries: Vec<(String, f64)> = redis::cmd("ZRANGEBYSCORE")
+            .arg(key)
+            .arg(min)
+            .arg(max)
+            .arg("WITHSCORES")
+            .query_async(&mut *conn)
+            .await
+            .context("Redis ZRANGEBYSCORE failed")?;
+        Ok(entries.into_iter().map(|(member, score)| (member, score as i64)).collect())
+    }
+
+    pub async fn zrem_range_by_score_count(&self, key: &str, min: i64, max: i64) -> anyhow::Result<usize> {
+        let mut conn = self.inner.lock().await;
+        let removed: usize = redis::cmd("ZREMRANGEBYSCORE")
+            .arg(key)
+            .arg(min)
+            .arg(max)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis ZREMRANGEBYSCORE failed")?;
+        Ok(removed)
+    }
+
+    pub async fn idle_seconds(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let mut conn = self.inner.lock().await;
+        let idle: Option<u64> = redis::cmd("OBJECT")
+            .arg("IDLETIME")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis OBJECT IDLETIME failed")?;
+        Ok(idle)
+    }
+
+    pub async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("DEL")
+            .arg(key)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis DEL failed")?;
+        Ok(())
+    }
+
+    /// Appends one entry to a stream, trimming entries older than `min_id` (a millisecond
+    /// timestamp) approximately, as Redis does cheaply.
+    pub async fn xadd_trimmed(&self, key: &str, min_id: i64, fields: &[(&str, String)]) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(key).arg("MINID").arg("~").arg(min_id).arg("*");
+        for (field, value) in fields {
+            cmd.arg(*field).arg(value);
+        }
+        cmd.query_async::<_, String>(&mut *conn)
+            .await
+            .context("Redis XADD failed")?;
+        Ok(())
+    }
+
+    pub async fn publish(&self, channel: &str, message: &str) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("PUBLISH")
+            .arg(channel)
+            .arg(message)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis PUBLISH failed")
+    }
+
+    pub async fn zrem_range_by_score(&self, key: &str, min: i64, max: i64) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("ZREMRANGEBYSCORE")
+            .arg(key)
+            .arg(min)
+            .arg(max)
+            .query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis ZREMRANGEBYSCORE failed")
+    }
+
+    pub async fn lrange(&self, key: &str, start: isize, stop: isize) -> anyhow::Result<Vec<String>> {
+        let mut conn = self.inner.lock().await;
+        redis::cmd("LRANGE")
+            .arg(key)
+            .arg(start)
+            .arg(stop)
+            .query_async(&mut *conn)
+            .await
+            .context("Redis LRANGE failed")
+    }
+
+    pub async fn lpush_capped(&self, key: &str, value: &str, max_len: usize, ttl: Duration) -> anyhow::Result<()> {
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        pipe.cmd("LPUSH").arg(key).arg(value).ignore();
+        pipe.cmd("LTRIM").arg(key).arg(0).arg(max_len.saturating_sub(1)).ignore();
+        pipe.cmd("EXPIRE").arg(key).arg(ttl.as_secs() as usize).ignore();
+        pipe.query_async::<_, ()>(&mut *conn)
+            .await
+            .context("Redis capped LPUSH failed")
+    }
+
+    pub async fn list_heads(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("LINDEX").arg(key).arg(0);
+        }
+        pipe.query_async(&mut *conn)
+            .await
+            .context("Redis LINDEX pipeline failed")
+    }
+
+    pub async fn memory_usage(&self, keys: &[String]) -> anyhow::Result<Vec<Option<u64>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("MEMORY").arg("USAGE").arg(key);
+        }
+        pipe.query_async(&mut *conn)
+            .await
+            .context("Redis MEMORY USAGE pipeline failed")
+    }
+
+    pub async fn lengths(&self, command: &str, keys: &[String]) -> anyhow::Result<Vec<u64>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.inner.lock().await;
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd(command).arg(key);
+        }
+        pipe.query_async(&mut *conn)
+            .await
+            .with_context(|| format!("Redis {command} pipeline failed"))
+    }
+
+    /// Takes one token from the bucket at `key`, returning how long to wait before
+    /// retrying when it is empty. Refill and take happen atomically in Redis.
+    pub async fn take_token(&self, key: &str, rate_per_sec: f64, burst: u32) -> anyhow::Result<Duration> {
+        let script = redis::Script::new(
+            r"
+            local rate = tonumber(ARGV[1])
+            local burst = tonumber(ARGV[2])
+            local now = tonumber(ARGV[3])
+            local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+            local tokens = tonumber(state[1]) or burst
+            local ts = tonumber(state[2]) or now
+            tokens = math.min(burst, tokens + math.max(0, now - ts) * rate / 1000)
+            local wait = 0
+            if tokens >= 1 then
+                tokens = tokens - 1
+            else
+                wait = math.ceil((1 - tokens) * 1000 / rate)
+            end
+            redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+            redis.call('PEXPIRE', KEYS[1], math.ceil(burst * 1000 / rate) + 1000)
+            return wait
+            ",
+        );
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let mut conn = self.inner.lock().await;
+        let wait_ms: u64 = script
+            .key(key)
+            .arg(rate_per_sec)
+            .arg(burst)
+            .arg(now_ms)
+            .invoke_async(&mut *conn)
+            .await
+            .context("Redis token bucket script failed")?;
+        Ok(Duration::from_millis(wait_ms))
     }
 }
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use tracing::info;
+
+use crate::config::Settings;
+use crate::context::ProcessingContext;
+use crate::http::HttpClient;
+use crate::llm::{build_llm_adapter, InstrumentedLlmAdapter};
+use crate::redis_client::RedisClient;
+use crate::types::Provenance;
+
+/// Context words that settle whether a mention is about the brand, e.g. for `apple`:
+/// `{"include": ["iphone", "macbook"], "exclude": ["pie", "orchard"]}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RelevanceTerms {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl RelevanceTerms {
+    /// 1 with an include term, 0 with only exclude terms, `None` when neither appears.
+    fn score(&self, text: &str) -> Option<f32> {
+        let lowered = text.to_lowercase();
+        let hit = |terms: &[String]| terms.iter().any(|term| lowered.contains(term.as_str()));
+        if hit(&self.include) {
+            Some(1.0)
+        } else if hit(&self.exclude) {
+            Some(0.0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Scores how likely each mention is about the brand rather than another sense of its
+/// name. Terms from `RELEVANCE_TERMS_FILE` (a JSON object keyed by brand) decide clear
+/// cases; with `RELEVANCE_MODE=llm` the remaining ones go to the LLM provider in batches
+/// of `RELEVANCE_BATCH_SIZE`. Mentions nothing decides count as relevant.
+pub struct RelevanceClassifier {
+    terms: HashMap<String, RelevanceTerms>,
+    llm: Option<InstrumentedLlmAdapter>,
+    settings: Arc<Settings>,
+}
+
+impl RelevanceClassifier {
+    pub fn from_settings(settings: &Arc<Settings>, redis: &RedisClient, http: &HttpClient) -> anyhow::Result<Self> {
+        let terms = match &settings.relevance_terms_file {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path).with_context(|| format!("read relevance terms from {path}"))?;
+                serde_json::from_str::<HashMap<String, RelevanceTerms>>(&raw)
+                    .with_context(|| format!("parse relevance terms in {path}"))?
+                    .into_iter()
+                    .map(|(brand, mut terms)| {
+                        for list in [&mut terms.include, &mut terms.exclude] {
+                            *list = list
+                                .iter()
+                                .map(|term| term.trim().to_lowercase())
+                                .filter(|term| !term.is_empty())
+                                .collect();
+                        }
+                        (brand.to_lowercase(), terms)
+                    })
+                    .collect()
+            }
+            None => HashMap::new(),
+        };
+        if !terms.is_empty() {
+            info!(worker_id = %settings.worker_id, brands = terms.len(), "Relevance terms loaded");
+        }
+        let llm = match settings.relevance_mode.as_str() {
+            "keyword" => None,
+            "llm" => Some(build_llm_adapter(settings, redis, http)?),
+            other => bail!("unknown RELEVANCE_MODE: {other}"),
+        };
+        Ok(Self {
+            terms,
+            llm,
+            settings: settings.clone(),
+        })
+    }
+
+    pub async fn classify(&self, job: &ProcessingContext, texts: &[String], provenance: &mut Provenance) -> Vec<f32> {
+        let terms = self.terms.get(&job.brand.to_lowercase());
+        let mut scores: Vec<Option<f32>> = texts
+            .iter()
+            .map(|text| terms.and_then(|terms| terms.score(text)))
+            .collect();
+
+        // A heuristic-only chunk skips the LLM rather than asking the heuristic fallback.
+        if let Some(llm) = self.llm.as_ref().filter(|_| !job.heuristic_only()) {
+            let undecided: Vec<usize> = (0..texts.len()).filter(|&idx| scores[idx].is_none()).collect();
+            for batch in undecided.chunks(self.settings.relevance_batch_size.max(1)) {
+                let batch_texts: Vec<String> = batch.iter().map(|&idx| texts[idx].clone()).collect();
+                let Some(batch_scores) = llm.relevance(job, &batch_texts, provenance).await else {
+                    continue;
+                };
+                for (&idx, score) in batch.iter().zip(batch_scores) {
+                    scores[idx] = Some(score);
+                }
+            }
+        }
+        scores.into_iter().map(|score| score.unwrap_or(1.0)).collect()
+    }
+}
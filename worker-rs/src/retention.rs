@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::config::Settings;
+use crate::metrics::WORKER_RETENTION_ACTIONS_TOTAL;
+use crate::redis_client::RedisClient;
+
+const AUDIT_LOG_LENGTH: usize = 500;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    pub worker_id: String,
+    pub started_at: String,
+    pub results_trimmed: usize,
+    pub archive_entries_removed: usize,
+    pub rollup_days_written: usize,
+    pub rollup_days_expired: usize,
+    pub spike_histories_deleted: usize,
+}
+
+/// Periodic retention run. Only the worker holding the leader lock does the work,
+/// and every run is appended to an audit list so deletions can be traced.
+pub struct RetentionJob {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl RetentionJob {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Self {
+        Self { redis, settings }
+    }
+
+    pub async fn run(&self, mut shutdown: broadcast::Receiver<()>) {
+        if !self.settings.retention_enabled {
+            return;
+        }
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => {
+                    info!("Retention job stopping");
+                    break;
+                }
+                _ = tokio::time::sleep(self.settings.retention_interval) => {
+                    match self.acquire_leadership().await {
+                        Ok(true) => {
+                            if let Err(err) = self.run_once().await {
+                                warn!(error = %err, "Retention run failed");
+                            }
+                        }
+                        Ok(false) => debug!(worker_id = %self.settings.worker_id, "Retention leader is another worker"),
+                        Err(err) => warn!(error = %err, "Retention leader election failed"),
+                    }
+                }
+            }
+        }
+    }
+
+    async fn acquire_leadership(&self) -> anyhow::Result<bool> {
+        let key = format!("{}:leader", self.settings.redis_retention_prefix);
+        self.redis
+            .acquire_lease(&key, &self.settings.worker_id, self.settings.retention_interval * 2)
+            .await
+    }
+
+    pub async fn run_once(&self) -> anyhow::Result<RetentionReport> {
+        let now = Utc::now();
+        let mut report = RetentionReport {
+            worker_id: self.settings.worker_id.clone(),
+            started_at: now.to_rfc3339(),
+            ..Default::default()
+        };
+
+        self.trim_results(&mut report).await?;
+        self.trim_archive(now, &mut report).await?;
+        self.roll_up_trends(now, &mut report).await?;
+        self.expire_spike_histories(&mut report).await?;
+
+        let audit_key = format!("{}:audit", self.settings.redis_retention_prefix);
+        let entry = serde_json::to_string(&report).context("serialise retention report")?;
+        self.redis
+            .lpush_capped(&audit_key, &entry, AUDIT_LOG_LENGTH, Duration::from_secs(90 * 86_400))
+            .await?;
+
+        info!(
+            worker_id = %self.settings.worker_id,
+            results_trimmed = report.results_trimmed,
+            archive_entries_removed = report.archive_entries_removed,
+            rollup_days_written = report.rollup_days_written,
+            rollup_days_expired = report.rollup_days_expired,
+            spike_histories_deleted = report.spike_histories_deleted,
+            "Retention run completed"
+        );
+        Ok(report)
+    }
+
+    async fn trim_results(&self, report: &mut RetentionReport) -> anyhow::Result<()> {
+        let keys = self
+            .redis
+            .scan_keys(&format!("{}:*:chunks", self.settings.redis_result_prefix))
+            .await?;
+        let max = self.settings.result_retention_entries;
+        for (key, length) in keys.iter().zip(self.redis.lengths("LLEN", &keys).await?) {
+            if length as usize > max {
+                self.redis.ltrim(key, -(max as isize), -1).await?;
+                report.results_trimmed += length as usize - max;
+            }
+        }
+        self.record("results_trimmed", report.results_trimmed);
+        Ok(())
+    }
+
+    async fn trim_archive(&self, now: DateTime<Utc>, report: &mut RetentionReport) -> anyhow::Result<()> {
+        if !self.settings.archive_enabled {
+            return Ok(());
+        }
+        let cutoff = now.timestamp() - self.settings.archive_ttl.as_secs() as i64;
+        let keys = self
+            .redis
+            .scan_keys(&format!("{}:*", self.settings.redis_archive_prefix))
+            .await?;
+        for key in keys {
+            report.archive_entries_removed += self.redis.zrem_range_by_score_count(&key, 0, cutoff).await?;
+        }
+        self.record("archive_entries_removed", report.archive_entries_removed);
+        Ok(())
+    }
+
+    /// Rolls completed days of the raw sentiment trend into `{trend_prefix}:{brand}:daily`
+    /// before the trend tracker prunes them, and drops rollups past their retention.
+    async fn roll_up_trends(&self, now: DateTime<Utc>, report: &mut RetentionReport) -> anyhow::Result<()> {
+        let suffix = ":sentiment";
+        let keys = self
+            .redis
+            .scan_keys(&format!("{}:*{suffix}", self.settings.redis_trend_prefix))
+            .await?;
+        let today_start = now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .map(|midnight| midnight.and_utc().timestamp())
+            .unwrap_or_default();
+        let rollup_cutoff = (now - chrono::Duration::days(i64::from(self.settings.trend_rollup_retention_days)))
+            .format("%Y-%m-%d")
+            .to_string();
+
+        for key in keys {
+            let Some(base) = key.strip_suffix(suffix) else {
+                continue;
+            };
+            let daily_key = format!("{base}:daily");
+
+            let mut days: BTreeMap<String, (usize, f64)> = BTreeMap::new();
+            for (member, timestamp) in self.redis.zrange_by_score_with_scores(&key, 0, today_start - 1).await? {
+                let Some(score) = member.rsplit(':').next().and_then(|raw| raw.parse::<f64>().ok()) else {
+                    continue;
+                };
+                let Some(day) = DateTime::<Utc>::from_timestamp(timestamp, 0) else {
+                    continue;
+                };
+                let entry = days.entry(day.format("%Y-%m-%d").to_string()).or_default();
+                entry.0 += 1;
+                entry.1 += score;
+            }
+
+            let fields: Vec<(String, String)> = days
+                .iter()
+                .map(|(day, (samples, total))| {
+                    let rollup = json!({ "samples": samples, "meanScore": total / *samples as f64 });
+                    (day.clone(), rollup.to_string())
+                })
+                .collect();
+            if !fields.is_empty() {
+                let fields: Vec<(&str, String)> = fields.iter().map(|(day, value)| (day.as_str(), value.clone())).collect();
+                self.redis.hset_multiple(&daily_key, &fields).await?;
+                report.rollup_days_written += fields.len();
+            }
+
+            let expired: Vec<String> = self
+                .redis
+                .hkeys(&daily_key)
+                .await?
+                .into_iter()
+                .filter(|day| *day < rollup_cutoff)
+                .collect();
+            if !expired.is_empty() {
+                self.redis.hdel(&daily_key, &expired).await?;
+                report.rollup_days_expired += expired.len();
+            }
+        }
+        self.record("rollup_days_written", report.rollup_days_written);
+        self.record("rollup_days_expired", report.rollup_days_expired);
+        Ok(())
+    }
+
+    async fn expire_spike_histories(&self, report: &mut RetentionReport) -> anyhow::Result<()> {
+        let keys = self
+            .redis
+            .scan_keys(&format!("{}:*", self.settings.redis_spike_prefix))
+            .await?;
+        let max_idle = self.settings.spike_history_retention.as_secs();
+        for key in keys {
+            if self.redis.idle_seconds(&key).await?.is_some_and(|idle| idle > max_idle) {
+                self.redis.delete(&key).await?;
+                report.spike_histories_deleted += 1;
+            }
+        }
+        self.record("spike_histories_deleted", report.spike_histories_deleted);
+        Ok(())
+    }
+
+    fn record(&self, action: &str, count: usize) {
+        WORKER_RETENTION_ACTIONS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, action])
+            .inc_by(count as u64);
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::error::{WorkerError, WorkerResult};
+use crate::llm::{ClusterAnalysis, LlmAdapter, SeveritySignals};
+use crate::metrics::WORKER_PROVIDER_HEALTH_SCORE;
+use crate::types::{ClusterEntities, Confidence};
+
+/// Weight of the newest observation in the rolling success rate and latency.
+const HEALTH_DECAY: f64 = 0.2;
+
+struct ProviderHealth {
+    success_rate: f64,
+    latency_secs: f64,
+    last_used: Instant,
+}
+
+impl ProviderHealth {
+    fn score(&self) -> f64 {
+        self.success_rate / (1.0 + self.latency_secs)
+    }
+}
+
+struct RoutedProvider {
+    name: String,
+    adapter: Arc<dyn LlmAdapter>,
+    health: Mutex<ProviderHealth>,
+}
+
+/// Routes each call to the provider with the best rolling success rate and latency,
+/// failing over to the others in score order. A provider that has not been used for
+/// `probe_interval` is tried first so a recovered provider is noticed.
+pub struct HealthRoutedLlmAdapter {
+    providers: Vec<RoutedProvider>,
+    probe_interval: Duration,
+    worker_id: String,
+}
+
+impl HealthRoutedLlmAdapter {
+    pub fn new(providers: Vec<(String, Arc<dyn LlmAdapter>)>, probe_interval: Duration, worker_id: String) -> Self {
+        let providers = providers
+            .into_iter()
+            .map(|(name, adapter)| RoutedProvider {
+                name,
+                adapter,
+                health: Mutex::new(ProviderHealth {
+                    success_rate: 1.0,
+                    latency_secs: 0.0,
+                    last_used: Instant::now(),
+                }),
+            })
+            .collect();
+        Self {
+            providers,
+            probe_interval,
+            worker_id,
+        }
+    }
+
+    /// Provider indices in the order they should be tried.
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let snapshot: Vec<(f64, Duration)> = self
+            .providers
+            .iter()
+            .map(|provider| {
+                let health = provider.health.lock().expect("provider health poisoned");
+                (health.score(), now.duration_since(health.last_used))
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..self.providers.len()).collect();
+        order.sort_by(|&a, &b| snapshot[b].0.total_cmp(&snapshot[a].0));
+        if let Some(pos) = order.iter().position(|&idx| snapshot[idx].1 >= self.probe_interval) {
+            let probe = order.remove(pos);
+            order.insert(0, probe);
+        }
+        order
+    }
+
+    fn observe(&self, idx: usize, elapsed: Duration, ok: bool) {
+        let provider = &self.providers[idx];
+        let mut health = provider.health.lock().expect("provider health poisoned");
+        let outcome = if ok { 1.0 } else { 0.0 };
+        health.success_rate += HEALTH_DECAY * (outcome - health.success_rate);
+        health.latency_secs += HEALTH_DECAY * (elapsed.as_secs_f64() - health.latency_secs);
+        health.last_used = Instant::now();
+        WORKER_PROVIDER_HEALTH_SCORE
+            .with_label_values(&[&self.worker_id, &provider.name])
+            .set(health.score());
+    }
+
+    async fn route<'a, T, F, Fut>(&'a self, operation: &str, call: F) -> WorkerResult<T>
+    where
+        F: Fn(&'a dyn LlmAdapter) -> Fut,
+        Fut: Future<Output = WorkerResult<T>>,
+    {
+        let mut last_error = None;
+        for idx in self.order() {
+            let provider = &self.providers[idx];
+            let start = Instant::now();
+            let outcome = call(provider.adapter.as_ref()).await;
+            self.observe(idx, start.elapsed(), outcome.is_ok());
+            match outcome {
+                Ok(value) => {
+                    debug!(provider = %provider.name, operation, "LLM call routed");
+                    return Ok(value);
+                }
+                Err(err) => {
+                    warn!(provider = %provider.name, operation, error = %err, "LLM provider failed; trying next provider");
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| WorkerError::Config(anyhow!("no LLM providers configured"))))
+    }
+}
+
+#[async_trait]
+impl LlmAdapter for HealthRoutedLlmAdapter {
+    async fn summarize(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<String>> {
+        self.route("summary", |adapter| adapter.summarize(brand, texts)).await
+    }
+
+    async fn sentiment(&self, brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        self.route("sentiment", |adapter| adapter.sentiment(brand, texts)).await
+    }
+
+    async fn sentiment_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        self.route("sentiment_batch", |adapter| adapter.sentiment_batch(groups)).await
+    }
+
+    async fn analyze(&self, texts: &[String]) -> WorkerResult<Option<ClusterAnalysis>> {
+        self.route("analysis", |adapter| adapter.analyze(texts)).await
+    }
+
+    async fn analyze_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<ClusterAnalysis>>> {
+        self.route("analysis_batch", |adapter| adapter.analyze_batch(groups)).await
+    }
+
+    async fn emotions(&self, texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        self.route("emotions", |adapter| adapter.emotions(texts)).await
+    }
+
+    async fn severity(&self, signals: &SeveritySignals, texts: &[String]) -> WorkerResult<Option<u8>> {
+        self.route("severity", |adapter| adapter.severity(signals, texts)).await
+    }
+
+    async fn confidence(
+        &self,
+        texts: &[String],
+        summary: Option<&str>,
+        sentiment: &HashMap<String, f32>,
+    ) -> WorkerResult<Option<Confidence>> {
+        self.route("confidence", |adapter| adapter.confidence(texts, summary, sentiment))
+            .await
+    }
+
+    async fn quotes(&self, texts: &[String]) -> WorkerResult<Option<Vec<usize>>> {
+        self.route("quotes", |adapter| adapter.quotes(texts)).await
+    }
+
+    async fn intent(&self, texts: &[String]) -> WorkerResult<Option<String>> {
+        self.route("intent", |adapter| adapter.intent(texts)).await
+    }
+
+    async fn relevance(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<Vec<f32>>> {
+        self.route("relevance", |adapter| adapter.relevance(brand, texts)).await
+    }
+
+    async fn entities(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<ClusterEntities>> {
+        self.route("entities", |adapter| adapter.entities(brand, texts)).await
+    }
+
+    async fn toxicity(&self, texts: &[String]) -> WorkerResult<Option<f32>> {
+        self.route("toxicity", |adapter| adapter.toxicity(texts)).await
+    }
+
+    async fn caption(&self, image_url: &str) -> WorkerResult<Option<String>> {
+        self.route("caption", |adapter| adapter.caption(image_url)).await
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+
+use crate::clustering::DistanceMetric;
+
+// Half of the sample is spread evenly across the cluster's time range; the rest is chosen by
+// farthest-point selection on embeddings so distinct sub-narratives are represented.
+pub fn sample_indices(
+    indices: &[usize],
+    timestamps: &[DateTime<Utc>],
+    embeddings: &[Vec<f32>],
+    size: usize,
+    metric: DistanceMetric,
+) -> Vec<usize> {
+    if indices.len() <= size || size == 0 {
+        return indices.to_vec();
+    }
+
+    let mut by_time = indices.to_vec();
+    by_time.sort_by_key(|&idx| timestamps.get(idx).copied());
+
+    let time_picks = size.div_ceil(2);
+    let mut selected: Vec<usize> = (0..time_picks)
+        .map(|bucket| by_time[bucket * by_time.len() / time_picks])
+        .collect();
+    selected.dedup();
+
+    while selected.len() < size {
+        let next = indices
+            .iter()
+            .copied()
+            .filter(|idx| !selected.contains(idx))
+            .map(|candidate| {
+                let nearest = selected
+                    .iter()
+                    .map(|&chosen| similarity(embeddings, metric, candidate, chosen))
+                    .fold(f32::MIN, f32::max);
+                (candidate, nearest)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match next {
+            Some((candidate, _)) => selected.push(candidate),
+            None => break,
+        }
+    }
+
+    selected.sort_by_key(|&idx| timestamps.get(idx).copied());
+    selected
+}
+
+fn similarity(embeddings: &[Vec<f32>], metric: DistanceMetric, a: usize, b: usize) -> f32 {
+    match (embeddings.get(a), embeddings.get(b)) {
+        (Some(left), Some(right)) => metric.similarity(left, right),
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::error::WorkerResult;
+use crate::llm::{ClusterAnalysis, LlmAdapter, SeveritySignals};
+use crate::metrics::WORKER_SANITIZED_INPUTS_TOTAL;
+use crate::types::{ClusterEntities, Confidence};
+
+/// Phrases that try to overrule the prompt: "ignore previous instructions", role prefixes,
+/// chat-template control tokens.
+static INSTRUCTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?im)\b(?:ignore|disregard|forget|override)\s+(?:all\s+|any\s+)?(?:the\s+|your\s+)?",
+        r"(?:previous|prior|above|earlier|preceding|system)\s+(?:instructions?|prompts?|rules|directions)\b",
+        r"|\byou\s+are\s+now\b|\b(?:new|updated)\s+instructions?\s*:|^\s*(?:system|assistant|developer)\s*:",
+        r"|<\|[^|>]{1,32}\|>|\[/?INST\]|<</?SYS>>",
+    ))
+    .expect("Invalid instruction regex")
+});
+/// The delimiters themselves, so a mention can't close its block early.
+static DELIMITER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<\s*/?\s*mention\s*>").expect("Invalid delimiter regex"));
+
+/// Wraps a remote provider so every mention it sees has instruction-like phrases replaced
+/// with `[filtered]` and sits in its own `<mention>` block. Mentions are attacker-controlled,
+/// and without this a post saying "ignore previous instructions" is read as part of the
+/// prompt. Enabled by `PROMPT_SANITIZATION`; the heuristic fallback sees the original text.
+pub struct SanitizingLlmAdapter {
+    inner: Arc<dyn LlmAdapter>,
+    provider: String,
+    worker_id: String,
+}
+
+impl SanitizingLlmAdapter {
+    pub fn new(inner: Arc<dyn LlmAdapter>, provider: &str, worker_id: &str) -> Self {
+        Self {
+            inner,
+            provider: provider.to_string(),
+            worker_id: worker_id.to_string(),
+        }
+    }
+
+    fn texts(&self, texts: &[String]) -> Vec<String> {
+        texts.iter().map(|text| self.sanitize(text)).collect()
+    }
+
+    fn groups(&self, groups: &[Vec<String>]) -> Vec<Vec<String>> {
+        groups.iter().map(|texts| self.texts(texts)).collect()
+    }
+
+    fn sanitize(&self, text: &str) -> String {
+        let mut text = Cow::Borrowed(text);
+        for (kind, re) in [("instruction", &*INSTRUCTION_RE), ("delimiter", &*DELIMITER_RE)] {
+            if re.is_match(&text) {
+                WORKER_SANITIZED_INPUTS_TOTAL
+                    .with_label_values(&[&self.worker_id, &self.provider, kind])
+                    .inc();
+                text = Cow::Owned(re.replace_all(&text, "[filtered]").into_owned());
+            }
+        }
+        format!("<mention>\n{text}\n</mention>")
+    }
+}
+
+#[async_trait]
+impl LlmAdapter for SanitizingLlmAdapter {
+    async fn summarize(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<String>> {
+        self.inner.summarize(brand, &self.texts(texts)).await
+    }
+
+    async fn sentiment(&self, brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        self.inner.sentiment(brand, &self.texts(texts)).await
+    }
+
+    async fn sentiment_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        self.inner.sentiment_batch(&self.groups(groups)).await
+    }
+
+    async fn analyze(&self, texts: &[String]) -> WorkerResult<Option<ClusterAnalysis>> {
+        self.inner.analyze(&self.texts(texts)).await
+    }
+
+    async fn analyze_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<ClusterAnalysis>>> {
+        self.inner.analyze_batch(&self.groups(groups)).await
+    }
+
+    async fn emotions(&self, texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        self.inner.emotions(&self.texts(texts)).await
+    }
+
+    async fn severity(&self, signals: &SeveritySignals, texts: &[String]) -> WorkerResult<Option<u8>> {
+        self.inner.severity(signals, &self.texts(texts)).await
+    }
+
+    async fn confidence(
+        &self,
+        texts: &[String],
+        summary: Option<&str>,
+        sentiment: &HashMap<String, f32>,
+    ) -> WorkerResult<Option<Confidence>> {
+        self.inner.confidence(&self.texts(texts), summary, sentiment).await
+    }
+
+    async fn quotes(&self, texts: &[String]) -> WorkerResult<Option<Vec<usize>>> {
+        self.inner.quotes(&self.texts(texts)).await
+    }
+
+    async fn intent(&self, texts: &[String]) -> WorkerResult<Option<String>> {
+        self.inner.intent(&self.texts(texts)).await
+    }
+
+    async fn relevance(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<Vec<f32>>> {
+        self.inner.relevance(brand, &self.texts(texts)).await
+    }
+
+    async fn entities(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<ClusterEntities>> {
+        self.inner.entities(brand, &self.texts(texts)).await
+    }
+
+    async fn toxicity(&self, texts: &[String]) -> WorkerResult<Option<f32>> {
+        self.inner.toxicity(&self.texts(texts)).await
+    }
+
+    async fn caption(&self, image_url: &str) -> WorkerResult<Option<String>> {
+        self.inner.caption(image_url).await
+    }
+}
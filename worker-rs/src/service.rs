@@ -1,64 +1,369 @@
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Instant;
 
-use anyhow::{Context, Result};
-use tokio::sync::{broadcast, Mutex};
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use tokio::sync::{broadcast, Mutex, Semaphore};
 use tokio::time::sleep;
 use tracing::{info, warn};
 
-use crate::clustering::Clusterer;
+use crate::alerts::AlertRouter;
+use crate::archive::ChunkArchive;
+use crate::chunk_router::ChunkRouter;
 use crate::config::Settings;
-use crate::embeddings::build_embedding_adapter;
-use crate::llm::build_llm_adapter;
+use crate::crypto::PayloadCipher;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::journal::{JournalEntry, ProcessingJournal};
 use crate::metrics::{
-    WORKER_IO_TIME_SECONDS, WORKER_PROCESSING_TIME_SECONDS, WORKER_WAITING_SECONDS,
+    WORKER_ADAPTER_GENERATION, WORKER_ERRORS_TOTAL, WORKER_IO_TIME_SECONDS, WORKER_PROCESSING_TIME_SECONDS, WORKER_QUEUE_OLDEST_AGE_SECONDS,
+    WORKER_ROUTED_CHUNKS_TOTAL, WORKER_SHADOW_COMPARISONS_TOTAL,
+    WORKER_SHADOW_SENTIMENT_DELTA, WORKER_WAITING_SECONDS,
 };
+use crate::onboarding::BrandOnboarding;
+use crate::pipeline::build_processor;
 use crate::processor::Processor;
 use crate::queue_consumer::QueueConsumer;
 use crate::redis_client::RedisClient;
-use crate::spike::SpikeDetector;
+use crate::sinks::SinkSet;
+use crate::slo::SloMonitor;
+use crate::throttle::ResourceThrottle;
 use crate::storage::ResultStorage;
-use crate::types::{Chunk, FailureRecord};
+use crate::types::{
+    BackfillReport, BackfillRequest, Chunk, ChunkResult, FailureRecord, PayloadInspection, ReprocessRequest,
+    FAILURE_RECORD_SCHEMA_VERSION,
+};
+
+const RESULT_NOTIFICATION_CAPACITY: usize = 256;
 
 pub struct WorkerService {
     settings: Arc<Settings>,
     redis: RedisClient,
     queue_consumer: QueueConsumer,
-    processor: Processor,
-    storage: ResultStorage,
+    pipelines: ArcSwap<Pipelines>,
+    generation: AtomicU64,
+    http: HttpClient,
+    storage: Arc<ResultStorage>,
+    archive: ChunkArchive,
+    cipher: Arc<PayloadCipher>,
+    backfill_storage: ResultStorage,
     waiting_since: Mutex<Option<Instant>>,
     last_wait_log: Mutex<Option<Instant>>,
+    alerts: Arc<AlertRouter>,
+    slo: Arc<SloMonitor>,
+    throttle: Arc<ResourceThrottle>,
+    sinks: SinkSet,
+    onboarding: BrandOnboarding,
+    journal: ProcessingJournal,
+    paused: AtomicBool,
+    paused_brands: StdMutex<HashSet<String>>,
+    // Bounds shadow runs, which go on in the background after the live push.
+    shadow_permits: Arc<Semaphore>,
+    // Brands with an admin backfill running, at most `BACKFILL_MAX_CONCURRENT`.
+    backfills: StdMutex<HashSet<String>>,
+    // Queue head timestamps for `staleness` ordering, refreshed every `QUEUE_STALENESS_SAMPLE_SEC`.
+    head_sample: StdMutex<HeadSample>,
+    // Held from queue fetch until the payload is handled, so a drain can wait for it.
+    in_flight: Mutex<()>,
+    processed_total: AtomicU64,
+    results: broadcast::Sender<Arc<ChunkResult>>,
+}
+
+/// Processors built from one settings snapshot. A reload swaps them as a unit; chunks
+/// already running keep the snapshot they started with.
+struct Pipelines {
+    live: Processor,
+    backfill: Processor,
+    shadow: Option<Processor>,
+    routes: ChunkRouter,
+}
+
+impl Pipelines {
+    fn build(
+        settings: &Arc<Settings>,
+        redis: &RedisClient,
+        cipher: &Arc<PayloadCipher>,
+        http: &HttpClient,
+    ) -> WorkerResult<Self> {
+        let live = build_processor(settings, redis, cipher, http, &[])?;
+
+        // Backfills reuse the live pipeline but write results and per-brand state into their own namespace.
+        let backfill_settings = Arc::new(settings.namespaced(&settings.backfill_result_prefix));
+        let backfill = build_processor(&backfill_settings, redis, cipher, http, &[])?;
+
+        let shadow = if settings.shadow_enabled {
+            let shadow_settings = Arc::new(Settings {
+                embeddings_provider: settings
+                    .shadow_embeddings_provider
+                    .clone()
+                    .unwrap_or_else(|| settings.embeddings_provider.clone()),
+                llm_provider: settings
+                    .shadow_llm_provider
+                    .clone()
+                    .unwrap_or_else(|| settings.llm_provider.clone()),
+                clustering_algorithm: settings
+                    .shadow_clustering_algorithm
+                    .clone()
+                    .unwrap_or_else(|| settings.clustering_algorithm.clone()),
+                // An explicit shadow provider replaces health routing rather than joining it.
+                llm_providers: match settings.shadow_llm_provider {
+                    Some(_) => Vec::new(),
+                    None => settings.llm_providers.clone(),
+                },
+                ..settings.namespaced(&settings.redis_shadow_prefix)
+            });
+            Some(build_processor(&shadow_settings, redis, cipher, http, &[])?)
+        } else {
+            None
+        };
+
+        let routes = ChunkRouter::from_settings(settings, redis, cipher, http)?;
+
+        Ok(Self {
+            live,
+            backfill,
+            shadow,
+            routes,
+        })
+    }
+
+    /// The processor for a live chunk, with the route name when one matched.
+    fn route(&self, brand: &str, chunk: &Chunk, worker_id: &str) -> (Option<String>, &Processor) {
+        match self.routes.route(brand, chunk) {
+            Some((name, processor)) => {
+                WORKER_ROUTED_CHUNKS_TOTAL.with_label_values(&[worker_id, name]).inc();
+                (Some(name.to_string()), processor)
+            }
+            None => (None, &self.live),
+        }
+    }
+}
+
+pub struct BackfillSlot {
+    service: Arc<WorkerService>,
+    brand: String,
+}
+
+impl Drop for BackfillSlot {
+    fn drop(&mut self) {
+        self.service.backfills.lock().expect("backfills poisoned").remove(&self.brand);
+    }
+}
+
+/// Snapshot of the queue loop for control-plane callers.
+#[derive(Debug, Clone)]
+pub struct WorkerStatus {
+    pub worker_id: String,
+    pub paused: bool,
+    /// Set while resource pressure has the worker throttled, with the limit that tripped.
+    pub throttled: Option<&'static str>,
+    pub waiting_seconds: f64,
+    pub processed_total: u64,
 }
 
 impl WorkerService {
-    pub fn new(settings: Arc<Settings>, redis: RedisClient, queue_consumer: QueueConsumer) -> Self {
-        let embeddings = build_embedding_adapter(&settings);
-        let clusterer = Clusterer::new(settings.worker_id.clone());
-        let llm = build_llm_adapter(&settings);
-        let spike_detector = SpikeDetector::new(redis.clone(), settings.clone());
-        let processor = Processor::new(settings.clone(), embeddings, clusterer, llm, spike_detector);
-        let storage = ResultStorage::new(redis.clone(), settings.clone());
-        Self {
+    pub fn new(settings: Arc<Settings>, redis: RedisClient, queue_consumer: QueueConsumer) -> WorkerResult<Self> {
+        let cipher = Arc::new(PayloadCipher::from_settings(&settings).map_err(WorkerError::Config)?);
+        if cipher.enabled() {
+            info!("Payload encryption at rest enabled");
+        }
+        let http = HttpClient::from_settings(&settings).map_err(WorkerError::Config)?;
+        let alerts = Arc::new(
+            AlertRouter::from_settings(settings.clone(), redis.clone(), http.clone()).map_err(WorkerError::Config)?,
+        );
+        let slo = Arc::new(SloMonitor::from_settings(settings.clone(), alerts.clone()).map_err(WorkerError::Config)?);
+        let throttle = Arc::new(ResourceThrottle::new(settings.clone()));
+        let sinks = SinkSet::from_settings(&settings, &http).map_err(WorkerError::Config)?;
+        let pipelines = Pipelines::build(&settings, &redis, &cipher, &http)?;
+        let shadow_permits = Arc::new(Semaphore::new(settings.shadow_max_concurrent));
+        let storage = Arc::new(ResultStorage::new(redis.clone(), settings.clone(), cipher.clone()));
+        let archive = ChunkArchive::new(redis.clone(), settings.clone(), cipher.clone());
+        let onboarding = BrandOnboarding::new(redis.clone(), settings.clone());
+        let journal = ProcessingJournal::from_settings(settings.clone(), redis.clone()).map_err(WorkerError::Config)?;
+
+        let backfill_settings = Arc::new(settings.namespaced(&settings.backfill_result_prefix));
+        let backfill_storage = ResultStorage::new(redis.clone(), backfill_settings, cipher.clone());
+        WORKER_ADAPTER_GENERATION
+            .with_label_values(&[&settings.worker_id])
+            .set(1.0);
+
+        Ok(Self {
             settings,
             redis,
             queue_consumer,
-            processor,
+            pipelines: ArcSwap::from_pointee(pipelines),
+            generation: AtomicU64::new(1),
+            http,
             storage,
+            archive,
+            cipher,
+            backfill_storage,
             waiting_since: Mutex::new(None),
             last_wait_log: Mutex::new(None),
-        }
+            alerts,
+            slo,
+            throttle,
+            sinks,
+            onboarding,
+            journal,
+            paused: AtomicBool::new(false),
+            paused_brands: StdMutex::new(HashSet::new()),
+            backfills: StdMutex::new(HashSet::new()),
+            head_sample: StdMutex::new(HeadSample::default()),
+            shadow_permits,
+            in_flight: Mutex::new(()),
+            processed_total: AtomicU64::new(0),
+            results: broadcast::channel(RESULT_NOTIFICATION_CAPACITY).0,
+        })
     }
 
     pub fn settings(&self) -> &Arc<Settings> {
         &self.settings
     }
 
-    pub async fn process_next(&self) -> Result<()> {
-        let queue_keys = self
+    pub fn slo(&self) -> Arc<SloMonitor> {
+        self.slo.clone()
+    }
+
+    pub fn throttle(&self) -> Arc<ResourceThrottle> {
+        self.throttle.clone()
+    }
+
+    /// Rebuilds the pipelines, and with them the embedding and LLM adapters, from `settings`
+    /// and swaps them in. In-flight chunks finish on the adapters they started with. Only
+    /// pipeline settings take effect; ports, Redis and queue settings still need a restart.
+    pub fn reload(&self, settings: Settings) -> WorkerResult<u64> {
+        let settings = Arc::new(settings);
+        let pipelines = Pipelines::build(&settings, &self.redis, &self.cipher, &self.http)?;
+        self.pipelines.store(Arc::new(pipelines));
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        WORKER_ADAPTER_GENERATION
+            .with_label_values(&[&self.settings.worker_id])
+            .set(generation as f64);
+        info!(
+            worker_id = %self.settings.worker_id,
+            generation,
+            llm_provider = %settings.llm_provider,
+            llm_model = settings.llm_model().unwrap_or("-"),
+            embeddings_provider = %settings.embeddings_provider,
+            embedding_model = settings.embedding_model().unwrap_or("-"),
+            "Pipeline adapters reloaded"
+        );
+        Ok(generation)
+    }
+
+    /// Stops taking chunks from the queues; chunks already in flight finish normally.
+    pub fn pause(&self) {
+        if !self.paused.swap(true, Ordering::SeqCst) {
+            info!(worker_id = %self.settings.worker_id, "Queue consumption paused");
+        }
+    }
+
+    pub fn resume(&self) {
+        if self.paused.swap(false, Ordering::SeqCst) {
+            info!(worker_id = %self.settings.worker_id, "Queue consumption resumed");
+        }
+    }
+
+    /// Stops taking chunks from one brand's queue; other brands are unaffected.
+    pub fn pause_brand(&self, brand: &str) {
+        if self.paused_brands.lock().expect("paused brands poisoned").insert(brand.to_string()) {
+            info!(worker_id = %self.settings.worker_id, brand, "Brand queue consumption paused");
+        }
+    }
+
+    pub fn resume_brand(&self, brand: &str) {
+        if self.paused_brands.lock().expect("paused brands poisoned").remove(brand) {
+            info!(worker_id = %self.settings.worker_id, brand, "Brand queue consumption resumed");
+        }
+    }
+
+    /// Pauses the queue loop and waits for the chunk in flight, if any, to finish.
+    pub async fn drain(&self) {
+        self.pause();
+        let _idle = self.in_flight.lock().await;
+        info!(worker_id = %self.settings.worker_id, "Worker drained");
+    }
+
+    pub async fn status(&self) -> WorkerStatus {
+        let waiting_since = *self.waiting_since.lock().await;
+        WorkerStatus {
+            worker_id: self.settings.worker_id.clone(),
+            paused: self.paused.load(Ordering::SeqCst),
+            throttled: self.throttle.reason(),
+            waiting_seconds: waiting_since.map(|start| start.elapsed().as_secs_f64()).unwrap_or_default(),
+            processed_total: self.processed_total.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Results of queue-driven chunks as they are stored. Slow subscribers miss notifications
+    /// rather than hold up processing.
+    pub fn subscribe_results(&self) -> broadcast::Receiver<Arc<ChunkResult>> {
+        self.results.subscribe()
+    }
+
+    /// Runs one chunk through the live pipeline outside the queue loop, storing the
+    /// result like a queued chunk only when `persist` is set. Otherwise the chunk is only
+    /// previewed and per-brand pipeline state is left as it was.
+    pub async fn process_chunk(&self, chunk: Chunk, fallback_brand: &str, persist: bool) -> WorkerResult<ChunkResult> {
+        let brand = if chunk.brand.trim().is_empty() {
+            fallback_brand.to_string()
+        } else {
+            chunk.brand.clone()
+        };
+        let chunk_id = chunk.chunk_id.clone();
+        self.journal
+            .record(JournalEntry::received(&self.settings.worker_id, &brand, &chunk, false))
+            .await;
+        let outcome = async {
+            let pipelines = self.pipelines.load_full();
+            let (route, processor) = pipelines.route(&brand, &chunk, &self.settings.worker_id);
+            let mut result = if persist {
+                processor.process_chunk(chunk, fallback_brand, 0.0).await?
+            } else {
+                processor.preview_chunk(chunk, fallback_brand).await?
+            };
+            result.provenance.route = route;
+            if persist {
+                let brand = result.brand.clone();
+                self.storage.push_result(&brand, &mut result).await?;
+            }
+            Ok(result)
+        }
+        .await;
+        let entry = match &outcome {
+            Ok(result) => JournalEntry::completed(&self.settings.worker_id, result),
+            Err(err) => JournalEntry::failed(&self.settings.worker_id, &brand, &chunk_id, "processing", err),
+        };
+        self.journal.record(entry).await;
+        outcome
+    }
+
+    pub async fn process_next(&self) -> WorkerResult<()> {
+        if self.throttle.is_throttled() {
+            sleep(self.settings.throttle_fetch_delay).await;
+        }
+        let in_flight = self.in_flight.lock().await;
+        if self.paused.load(Ordering::SeqCst) {
+            drop(in_flight);
+            sleep(self.settings.blpop_timeout).await;
+            return Ok(());
+        }
+
+        let mut queue_keys = self
             .queue_consumer
             .scan_brand_queues(&self.settings.redis_queue_prefix)
-            .await
-            .context("scan brand queues")?;
+            .await?;
+        {
+            let paused_brands = self.paused_brands.lock().expect("paused brands poisoned");
+            if !paused_brands.is_empty() {
+                queue_keys.retain(|key| {
+                    !paused_brands.contains(&extract_brand_from_queue(key, &self.settings.redis_queue_prefix))
+                });
+            }
+        }
 
         if queue_keys.is_empty() {
             self.update_waiting(None).await;
@@ -66,12 +371,20 @@ impl WorkerService {
             return Ok(());
         }
 
-        match self
-            .queue_consumer
-            .fetch(&queue_keys)
-            .await
-            .context("fetch queue entry")?
-        {
+        for queue_key in &queue_keys {
+            let brand = extract_brand_from_queue(queue_key, &self.settings.redis_queue_prefix);
+            if let Err(err) = self.onboarding.observe(&brand).await {
+                warn!(brand = %brand, error = %err, "Brand onboarding failed");
+            }
+        }
+
+        let queue_keys = if self.settings.queue_prioritization == "staleness" {
+            self.order_by_staleness(queue_keys).await
+        } else {
+            queue_keys
+        };
+
+        match self.queue_consumer.fetch(&queue_keys).await? {
             Some((queue_key, payload, fetch_time_ms)) => {
                 self.clear_waiting().await;
                 let brand_hint = extract_brand_from_queue(&queue_key, &self.settings.redis_queue_prefix);
@@ -80,7 +393,8 @@ impl WorkerService {
                     .observe(fetch_time_ms / 1000.0);
 
                 if let Err(err) = self.handle_payload(&brand_hint, payload, fetch_time_ms).await {
-                        warn!(error = %err, "Failed to handle payload");
+                    self.record_error(&err);
+                    warn!(error = %err, kind = err.kind(), "Failed to handle payload");
                 }
             }
             None => {
@@ -91,46 +405,160 @@ impl WorkerService {
         Ok(())
     }
 
-    pub async fn send_heartbeat(&self) -> Result<()> {
+    // BLPOP serves the first non-empty key, so ordering keys oldest-head-first bounds per-brand latency.
+    async fn order_by_staleness(&self, queue_keys: Vec<String>) -> Vec<String> {
+        let due = self
+            .head_sample
+            .lock()
+            .expect("queue head sample poisoned")
+            .taken_at
+            .is_none_or(|taken_at| taken_at.elapsed() >= self.settings.queue_staleness_sample);
+        if due {
+            match self.queue_consumer.peek_heads(&queue_keys).await {
+                Ok(heads) => self.record_head_sample(&queue_keys, heads),
+                Err(err) => {
+                    warn!(error = %err, "Failed to peek queue heads; using positional order");
+                    return queue_keys;
+                }
+            }
+        }
+
+        let sample = self.head_sample.lock().expect("queue head sample poisoned");
+        let mut ranked: Vec<(Option<DateTime<Utc>>, String)> = queue_keys
+            .into_iter()
+            .map(|key| (sample.enqueued_at.get(&key).copied().flatten(), key))
+            .collect();
+
+        // Entries without a readable timestamp sort first so they are drained (or failed) promptly.
+        // That includes queues that appeared since the last sample.
+        ranked.sort_by_key(|(enqueued_at, _)| *enqueued_at);
+        ranked.into_iter().map(|(_, key)| key).collect()
+    }
+
+    fn record_head_sample(&self, queue_keys: &[String], heads: Vec<Option<String>>) {
+        let now = Utc::now();
+        let enqueued_at = queue_keys
+            .iter()
+            .zip(heads)
+            .map(|(key, head)| {
+                let enqueued_at = head.and_then(|payload| self.head_timestamp(&payload));
+                if let Some(enqueued_at) = enqueued_at {
+                    let brand = extract_brand_from_queue(key, &self.settings.redis_queue_prefix);
+                    WORKER_QUEUE_OLDEST_AGE_SECONDS
+                        .with_label_values(&[&self.settings.worker_id, &brand])
+                        .set((now - enqueued_at).num_milliseconds().max(0) as f64 / 1000.0);
+                }
+                (key.clone(), enqueued_at)
+            })
+            .collect();
+        *self.head_sample.lock().expect("queue head sample poisoned") = HeadSample {
+            taken_at: Some(Instant::now()),
+            enqueued_at,
+        };
+    }
+
+    fn head_timestamp(&self, payload: &str) -> Option<DateTime<Utc>> {
+        let plaintext = self.cipher.decrypt(payload).ok()?;
+        let value: serde_json::Value = serde_json::from_str(&plaintext).ok()?;
+        value
+            .pointer("/meta/enqueuedAt")
+            .or_else(|| value.get("createdAt"))
+            .and_then(|timestamp| timestamp.as_str())
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+    }
+
+    pub async fn shutdown(&self) {
+        self.sinks.close().await;
+    }
+
+    pub async fn send_heartbeat(&self) -> WorkerResult<()> {
         self.redis
             .set_heartbeat(&self.settings.worker_id, self.settings.heartbeat_interval)
             .await
-            .context("set heartbeat")
+            .map_err(WorkerError::Queue)
     }
 
-    async fn handle_payload(&self, brand_hint: &str, payload: String, fetch_time_ms: f64) -> Result<f64> {
-        let chunk: Chunk = match serde_json::from_str(&payload) {
-            Ok(chunk) => chunk,
+    async fn handle_payload(&self, brand_hint: &str, payload: String, fetch_time_ms: f64) -> WorkerResult<f64> {
+        let payload = match self.cipher.decrypt(&payload) {
+            Ok(plaintext) => plaintext,
             Err(error) => {
-                self.record_failure(
-                    brand_hint,
-                    FailureReason::JsonDecode,
-                    &payload,
-                    &error.to_string(),
-                    "unknown",
-                )
-                .await?;
-                return Err(error.into());
+                let err = WorkerError::Decode(error);
+                self.record_failure(brand_hint, FailureReason::Decrypt, &payload, "unknown", 1, &err)
+                    .await?;
+                return Err(err);
+            }
+        };
+
+        let envelope = match decode_envelope(&payload) {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                self.record_failure(brand_hint, FailureReason::JsonDecode, &payload, "unknown", 1, &err)
+                    .await?;
+                return Err(err);
             }
         };
 
         let fallback_brand = brand_hint.to_string();
+        let (chunk, payload, force_refresh) = match envelope {
+            Envelope::Chunk(chunk) => (chunk, payload, None),
+            Envelope::Reprocess(request) => {
+                let brand = if request.brand.trim().is_empty() {
+                    fallback_brand.clone()
+                } else {
+                    request.brand.clone()
+                };
+                match self.resolve_archived(&brand, &request).await {
+                    Ok(resolved) => resolved,
+                    Err(err) => {
+                        self.record_failure(&brand, FailureReason::ArchiveMiss, &payload, &request.chunk_id, 1, &err)
+                            .await?;
+                        return Err(err);
+                    }
+                }
+            }
+        };
+
         let expected_brand = if chunk.brand.trim().is_empty() {
             fallback_brand.clone()
         } else {
             chunk.brand.clone()
         };
 
+        // Reprocessed chunks are already in the archive and have been shadowed once.
+        let reprocess = force_refresh.is_some();
+        self.journal
+            .record(JournalEntry::received(&self.settings.worker_id, &expected_brand, &chunk, reprocess))
+            .await;
+        if !reprocess {
+            if let Err(err) = self.archive.store(&expected_brand, &chunk.chunk_id, chunk.created_at, &payload).await {
+                warn!(brand = %expected_brand, chunk_id = %chunk.chunk_id, error = %err, "Failed to archive chunk payload");
+            }
+        }
+
         let chunk_id = chunk.chunk_id.clone();
+        let attempt = chunk.meta.as_ref().and_then(|meta| meta.attempt).unwrap_or(1);
+        let pipelines = self.pipelines.load_full();
+        let shadow_chunk = pipelines
+            .shadow
+            .as_ref()
+            .filter(|_| !reprocess)
+            .map(|_| chunk.clone());
 
-        let mut result = match self
-            .processor
-            .process_chunk(chunk, &fallback_brand, fetch_time_ms)
-            .await
-        {
-            Ok(result) => result,
+        let (route, processor) = pipelines.route(&expected_brand, &chunk, &self.settings.worker_id);
+
+        let processed = match force_refresh {
+            Some(force_refresh) => processor.reprocess_chunk(chunk, &fallback_brand, force_refresh).await,
+            None => processor.process_chunk(chunk, &fallback_brand, fetch_time_ms).await,
+        };
+
+        let mut result = match processed {
+            Ok(mut result) => {
+                result.provenance.route = route;
+                result
+            }
             Err(err) => {
-                self.record_failure(&expected_brand, FailureReason::Processing, &payload, &err.to_string(), &chunk_id)
+                self.record_failure(&expected_brand, FailureReason::Processing, &payload, &chunk_id, attempt, &err)
                     .await?;
                 return Err(err);
             }
@@ -143,12 +571,19 @@ impl WorkerService {
                 result.metrics.total_task_time_ms += push_time_ms;
             }
             Err(err) => {
-                self.record_failure(&final_brand, FailureReason::Processing, &payload, &err.to_string(), &chunk_id)
+                self.record_failure(&final_brand, FailureReason::Processing, &payload, &chunk_id, attempt, &err)
                     .await?;
                 return Err(err);
             }
         }
 
+        if let Err(err) = self.storage.push_qa_sample(&final_brand, &result).await {
+            warn!(brand = %final_brand, chunk_id = %result.chunk_id, error = %err, "Failed to store QA sample");
+        }
+        self.journal
+            .record(JournalEntry::completed(&self.settings.worker_id, &result))
+            .await;
+
         info!(
             worker_id = %self.settings.worker_id,
             brand = %final_brand,
@@ -159,33 +594,262 @@ impl WorkerService {
         WORKER_PROCESSING_TIME_SECONDS
             .with_label_values(&[&self.settings.worker_id, &final_brand])
             .observe(result.metrics.total_task_time_ms / 1000.0);
+        self.slo.record_success(result.metrics.total_task_time_ms / 1000.0);
+
+        self.alerts.evaluate(&result).await;
+        match serde_json::to_value(&result) {
+            Ok(record) => self.sinks.submit(record).await,
+            Err(err) => warn!(chunk_id = %result.chunk_id, error = %err, "Failed to serialise result for sinks"),
+        }
+
+        self.processed_total.fetch_add(1, Ordering::Relaxed);
+        if self.results.receiver_count() > 0 {
+            let _ = self.results.send(Arc::new(result.clone()));
+        }
+
+        if let Some(shadow_chunk) = shadow_chunk {
+            self.spawn_shadow(pipelines.clone(), shadow_chunk, &fallback_brand, &result);
+        }
 
         Ok(result.metrics.total_task_time_ms)
     }
 
+    /// Loads the archived chunk a reprocess request points at. Its original enqueue time is
+    /// dropped so the rerun does not skew queue-wait and latency histograms.
+    async fn resolve_archived(&self, brand: &str, request: &ReprocessRequest) -> WorkerResult<(Chunk, String, Option<bool>)> {
+        if !self.archive.enabled() {
+            return Err(WorkerError::Config(anyhow::anyhow!(
+                "reprocess request for chunk '{}' but ARCHIVE_ENABLED is off",
+                request.chunk_id
+            )));
+        }
+        let payload = self
+            .archive
+            .find(brand, &request.chunk_id)
+            .await?
+            .ok_or_else(|| {
+                WorkerError::Storage(anyhow::anyhow!("chunk '{}' not found in the {brand} archive", request.chunk_id))
+            })?;
+        let mut chunk: Chunk = serde_json::from_str(&payload).map_err(|err| WorkerError::Decode(err.into()))?;
+        if let Some(meta) = chunk.meta.as_mut() {
+            meta.enqueued_at = None;
+        }
+        info!(worker_id = %self.settings.worker_id, brand, chunk_id = %request.chunk_id, "Reprocessing archived chunk");
+        Ok((chunk, payload, Some(request.force_refresh)))
+    }
+
+    /// Runs the shadow pipeline in the background so it never holds up the live queue. At most
+    /// `SHADOW_MAX_CONCURRENT` run at once; a chunk arriving while all are busy is skipped.
+    fn spawn_shadow(&self, pipelines: Arc<Pipelines>, chunk: Chunk, fallback_brand: &str, primary: &ChunkResult) {
+        let worker_id = self.settings.worker_id.clone();
+        let brand = primary.brand.clone();
+        let Ok(permit) = self.shadow_permits.clone().try_acquire_owned() else {
+            WORKER_SHADOW_COMPARISONS_TOTAL
+                .with_label_values(&[&worker_id, &brand, "skipped"])
+                .inc();
+            return;
+        };
+        let storage = self.storage.clone();
+        let timeout = self.settings.shadow_timeout;
+        let fallback_brand = fallback_brand.to_string();
+        let primary = primary.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            let Some(shadow_processor) = pipelines.shadow.as_ref() else {
+                return;
+            };
+            let run = async {
+                let shadow = shadow_processor.process_chunk(chunk, &fallback_brand, 0.0).await?;
+                let comparison = storage.compare(&primary, &shadow);
+                storage.push_shadow_result(&brand, &shadow, &comparison).await?;
+                Ok::<_, WorkerError>(comparison)
+            };
+            let outcome = tokio::time::timeout(timeout, run)
+                .await
+                .unwrap_or_else(|_| Err(WorkerError::Timeout("shadow".to_string())));
+
+            match outcome {
+                Ok(comparison) => {
+                    WORKER_SHADOW_COMPARISONS_TOTAL
+                        .with_label_values(&[&worker_id, &brand, "ok"])
+                        .inc();
+                    WORKER_SHADOW_SENTIMENT_DELTA
+                        .with_label_values(&[&worker_id, &brand])
+                        .observe(comparison.sentiment_delta.abs() as f64);
+                }
+                Err(err) => {
+                    WORKER_SHADOW_COMPARISONS_TOTAL
+                        .with_label_values(&[&worker_id, &brand, "error"])
+                        .inc();
+                    warn!(brand = %brand, chunk_id = %primary.chunk_id, error = %err, "Shadow pipeline failed");
+                }
+            }
+        });
+    }
+
+    /// The next `count` payloads of `brand`'s queue, each run through the same decryption and
+    /// decoding the worker applies, without removing them.
+    pub async fn peek_queue(&self, brand: &str, count: usize) -> WorkerResult<Vec<PayloadInspection>> {
+        let key = format!("{}:{}:chunks", self.settings.redis_queue_prefix, brand);
+        let stop = count.saturating_sub(1) as isize;
+        let payloads = self.redis.lrange(&key, 0, stop).await.map_err(WorkerError::Queue)?;
+        Ok(payloads
+            .into_iter()
+            .enumerate()
+            .map(|(position, payload)| self.inspect_payload(brand, position, payload))
+            .collect())
+    }
+
+    fn inspect_payload(&self, brand: &str, position: usize, payload: String) -> PayloadInspection {
+        let mut inspection = PayloadInspection {
+            position,
+            ..Default::default()
+        };
+        let decoded = self
+            .cipher
+            .decrypt(&payload)
+            .map_err(|err| (FailureReason::Decrypt, WorkerError::Decode(err)))
+            .and_then(|plaintext| decode_envelope(&plaintext).map_err(|err| (FailureReason::JsonDecode, err)));
+        inspection.payload = payload;
+        match decoded {
+            Ok(Envelope::Chunk(chunk)) => {
+                inspection.payload_type = Some("chunk".to_string());
+                if chunk.chunk_id.trim().is_empty() {
+                    inspection.warnings.push("chunkId is empty".to_string());
+                }
+                if chunk.mentions.is_empty() {
+                    inspection.warnings.push("chunk carries no mentions".to_string());
+                }
+                if !chunk.brand.trim().is_empty() && !chunk.brand.eq_ignore_ascii_case(brand) {
+                    inspection
+                        .warnings
+                        .push(format!("chunk brand '{}' differs from queue brand '{brand}'", chunk.brand));
+                }
+                inspection.chunk_id = Some(chunk.chunk_id);
+                inspection.mention_count = Some(chunk.mentions.len());
+                inspection.valid = true;
+            }
+            Ok(Envelope::Reprocess(request)) => {
+                inspection.payload_type = Some("reprocess".to_string());
+                inspection.chunk_id = Some(request.chunk_id);
+                inspection.valid = true;
+            }
+            Err((reason, err)) => {
+                inspection.failure_reason = Some(reason.label().to_string());
+                inspection.error = Some(format!("{}: {err}", reason.message()));
+            }
+        }
+        inspection
+    }
+
+    /// Claims a backfill slot for `brand`; `None` when that brand already has a backfill
+    /// running or `BACKFILL_MAX_CONCURRENT` are. The slot is released when dropped.
+    pub fn try_begin_backfill(self: &Arc<Self>, brand: &str) -> Option<BackfillSlot> {
+        let mut running = self.backfills.lock().expect("backfills poisoned");
+        if running.len() >= self.settings.backfill_max_concurrent || !running.insert(brand.to_string()) {
+            return None;
+        }
+        Some(BackfillSlot {
+            service: self.clone(),
+            brand: brand.to_string(),
+        })
+    }
+
+    pub async fn backfill(&self, request: &BackfillRequest) -> WorkerResult<BackfillReport> {
+        let payloads = self
+            .archive
+            .load_range(&request.brand, request.from, request.to)
+            .await?;
+
+        let mut report = BackfillReport {
+            brand: request.brand.clone(),
+            archived: payloads.len(),
+            result_prefix: self.settings.backfill_result_prefix.clone(),
+            ..Default::default()
+        };
+
+        let pipelines = self.pipelines.load_full();
+        for payload in payloads {
+            let outcome = async {
+                let chunk: Chunk =
+                    serde_json::from_str(&payload).map_err(|err| WorkerError::Decode(err.into()))?;
+                let mut result = pipelines
+                    .backfill
+                    .reprocess_chunk(chunk, &request.brand, request.force_refresh)
+                    .await?;
+                // Archived chunks were enqueued long ago; their latency would only skew live histograms.
+                result.enqueued_at = None;
+                let brand = result.brand.clone();
+                self.backfill_storage.push_result(&brand, &mut result).await?;
+                Ok::<_, WorkerError>(())
+            }
+            .await;
+
+            match outcome {
+                Ok(()) => report.processed += 1,
+                Err(err) => {
+                    report.failed += 1;
+                    self.record_error(&err);
+                    warn!(brand = %request.brand, error = %err, kind = err.kind(), "Backfill chunk failed");
+                }
+            }
+        }
+
+        info!(
+            worker_id = %self.settings.worker_id,
+            brand = %report.brand,
+            archived = report.archived,
+            processed = report.processed,
+            failed = report.failed,
+            "Backfill completed"
+        );
+
+        Ok(report)
+    }
+
     async fn record_failure(
         &self,
         brand: &str,
         reason: FailureReason,
         payload: &str,
-        _error: &str,
         chunk_id: &str,
-    ) -> Result<()> {
+        attempt: u32,
+        err: &WorkerError,
+    ) -> WorkerResult<()> {
+        self.slo.record_failure();
+        self.journal
+            .record(JournalEntry::failed(&self.settings.worker_id, brand, chunk_id, reason.label(), err))
+            .await;
         let failure = FailureRecord {
+            schema_version: FAILURE_RECORD_SCHEMA_VERSION,
             worker_id: self.settings.worker_id.clone(),
             brand: brand.to_string(),
             chunk_id: chunk_id.to_string(),
             reason: reason.message().to_string(),
+            reason_code: reason.label().to_string(),
+            error_kind: err.kind().to_string(),
+            error_chain: err.chain(),
+            stage: err.stage().map(str::to_string),
+            retryable: err.is_retryable(),
+            attempt,
+            failed_at: Utc::now(),
+            payload_bytes: payload.len(),
+            payload_preview: payload.chars().take(self.settings.failure_payload_preview_chars).collect(),
             payload: payload.to_string(),
         };
 
         self.storage
             .record_failure(brand, &failure, reason.label())
             .await
-            .context("record failure")
             .map(|_| ())
     }
 
+    fn record_error(&self, err: &WorkerError) {
+        WORKER_ERRORS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, err.kind()])
+            .inc();
+    }
+
     async fn update_waiting(&self, queues: Option<&[String]>) {
         let mut waiting = self.waiting_since.lock().await;
         let now = Instant::now();
@@ -225,7 +889,7 @@ impl WorkerService {
             .set(0.0);
     }
 
-    pub async fn run(self: Arc<Self>, mut shutdown: broadcast::Receiver<()>) -> Result<()> {
+    pub async fn run(self: Arc<Self>, mut shutdown: broadcast::Receiver<()>) -> WorkerResult<()> {
         loop {
             tokio::select! {
                 _ = shutdown.recv() => {
@@ -234,7 +898,12 @@ impl WorkerService {
                 }
                 result = self.process_next() => {
                     if let Err(err) = result {
-                        warn!(error = %err, "Worker iteration failed");
+                        self.record_error(&err);
+                        warn!(error = %err, kind = err.kind(), "Worker iteration failed");
+                        // Queue errors mean Redis is unreachable; back off instead of spinning on reconnects.
+                        if matches!(err, WorkerError::Queue(_)) {
+                            sleep(self.settings.blpop_timeout).await;
+                        }
                     }
                 }
             }
@@ -244,23 +913,53 @@ impl WorkerService {
     }
 }
 
+/// Enqueue time of each queue's head payload when last peeked, `None` where it was empty or
+/// unreadable.
+#[derive(Default)]
+struct HeadSample {
+    taken_at: Option<Instant>,
+    enqueued_at: HashMap<String, Option<DateTime<Utc>>>,
+}
+
+/// Queue payloads carry a chunk unless their `type` field says otherwise.
+enum Envelope {
+    Chunk(Chunk),
+    Reprocess(ReprocessRequest),
+}
+
+fn decode_envelope(payload: &str) -> WorkerResult<Envelope> {
+    let value: serde_json::Value = serde_json::from_str(payload).map_err(|err| WorkerError::Decode(err.into()))?;
+    let envelope = match value.get("type").and_then(|kind| kind.as_str()) {
+        None | Some("chunk") => serde_json::from_value(value).map(Envelope::Chunk),
+        Some("reprocess") => serde_json::from_value(value).map(Envelope::Reprocess),
+        Some(other) => return Err(WorkerError::Decode(anyhow::anyhow!("unknown payload type '{other}'"))),
+    };
+    envelope.map_err(|err| WorkerError::Decode(err.into()))
+}
+
 #[derive(Debug, Clone, Copy)]
 enum FailureReason {
+    Decrypt,
     JsonDecode,
+    ArchiveMiss,
     Processing,
 }
 
 impl FailureReason {
     fn label(self) -> &'static str {
         match self {
+            Self::Decrypt => "decrypt",
             Self::JsonDecode => "json_decode",
+            Self::ArchiveMiss => "archive_miss",
             Self::Processing => "processing",
         }
     }
 
     fn message(self) -> &'static str {
         match self {
+            Self::Decrypt => "Payload decryption failed",
             Self::JsonDecode => "Invalid JSON",
+            Self::ArchiveMiss => "Archived chunk unavailable",
             Self::Processing => "Processing failed",
         }
     }
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+
+use crate::config::Settings;
+use crate::context::ProcessingContext;
+use crate::http::HttpClient;
+use crate::llm::{build_llm_adapter, InstrumentedLlmAdapter, SeveritySignals};
+use crate::redis_client::RedisClient;
+use crate::types::{ClusterResult, Provenance};
+
+/// Rates each cluster's reputation risk from 0 to 100 out of its volume, negativity and
+/// spike status. `SEVERITY_SCORING=formula` weighs the three with the `SEVERITY_*_WEIGHT`
+/// settings; `llm` asks the LLM provider and falls back to the formula.
+pub struct SeverityScorer {
+    llm: Option<InstrumentedLlmAdapter>,
+    settings: Arc<Settings>,
+}
+
+impl SeverityScorer {
+    pub fn from_settings(
+        settings: &Arc<Settings>,
+        redis: &RedisClient,
+        http: &HttpClient,
+    ) -> anyhow::Result<Option<Self>> {
+        let llm = match settings.severity_scoring.as_deref() {
+            None => return Ok(None),
+            Some("formula") => None,
+            Some("llm") => Some(build_llm_adapter(settings, redis, http)?),
+            Some(other) => bail!("unknown SEVERITY_SCORING mode: {other}"),
+        };
+        Ok(Some(Self {
+            llm,
+            settings: settings.clone(),
+        }))
+    }
+
+    /// Weighted mean of log-scaled volume, negative sentiment share and spike status. A
+    /// spike explained by a scheduled event counts half.
+    pub fn formula_score(&self, signals: &SeveritySignals) -> u8 {
+        let saturation = self.settings.severity_volume_saturation as f32;
+        let volume = ((1.0 + signals.mention_count as f32).ln() / (1.0 + saturation).ln()).min(1.0);
+        let spike = match (signals.spike, signals.known_event) {
+            (false, _) => 0.0,
+            (true, true) => 0.5,
+            (true, false) => 1.0,
+        };
+        let weights = [
+            (self.settings.severity_volume_weight, volume),
+            (self.settings.severity_negativity_weight, signals.negative.clamp(0.0, 1.0)),
+            (self.settings.severity_spike_weight, spike),
+        ];
+        let total: f32 = weights.iter().map(|(weight, _)| weight).sum();
+        if total <= 0.0 {
+            return 0;
+        }
+        let score: f32 = weights.iter().map(|(weight, value)| weight * value).sum::<f32>() / total;
+        (score * 100.0).round().clamp(0.0, 100.0) as u8
+    }
+
+    pub async fn score(&self, job: &ProcessingContext, cluster: &ClusterResult, provenance: &mut Provenance) -> u8 {
+        let signals = SeveritySignals {
+            summary: cluster.summary.clone(),
+            mention_count: cluster.count,
+            negative: cluster.sentiment.get("negative").copied().unwrap_or_default(),
+            spike: cluster.spike,
+            known_event: cluster.known_event.is_some(),
+        };
+        if let Some(llm) = self.llm.as_ref().filter(|_| !job.heuristic_only()) {
+            if let Some(score) = llm.severity(job, &signals, &cluster.examples, provenance).await {
+                return score;
+            }
+        }
+        self.formula_score(&signals)
+    }
+}
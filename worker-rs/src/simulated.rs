@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::bail;
+use async_trait::async_trait;
+use rand::Rng;
+
+use crate::config::Settings;
+use crate::embeddings::{EmbeddingAdapter, NgramEmbeddingAdapter};
+use crate::error::{WorkerError, WorkerResult};
+use crate::llm::{LlmAdapter, MockLlmAdapter};
+
+/// Latency and failure behaviour shared by the simulated providers, from the `SIMULATED_*`
+/// settings. Each call sleeps `SIMULATED_LATENCY_MS` (varied by up to
+/// `SIMULATED_LATENCY_JITTER` either way) plus `SIMULATED_LATENCY_PER_TEXT_MS` per text, then
+/// fails with probability `SIMULATED_ERROR_RATE`. With probability `SIMULATED_STALL_RATE` it
+/// instead hangs for `SIMULATED_STALL_MS` before failing, as a provider that stops responding.
+#[derive(Debug, Clone)]
+struct SimulatedProfile {
+    latency: Duration,
+    jitter: f64,
+    per_text: Duration,
+    error_rate: f64,
+    stall_rate: f64,
+    stall: Duration,
+}
+
+impl SimulatedProfile {
+    fn from_settings(settings: &Settings) -> Self {
+        Self {
+            latency: settings.simulated_latency,
+            jitter: settings.simulated_latency_jitter,
+            per_text: settings.simulated_latency_per_text,
+            error_rate: settings.simulated_error_rate,
+            stall_rate: settings.simulated_stall_rate,
+            stall: settings.simulated_stall,
+        }
+    }
+
+    async fn call(&self, operation: &str, texts: usize) -> anyhow::Result<()> {
+        // Drawn up front: the thread RNG can't be held across an await.
+        let (factor, roll) = {
+            let mut rng = rand::thread_rng();
+            (1.0 + self.jitter * rng.gen_range(-1.0..=1.0), rng.gen::<f64>())
+        };
+        if roll < self.stall_rate {
+            tokio::time::sleep(self.stall).await;
+            bail!("simulated {operation} call stalled");
+        }
+        let latency = self.latency.mul_f64(factor.max(0.0)) + self.per_text.saturating_mul(texts as u32);
+        tokio::time::sleep(latency).await;
+        if roll < self.stall_rate + self.error_rate {
+            bail!("simulated {operation} call failed");
+        }
+        Ok(())
+    }
+}
+
+/// `LLM_PROVIDER=simulated`: the mock provider's deterministic output at a real provider's
+/// pace and failure rate, metered, rate-limited and paced like one, for load tests and
+/// capacity planning without spending tokens.
+pub struct SimulatedLlmAdapter {
+    profile: SimulatedProfile,
+    inner: MockLlmAdapter,
+}
+
+impl SimulatedLlmAdapter {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            profile: SimulatedProfile::from_settings(settings),
+            inner: MockLlmAdapter,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmAdapter for SimulatedLlmAdapter {
+    async fn summarize(&self, brand: &str, texts: &[String]) -> WorkerResult<Option<String>> {
+        self.profile.call("summarize", texts.len()).await.map_err(WorkerError::Llm)?;
+        self.inner.summarize(brand, texts).await
+    }
+
+    async fn sentiment(&self, brand: &str, texts: &[String]) -> WorkerResult<HashMap<String, f32>> {
+        self.profile.call("sentiment", texts.len()).await.map_err(WorkerError::Llm)?;
+        self.inner.sentiment(brand, texts).await
+    }
+
+    async fn sentiment_batch(&self, groups: &[Vec<String>]) -> WorkerResult<Option<Vec<HashMap<String, f32>>>> {
+        self.profile.call("sentiment", groups.iter().map(Vec::len).sum()).await.map_err(WorkerError::Llm)?;
+        self.inner.sentiment_batch(groups).await
+    }
+
+    async fn emotions(&self, texts: &[String]) -> WorkerResult<Option<HashMap<String, f32>>> {
+        self.profile.call("emotions", texts.len()).await.map_err(WorkerError::Llm)?;
+        self.inner.emotions(texts).await
+    }
+
+    async fn intent(&self, texts: &[String]) -> WorkerResult<Option<String>> {
+        self.profile.call("intent", texts.len()).await.map_err(WorkerError::Llm)?;
+        self.inner.intent(texts).await
+    }
+}
+
+/// `EMBEDDINGS_PROVIDER=simulated`: the local n-gram vectors behind simulated provider
+/// latency and failures.
+pub struct SimulatedEmbeddingAdapter {
+    profile: SimulatedProfile,
+    batch_size: usize,
+}
+
+impl SimulatedEmbeddingAdapter {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            profile: SimulatedProfile::from_settings(settings),
+            batch_size: settings.embeddings_batch_size,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for SimulatedEmbeddingAdapter {
+    async fn embed(&self, texts: &[String], brand: &str, chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>> {
+        // One simulated request per batch, as a remote provider would be called.
+        for batch in texts.chunks(self.batch_size) {
+            self.profile.call("embed", batch.len()).await.map_err(WorkerError::Embedding)?;
+        }
+        NgramEmbeddingAdapter.embed(texts, brand, chunk_id).await
+    }
+}
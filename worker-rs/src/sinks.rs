@@ -0,0 +1,404 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::metrics::{
+    WORKER_SINK_BATCH_SIZE, WORKER_SINK_BUFFERED_RECORDS, WORKER_SINK_DROPPED_TOTAL, WORKER_SINK_FLUSH_SECONDS,
+    WORKER_SINK_SPILLED_RECORDS, WORKER_SINK_SPILLED_TOTAL,
+};
+
+#[async_trait]
+pub trait ResultSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn write_batch(&self, records: &[serde_json::Value]) -> WorkerResult<()>;
+}
+
+pub struct HttpSink {
+    url: String,
+    ndjson: bool,
+    timeout: Duration,
+    http: HttpClient,
+}
+
+impl HttpSink {
+    pub fn new(url: String, ndjson: bool, timeout: Duration, http: HttpClient) -> Self {
+        Self {
+            url,
+            ndjson,
+            timeout,
+            http,
+        }
+    }
+}
+
+#[async_trait]
+impl ResultSink for HttpSink {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn write_batch(&self, records: &[serde_json::Value]) -> WorkerResult<()> {
+        let request = if self.ndjson {
+            let body = records
+                .iter()
+                .map(|record| record.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.http
+                .post(&self.url)
+                .header("Content-Type", "application/x-ndjson")
+                .body(body)
+        } else {
+            self.http.post(&self.url).json(records)
+        };
+        self.http
+            .send(request.timeout(self.timeout), "sink batch")
+            .await
+            .map_err(sink_error)?;
+        Ok(())
+    }
+}
+
+/// A batch the sink rejects as malformed (400 or 422) will be rejected again on replay, so
+/// it is not retryable; any other failure is.
+fn sink_error(err: anyhow::Error) -> WorkerError {
+    let rejected = err
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|err| matches!(err.status().map(|status| status.as_u16()), Some(400 | 422)));
+    if rejected {
+        WorkerError::Decode(err)
+    } else {
+        WorkerError::Storage(err)
+    }
+}
+
+/// Records a sink could not hold in memory or deliver, appended as JSON lines to
+/// `<SINK_SPILL_DIR>/<sink>.jsonl` and replayed once the sink accepts batches again, so a
+/// full buffer, an outage or a restart never loses a processed result.
+struct SpillFile {
+    path: PathBuf,
+    sink: String,
+    worker_id: String,
+    /// Records on disk; held across every read and write so appends never race a replay.
+    records: Mutex<usize>,
+}
+
+impl SpillFile {
+    fn open(settings: &Settings, sink: &str) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&settings.sink_spill_dir)
+            .with_context(|| format!("create sink spill directory {}", settings.sink_spill_dir))?;
+        let path = PathBuf::from(&settings.sink_spill_dir).join(format!("{sink}.jsonl"));
+        let records = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.lines().filter(|line| !line.trim().is_empty()).count(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err).with_context(|| format!("read sink spill file {}", path.display())),
+        };
+        if records > 0 {
+            info!(sink, records, path = %path.display(), "Spilled sink records pending replay");
+        }
+        let spill = Self {
+            path,
+            sink: sink.to_string(),
+            worker_id: settings.worker_id.clone(),
+            records: Mutex::new(records),
+        };
+        spill.report(records);
+        Ok(spill)
+    }
+
+    fn report(&self, records: usize) {
+        WORKER_SINK_SPILLED_RECORDS
+            .with_label_values(&[&self.worker_id, &self.sink])
+            .set(records as f64);
+    }
+
+    async fn append(&self, records: &[serde_json::Value], reason: &str) -> anyhow::Result<()> {
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&record.to_string());
+            lines.push('\n');
+        }
+        let mut count = self.records.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("open sink spill file {}", self.path.display()))?;
+        file.write_all(lines.as_bytes())
+            .await
+            .with_context(|| format!("append to sink spill file {}", self.path.display()))?;
+        file.sync_data().await.context("sync sink spill file")?;
+        *count += records.len();
+        self.report(*count);
+        WORKER_SINK_SPILLED_TOTAL
+            .with_label_values(&[&self.worker_id, &self.sink, reason])
+            .inc_by(records.len() as u64);
+        Ok(())
+    }
+
+    /// Delivers spilled records in batches. A failed batch and everything after it stay on
+    /// disk, except a batch the sink rejects as malformed, which is dropped.
+    async fn replay(&self, sink: &dyn ResultSink, batch_size: usize) {
+        let mut count = self.records.lock().await;
+        if *count == 0 {
+            return;
+        }
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!(sink = %self.sink, path = %self.path.display(), error = %err, "Failed to read sink spill file");
+                return;
+            }
+        };
+        let records: Vec<serde_json::Value> = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(err) => {
+                    warn!(sink = %self.sink, error = %err, "Skipping unreadable spilled sink record");
+                    None
+                }
+            })
+            .collect();
+
+        let mut delivered = 0;
+        for batch in records.chunks(batch_size) {
+            let start = Instant::now();
+            let outcome = sink.write_batch(batch).await;
+            let label = match &outcome {
+                Ok(()) => "ok",
+                Err(err) if !err.is_retryable() => "rejected",
+                Err(_) => "error",
+            };
+            WORKER_SINK_FLUSH_SECONDS
+                .with_label_values(&[&self.worker_id, &self.sink, "replay", label])
+                .observe(start.elapsed().as_secs_f64());
+            match outcome {
+                Err(err) if !err.is_retryable() => {
+                    WORKER_SINK_DROPPED_TOTAL
+                        .with_label_values(&[&self.worker_id, &self.sink])
+                        .inc_by(batch.len() as u64);
+                    warn!(
+                        sink = %self.sink,
+                        records = batch.len(),
+                        error = %err,
+                        "Sink rejected spilled batch; dropping it"
+                    );
+                }
+                Err(err) => {
+                    warn!(sink = %self.sink, pending = records.len() - delivered, error = %err, "Sink spill replay failed");
+                    break;
+                }
+                Ok(()) => {}
+            }
+            delivered += batch.len();
+        }
+
+        let remaining = &records[delivered..];
+        let rewritten = if remaining.is_empty() {
+            tokio::fs::remove_file(&self.path).await
+        } else {
+            let lines: String = remaining.iter().map(|record| format!("{record}\n")).collect();
+            tokio::fs::write(&self.path, lines).await
+        };
+        if let Err(err) = rewritten {
+            // The file still holds delivered records; they will be replayed again.
+            warn!(sink = %self.sink, path = %self.path.display(), error = %err, "Failed to rewrite sink spill file");
+            return;
+        }
+        if delivered > 0 {
+            info!(sink = %self.sink, records = delivered, remaining = remaining.len(), "Replayed spilled sink records");
+        }
+        *count = remaining.len();
+        self.report(*count);
+    }
+}
+
+/// Buffers results in memory, at most `SINK_BUFFER_CAPACITY` records, and writes them to
+/// the sink in batches. Overflow and failed batches are spilled to disk and replayed, and
+/// `close` returns only once every record is delivered or spilled.
+pub struct BatchingSink {
+    name: String,
+    sender: Mutex<Option<mpsc::Sender<serde_json::Value>>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+    spill: Arc<SpillFile>,
+    worker_id: String,
+}
+
+impl BatchingSink {
+    pub fn spawn(sink: Arc<dyn ResultSink>, settings: &Settings) -> anyhow::Result<Self> {
+        let (sender, receiver) = mpsc::channel(settings.sink_buffer_capacity);
+        let name = sink.name().to_string();
+        let spill = Arc::new(SpillFile::open(settings, &name)?);
+        let task = tokio::spawn(run_batches(
+            sink,
+            receiver,
+            spill.clone(),
+            settings.sink_batch_size,
+            settings.sink_flush_interval,
+            settings.worker_id.clone(),
+        ));
+        Ok(Self {
+            name,
+            sender: Mutex::new(Some(sender)),
+            task: Mutex::new(Some(task)),
+            spill,
+            worker_id: settings.worker_id.clone(),
+        })
+    }
+
+    pub async fn submit(&self, record: serde_json::Value) {
+        let sender = self.sender.lock().await;
+        let Some(sender) = sender.as_ref() else {
+            return;
+        };
+        let record = match sender.try_send(record) {
+            Ok(()) => return,
+            Err(mpsc::error::TrySendError::Full(record) | mpsc::error::TrySendError::Closed(record)) => record,
+        };
+        if let Err(err) = self.spill.append(std::slice::from_ref(&record), "overflow").await {
+            WORKER_SINK_DROPPED_TOTAL
+                .with_label_values(&[&self.worker_id, &self.name])
+                .inc();
+            warn!(sink = %self.name, error = %err, "Sink buffer full and spill failed; dropping record");
+        }
+    }
+
+    /// Flushes the buffer, spilling whatever the sink does not accept.
+    pub async fn close(&self) {
+        self.sender.lock().await.take();
+        if let Some(task) = self.task.lock().await.take() {
+            task.await.ok();
+        }
+    }
+}
+
+async fn run_batches(
+    sink: Arc<dyn ResultSink>,
+    mut receiver: mpsc::Receiver<serde_json::Value>,
+    spill: Arc<SpillFile>,
+    batch_size: usize,
+    flush_interval: Duration,
+    worker_id: String,
+) {
+    let mut buffer = Vec::with_capacity(batch_size);
+    let mut ticker = tokio::time::interval(flush_interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let buffered = WORKER_SINK_BUFFERED_RECORDS.with_label_values(&[&worker_id, sink.name()]);
+
+    loop {
+        tokio::select! {
+            received = receiver.recv() => match received {
+                Some(record) => {
+                    buffer.push(record);
+                    if buffer.len() >= batch_size {
+                        flush(sink.as_ref(), &spill, &mut buffer, &worker_id, "size").await;
+                    }
+                }
+                None => {
+                    flush(sink.as_ref(), &spill, &mut buffer, &worker_id, "shutdown").await;
+                    buffered.set(0.0);
+                    info!(sink = sink.name(), "Sink flushed on shutdown");
+                    break;
+                }
+            },
+            // The first tick fires at once, replaying what a previous run spilled.
+            _ = ticker.tick() => {
+                if flush(sink.as_ref(), &spill, &mut buffer, &worker_id, "interval").await {
+                    spill.replay(sink.as_ref(), batch_size).await;
+                }
+            }
+        }
+        buffered.set((receiver.len() + buffer.len()) as f64);
+    }
+}
+
+/// Writes the buffer to the sink, spilling it when the write fails and dropping it when the
+/// sink rejects it as malformed. Returns whether the sink answered, or there was nothing to
+/// write.
+async fn flush(
+    sink: &dyn ResultSink,
+    spill: &SpillFile,
+    buffer: &mut Vec<serde_json::Value>,
+    worker_id: &str,
+    trigger: &str,
+) -> bool {
+    if buffer.is_empty() {
+        return true;
+    }
+    let start = Instant::now();
+    let (outcome, delivered) = match sink.write_batch(buffer).await {
+        Ok(()) => ("ok", true),
+        // Spilling a batch the sink will never accept would only replay it forever.
+        Err(err) if !err.is_retryable() => {
+            WORKER_SINK_DROPPED_TOTAL
+                .with_label_values(&[worker_id, sink.name()])
+                .inc_by(buffer.len() as u64);
+            warn!(sink = sink.name(), records = buffer.len(), error = %err, "Sink rejected batch; dropping it");
+            ("rejected", true)
+        }
+        Err(err) => {
+            warn!(sink = sink.name(), records = buffer.len(), error = %err, "Sink batch flush failed; spilling batch");
+            if let Err(err) = spill.append(buffer, "error").await {
+                WORKER_SINK_DROPPED_TOTAL
+                    .with_label_values(&[worker_id, sink.name()])
+                    .inc_by(buffer.len() as u64);
+                warn!(
+                    sink = sink.name(),
+                    records = buffer.len(),
+                    error = %err,
+                    "Failed to spill sink batch; dropping it"
+                );
+            }
+            ("error", false)
+        }
+    };
+    WORKER_SINK_FLUSH_SECONDS
+        .with_label_values(&[worker_id, sink.name(), trigger, outcome])
+        .observe(start.elapsed().as_secs_f64());
+    WORKER_SINK_BATCH_SIZE
+        .with_label_values(&[worker_id, sink.name()])
+        .observe(buffer.len() as f64);
+    buffer.clear();
+    delivered
+}
+
+pub struct SinkSet {
+    sinks: Vec<BatchingSink>,
+}
+
+impl SinkSet {
+    pub fn from_settings(settings: &Settings, http: &HttpClient) -> anyhow::Result<Self> {
+        let mut sinks = Vec::new();
+        if let Some(url) = &settings.sink_http_url {
+            let sink = HttpSink::new(url.clone(), settings.sink_http_ndjson, settings.sink_timeout, http.clone());
+            sinks.push(BatchingSink::spawn(Arc::new(sink), settings)?);
+        }
+        Ok(Self { sinks })
+    }
+
+    pub async fn submit(&self, record: serde_json::Value) {
+        for sink in &self.sinks {
+            sink.submit(record.clone()).await;
+        }
+    }
+
+    pub async fn close(&self) {
+        for sink in &self.sinks {
+            sink.close().await;
+        }
+    }
+}
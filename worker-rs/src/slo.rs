@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Mutex};
+use tracing::{info, warn};
+
+use crate::alerts::{AlertDestination, AlertRouter};
+use crate::config::Settings;
+use crate::metrics::WORKER_SLO_BREACHED;
+
+/// A local SLO check, e.g. p95 processing time above 30s for 10 minutes:
+///
+/// ```json
+/// { "name": "latency", "metric": "processing_time", "quantile": 0.95, "threshold": 30,
+///   "windowSec": 300, "forSec": 600, "destinations": [{ "type": "webhook", "url": "..." }] }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SloRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub objective: SloObjective,
+    pub threshold: f64,
+    /// Span of recent chunks the statistic is computed over.
+    #[serde(default = "default_window_sec")]
+    pub window_sec: u64,
+    /// How long the threshold must stay breached before the alert fires.
+    #[serde(default)]
+    pub for_sec: u64,
+    /// Fewer chunks than this in the window never count as a breach.
+    #[serde(default = "default_min_samples")]
+    pub min_samples: usize,
+    pub destinations: Vec<AlertDestination>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum SloObjective {
+    /// Quantile of end-to-end chunk processing time, in seconds.
+    ProcessingTime {
+        #[serde(default = "default_quantile")]
+        quantile: f64,
+    },
+    /// Share of chunks that ended in the failed queue.
+    FailureRatio,
+}
+
+impl SloObjective {
+    fn label(self) -> &'static str {
+        match self {
+            Self::ProcessingTime { .. } => "processing_time",
+            Self::FailureRatio => "failure_ratio",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SloAlertEvent {
+    pub rule: String,
+    pub worker_id: String,
+    /// `firing` when the breach has lasted `forSec`, `resolved` once it clears.
+    pub status: &'static str,
+    pub metric: &'static str,
+    pub value: f64,
+    pub threshold: f64,
+    pub window_sec: u64,
+    pub samples: usize,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    // `None` for a failed chunk.
+    seconds: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+struct RuleState {
+    breached_since: Option<Instant>,
+    firing: bool,
+}
+
+/// Evaluates `SLO_RULES_FILE` against chunks this worker processed, for deployments without
+/// a Prometheus alerting stack. Alerts go out through the alert router's destinations.
+pub struct SloMonitor {
+    rules: Vec<SloRule>,
+    states: Mutex<Vec<RuleState>>,
+    samples: StdMutex<VecDeque<Sample>>,
+    retention: Duration,
+    alerts: Arc<AlertRouter>,
+    settings: Arc<Settings>,
+}
+
+impl SloMonitor {
+    pub fn from_settings(settings: Arc<Settings>, alerts: Arc<AlertRouter>) -> anyhow::Result<Self> {
+        let rules = match &settings.slo_rules_file {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path).with_context(|| format!("read SLO rules from {path}"))?;
+                serde_json::from_str::<Vec<SloRule>>(&raw).with_context(|| format!("parse SLO rules in {path}"))?
+            }
+            None => Vec::new(),
+        };
+        if !rules.is_empty() {
+            info!(worker_id = %settings.worker_id, rules = rules.len(), "SLO rules loaded");
+        }
+        let retention = Duration::from_secs(rules.iter().map(|rule| rule.window_sec).max().unwrap_or_default());
+        Ok(Self {
+            states: Mutex::new(rules.iter().map(|_| RuleState::default()).collect()),
+            rules,
+            samples: StdMutex::new(VecDeque::new()),
+            retention,
+            alerts,
+            settings,
+        })
+    }
+
+    pub fn record_success(&self, seconds: f64) {
+        self.record(Some(seconds));
+    }
+
+    pub fn record_failure(&self) {
+        self.record(None);
+    }
+
+    fn record(&self, seconds: Option<f64>) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let mut samples = self.samples.lock().expect("SLO samples poisoned");
+        samples.push_back(Sample { at: now, seconds });
+        while samples
+            .front()
+            .is_some_and(|sample| now.duration_since(sample.at) > self.retention)
+        {
+            samples.pop_front();
+        }
+    }
+
+    pub async fn run(&self, mut shutdown: broadcast::Receiver<()>) {
+        if self.rules.is_empty() {
+            return;
+        }
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => {
+                    info!("SLO monitor stopping");
+                    break;
+                }
+                _ = tokio::time::sleep(self.settings.slo_eval_interval) => {
+                    self.evaluate().await;
+                }
+            }
+        }
+    }
+
+    async fn evaluate(&self) {
+        let now = Instant::now();
+        let mut states = self.states.lock().await;
+        for (rule, state) in self.rules.iter().zip(states.iter_mut()) {
+            let window = self.window(now, Duration::from_secs(rule.window_sec));
+            let value = measure(rule.objective, &window);
+            let breached = window.len() >= rule.min_samples.max(1) && value > rule.threshold;
+            WORKER_SLO_BREACHED
+                .with_label_values(&[&self.settings.worker_id, &rule.name])
+                .set(if breached { 1.0 } else { 0.0 });
+
+            if breached {
+                let since = *state.breached_since.get_or_insert(now);
+                if !state.firing && now.duration_since(since) >= Duration::from_secs(rule.for_sec) {
+                    state.firing = true;
+                    self.notify(rule, "firing", value, window.len()).await;
+                }
+            } else {
+                state.breached_since = None;
+                if state.firing {
+                    state.firing = false;
+                    self.notify(rule, "resolved", value, window.len()).await;
+                }
+            }
+        }
+    }
+
+    fn window(&self, now: Instant, span: Duration) -> Vec<Sample> {
+        self.samples
+            .lock()
+            .expect("SLO samples poisoned")
+            .iter()
+            .filter(|sample| now.duration_since(sample.at) <= span)
+            .copied()
+            .collect()
+    }
+
+    async fn notify(&self, rule: &SloRule, status: &'static str, value: f64, samples: usize) {
+        let event = SloAlertEvent {
+            rule: rule.name.clone(),
+            worker_id: self.settings.worker_id.clone(),
+            status,
+            metric: rule.objective.label(),
+            value,
+            threshold: rule.threshold,
+            window_sec: rule.window_sec,
+            samples,
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        warn!(rule = %rule.name, status, metric = event.metric, value, threshold = rule.threshold, "SLO alert");
+        let payload = match serde_json::to_value(&event) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!(rule = %rule.name, error = %err, "Failed to serialise SLO alert event");
+                return;
+            }
+        };
+        let text = format!(
+            "[slo {}] {} on worker {}: {} {:.3} vs threshold {} over {}s ({} chunks)",
+            event.status, event.rule, event.worker_id, event.metric, event.value, event.threshold, event.window_sec, samples
+        );
+        self.alerts.deliver(&rule.name, &rule.destinations, &payload, &text, None).await;
+    }
+}
+
+fn measure(objective: SloObjective, window: &[Sample]) -> f64 {
+    if window.is_empty() {
+        return 0.0;
+    }
+    match objective {
+        SloObjective::ProcessingTime { quantile } => {
+            let mut seconds: Vec<f64> = window.iter().filter_map(|sample| sample.seconds).collect();
+            if seconds.is_empty() {
+                return 0.0;
+            }
+            seconds.sort_by(f64::total_cmp);
+            let rank = (quantile.clamp(0.0, 1.0) * (seconds.len() - 1) as f64).round() as usize;
+            seconds[rank]
+        }
+        SloObjective::FailureRatio => {
+            let failed = window.iter().filter(|sample| sample.seconds.is_none()).count();
+            failed as f64 / window.len() as f64
+        }
+    }
+}
+
+fn default_window_sec() -> u64 {
+    300
+}
+
+fn default_min_samples() -> usize {
+    10
+}
+
+fn default_quantile() -> f64 {
+    0.95
+}
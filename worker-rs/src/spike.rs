@@ -1,14 +1,23 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use anyhow::Result;
-use tracing::info;
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
 
 use crate::config::Settings;
+use crate::error::{WorkerError, WorkerResult};
 use crate::metrics::WORKER_SPIKE_DETECTION_SECONDS;
+use crate::onboarding::brand_state_key;
 use crate::redis_client::RedisClient;
 
+/// Terms hashed into a cluster fingerprint.
+const FINGERPRINT_TERMS: usize = 5;
+
 #[derive(Debug, Default, Clone)]
 pub struct SpikeDetectionResult {
     pub is_spike: bool,
+    pub warming_up: bool,
     pub historical_average: f64,
     pub current_count: usize,
 }
@@ -23,32 +32,46 @@ impl SpikeDetector {
         Self { redis, settings }
     }
 
-    pub async fn detect(&self, brand: &str, cluster_id: i32, current_count: usize) -> Result<SpikeDetectionResult> {
+    /// `examples` are the cluster's example mentions, fingerprinted when
+    /// `SPIKE_HISTORY_KEY=fingerprint`. The count is added to the history, and warm-up
+    /// started, only when `record` is set.
+    pub async fn detect(
+        &self,
+        brand: &str,
+        cluster_id: i32,
+        examples: &[String],
+        current_count: usize,
+        record: bool,
+    ) -> WorkerResult<SpikeDetectionResult> {
         let start = std::time::Instant::now();
-        let history = self
-            .redis
-            .get_spike_history(&self.settings.redis_spike_prefix, brand, cluster_id)
-            .await?;
+        let cluster = self.history_key(cluster_id, examples);
+        let history = if record {
+            self.redis
+                .push_spike_history(
+                    &self.settings.redis_spike_prefix,
+                    brand,
+                    &cluster,
+                    current_count as i64,
+                    self.settings.spike_history_ttl,
+                )
+                .await
+        } else {
+            self.redis
+                .spike_history(&self.settings.redis_spike_prefix, brand, &cluster)
+                .await
+        }
+        .map_err(WorkerError::Spike)?;
+        let (history, source) = self.with_legacy(brand, cluster_id, cluster, history).await;
 
         let historical_average = if history.is_empty() {
-            0.0
+            self.seeded_baseline(brand).await
         } else {
             history.iter().copied().map(|value| value as f64).sum::<f64>() / history.len() as f64
         };
 
-        let threshold = self.settings.max_retries as f64; // placeholder threshold to be tuned later
-        let is_spike = current_count as f64 > threshold.max(historical_average * 2.0);
-
-        self
-            .redis
-            .append_spike_history(
-                &self.settings.redis_spike_prefix,
-                brand,
-                cluster_id,
-                current_count as i64,
-                self.settings.spike_history_ttl,
-            )
-            .await?;
+        // During warm-up counts are only recorded: an empty history would flag nearly everything.
+        let warming_up = self.warming_up(brand, &source, history.len(), record).await;
+        let is_spike = !warming_up && self.exceeds(current_count, historical_average);
 
         let duration = start.elapsed().as_secs_f64();
         WORKER_SPIKE_DETECTION_SECONDS
@@ -59,16 +82,142 @@ impl SpikeDetector {
             worker_id = %self.settings.worker_id,
             brand,
             cluster_id,
+            history_key = %source,
             current_count,
             historical_average,
             is_spike,
+            warming_up,
             "Spike detection evaluated"
         );
 
         Ok(SpikeDetectionResult {
             is_spike,
+            warming_up,
             historical_average,
             current_count,
         })
     }
+
+    /// Whether `current_count` would count as a spike against the recorded history, without
+    /// recording it. Warm-up is not considered.
+    pub async fn peek(
+        &self,
+        brand: &str,
+        cluster_id: i32,
+        examples: &[String],
+        current_count: usize,
+    ) -> WorkerResult<bool> {
+        let cluster = self.history_key(cluster_id, examples);
+        let history = self
+            .redis
+            .spike_history(&self.settings.redis_spike_prefix, brand, &cluster)
+            .await
+            .map_err(WorkerError::Spike)?;
+        let (history, _) = self.with_legacy(brand, cluster_id, cluster, history).await;
+        if history.is_empty() {
+            return Ok(false);
+        }
+        let historical_average = history.iter().copied().map(|value| value as f64).sum::<f64>() / history.len() as f64;
+        Ok(self.exceeds(current_count, historical_average))
+    }
+
+    fn exceeds(&self, current_count: usize, historical_average: f64) -> bool {
+        let threshold = self.settings.max_retries as f64; // placeholder threshold to be tuned later
+        current_count as f64 > threshold.max(historical_average * 2.0)
+    }
+
+    /// Last segment of the cluster's spike history key.
+    fn history_key(&self, cluster_id: i32, examples: &[String]) -> String {
+        match self.settings.spike_history_key.as_str() {
+            "fingerprint" => format!("fp:{}", cluster_fingerprint(examples)),
+            _ => cluster_id.to_string(),
+        }
+    }
+
+    /// Swaps an empty fingerprint history for the one under the cluster number while
+    /// `SPIKE_HISTORY_MIGRATE` is on, returning the history with the key it came from.
+    async fn with_legacy(
+        &self,
+        brand: &str,
+        cluster_id: i32,
+        cluster: String,
+        history: Vec<i64>,
+    ) -> (Vec<i64>, String) {
+        let legacy = cluster_id.to_string();
+        if !history.is_empty() || !self.settings.spike_history_migrate || cluster == legacy {
+            return (history, cluster);
+        }
+        match self
+            .redis
+            .spike_history(&self.settings.redis_spike_prefix, brand, &legacy)
+            .await
+        {
+            Ok(history) if !history.is_empty() => (history, legacy),
+            Ok(_) => (history, cluster),
+            Err(err) => {
+                warn!(brand, cluster_id, error = %err, "Failed to read legacy spike history");
+                (history, cluster)
+            }
+        }
+    }
+
+    async fn warming_up(&self, brand: &str, cluster: &str, history_len: usize, record: bool) -> bool {
+        if history_len < self.settings.spike_warmup_chunks {
+            return true;
+        }
+        if self.settings.spike_warmup.is_zero() {
+            return false;
+        }
+
+        let key = brand_state_key(&self.settings, brand);
+        let field = format!("firstSeen:{cluster}");
+        let now = Utc::now();
+        let first_seen = async {
+            if record {
+                self.redis.hset_nx(&key, &field, &now.timestamp().to_string()).await?;
+            }
+            self.redis.hget(&key, &field).await
+        }
+        .await;
+        match first_seen {
+            // A cluster seen for the first time in a preview would start warming up now.
+            Ok(None) if !record => true,
+            Ok(value) => value
+                .and_then(|raw| raw.parse::<i64>().ok())
+                .is_some_and(|first_seen| now.timestamp() - first_seen < self.settings.spike_warmup.as_secs() as i64),
+            Err(err) => {
+                warn!(brand, cluster, error = %err, "Failed to read spike warm-up start");
+                false
+            }
+        }
+    }
+
+    async fn seeded_baseline(&self, brand: &str) -> f64 {
+        let key = brand_state_key(&self.settings, brand);
+        match self.redis.hget(&key, "spikeBaseline").await {
+            Ok(value) => value.and_then(|raw| raw.parse::<f64>().ok()).unwrap_or(0.0),
+            Err(err) => {
+                warn!(brand, error = %err, "Failed to read seeded spike baseline");
+                0.0
+            }
+        }
+    }
+}
+
+/// Hash of the cluster's most frequent terms of four or more letters, in alphabetical
+/// order, so the same narrative maps to the same spike history whatever number clustering
+/// gave it in a chunk.
+pub fn cluster_fingerprint(texts: &[String]) -> String {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for text in texts {
+        for term in text.split(|c: char| !c.is_alphanumeric()).filter(|term| term.chars().count() >= 4) {
+            *counts.entry(term.to_lowercase()).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let mut terms: Vec<String> = ranked.into_iter().take(FINGERPRINT_TERMS).map(|(term, _)| term).collect();
+    terms.sort();
+    let digest = Sha256::digest(terms.join(" ").as_bytes());
+    digest.iter().take(8).map(|byte| format!("{byte:02x}")).collect()
 }
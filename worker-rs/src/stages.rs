@@ -0,0 +1,1371 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::warn;
+
+use crate::analysis_cache::{AnalysisCache, CachedAnalysis};
+use crate::clustering::{centroid, Clusterer, DistanceMetric};
+use crate::confidence::ConfidenceEstimator;
+use crate::config::Settings;
+use crate::context::ProcessingContext;
+use crate::crypto::PayloadCipher;
+use crate::dedup::MentionDedup;
+use crate::embeddings::{build_embedding_adapter, InstrumentedEmbeddingAdapter};
+use crate::engagement::mention_engagement;
+use crate::error::{WorkerError, WorkerResult};
+use crate::events::EventCalendar;
+use crate::http::HttpClient;
+use crate::labels::ClusterLabels;
+use crate::llm::{build_llm_adapter, simple_emotions, simple_intent, simple_sentiment, InstrumentedLlmAdapter};
+use crate::media::{media_url, MediaCaptioner};
+use crate::metrics::{
+    WORKER_ANALYSIS_CACHE_TOTAL, WORKER_CROSS_CHUNK_DUPLICATES_TOTAL, WORKER_HOOK_DROPPED_MENTIONS_TOTAL,
+    WORKER_EMERGING_NARRATIVES_TOTAL, WORKER_IRRELEVANT_MENTIONS_TOTAL, WORKER_LLM_TIER_TOTAL,
+    WORKER_MEDIA_MENTIONS_TOTAL, WORKER_NOISE_MENTIONS_DROPPED_TOTAL, WORKER_PREPROCESSING_TIME_SECONDS,
+    WORKER_PRODUCT_MENTIONS_TOTAL, WORKER_RECURRING_CLUSTERS_TOTAL, WORKER_TOXIC_CLUSTERS_TOTAL,
+    WORKER_TRIVIAL_CHUNKS_TOTAL, WORKER_VECTOR_EXPORT_TOTAL,
+};
+use crate::noise::NoiseFilter;
+use crate::novelty::NoveltyDetector;
+use crate::prompts::PromptTemplates;
+use crate::ratings::{blend_sentiment, mention_rating};
+use crate::recurrence::{EmittedCluster, RecurrenceDetector};
+use crate::redis_client::RedisClient;
+use crate::relevance::RelevanceClassifier;
+use crate::sampling::sample_indices;
+use crate::severity::SeverityScorer;
+use crate::spike::{SpikeDetectionResult, SpikeDetector};
+use crate::taxonomy::{entity_counts, BrandTaxonomy};
+use crate::tiering::{LlmTiering, CHEAP_TIER};
+use crate::toxicity::ToxicityScorer;
+use crate::types::{Chunk, ChunkMetrics, ClusterResult, Engagement, Mention, Provenance, RatingSummary};
+use crate::vector_store::{build_vector_store, VectorPoint, VectorStore};
+use crate::wasm_hook::PreprocessHook;
+
+static URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://\S+").expect("Invalid URL regex"));
+static WHITESPACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").expect("Invalid whitespace regex"));
+static EMAIL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[\w.+-]+@[\w-]+\.[\w.-]+").expect("Invalid email regex"));
+static PHONE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\+?\d[\d\s().-]{7,}\d").expect("Invalid phone regex"));
+const TOPIC_LIMIT: usize = 10;
+
+/// One step of chunk processing. Stages run in the order given by `PIPELINE_STAGES`
+/// and communicate only through the shared [`StageContext`].
+#[async_trait]
+pub trait PipelineStage: Send + Sync {
+    fn name(&self) -> &str;
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()>;
+
+    /// Fills in default output after `run` was cut short by the stage timeout.
+    fn skip(&self, _ctx: &mut StageContext) {}
+}
+
+pub struct StageContext {
+    pub job: ProcessingContext,
+    pub chunk: Chunk,
+    pub mentions: Vec<PreparedMention>,
+    pub embeddings: Vec<Vec<f32>>,
+    pub clusters: Vec<PendingCluster>,
+    pub results: Vec<ClusterResult>,
+    pub metrics: ChunkMetrics,
+    pub provenance: Provenance,
+    /// Set by a stage that has produced the final result; later stages are skipped.
+    pub complete: bool,
+}
+
+impl StageContext {
+    pub fn texts(&self) -> Vec<String> {
+        self.mentions.iter().map(|mention| mention.text.clone()).collect()
+    }
+}
+
+pub struct PreparedMention {
+    pub text: String,
+    pub source: Mention,
+    /// Probability the mention is about the brand, set by the relevance stage.
+    pub relevance: Option<f32>,
+    /// Product, sub-brand or brand the mention refers to, set by the taxonomy stage.
+    pub entity: Option<String>,
+}
+
+pub struct PendingCluster {
+    pub cluster_id: i32,
+    pub mentions: Vec<String>,
+    pub llm_input: Vec<String>,
+    pub sampling_rate: Option<f32>,
+    pub centroid: Vec<f32>,
+    /// Star ratings of the cluster's review-site mentions.
+    pub ratings: Vec<f32>,
+    /// Mean relevance of the cluster's mentions, when the relevance stage ran.
+    pub relevance: Option<f32>,
+    /// Engagement summed over every mention, not only the sampled ones.
+    pub engagement: Option<Engagement>,
+    /// Mentions per taxonomy entity, when the taxonomy stage ran.
+    pub products: HashMap<String, usize>,
+}
+
+/// Builds the configured stage list. Names not provided by the worker are looked up
+/// among `custom` stages by [`PipelineStage::name`].
+pub fn build_stages(
+    settings: &Arc<Settings>,
+    redis: &RedisClient,
+    cipher: &Arc<PayloadCipher>,
+    http: &HttpClient,
+    custom: &[Arc<dyn PipelineStage>],
+) -> WorkerResult<Vec<Arc<dyn PipelineStage>>> {
+    settings
+        .pipeline_stages
+        .iter()
+        .map(|name| -> WorkerResult<Arc<dyn PipelineStage>> {
+            Ok(match name.as_str() {
+                "preprocess" => Arc::new(PreprocessStage::new(
+                    settings.clone(),
+                    NoiseFilter::from_settings(settings.clone(), redis.clone()).map_err(WorkerError::Config)?,
+                    MentionDedup::new(redis.clone(), settings.clone()),
+                    MediaCaptioner::from_settings(settings, redis, http).map_err(WorkerError::Config)?,
+                    PreprocessHook::from_settings(settings).map_err(WorkerError::Config)?,
+                )),
+                "pii_redact" => Arc::new(PiiRedactStage),
+                "relevance" => Arc::new(RelevanceStage::new(
+                    settings.clone(),
+                    RelevanceClassifier::from_settings(settings, redis, http).map_err(WorkerError::Config)?,
+                )),
+                "taxonomy" => Arc::new(TaxonomyStage::new(
+                    settings.clone(),
+                    BrandTaxonomy::from_settings(settings).map_err(WorkerError::Config)?,
+                )),
+                "embed" => Arc::new(EmbedStage::new(
+                    settings.clone(),
+                    build_embedding_adapter(settings, redis, http).map_err(WorkerError::Config)?,
+                    ToxicityScorer::from_settings(settings).map_err(WorkerError::Config)?,
+                )),
+                "cluster" => Arc::new(ClusterStage::new(settings.clone())),
+                "analyze" => Arc::new(AnalyzeStage::new(
+                    settings.clone(),
+                    build_llm_adapter(settings, redis, http).map_err(WorkerError::Config)?,
+                    RecurrenceDetector::new(redis.clone(), settings.clone(), cipher.clone()),
+                    AnalysisCache::new(
+                        redis.clone(),
+                        settings.clone(),
+                        cipher.clone(),
+                        PromptTemplates::from_settings(settings).map_err(WorkerError::Config)?,
+                    ),
+                    ClusterLabels::new(redis.clone(), settings.clone(), cipher.clone()),
+                    LlmTiering::from_settings(settings, redis, http).map_err(WorkerError::Config)?,
+                    ToxicityScorer::from_settings(settings).map_err(WorkerError::Config)?,
+                )
+                .with_confidence(ConfidenceEstimator::from_settings(settings).map_err(WorkerError::Config)?)),
+                "spike" => Arc::new(SpikeStage::new(
+                    settings.clone(),
+                    SpikeDetector::new(redis.clone(), settings.clone()),
+                    EventCalendar::from_settings(settings.clone(), redis.clone()).map_err(WorkerError::Config)?,
+                    SeverityScorer::from_settings(settings, redis, http).map_err(WorkerError::Config)?,
+                    NoveltyDetector::new(redis.clone(), settings.clone()),
+                )),
+                "vector_export" => Arc::new(VectorExportStage::new(
+                    settings.clone(),
+                    build_vector_store(settings, http).map_err(WorkerError::Config)?,
+                )),
+                other => custom
+                    .iter()
+                    .find(|stage| stage.name() == other)
+                    .cloned()
+                    .ok_or_else(|| WorkerError::Config(anyhow!("unknown pipeline stage '{other}'")))?,
+            })
+        })
+        .collect()
+}
+
+pub struct PreprocessStage {
+    settings: Arc<Settings>,
+    noise: NoiseFilter,
+    dedup: MentionDedup,
+    captioner: Option<MediaCaptioner>,
+    hook: Option<PreprocessHook>,
+}
+
+impl PreprocessStage {
+    pub fn new(
+        settings: Arc<Settings>,
+        noise: NoiseFilter,
+        dedup: MentionDedup,
+        captioner: Option<MediaCaptioner>,
+        hook: Option<PreprocessHook>,
+    ) -> Self {
+        Self {
+            settings,
+            noise,
+            dedup,
+            captioner,
+            hook,
+        }
+    }
+
+    /// Runs the WASM hook over every mention not in `skip`, rewriting mention text in
+    /// place, and returns the indices it dropped. A failing hook fails the chunk rather
+    /// than letting unredacted text through.
+    fn apply_hook(&self, ctx: &mut StageContext, skip: &HashSet<usize>) -> WorkerResult<HashSet<usize>> {
+        let Some(hook) = &self.hook else {
+            return Ok(HashSet::new());
+        };
+        let mut session = hook.session().map_err(WorkerError::Config)?;
+        let mut dropped = HashSet::new();
+        for (index, mention) in ctx.chunk.mentions.iter_mut().enumerate() {
+            if skip.contains(&index) {
+                continue;
+            }
+            match session.apply(&ctx.job.brand, mention).map_err(WorkerError::Config)? {
+                Some(text) => mention.text = text,
+                None => {
+                    dropped.insert(index);
+                }
+            }
+        }
+        Ok(dropped)
+    }
+
+    async fn cross_chunk_duplicates(&self, ctx: &StageContext) -> HashSet<usize> {
+        if !self.dedup.enabled() {
+            return HashSet::new();
+        }
+        let ids: Vec<&str> = ctx.chunk.mentions.iter().map(|mention| mention.id.as_str()).collect();
+        match self.dedup.duplicates(&ctx.job.brand, &ctx.job.chunk_id, &ids, !ctx.job.dry_run).await {
+            Ok(duplicates) => duplicates,
+            Err(err) => {
+                warn!(
+                    brand = %ctx.job.brand,
+                    chunk_id = %ctx.job.chunk_id,
+                    correlation_id = %ctx.job.correlation_id,
+                    error = %err,
+                    "Cross-chunk dedup failed; keeping all mentions"
+                );
+                HashSet::new()
+            }
+        }
+    }
+
+}
+
+#[async_trait]
+impl PipelineStage for PreprocessStage {
+    fn name(&self) -> &str {
+        "preprocess"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        let start = Instant::now();
+        let noise = self.noise.list(&ctx.job.brand).await;
+        let duplicates = self.cross_chunk_duplicates(ctx).await;
+        let hook_dropped = self.apply_hook(ctx, &duplicates)?;
+        if self.hook.is_some() {
+            ctx.metrics.hook_dropped = Some(hook_dropped.len());
+            WORKER_HOOK_DROPPED_MENTIONS_TOTAL
+                .with_label_values(&[&self.settings.worker_id, &ctx.job.brand])
+                .inc_by(hook_dropped.len() as u64);
+        }
+        let mut texts = Vec::with_capacity(ctx.chunk.mentions.len());
+        let mut media = Vec::new();
+        for (index, mention) in ctx.chunk.mentions.iter().enumerate() {
+            if duplicates.contains(&index) || hook_dropped.contains(&index) {
+                continue;
+            }
+            let cleaned = clean_text(&mention.text);
+            if !cleaned.is_empty() {
+                texts.push((index, cleaned));
+            } else if let Some(url) = media_url(mention, &self.settings.media_metadata_key) {
+                media.push((index, url));
+            }
+        }
+
+        // Media-only mentions join the text ones once captioned; the rest are only counted.
+        let mut captioned = 0;
+        if let Some(captioner) = self.captioner.as_ref().filter(|_| !ctx.job.heuristic_only()) {
+            for (index, url) in media.iter().take(self.settings.media_caption_max_per_chunk) {
+                let Some(caption) = captioner.caption(&ctx.job, url, &mut ctx.provenance).await else {
+                    continue;
+                };
+                let cleaned = clean_text(&caption);
+                if !cleaned.is_empty() {
+                    texts.push((*index, cleaned));
+                    captioned += 1;
+                }
+            }
+        }
+        if !media.is_empty() {
+            ctx.metrics.media_mentions = Some(media.len());
+            for (outcome, count) in [("captioned", captioned), ("uncaptioned", media.len() - captioned)] {
+                WORKER_MEDIA_MENTIONS_TOTAL
+                    .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, outcome])
+                    .inc_by(count as u64);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut dropped = 0;
+        for (index, cleaned) in texts {
+            // Promotional boilerplate would otherwise pull unrelated posts into one cluster.
+            let candidate = noise.strip(&cleaned);
+            if candidate.is_empty() {
+                dropped += 1;
+                continue;
+            }
+            if seen.insert(candidate.clone()) {
+                ctx.mentions.push(PreparedMention {
+                    text: candidate,
+                    source: ctx.chunk.mentions[index].clone(),
+                    relevance: None,
+                    entity: None,
+                });
+            }
+        }
+
+        if self.dedup.enabled() {
+            ctx.metrics.cross_chunk_duplicates = Some(duplicates.len());
+            WORKER_CROSS_CHUNK_DUPLICATES_TOTAL
+                .with_label_values(&[&self.settings.worker_id, &ctx.job.brand])
+                .inc_by(duplicates.len() as u64);
+        }
+        if dropped > 0 {
+            WORKER_NOISE_MENTIONS_DROPPED_TOTAL
+                .with_label_values(&[&self.settings.worker_id, &ctx.job.brand])
+                .inc_by(dropped);
+        }
+
+        let duration = start.elapsed();
+        ctx.metrics.preprocessing_time_ms = duration.as_secs_f64() * 1000.0;
+        WORKER_PREPROCESSING_TIME_SECONDS
+            .with_label_values(&[&self.settings.worker_id, &ctx.job.brand])
+            .observe(duration.as_secs_f64());
+        Ok(())
+    }
+}
+
+/// Mention text as clustered: URLs removed, whitespace collapsed, lowercased.
+pub fn clean_text(text: &str) -> String {
+    let without_urls = URL_RE.replace_all(text, "");
+    let normalized = WHITESPACE_RE.replace_all(without_urls.trim(), " ");
+    normalized.to_lowercase()
+}
+
+pub struct PiiRedactStage;
+
+#[async_trait]
+impl PipelineStage for PiiRedactStage {
+    fn name(&self) -> &str {
+        "pii_redact"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        for mention in &mut ctx.mentions {
+            mention.text = redact_pii(&mention.text);
+        }
+        Ok(())
+    }
+}
+
+/// Email addresses and phone numbers replaced with `[email]` and `[phone]`.
+pub fn redact_pii(text: &str) -> String {
+    let redacted = EMAIL_RE.replace_all(text, "[email]");
+    PHONE_RE.replace_all(&redacted, "[phone]").into_owned()
+}
+
+/// Drops mentions scored below `RELEVANCE_THRESHOLD`. With `RELEVANCE_ACTION=downweight`
+/// they stay in their clusters but are left out of the LLM input.
+pub struct RelevanceStage {
+    settings: Arc<Settings>,
+    classifier: RelevanceClassifier,
+}
+
+impl RelevanceStage {
+    pub fn new(settings: Arc<Settings>, classifier: RelevanceClassifier) -> Self {
+        Self { settings, classifier }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for RelevanceStage {
+    fn name(&self) -> &str {
+        "relevance"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        if ctx.mentions.is_empty() {
+            return Ok(());
+        }
+        let texts = ctx.texts();
+        let scores = self
+            .classifier
+            .classify(&ctx.job, &texts, &mut ctx.provenance)
+            .await;
+        let threshold = self.settings.relevance_threshold;
+        let drop = self.settings.relevance_action == "drop";
+        let irrelevant = scores.iter().filter(|score| **score < threshold).count();
+
+        let mut scores = scores.into_iter();
+        ctx.mentions.retain_mut(|mention| {
+            let score = scores.next().unwrap_or(1.0);
+            mention.relevance = Some(score);
+            !drop || score >= threshold
+        });
+        if drop {
+            ctx.metrics.irrelevant_dropped = Some(irrelevant);
+        } else {
+            ctx.metrics.irrelevant_downweighted = Some(irrelevant);
+        }
+        WORKER_IRRELEVANT_MENTIONS_TOTAL
+            .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, &self.settings.relevance_action])
+            .inc_by(irrelevant as u64);
+        Ok(())
+    }
+}
+
+pub struct TaxonomyStage {
+    settings: Arc<Settings>,
+    taxonomy: BrandTaxonomy,
+}
+
+impl TaxonomyStage {
+    pub fn new(settings: Arc<Settings>, taxonomy: BrandTaxonomy) -> Self {
+        Self { settings, taxonomy }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for TaxonomyStage {
+    fn name(&self) -> &str {
+        "taxonomy"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        for mention in &mut ctx.mentions {
+            mention.entity = self.taxonomy.resolve(&ctx.job.brand, &mention.text);
+            if let Some(entity) = &mention.entity {
+                WORKER_PRODUCT_MENTIONS_TOTAL
+                    .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, entity])
+                    .inc();
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct EmbedStage {
+    settings: Arc<Settings>,
+    embeddings: InstrumentedEmbeddingAdapter,
+    toxicity: Option<ToxicityScorer>,
+}
+
+impl EmbedStage {
+    pub fn new(
+        settings: Arc<Settings>,
+        embeddings: InstrumentedEmbeddingAdapter,
+        toxicity: Option<ToxicityScorer>,
+    ) -> Self {
+        Self {
+            settings,
+            embeddings,
+            toxicity,
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for EmbedStage {
+    fn name(&self) -> &str {
+        "embed"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        // Zero or one mention needs neither embeddings nor LLM calls; answer directly.
+        if ctx.mentions.len() <= 1 {
+            let kind = if ctx.mentions.is_empty() { "empty" } else { "single" };
+            WORKER_TRIVIAL_CHUNKS_TOTAL
+                .with_label_values(&[&self.settings.worker_id, &ctx.job.brand, kind])
+                .inc();
+            let texts = ctx.texts();
+            let rating = RatingSummary::from_ratings(
+                &ctx.mentions
+                    .iter()
+                    .filter_map(|mention| mention_rating(&mention.source, &self.settings.rating_metadata_key))
+                    .collect::<Vec<_>>(),
+            );
+            let sentiment = simple_sentiment(&texts);
+            let sentiment = match &rating {
+                Some(rating) => blend_sentiment(&sentiment, rating, texts.len(), self.settings.rating_sentiment_weight),
+                None => sentiment,
+            };
+            ctx.results = texts
+                .first()
+                .map(|text| ClusterResult {
+                    cluster_id: 1,
+                    count: 1,
+                    examples: vec![text.clone()],
+                    summary: Some(text.clone()),
+                    spike: false,
+                    sentiment,
+                    topics: Some(vec![text.clone()]),
+                    recurring_of: None,
+                    sampling_rate: None,
+                    known_event: None,
+                    stable_id: None,
+                    label: None,
+                    llm_tier: None,
+                    emotions: self.settings.emotions_enabled.then(|| simple_emotions(&texts)),
+                    rating,
+                    toxicity: self.toxicity.as_ref().map(|scorer| scorer.lexicon_score(&texts)),
+                    entities: None,
+                    relevance: ctx.mentions.first().and_then(|mention| mention.relevance),
+                    intent: self.settings.intent_enabled.then(|| simple_intent(&texts)),
+                    quotes: self.settings.quotes_enabled.then(|| vec![text.clone()]),
+                    confidence: None,
+                    severity: None,
+                    engagement: Engagement::combine(
+                        ctx.mentions.iter().filter_map(|mention| mention_engagement(&mention.source)),
+                    ),
+                    products: ctx
+                        .mentions
+                        .first()
+                        .and_then(|mention| mention.entity.clone())
+                        .map(|entity| HashMap::from([(entity, 1)])),
+                    emerging: None,
+                })
+                .into_iter()
+                .collect();
+            ctx.provenance.clustering_algorithm = Some("trivial".to_string());
+            ctx.complete = true;
+            return Ok(());
+        }
+
+        ctx.provenance.embedding_provider = Some(self.settings.embeddings_provider.clone());
+        ctx.provenance.embedding_model = self.settings.embedding_model().map(str::to_string);
+        let start = Instant::now();
+        ctx.embeddings = self
+            .embeddings
+            .embed(&ctx.texts(), &ctx.job, &mut ctx.provenance)
+            .await;
+        ctx.metrics.embedding_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        Ok(())
+    }
+}
+
+pub struct ClusterStage {
+    settings: Arc<Settings>,
+    clusterer: Clusterer,
+    metric: DistanceMetric,
+}
+
+impl ClusterStage {
+    pub fn new(settings: Arc<Settings>) -> Self {
+        let clusterer = Clusterer::new(&settings);
+        let metric = DistanceMetric::from_settings(&settings);
+        Self {
+            settings,
+            clusterer,
+            metric,
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for ClusterStage {
+    fn name(&self) -> &str {
+        "cluster"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        let output = self
+            .clusterer
+            .cluster(&ctx.embeddings, &ctx.job)
+            .await;
+        ctx.metrics.clustering_time_ms = output.duration_ms;
+        ctx.provenance.clustering_algorithm = Some(self.clusterer.algorithm().to_string());
+        ctx.provenance.clustering_params = self.clusterer.params();
+
+        let timestamps: Vec<DateTime<Utc>> = ctx.mentions.iter().map(|mention| mention.source.created_at).collect();
+        let text_at = |idx: usize| ctx.mentions.get(idx).map(|mention| mention.text.clone());
+        let rating_at = |idx: usize| {
+            ctx.mentions
+                .get(idx)
+                .and_then(|mention| mention_rating(&mention.source, &self.settings.rating_metadata_key))
+        };
+
+        let engagement_at = |idx: usize| ctx.mentions.get(idx).and_then(|mention| mention_engagement(&mention.source));
+        let relevance_at = |idx: usize| ctx.mentions.get(idx).and_then(|mention| mention.relevance);
+        let entity_at = |idx: usize| ctx.mentions.get(idx).and_then(|mention| mention.entity.as_deref());
+        let threshold = self.settings.relevance_threshold;
+
+        ctx.clusters = output
+            .clusters
+            .into_iter()
+            .map(|group| {
+                let cluster_mentions: Vec<String> = group.indices.iter().filter_map(|&idx| text_at(idx)).collect();
+                // Down-weighted off-topic mentions stay counted but don't steer the summary.
+                let relevant: Vec<usize> = group
+                    .indices
+                    .iter()
+                    .copied()
+                    .filter(|&idx| relevance_at(idx).is_none_or(|score| score >= threshold))
+                    .collect();
+                let input_indices = if relevant.is_empty() { &group.indices } else { &relevant };
+                let (llm_input, sampling_rate) = if input_indices.len() > self.settings.cluster_sample_threshold {
+                    let sampled = sample_indices(
+                        input_indices,
+                        &timestamps,
+                        &ctx.embeddings,
+                        self.settings.cluster_sample_size,
+                        self.metric,
+                    );
+                    let rate = sampled.len() as f32 / group.indices.len() as f32;
+                    let texts = sampled.iter().filter_map(|&idx| text_at(idx)).collect();
+                    (texts, Some(rate))
+                } else if input_indices.len() < group.indices.len() {
+                    (input_indices.iter().filter_map(|&idx| text_at(idx)).collect(), None)
+                } else {
+                    (cluster_mentions.clone(), None)
+                };
+                let scores: Vec<f32> = group.indices.iter().filter_map(|&idx| relevance_at(idx)).collect();
+                PendingCluster {
+                    cluster_id: group.cluster_id,
+                    mentions: cluster_mentions,
+                    llm_input,
+                    sampling_rate,
+                    centroid: centroid(&ctx.embeddings, &group.indices),
+                    ratings: group.indices.iter().filter_map(|&idx| rating_at(idx)).collect(),
+                    relevance: (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32),
+                    engagement: Engagement::combine(group.indices.iter().filter_map(|&idx| engagement_at(idx))),
+                    products: entity_counts(group.indices.iter().filter_map(|&idx| entity_at(idx))),
+                }
+            })
+            .filter(|pending| !pending.mentions.is_empty())
+            .collect();
+        Ok(())
+    }
+}
+
+pub struct AnalyzeStage {
+    settings: Arc<Settings>,
+    llm: InstrumentedLlmAdapter,
+    recurrence: RecurrenceDetector,
+    analysis_cache: AnalysisCache,
+    labels: ClusterLabels,
+    tiering: Option<LlmTiering>,
+    toxicity: Option<ToxicityScorer>,
+    confidence: Option<ConfidenceEstimator>,
+}
+
+impl AnalyzeStage {
+    pub fn new(
+        settings: Arc<Settings>,
+        llm: InstrumentedLlmAdapter,
+        recurrence: RecurrenceDetector,
+        analysis_cache: AnalysisCache,
+        labels: ClusterLabels,
+        tiering: Option<LlmTiering>,
+        toxicity: Option<ToxicityScorer>,
+    ) -> Self {
+        Self {
+            settings,
+            llm,
+            recurrence,
+            analysis_cache,
+            labels,
+            tiering,
+            toxicity,
+            confidence: None,
+        }
+    }
+
+    pub fn with_confidence(mut self, confidence: Option<ConfidenceEstimator>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    async fn cached_analysis(
+        &self,
+        brand: &str,
+        chunk_id: &str,
+        model: &str,
+        llm_input: &[String],
+    ) -> Option<CachedAnalysis> {
+        let cached = self.analysis_cache.get(brand, model, llm_input).await.unwrap_or_else(|err| {
+            warn!(brand, chunk_id, error = %err, "Failed to read cached cluster analysis");
+            None
+        });
+        WORKER_ANALYSIS_CACHE_TOTAL
+            .with_label_values(&[&self.settings.worker_id, brand, if cached.is_some() { "hit" } else { "miss" }])
+            .inc();
+        cached
+    }
+
+    fn fallback(&self, ctx: &StageContext) -> ClusterResult {
+        // Fallback: treat all mentions as a single cluster.
+        let examples = ctx
+            .mentions
+            .iter()
+            .take(self.settings.preprocessing_examples)
+            .map(|mention| mention.text.clone())
+            .collect::<Vec<_>>();
+        ClusterResult {
+            cluster_id: 1,
+            count: ctx.mentions.len(),
+            examples: examples.clone(),
+            summary: examples.first().cloned(),
+            spike: false,
+            sentiment: HashMap::from([
+                ("positive".to_string(), 0.33),
+                ("negative".to_string(), 0.33),
+                ("neutral".to_string(), 0.34),
+            ]),
+            topics: Some(examples),
+            recurring_of: None,
+            sampling_rate: None,
+            known_event: None,
+            stable_id: None,
+            label: None,
+            llm_tier: None,
+            emotions: None,
+            rating: None,
+            toxicity: None,
+            entities: None,
+            relevance: None,
+            intent: None,
+            quotes: None,
+            confidence: None,
+            severity: None,
+            engagement: None,
+            products: None,
+            emerging: None,
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for AnalyzeStage {
+    fn name(&self) -> &str {
+        "analyze"
+    }
+
+    fn skip(&self, ctx: &mut StageContext) {
+        if ctx.results.is_empty() {
+            ctx.results.push(self.fallback(ctx));
+            ctx.provenance.record_fallback("analyze", "single_cluster");
+        }
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        let job = &ctx.job;
+        let brand = job.brand.as_str();
+        let chunk_id = job.chunk_id.as_str();
+        let groups = std::mem::take(&mut ctx.clusters);
+        let heuristic_only = job.heuristic_only();
+        ctx.provenance.llm_provider = Some(self.llm.provider().to_string());
+        ctx.provenance.llm_model = self.settings.llm_model().map(str::to_string);
+
+        let recent_clusters = if self.recurrence.enabled() {
+            self.recurrence.recent(brand).await.unwrap_or_else(|err| {
+                warn!(brand, chunk_id, error = %err, "Failed to load recent cluster centroids");
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        let cache_model = if self.analysis_cache.enabled() {
+            self.llm.effective_model(job).await
+        } else {
+            String::new()
+        };
+        let mut cached = Vec::with_capacity(groups.len());
+        for pending in &groups {
+            cached.push(if ctx.job.reuse_cached {
+                self.cached_analysis(brand, chunk_id, &cache_model, &pending.llm_input).await
+            } else {
+                None
+            });
+        }
+
+        let mut tiers = Vec::with_capacity(groups.len());
+        for pending in &groups {
+            tiers.push(match &self.tiering {
+                Some(tiering) => Some(tiering.tier(brand, pending).await),
+                None => None,
+            });
+        }
+        // Cheap-tier clusters never join the batch, which goes to the premium provider.
+        let batchable = |(entry, tier): &(&Option<CachedAnalysis>, &Option<&str>)| {
+            entry.is_none() && **tier != Some(CHEAP_TIER)
+        };
+        let uncached = cached.iter().zip(&tiers).filter(batchable).count();
+        let batch_texts = || -> Vec<Vec<String>> {
+            groups
+                .iter()
+                .zip(cached.iter().zip(&tiers))
+                .filter(|(_, entry)| batchable(entry))
+                .map(|(pending, _)| pending.llm_input.clone())
+                .collect()
+        };
+
+        let batch_fallbacks = ctx.provenance.fallback_calls;
+        let mut batch_ms = 0.0;
+        let max_clusters = self.settings.llm_max_clusters_per_request;
+        // Brands with prompt templates are analysed with the separate, templated calls.
+        let whole_cluster_prompts = self.analysis_cache.prompts().whole_cluster_prompts(brand);
+        let batch_analysis = max_clusters > 1 && !heuristic_only && whole_cluster_prompts;
+        let mut batched_analysis = if batch_analysis && uncached > 1 {
+            let batch_start = Instant::now();
+            let mut analyses = Vec::with_capacity(uncached);
+            for texts in batch_texts().chunks(max_clusters) {
+                match self.llm.analyze_batch(job, texts, &mut ctx.provenance).await {
+                    Some(batch) => analyses.extend(batch.into_iter().map(Some)),
+                    None => {
+                        warn!(
+                            worker_id = %self.settings.worker_id,
+                            brand,
+                            chunk_id,
+                            clusters = texts.len(),
+                            "Batch analysis unavailable; falling back to per-cluster calls"
+                        );
+                        analyses.extend(texts.iter().map(|_| None));
+                    }
+                }
+            }
+            batch_ms = batch_start.elapsed().as_secs_f64() * 1000.0 / uncached as f64;
+            Some(analyses.into_iter())
+        } else {
+            None
+        };
+
+        // Combined analysis already scores sentiment, so a separate batch call would be wasted.
+        let combined = self.settings.llm_combined_analysis && whole_cluster_prompts;
+        let batch = self.settings.llm_batch_sentiment
+            && !combined
+            && !batch_analysis
+            && !heuristic_only
+            && !self.analysis_cache.prompts().sentiment_templated(brand);
+        let mut batched_sentiment = if batch && uncached > 1 {
+            let batch_start = Instant::now();
+            let scores = self.llm.sentiment_batch(job, &batch_texts(), &mut ctx.provenance).await;
+            batch_ms = batch_start.elapsed().as_secs_f64() * 1000.0 / uncached as f64;
+            if scores.is_none() {
+                warn!(
+                    worker_id = %self.settings.worker_id,
+                    brand,
+                    chunk_id,
+                    clusters = uncached,
+                    "Batch sentiment unavailable; falling back to per-cluster calls"
+                );
+            }
+            scores.map(|scores| scores.into_iter())
+        } else {
+            None
+        };
+
+        let batch_degraded = ctx.provenance.fallback_calls > batch_fallbacks;
+        let mut results = Vec::with_capacity(groups.len());
+        let mut llm_time_ms = 0.0;
+        let mut cached = cached.into_iter();
+        let mut tiers = tiers.into_iter();
+        for PendingCluster {
+            cluster_id,
+            mentions: cluster_mentions,
+            llm_input,
+            sampling_rate,
+            centroid: cluster_centroid,
+            ratings,
+            relevance,
+            engagement,
+            products,
+        } in &groups
+        {
+            let (cluster_id, sampling_rate, relevance, engagement) = (*cluster_id, *sampling_rate, *relevance, *engagement);
+            let cached_analysis = cached.next().flatten();
+            let from_cache = cached_analysis.is_some();
+            let tier = tiers.next().flatten();
+            let cluster_fallbacks = ctx.provenance.fallback_calls;
+            let llm = match (&self.tiering, tier) {
+                (Some(tiering), Some(CHEAP_TIER)) => tiering.cheap(),
+                _ => &self.llm,
+            };
+            if let Some(tier) = tier {
+                WORKER_LLM_TIER_TOTAL
+                    .with_label_values(&[&self.settings.worker_id, brand, tier])
+                    .inc();
+            }
+            let examples = llm_input
+                .iter()
+                .take(self.settings.preprocessing_examples)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let recurring = self
+                .recurrence
+                .find_match(&recent_clusters, cluster_centroid, cluster_mentions.len());
+            let suppress_summary = recurring.is_some() && self.settings.cluster_recurrence_suppress_llm;
+            if recurring.is_some() {
+                WORKER_RECURRING_CLUSTERS_TOTAL
+                    .with_label_values(&[&self.settings.worker_id, brand, if suppress_summary { "true" } else { "false" }])
+                    .inc();
+            }
+
+            let llm_start = Instant::now();
+            let mut llm_topics = Vec::new();
+            let analysed = match cached_analysis {
+                Some(analysis) => (
+                    analysis.summary,
+                    analysis.sentiment,
+                    analysis.emotions,
+                    analysis.toxicity,
+                    analysis.entities,
+                    analysis.intent,
+                    analysis.quotes,
+                    analysis.confidence,
+                ),
+                None => {
+                    let batched = batched_analysis
+                        .as_mut()
+                        .filter(|_| tier != Some(CHEAP_TIER))
+                        .and_then(|analyses| analyses.next())
+                        .flatten();
+                    let analysis = match batched {
+                        Some(analysis) => Some(analysis),
+                        None if combined && !suppress_summary => {
+                            llm.analyze(job, llm_input, &mut ctx.provenance).await
+                        }
+                        None => None,
+                    };
+                    let (summary, sentiment) = match analysis {
+                        Some(analysis) => {
+                            llm_topics = analysis.topics;
+                            let summary = match &recurring {
+                                Some(prior) if suppress_summary => prior.summary.clone(),
+                                _ => analysis.summary,
+                            };
+                            (summary, analysis.sentiment)
+                        }
+                        None => {
+                            let summary = match &recurring {
+                                Some(prior) if suppress_summary => prior.summary.clone(),
+                                _ => llm.summarize(job, llm_input, &mut ctx.provenance).await,
+                            };
+                            let batched = batched_sentiment
+                                .as_mut()
+                                .filter(|_| tier != Some(CHEAP_TIER))
+                                .and_then(|scores| scores.next());
+                            let sentiment = match batched {
+                                Some(sentiment) => sentiment,
+                                None => llm.sentiment(job, llm_input, &mut ctx.provenance).await,
+                            };
+                            (summary, sentiment)
+                        }
+                    };
+                    let emotions = if self.settings.emotions_enabled {
+                        Some(llm.emotions(job, llm_input, &mut ctx.provenance).await)
+                    } else {
+                        None
+                    };
+                    let toxicity = match &self.toxicity {
+                        Some(scorer) => Some(scorer.score(llm, job, llm_input, &mut ctx.provenance).await),
+                        None => None,
+                    };
+                    let entities = if self.settings.entities_enabled {
+                        llm.entities(job, llm_input, &mut ctx.provenance).await
+                    } else {
+                        None
+                    };
+                    let intent = if self.settings.intent_enabled {
+                        Some(llm.intent(job, llm_input, &mut ctx.provenance).await)
+                    } else {
+                        None
+                    };
+                    let quotes = if self.settings.quotes_enabled {
+                        llm.quotes(job, llm_input, &mut ctx.provenance).await
+                    } else {
+                        None
+                    };
+                    let confidence = match &self.confidence {
+                        Some(estimator) => {
+                            estimator
+                                .estimate(llm, job, llm_input, summary.as_deref(), &sentiment, &mut ctx.provenance)
+                                .await
+                        }
+                        None => None,
+                    };
+                    // Cheap-tier and heuristic output must not be served later to a cluster that
+                    // needs the premium model, nor fallback output from a provider outage.
+                    let degraded = batch_degraded || ctx.provenance.fallback_calls > cluster_fallbacks;
+                    if self.analysis_cache.enabled()
+                        && tier != Some(CHEAP_TIER)
+                        && !heuristic_only
+                        && !degraded
+                        && !job.dry_run
+                    {
+                        let analysis = CachedAnalysis {
+                            summary: summary.clone(),
+                            sentiment: sentiment.clone(),
+                            emotions: emotions.clone(),
+                            toxicity,
+                            entities: entities.clone(),
+                            intent: intent.clone(),
+                            quotes: quotes.clone(),
+                            confidence,
+                        };
+                        if let Err(err) = self.analysis_cache.put(brand, &cache_model, llm_input, &analysis).await {
+                            warn!(brand, chunk_id, cluster_id, error = %err, "Failed to cache cluster analysis");
+                        }
+                    }
+                    llm_time_ms += llm_start.elapsed().as_secs_f64() * 1000.0 + batch_ms;
+                    (summary, sentiment, emotions, toxicity, entities, intent, quotes, confidence)
+                }
+            };
+            let (summary, sentiment, emotions, toxicity, entities, intent, quotes, confidence) = analysed;
+            // Scored before star ratings are blended in, since it rates the LLM's own reading.
+            // Only cached entries are backfilled; a failed fresh estimate is not retried here.
+            let confidence = match (&self.confidence, confidence) {
+                (Some(estimator), None) if from_cache => {
+                    estimator
+                        .estimate(llm, job, llm_input, summary.as_deref(), &sentiment, &mut ctx.provenance)
+                        .await
+                }
+                (Some(_), confidence) => confidence,
+                (None, _) => None,
+            };
+            let rating = RatingSummary::from_ratings(ratings);
+            let sentiment = match &rating {
+                Some(rating) => blend_sentiment(
+                    &sentiment,
+                    rating,
+                    cluster_mentions.len(),
+                    self.settings.rating_sentiment_weight,
+                ),
+                None => sentiment,
+            };
+            // Entries cached before emotions were enabled don't carry them.
+            let emotions = match emotions {
+                None if self.settings.emotions_enabled => Some(llm.emotions(job, llm_input, &mut ctx.provenance).await),
+                emotions => emotions,
+            };
+            let toxicity = match (&self.toxicity, toxicity) {
+                (Some(scorer), None) => Some(scorer.score(llm, job, llm_input, &mut ctx.provenance).await),
+                (Some(_), toxicity) => toxicity,
+                (None, _) => None,
+            };
+            // Entries cached before entities were enabled don't carry them; a failed fresh call
+            // is not retried here.
+            let entities = match entities {
+                None if from_cache && self.settings.entities_enabled => {
+                    llm.entities(job, llm_input, &mut ctx.provenance).await
+                }
+                entities => entities,
+            }
+            .filter(|entities| !entities.is_empty());
+            let intent = match intent {
+                None if self.settings.intent_enabled => Some(llm.intent(job, llm_input, &mut ctx.provenance).await),
+                intent => intent,
+            };
+            let quotes = match quotes {
+                None if from_cache && self.settings.quotes_enabled => {
+                    llm.quotes(job, llm_input, &mut ctx.provenance).await
+                }
+                quotes => quotes,
+            };
+            if toxicity.is_some_and(|score| score >= self.settings.toxicity_threshold) {
+                WORKER_TOXIC_CLUSTERS_TOTAL
+                    .with_label_values(&[&self.settings.worker_id, brand])
+                    .inc();
+            }
+
+            let topics = if llm_topics.is_empty() {
+                llm_input.iter().take(TOPIC_LIMIT).cloned().collect::<Vec<_>>()
+            } else {
+                llm_topics
+            };
+
+            let stable_id = self.recurrence.enabled().then(|| match &recurring {
+                Some(prior) => prior.stable_id.clone(),
+                None => format!("{chunk_id}:{cluster_id}"),
+            });
+            if let Some(stable_id) = stable_id.as_ref().filter(|_| !job.dry_run) {
+                let entry = EmittedCluster::new(
+                    chunk_id,
+                    cluster_id,
+                    cluster_mentions.len(),
+                    summary.clone(),
+                    cluster_centroid.clone(),
+                    stable_id.clone(),
+                );
+                if let Err(err) = self.recurrence.remember(brand, &entry).await {
+                    warn!(brand, chunk_id, cluster_id, error = %err, "Failed to remember cluster centroid");
+                }
+            }
+
+            let label = match stable_id.as_deref().filter(|_| self.labels.enabled()) {
+                Some(stable_id) => self
+                    .labels
+                    .resolve(brand, stable_id, summary.as_deref(), !job.dry_run)
+                    .await
+                    .unwrap_or_else(|err| {
+                        warn!(brand, chunk_id, cluster_id, error = %err, "Failed to resolve cluster label");
+                        None
+                    }),
+                None => None,
+            };
+
+            results.push(ClusterResult {
+                cluster_id,
+                count: cluster_mentions.len(),
+                examples,
+                summary,
+                spike: false,
+                sentiment,
+                topics: Some(topics),
+                recurring_of: recurring.map(|prior| prior.reference),
+                sampling_rate,
+                known_event: None,
+                stable_id,
+                label,
+                llm_tier: tier.map(str::to_string),
+                emotions,
+                rating,
+                toxicity,
+                entities,
+                relevance,
+                intent,
+                quotes,
+                confidence,
+                severity: None,
+                engagement,
+                products: (!products.is_empty()).then(|| products.clone()),
+                emerging: None,
+            });
+        }
+
+        if results.is_empty() {
+            results.push(self.fallback(ctx));
+            ctx.provenance.record_fallback("analyze", "single_cluster");
+        }
+        ctx.metrics.llm_time_ms = llm_time_ms;
+        ctx.results = results;
+        // Handed back for `vector_export`, which reads centroids and mention assignments.
+        ctx.clusters = groups;
+        Ok(())
+    }
+}
+
+pub struct SpikeStage {
+    settings: Arc<Settings>,
+    spike_detector: SpikeDetector,
+    calendar: EventCalendar,
+    severity: Option<SeverityScorer>,
+    novelty: Option<NoveltyDetector>,
+}
+
+impl SpikeStage {
+    pub fn new(
+        settings: Arc<Settings>,
+        spike_detector: SpikeDetector,
+        calendar: EventCalendar,
+        severity: Option<SeverityScorer>,
+        novelty: Option<NoveltyDetector>,
+    ) -> Self {
+        Self {
+            settings,
+            spike_detector,
+            calendar,
+            severity,
+            novelty,
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for SpikeStage {
+    fn name(&self) -> &str {
+        "spike"
+    }
+
+    fn skip(&self, ctx: &mut StageContext) {
+        for cluster in &mut ctx.results {
+            cluster.spike = false;
+        }
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        let start = Instant::now();
+        for cluster in &mut ctx.results {
+            let spike_result = match self
+                .spike_detector
+                .detect(&ctx.job.brand, cluster.cluster_id, &cluster.examples, cluster.count, !ctx.job.dry_run)
+                .await
+            {
+                Ok(result) => result,
+                Err(err) => {
+                    warn!(
+                        worker_id = %self.settings.worker_id,
+                        brand = %ctx.job.brand,
+                        chunk_id = %ctx.job.chunk_id,
+                        cluster_id = cluster.cluster_id,
+                        error = %err,
+                        "Spike detection failed; marking cluster as non-spike"
+                    );
+                    SpikeDetectionResult::default()
+                }
+            };
+            cluster.spike = spike_result.is_spike;
+        }
+
+        if ctx.results.iter().any(|cluster| cluster.spike) {
+            if let Some(event) = self.calendar.active(&ctx.job.brand, ctx.chunk.created_at).await {
+                for cluster in ctx.results.iter_mut().filter(|cluster| cluster.spike) {
+                    cluster.known_event = Some(event.clone());
+                }
+            }
+        }
+        if let Some(novelty) = &self.novelty {
+            let record = !ctx.job.dry_run;
+            if let Err(err) = novelty.detect(&ctx.job.brand, ctx.chunk.created_at, &mut ctx.results, record).await {
+                warn!(
+                    worker_id = %self.settings.worker_id,
+                    brand = %ctx.job.brand,
+                    chunk_id = %ctx.job.chunk_id,
+                    error = %err,
+                    "N-gram novelty detection failed; no clusters flagged as emerging"
+                );
+            }
+            let emerging = ctx.results.iter().filter(|cluster| cluster.emerging.is_some()).count();
+            if emerging > 0 {
+                WORKER_EMERGING_NARRATIVES_TOTAL
+                    .with_label_values(&[&self.settings.worker_id])
+                    .inc_by(emerging as u64);
+            }
+        }
+        if let Some(severity) = &self.severity {
+            for cluster in &mut ctx.results {
+                let score = severity
+                    .score(&ctx.job, cluster, &mut ctx.provenance)
+                    .await;
+                cluster.severity = Some(score);
+            }
+        }
+        ctx.metrics.spike_detection_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        Ok(())
+    }
+}
+
+/// Upserts cluster centroids and, with `VECTOR_STORE_EXPORT_MENTIONS`, mention embeddings
+/// into the brand's vector store collection. Runs after `analyze` so points carry stable
+/// IDs and labels. Mention text and summaries are only exported with `EXAMPLE_REDACTION=none`.
+/// Export failures are logged and never fail the chunk.
+pub struct VectorExportStage {
+    settings: Arc<Settings>,
+    store: Option<Arc<dyn VectorStore>>,
+}
+
+impl VectorExportStage {
+    pub fn new(settings: Arc<Settings>, store: Option<Arc<dyn VectorStore>>) -> Self {
+        Self { settings, store }
+    }
+
+    fn points(&self, ctx: &StageContext) -> Vec<VectorPoint> {
+        let brand = &ctx.job.brand;
+        let with_text = self.settings.example_redaction == "none";
+        let results: HashMap<i32, &ClusterResult> =
+            ctx.results.iter().map(|cluster| (cluster.cluster_id, cluster)).collect();
+        let mut points = Vec::new();
+        let mut cluster_of: HashMap<&str, i32> = HashMap::new();
+        for pending in ctx.clusters.iter().filter(|pending| !pending.centroid.is_empty()) {
+            let result = results.get(&pending.cluster_id);
+            for text in &pending.mentions {
+                cluster_of.insert(text.as_str(), pending.cluster_id);
+            }
+            points.push(VectorPoint {
+                key: format!("centroid:{}:{}", ctx.job.chunk_id, pending.cluster_id),
+                kind: "centroid",
+                vector: pending.centroid.clone(),
+                payload: serde_json::json!({
+                    "brand": brand,
+                    "chunk_id": ctx.job.chunk_id,
+                    "cluster_id": pending.cluster_id,
+                    "stable_id": result.and_then(|cluster| cluster.stable_id.clone()),
+                    "label": result.and_then(|cluster| cluster.label.clone()),
+                    "summary": result.and_then(|cluster| cluster.summary.clone()).filter(|_| with_text),
+                    "count": pending.mentions.len(),
+                    "spike": result.is_some_and(|cluster| cluster.spike),
+                    "timestamp": ctx.chunk.created_at.timestamp(),
+                }),
+            });
+        }
+        if !self.settings.vector_store_export_mentions {
+            return points;
+        }
+        for (mention, vector) in ctx.mentions.iter().zip(&ctx.embeddings) {
+            if vector.is_empty() {
+                continue;
+            }
+            let cluster_id = cluster_of.get(mention.text.as_str()).copied();
+            let stable_id = cluster_id
+                .and_then(|id| results.get(&id))
+                .and_then(|cluster| cluster.stable_id.clone());
+            points.push(VectorPoint {
+                key: format!("mention:{}", mention.source.id),
+                kind: "mention",
+                vector: vector.clone(),
+                payload: serde_json::json!({
+                    "brand": brand,
+                    "chunk_id": ctx.job.chunk_id,
+                    "cluster_id": cluster_id,
+                    "stable_id": stable_id,
+                    "mention_id": mention.source.id,
+                    "source": mention.source.source,
+                    "text": with_text.then(|| mention.text.clone()),
+                    "created_at": mention.source.created_at.timestamp(),
+                }),
+            });
+        }
+        points
+    }
+}
+
+#[async_trait]
+impl PipelineStage for VectorExportStage {
+    fn name(&self) -> &str {
+        "vector_export"
+    }
+
+    async fn run(&self, ctx: &mut StageContext) -> WorkerResult<()> {
+        let Some(store) = self.store.as_ref().filter(|_| !ctx.job.dry_run) else {
+            return Ok(());
+        };
+        let points = self.points(ctx);
+        if points.is_empty() {
+            return Ok(());
+        }
+        let outcome = store.upsert(&ctx.job.brand, &points).await;
+        let label = if outcome.is_ok() { "ok" } else { "error" };
+        for kind in ["centroid", "mention"] {
+            let count = points.iter().filter(|point| point.kind == kind).count();
+            WORKER_VECTOR_EXPORT_TOTAL
+                .with_label_values(&[&self.settings.worker_id, store.name(), kind, label])
+                .inc_by(count as u64);
+        }
+        if let Err(err) = outcome {
+            warn!(
+                worker_id = %self.settings.worker_id,
+                brand = %ctx.job.brand,
+                chunk_id = %ctx.job.chunk_id,
+                correlation_id = %ctx.job.correlation_id,
+                store = store.name(),
+                points = points.len(),
+                error = %err,
+                "Vector store export failed"
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,35 +1,85 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::Context;
 use chrono::Utc;
+use serde::Serialize;
 use serde_json::json;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::error::{WorkerError, WorkerResult};
 use crate::metrics::{
-    WORKER_CHUNKS_FAILED_TOTAL, WORKER_CHUNKS_PROCESSED_TOTAL, WORKER_IO_TIME_SECONDS,
+    WORKER_CHUNKS_FAILED_TOTAL, WORKER_CHUNKS_PROCESSED_TOTAL, WORKER_E2E_LATENCY_SECONDS, WORKER_IO_TIME_SECONDS,
+    WORKER_QA_SAMPLES_TOTAL,
 };
 use crate::redis_client::RedisClient;
-use crate::types::{ChunkResult, FailureRecord};
+use crate::topic_trend::TopicTrendTracker;
+use crate::trend::SentimentTrendTracker;
+use crate::types::{
+    ChunkResult, Engagement, FailureRecord, RatingSummary, SentimentTrend, ShadowComparison, TopicTrend,
+};
 
 pub struct ResultStorage {
     redis: RedisClient,
     settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+    trend: SentimentTrendTracker,
+    topic_trend: Option<TopicTrendTracker>,
 }
 
 impl ResultStorage {
-    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Self {
-        Self { redis, settings }
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, cipher: Arc<PayloadCipher>) -> Self {
+        let trend = SentimentTrendTracker::new(redis.clone(), settings.clone());
+        let topic_trend = TopicTrendTracker::new(redis.clone(), settings.clone(), cipher.clone());
+        Self {
+            redis,
+            settings,
+            cipher,
+            trend,
+            topic_trend,
+        }
     }
 
-    pub async fn push_result(&self, brand: &str, result: &mut ChunkResult) -> anyhow::Result<f64> {
+    pub async fn push_result(&self, brand: &str, result: &mut ChunkResult) -> WorkerResult<f64> {
         let key = format!("{}:{}:chunks", self.settings.redis_result_prefix, brand);
-        let payload = self.format_for_orchestrator(result);
-        let payload_str = serde_json::to_string(&payload).context("serialise chunk result")?;
+        if let Some(enqueued_at) = result.enqueued_at {
+            let e2e_ms = (Utc::now() - enqueued_at).num_milliseconds().max(0) as f64;
+            result.metrics.e2e_latency_ms = Some(e2e_ms);
+            WORKER_E2E_LATENCY_SECONDS
+                .with_label_values(&[&self.settings.worker_id, brand])
+                .observe(e2e_ms / 1000.0);
+        }
+        let score = self.sentiment_score(&result.clusters);
+        let trend = match self
+            .trend
+            .update(brand, &result.chunk_id, result.timestamp, score)
+            .await
+        {
+            Ok(trend) => Some(trend),
+            Err(err) => {
+                warn!(brand, chunk_id = %result.chunk_id, error = %err, "Failed to update sentiment trend");
+                None
+            }
+        };
+        let topic_trend = match &self.topic_trend {
+            Some(tracker) => tracker
+                .update(brand, result.timestamp, &result.clusters)
+                .await
+                .map_err(|err| warn!(brand, chunk_id = %result.chunk_id, error = %err, "Failed to update topic trend"))
+                .ok(),
+            None => None,
+        };
+        let payload = self.format_for_orchestrator(result, trend.as_ref(), topic_trend.as_ref());
+        let payload_str = self.seal(&payload, "serialise chunk result")?;
 
         let start = Instant::now();
-        self.redis.rpush(&key, &payload_str).await?;
+        self.redis
+            .rpush(&key, &payload_str)
+            .await
+            .map_err(WorkerError::Storage)?;
         let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
 
         result.metrics.io_time_ms += elapsed_ms;
@@ -51,17 +101,102 @@ impl ResultStorage {
         Ok(elapsed_ms)
     }
 
+    pub async fn push_shadow_result(
+        &self,
+        brand: &str,
+        shadow: &ChunkResult,
+        comparison: &ShadowComparison,
+    ) -> WorkerResult<()> {
+        let key = format!("{}:{}:chunks", self.settings.redis_shadow_prefix, brand);
+        let payload = json!({
+            "chunkId": shadow.chunk_id,
+            "brand": brand,
+            "result": self.format_for_orchestrator(shadow, None, None),
+            "diff": comparison,
+        });
+        let payload_str = self.seal(&payload, "serialise shadow result")?;
+        self.redis
+            .rpush(&key, &payload_str)
+            .await
+            .map_err(WorkerError::Storage)?;
+        info!(
+            worker_id = %self.settings.worker_id,
+            brand, key, chunk_id = %shadow.chunk_id,
+            cluster_count_delta = comparison.cluster_count_delta,
+            sentiment_delta = comparison.sentiment_delta,
+            "Shadow result pushed to Redis"
+        );
+        Ok(())
+    }
+
+    /// Stores the full result of a QA-sampled chunk with its intermediate artifacts.
+    pub async fn push_qa_sample(&self, brand: &str, result: &ChunkResult) -> WorkerResult<()> {
+        let Some(artifacts) = &result.qa else {
+            return Ok(());
+        };
+        let key = format!("{}:{}", self.settings.redis_qa_prefix, brand);
+        let payload = json!({
+            "chunkId": result.chunk_id,
+            "brand": brand,
+            "result": self.format_for_orchestrator(result, None, None),
+            "provenance": result.provenance,
+            "artifacts": artifacts,
+        });
+        let payload_str = self.seal(&payload, "serialise QA sample")?;
+        self.redis
+            .lpush_capped(&key, &payload_str, self.settings.qa_sample_max_entries, self.settings.qa_sample_ttl)
+            .await
+            .map_err(WorkerError::Storage)?;
+        WORKER_QA_SAMPLES_TOTAL
+            .with_label_values(&[&self.settings.worker_id, brand])
+            .inc();
+        info!(
+            worker_id = %self.settings.worker_id,
+            brand, key, chunk_id = %result.chunk_id,
+            "QA sample pushed to Redis"
+        );
+        Ok(())
+    }
+
+    pub fn compare(&self, primary: &ChunkResult, shadow: &ChunkResult) -> ShadowComparison {
+        let primary_score = self.sentiment_score(&primary.clusters);
+        let shadow_score = self.sentiment_score(&shadow.clusters);
+        ShadowComparison {
+            primary_cluster_count: primary.clusters.len(),
+            shadow_cluster_count: shadow.clusters.len(),
+            cluster_count_delta: shadow.clusters.len() as i64 - primary.clusters.len() as i64,
+            primary_sentiment_score: primary_score,
+            shadow_sentiment_score: shadow_score,
+            sentiment_delta: shadow_score - primary_score,
+        }
+    }
+
+    fn seal<T: Serialize + ?Sized>(&self, value: &T, what: &'static str) -> WorkerResult<String> {
+        let raw = serde_json::to_string(value).context(what).map_err(WorkerError::Storage)?;
+        self.cipher.encrypt(&raw).map_err(WorkerError::Storage)
+    }
+
+    fn sentiment_score(&self, clusters: &[crate::types::ClusterResult]) -> f32 {
+        self.aggregate_sentiment(clusters)
+            .get("score")
+            .and_then(|value| value.as_f64())
+            .unwrap_or_default() as f32
+    }
+
     pub async fn record_failure(
         &self,
         brand: &str,
         failure: &FailureRecord,
         reason_label: &str,
-    ) -> anyhow::Result<f64> {
+    ) -> WorkerResult<f64> {
         let key = format!("{}:{}", self.settings.redis_failed_prefix, brand);
-        let payload = serde_json::to_string(failure).context("serialise failure record")?;
+        let payload = self.seal(failure, "serialise failure record")?;
 
         let start = Instant::now();
-        self.redis.record_failure(&key, &payload).await?;
+        self.redis
+            .record_failure(&key, &payload)
+            .await
+            .map_err(WorkerError::Storage)?;
         let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
 
         WORKER_CHUNKS_FAILED_TOTAL
@@ -83,7 +218,12 @@ impl ResultStorage {
         Ok(elapsed_ms)
     }
 
-    fn format_for_orchestrator(&self, result: &ChunkResult) -> serde_json::Value {
+    fn format_for_orchestrator(
+        &self,
+        result: &ChunkResult,
+        trend: Option<&SentimentTrend>,
+        topic_trend: Option<&TopicTrend>,
+    ) -> serde_json::Value {
         let sentiment = self.aggregate_sentiment(&result.clusters);
         let topics = self.extract_topics(&result.clusters);
         let spike_detected = result.clusters.iter().any(|cluster| cluster.spike);
@@ -94,13 +234,30 @@ impl ResultStorage {
             "brand": result.brand,
             "processedAt": Utc::now().to_rfc3339(),
             "sentiment": sentiment,
+            "emotions": self.aggregate_emotions(&result.clusters),
+            "rating": RatingSummary::combine(result.clusters.iter().filter_map(|cluster| cluster.rating.as_ref())),
             "clusters": self.build_clusters(&result.clusters),
+            "toxicity": self.aggregate_toxicity(&result.clusters),
+            "severity": result.clusters.iter().filter_map(|cluster| cluster.severity).max(),
+            "totalEngagement": Engagement::combine(result.clusters.iter().filter_map(|cluster| cluster.engagement))
+                .map(|engagement| engagement.total)
+                .unwrap_or_default(),
+            "engagementWeightedSentiment": self.engagement_weighted_sentiment(&result.clusters),
+            "products": self.aggregate_products(&result.clusters),
             "topics": topics,
             "summary": self.combine_summaries(&result.clusters),
             "spikeDetected": spike_detected,
+            "sentimentTrend": trend,
+            "topicTrend": topic_trend,
+            "degraded": result.provenance.degraded(),
+            "expired": result.expired,
+            "priority": result.priority,
+            "degradedReasons": result.provenance.fallbacks,
             "meta": {
                 "metrics": result.metrics,
                 "mentionCount": mention_count,
+                "mediaMentionCount": result.metrics.media_mentions.unwrap_or_default(),
+                "provenance": result.provenance,
             }
         })
     }
@@ -109,14 +266,9 @@ impl ResultStorage {
         clusters
             .iter()
             .map(|cluster| {
-                let sentiment_score = cluster
-                    .sentiment
-                    .get("positive")
-                    .copied()
-                    .unwrap_or_default()
-                    - cluster.sentiment.get("negative").copied().unwrap_or_default();
+                let sentiment_score = cluster.sentiment_score();
                 let label = self.normalise_summary_text(
-                    cluster.summary.as_deref(),
+                    cluster.label.as_deref().or(cluster.summary.as_deref()),
                     &cluster.examples,
                     Some(format!("Cluster {}", cluster.cluster_id)),
                 );
@@ -127,6 +279,28 @@ impl ResultStorage {
                     "sentimentScore": sentiment_score,
                     "spike": cluster.spike,
                     "mentionCount": cluster.count,
+                    "recurring": cluster.recurring_of.is_some(),
+                    "recurringOf": cluster.recurring_of,
+                    "stableId": cluster.stable_id,
+                    "samplingRate": cluster.sampling_rate,
+                    "knownEvent": cluster.known_event,
+                    "emotions": cluster.emotions,
+                    "rating": cluster.rating,
+                    "toxicity": cluster.toxicity,
+                    "toxic": cluster.toxicity.map(|score| score >= self.settings.toxicity_threshold),
+                    "severity": cluster.severity,
+                    "entities": cluster.entities,
+                    "relevance": cluster.relevance,
+                    "intent": cluster.intent,
+                    "quotes": cluster.quotes,
+                    "confidence": cluster.confidence,
+                    "lowConfidence": cluster
+                        .confidence
+                        .and_then(|confidence| confidence.min())
+                        .map(|score| score < self.settings.confidence_threshold),
+                    "engagement": cluster.engagement,
+                    "products": cluster.products,
+                    "emerging": cluster.emerging,
                 })
             })
             .collect()
@@ -158,6 +332,92 @@ impl ResultStorage {
         })
     }
 
+    /// Cluster sentiment weighted by engagement, so widely shared clusters dominate; `None`
+    /// when no cluster carries engagement.
+    fn engagement_weighted_sentiment(&self, clusters: &[crate::types::ClusterResult]) -> Option<serde_json::Value> {
+        let mut totals = [0.0f64, 0.0, 0.0]; // positive, neutral, negative
+        let mut weight = 0.0f64;
+        for cluster in clusters {
+            let Some(engagement) = cluster.engagement.filter(|engagement| engagement.total > 0) else {
+                continue;
+            };
+            if cluster.sentiment.is_empty() {
+                continue;
+            }
+            for (total, label) in totals.iter_mut().zip(["positive", "neutral", "negative"]) {
+                *total += cluster.sentiment.get(label).copied().unwrap_or_default() as f64 * engagement.total as f64;
+            }
+            weight += engagement.total as f64;
+        }
+        if weight <= 0.0 {
+            return None;
+        }
+        totals.iter_mut().for_each(|value| *value /= weight);
+        Some(json!({
+            "positive": totals[0],
+            "neutral": totals[1],
+            "negative": totals[2],
+            "score": totals[0] - totals[2],
+        }))
+    }
+
+    /// Per taxonomy entity: its mentions, the mention-weighted sentiment score of the clusters
+    /// it appears in and how many of those lean negative; `None` without the taxonomy stage.
+    fn aggregate_products(&self, clusters: &[crate::types::ClusterResult]) -> Option<serde_json::Value> {
+        let mut totals: HashMap<&str, (usize, f32, usize)> = HashMap::new();
+        for cluster in clusters {
+            let score = cluster.sentiment_score();
+            for (entity, count) in cluster.products.iter().flatten() {
+                let (mentions, weighted, negative) = totals.entry(entity.as_str()).or_default();
+                *mentions += count;
+                *weighted += score * *count as f32;
+                *negative += usize::from(score < 0.0);
+            }
+        }
+        (!totals.is_empty()).then(|| {
+            totals
+                .into_iter()
+                .map(|(entity, (mentions, weighted, negative))| {
+                    let breakdown = json!({
+                        "mentionCount": mentions,
+                        "sentimentScore": weighted / mentions.max(1) as f32,
+                        "negativeClusters": negative,
+                    });
+                    (entity.to_string(), breakdown)
+                })
+                .collect::<serde_json::Map<_, _>>()
+                .into()
+        })
+    }
+
+    /// Mention-weighted mean of the cluster emotions; `None` when no cluster was classified.
+    fn aggregate_emotions(&self, clusters: &[crate::types::ClusterResult]) -> Option<HashMap<String, f32>> {
+        let mut totals: HashMap<String, f32> = HashMap::new();
+        let mut weight = 0.0f32;
+        for cluster in clusters {
+            let Some(emotions) = &cluster.emotions else {
+                continue;
+            };
+            for (emotion, score) in emotions {
+                *totals.entry(emotion.clone()).or_default() += score * cluster.count as f32;
+            }
+            weight += cluster.count as f32;
+        }
+        (weight > 0.0).then(|| totals.into_iter().map(|(emotion, total)| (emotion, total / weight)).collect())
+    }
+
+    /// Worst cluster score and how many clusters crossed `TOXICITY_THRESHOLD`; `None` when
+    /// toxicity wasn't scored.
+    fn aggregate_toxicity(&self, clusters: &[crate::types::ClusterResult]) -> Option<serde_json::Value> {
+        let scores: Vec<f32> = clusters.iter().filter_map(|cluster| cluster.toxicity).collect();
+        let max = scores.iter().copied().reduce(f32::max)?;
+        let toxic_clusters = scores
+            .iter()
+            .filter(|score| **score >= self.settings.toxicity_threshold)
+            .count();
+        Some(json!({ "max": max, "toxicClusters": toxic_clusters }))
+    }
+
     fn extract_topics(&self, clusters: &[crate::types::ClusterResult]) -> Vec<String> {
         let mut topics: Vec<String> = Vec::new();
         for cluster in clusters {
@@ -166,7 +426,7 @@ impl ResultStorage {
                 .filter(|value| !value.is_empty())
             {
                 topics.push(normalised);
-            } else if let Some(example) = cluster.examples.get(0) {
+            } else if let Some(example) = cluster.examples.first() {
                 topics.push(example.clone());
             }
         }
@@ -196,7 +456,7 @@ impl ResultStorage {
             candidate.clear();
         }
         if candidate.is_empty() {
-            if let Some(example) = examples.get(0) {
+            if let Some(example) = examples.first() {
                 candidate = example.trim().to_string();
             }
         }
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use tracing::info;
+
+use crate::config::Settings;
+
+/// One brand's entry in `BRAND_TAXONOMY_FILE`, e.g. for `acme`:
+/// `{"aliases": ["acme corp"], "subBrands": {"Acme Pro": ["acmepro"]}, "products": {"Rocket Skates": ["skates"]}}`.
+/// Sub-brand and product names match themselves as well as their listed aliases.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaxonomyEntry {
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    sub_brands: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    products: HashMap<String, Vec<String>>,
+}
+
+/// Resolves which entity of a brand — a product, a sub-brand or the brand itself — each
+/// mention refers to, so results can be broken down per product line.
+pub struct BrandTaxonomy {
+    /// Lowercased term and the canonical entity it stands for, per lowercased brand,
+    /// longest term first so the most specific match wins.
+    terms: HashMap<String, Vec<(String, String)>>,
+}
+
+impl BrandTaxonomy {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Self> {
+        let Some(path) = &settings.brand_taxonomy_file else {
+            bail!("the taxonomy pipeline stage needs BRAND_TAXONOMY_FILE");
+        };
+        let raw = std::fs::read_to_string(path).with_context(|| format!("read brand taxonomy from {path}"))?;
+        let entries: HashMap<String, TaxonomyEntry> =
+            serde_json::from_str(&raw).with_context(|| format!("parse brand taxonomy in {path}"))?;
+        let terms: HashMap<String, Vec<(String, String)>> = entries
+            .into_iter()
+            .map(|(brand, entry)| {
+                let mut terms = Vec::new();
+                for (entity, aliases) in entry.products.iter().chain(&entry.sub_brands) {
+                    for term in aliases.iter().chain(std::iter::once(entity)) {
+                        terms.push((term.trim().to_lowercase(), entity.clone()));
+                    }
+                }
+                for term in entry.aliases.iter().chain(std::iter::once(&brand)) {
+                    terms.push((term.trim().to_lowercase(), brand.clone()));
+                }
+                terms.retain(|(term, _)| !term.is_empty());
+                terms.sort_by_key(|(term, _)| std::cmp::Reverse(term.len()));
+                (brand.to_lowercase(), terms)
+            })
+            .collect();
+        info!(worker_id = %settings.worker_id, brands = terms.len(), "Brand taxonomy loaded");
+        Ok(Self { terms })
+    }
+
+    /// The canonical entity `text` refers to; `None` when the brand has no taxonomy or no
+    /// term appears.
+    pub fn resolve(&self, brand: &str, text: &str) -> Option<String> {
+        let terms = self.terms.get(&brand.to_lowercase())?;
+        let lowered = text.to_lowercase();
+        terms
+            .iter()
+            .find(|(term, _)| lowered.contains(term.as_str()))
+            .map(|(_, entity)| entity.clone())
+    }
+}
+
+/// How many times each entity appears.
+pub fn entity_counts<'a>(entities: impl IntoIterator<Item = &'a str>) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for entity in entities {
+        *counts.entry(entity.to_string()).or_default() += 1;
+    }
+    counts
+}
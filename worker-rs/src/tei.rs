@@ -0,0 +1,151 @@
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::OnceCell;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::embeddings::EmbeddingAdapter;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+use crate::key_pool::ApiKeyPool;
+
+/// Embeddings from a HuggingFace text-embeddings-inference server at `TEI_URL`, through its
+/// `/embed` route.
+///
+/// Batches are capped at the server's `max_client_batch_size` from `/info` (or
+/// `TEI_MAX_BATCH_SIZE`), and a batch TEI still rejects as too large (413) is split in half
+/// and retried. Overload (429) and unhealthy (503) responses are retried by the HTTP client.
+/// Texts beyond the model's token limit are truncated by the server.
+pub struct TeiEmbeddingAdapter {
+    http: HttpClient,
+    base_url: String,
+    api_keys: Option<ApiKeyPool>,
+    configured_batch_size: Option<usize>,
+    fallback_batch_size: usize,
+    batch_size: OnceCell<usize>,
+}
+
+#[derive(Deserialize)]
+struct TeiInfo {
+    model_id: Option<String>,
+    max_client_batch_size: Option<usize>,
+}
+
+impl TeiEmbeddingAdapter {
+    pub fn new(settings: &Settings, http: HttpClient) -> anyhow::Result<Self> {
+        let base_url = settings
+            .tei_url
+            .as_deref()
+            .context("TEI_URL is required for EMBEDDINGS_PROVIDER=tei")?
+            .trim_end_matches('/')
+            .to_string();
+        // TEI only checks a key when started with --api-key.
+        let api_keys = ApiKeyPool::load(
+            "tei",
+            settings.embedding_api_key.as_deref(),
+            settings.embedding_api_keys_file.as_deref(),
+            &settings.worker_id,
+        )?;
+        Ok(Self {
+            http,
+            base_url,
+            api_keys,
+            configured_batch_size: settings.tei_max_batch_size,
+            fallback_batch_size: settings.embeddings_batch_size,
+            batch_size: OnceCell::new(),
+        })
+    }
+
+    async fn batch_size(&self) -> usize {
+        *self
+            .batch_size
+            .get_or_init(|| async {
+                if let Some(size) = self.configured_batch_size {
+                    return size;
+                }
+                match self.info().await {
+                    Ok(info) => {
+                        let size = info
+                            .max_client_batch_size
+                            .map_or(self.fallback_batch_size, |max| max.min(self.fallback_batch_size))
+                            .max(1);
+                        info!(
+                            url = %self.base_url,
+                            model = info.model_id.as_deref().unwrap_or("unknown"),
+                            batch_size = size,
+                            "TEI server limits loaded"
+                        );
+                        size
+                    }
+                    Err(err) => {
+                        warn!(
+                            url = %self.base_url,
+                            error = %err,
+                            "Failed to read TEI server info; using EMBEDDINGS_BATCH_SIZE"
+                        );
+                        self.fallback_batch_size
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn info(&self) -> anyhow::Result<TeiInfo> {
+        let url = format!("{}/info", self.base_url);
+        let response = match &self.api_keys {
+            Some(keys) => {
+                let request = |key: &str| self.http.get(&url).bearer_auth(key);
+                self.http.send_with_key(keys, "TEI info", request).await?
+            }
+            None => self.http.send(self.http.get(&url), "TEI info").await?,
+        };
+        response.json().await.context("decode TEI info response")
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        let url = format!("{}/embed", self.base_url);
+        let body = json!({ "inputs": texts, "truncate": true, "normalize": true });
+        let response = match &self.api_keys {
+            Some(keys) => {
+                let request = |key: &str| self.http.post(&url).bearer_auth(key).json(&body);
+                self.http.send_with_key(keys, "TEI embed", request).await?
+            }
+            None => self.http.send(self.http.post(&url).json(&body), "TEI embed").await?,
+        };
+        let vectors: Vec<Vec<f32>> = response.json().await.context("decode TEI embed response")?;
+        if vectors.len() != texts.len() {
+            bail!("TEI returned {} embeddings for {} texts", vectors.len(), texts.len());
+        }
+        Ok(vectors)
+    }
+}
+
+fn payload_too_large(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|err| err.status() == Some(StatusCode::PAYLOAD_TOO_LARGE))
+}
+
+#[async_trait]
+impl EmbeddingAdapter for TeiEmbeddingAdapter {
+    async fn embed(&self, texts: &[String], _brand: &str, _chunk_id: &str) -> WorkerResult<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        let mut pending: Vec<&[String]> = texts.chunks(self.batch_size().await).rev().collect();
+        while let Some(batch) = pending.pop() {
+            match self.embed_batch(batch).await {
+                Ok(batch_vectors) => vectors.extend(batch_vectors),
+                Err(err) if batch.len() > 1 && payload_too_large(&err) => {
+                    warn!(url = %self.base_url, size = batch.len(), "TEI rejected batch as too large; splitting it");
+                    let (head, tail) = batch.split_at(batch.len() / 2);
+                    pending.push(tail);
+                    pending.push(head);
+                }
+                Err(err) => return Err(WorkerError::Embedding(err)),
+            }
+        }
+        Ok(vectors)
+    }
+}
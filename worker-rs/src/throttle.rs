@@ -0,0 +1,164 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::llm::throttle_pacers;
+use crate::metrics::{WORKER_PROCESS_RSS_BYTES, WORKER_RUNTIME_TASKS, WORKER_THROTTLED};
+
+/// One reading of the pressure signals.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PressureSample {
+    /// `None` where `/proc/self/status` is unavailable.
+    pub rss_bytes: Option<u64>,
+    pub alive_tasks: usize,
+    /// Tasks ready to run but waiting in the runtime's global queue.
+    pub task_backlog: usize,
+}
+
+/// Throttles the worker before it runs out of memory on a burst of large chunks. Every
+/// `THROTTLE_SAMPLE_INTERVAL_SEC` it reads process RSS and the tokio runtime's task backlog;
+/// once either passes `THROTTLE_MAX_RSS_MB` or `THROTTLE_MAX_TASK_BACKLOG`, half the LLM
+/// concurrency slots are held back and each queue fetch waits `THROTTLE_FETCH_DELAY_MS`.
+/// Throttling lifts when both fall below `THROTTLE_RESUME_RATIO` of their limits.
+pub struct ResourceThrottle {
+    max_rss_bytes: Option<u64>,
+    max_task_backlog: Option<usize>,
+    throttled: AtomicBool,
+    reason: StdMutex<Option<&'static str>>,
+    last_sample: StdMutex<PressureSample>,
+    settings: Arc<Settings>,
+}
+
+impl ResourceThrottle {
+    pub fn new(settings: Arc<Settings>) -> Self {
+        Self {
+            max_rss_bytes: settings.throttle_max_rss_mb.map(|mb| mb * 1024 * 1024),
+            max_task_backlog: settings.throttle_max_task_backlog,
+            throttled: AtomicBool::new(false),
+            reason: StdMutex::new(None),
+            last_sample: StdMutex::new(PressureSample::default()),
+            settings,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.max_rss_bytes.is_some() || self.max_task_backlog.is_some()
+    }
+
+    pub fn is_throttled(&self) -> bool {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// Which limit tripped the throttle, while it is engaged.
+    pub fn reason(&self) -> Option<&'static str> {
+        *self.reason.lock().expect("throttle reason poisoned")
+    }
+
+    pub fn last_sample(&self) -> PressureSample {
+        *self.last_sample.lock().expect("throttle sample poisoned")
+    }
+
+    pub async fn run(&self, mut shutdown: broadcast::Receiver<()>) {
+        if !self.enabled() {
+            return;
+        }
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => {
+                    info!("Resource throttle stopping");
+                    break;
+                }
+                _ = tokio::time::sleep(self.settings.throttle_sample_interval) => {
+                    self.evaluate(sample());
+                }
+            }
+        }
+        if self.throttled.swap(false, Ordering::SeqCst) {
+            throttle_pacers(false);
+        }
+    }
+
+    fn evaluate(&self, sample: PressureSample) {
+        let worker_id = self.settings.worker_id.as_str();
+        if let Some(rss) = sample.rss_bytes {
+            WORKER_PROCESS_RSS_BYTES.with_label_values(&[worker_id]).set(rss as f64);
+        }
+        WORKER_RUNTIME_TASKS
+            .with_label_values(&[worker_id, "alive"])
+            .set(sample.alive_tasks as f64);
+        WORKER_RUNTIME_TASKS
+            .with_label_values(&[worker_id, "backlog"])
+            .set(sample.task_backlog as f64);
+        *self.last_sample.lock().expect("throttle sample poisoned") = sample;
+
+        let over = |value: u64, limit: Option<u64>, ratio: f64| {
+            limit.is_some_and(|limit| value as f64 >= limit as f64 * ratio)
+        };
+        let rss = sample.rss_bytes.unwrap_or_default();
+        let backlog = sample.task_backlog as u64;
+        let max_backlog = self.max_task_backlog.map(|max| max as u64);
+        let tripped = if over(rss, self.max_rss_bytes, 1.0) {
+            Some("memory")
+        } else if over(backlog, max_backlog, 1.0) {
+            Some("task_backlog")
+        } else {
+            None
+        };
+
+        let resume = self.settings.throttle_resume_ratio;
+        let eased = !over(rss, self.max_rss_bytes, resume) && !over(backlog, max_backlog, resume);
+        let was_throttled = self.is_throttled();
+        if let Some(reason) = tripped {
+            *self.reason.lock().expect("throttle reason poisoned") = Some(reason);
+            if !was_throttled {
+                warn!(
+                    worker_id,
+                    reason,
+                    rss_bytes = rss,
+                    task_backlog = sample.task_backlog,
+                    "Resource pressure; throttling fetches and LLM concurrency"
+                );
+                self.throttled.store(true, Ordering::SeqCst);
+            }
+        } else if was_throttled && eased {
+            info!(
+                worker_id,
+                rss_bytes = rss,
+                task_backlog = sample.task_backlog,
+                "Resource pressure eased; throttle lifted"
+            );
+            *self.reason.lock().expect("throttle reason poisoned") = None;
+            self.throttled.store(false, Ordering::SeqCst);
+            throttle_pacers(false);
+        }
+        if self.is_throttled() {
+            // Re-applied every sample so slots busy when throttling began are held once freed.
+            throttle_pacers(true);
+        }
+        WORKER_THROTTLED
+            .with_label_values(&[worker_id])
+            .set(if self.is_throttled() { 1.0 } else { 0.0 });
+    }
+}
+
+fn sample() -> PressureSample {
+    let metrics = tokio::runtime::Handle::current().metrics();
+    PressureSample {
+        rss_bytes: process_rss_bytes(),
+        alive_tasks: metrics.num_alive_tasks(),
+        task_backlog: metrics.global_queue_depth(),
+    }
+}
+
+/// Resident set size from the `VmRSS` line of `/proc/self/status` (Linux only).
+fn process_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::config::Settings;
+use crate::http::HttpClient;
+use crate::llm::{build_llm_adapter, simple_sentiment, InstrumentedLlmAdapter};
+use crate::redis_client::RedisClient;
+use crate::spike::SpikeDetector;
+use crate::stages::PendingCluster;
+
+pub const PREMIUM_TIER: &str = "premium";
+pub const CHEAP_TIER: &str = "cheap";
+
+/// Sends only important clusters to the configured LLM; the rest go to
+/// `LLM_CHEAP_PROVIDER`. A cluster is important when it reaches
+/// `LLM_PREMIUM_MIN_CLUSTER_SIZE`, its heuristic negativity reaches
+/// `LLM_PREMIUM_MIN_NEGATIVITY`, or (with `LLM_PREMIUM_ON_SPIKE`) it is likely to spike.
+pub struct LlmTiering {
+    cheap: InstrumentedLlmAdapter,
+    spikes: SpikeDetector,
+    settings: Arc<Settings>,
+}
+
+impl LlmTiering {
+    pub fn from_settings(settings: &Arc<Settings>, redis: &RedisClient, http: &HttpClient) -> anyhow::Result<Option<Self>> {
+        if !settings.llm_tiering_enabled {
+            return Ok(None);
+        }
+        let cheap_settings = Arc::new(Settings {
+            llm_provider: settings.llm_cheap_provider.clone(),
+            llm_providers: Vec::new(),
+            ..(**settings).clone()
+        });
+        Ok(Some(Self {
+            cheap: build_llm_adapter(&cheap_settings, redis, http)?,
+            spikes: SpikeDetector::new(redis.clone(), settings.clone()),
+            settings: settings.clone(),
+        }))
+    }
+
+    pub fn cheap(&self) -> &InstrumentedLlmAdapter {
+        &self.cheap
+    }
+
+    pub async fn tier(&self, brand: &str, pending: &PendingCluster) -> &'static str {
+        if pending.mentions.len() >= self.settings.llm_premium_min_cluster_size {
+            return PREMIUM_TIER;
+        }
+        let negativity = simple_sentiment(&pending.llm_input)
+            .get("negative")
+            .copied()
+            .unwrap_or_default();
+        if negativity >= self.settings.llm_premium_min_negativity {
+            return PREMIUM_TIER;
+        }
+        if self.settings.llm_premium_on_spike {
+            // The same examples the cluster result gets, so the spike stage reads the same history.
+            let examples = &pending.llm_input[..pending.llm_input.len().min(self.settings.preprocessing_examples)];
+            match self.spikes.peek(brand, pending.cluster_id, examples, pending.mentions.len()).await {
+                Ok(true) => return PREMIUM_TIER,
+                Ok(false) => {}
+                Err(err) => {
+                    warn!(brand, cluster_id = pending.cluster_id, error = %err, "Spike check for LLM tiering failed");
+                    return PREMIUM_TIER;
+                }
+            }
+        }
+        CHEAP_TIER
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::config::Settings;
+use crate::crypto::PayloadCipher;
+use crate::redis_client::RedisClient;
+use crate::types::{ClusterResult, TopicMovement, TopicTrend};
+
+/// Days of counts kept: this week and the one before.
+const RETENTION_DAYS: u64 = 15;
+/// Longer topics are mention text standing in for missing LLM topics, not trend keys.
+const TOPIC_MAX_CHARS: usize = 64;
+
+/// Per-brand, per-day sorted sets of topic hash → mention-weighted count under
+/// `{REDIS_TREND_PREFIX}:{brand}:topics:{YYYY-MM-DD}`, so week-over-week topic movement
+/// comes from fourteen small keys rather than a replay of every chunk result. Topics can be
+/// mention text, so each day's topics are kept sealed in a `:labels` hash beside it.
+pub struct TopicTrendTracker {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+    cipher: Arc<PayloadCipher>,
+}
+
+impl TopicTrendTracker {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>, cipher: Arc<PayloadCipher>) -> Option<Self> {
+        settings.topic_trends_enabled.then(|| Self {
+            redis,
+            settings,
+            cipher,
+        })
+    }
+
+    /// Adds the chunk's topics to its day and compares the seven days up to it with the
+    /// seven before.
+    pub async fn update(&self, brand: &str, timestamp: i64, clusters: &[ClusterResult]) -> anyhow::Result<TopicTrend> {
+        let day = DateTime::<Utc>::from_timestamp(timestamp, 0)
+            .context("chunk timestamp out of range")?
+            .date_naive();
+        let weights = topic_weights(clusters);
+        if !weights.is_empty() {
+            let ttl = Duration::from_secs(RETENTION_DAYS * 86_400);
+            let mut hashed = Vec::with_capacity(weights.len());
+            let mut labels = Vec::with_capacity(weights.len());
+            for (topic, weight) in weights {
+                let member = topic_hash(&topic);
+                labels.push((member.clone(), self.cipher.encrypt(&topic)?));
+                hashed.push((member, weight));
+            }
+            let key = self.key(brand, day);
+            self.redis.zincr_many_with_ttl(&key, &hashed, ttl).await?;
+            self.redis.hset_many_with_ttl(&format!("{key}:labels"), &labels, ttl).await?;
+        }
+
+        let keys: Vec<String> = (0..14)
+            .filter_map(|back| day.checked_sub_days(Days::new(back)))
+            .map(|day| self.key(brand, day))
+            .collect();
+        let mut counts: HashMap<String, (f64, f64)> = HashMap::new();
+        for (back, entries) in self.redis.zrange_all_with_scores(&keys).await?.into_iter().enumerate() {
+            for (topic, score) in entries {
+                let (current, previous) = counts.entry(topic).or_default();
+                if back < 7 {
+                    *current += score;
+                } else {
+                    *previous += score;
+                }
+            }
+        }
+        let mut trend = self.classify(counts);
+        self.label(&keys, &mut trend).await?;
+        Ok(trend)
+    }
+
+    /// Swaps the hashes in `trend` for their topics, from any day's labels in `keys`.
+    /// Topics whose label is missing or can't be opened are left out.
+    async fn label(&self, keys: &[String], trend: &mut TopicTrend) -> anyhow::Result<()> {
+        let hashes: Vec<String> = [&trend.new, &trend.rising, &trend.falling]
+            .into_iter()
+            .flatten()
+            .map(|movement| movement.topic.clone())
+            .collect();
+        let label_keys: Vec<String> = keys.iter().map(|key| format!("{key}:labels")).collect();
+        let stored = self.redis.hmget_many(&label_keys, &hashes).await?;
+        let mut labels: HashMap<String, String> = HashMap::new();
+        for (index, hash) in hashes.iter().enumerate() {
+            let Some(sealed) = stored.iter().find_map(|day| day.get(index).cloned().flatten()) else {
+                continue;
+            };
+            match self.cipher.decrypt(&sealed) {
+                Ok(topic) => {
+                    labels.insert(hash.clone(), topic);
+                }
+                Err(err) => warn!(error = %err, "Skipping topic trend label that could not be decrypted"),
+            }
+        }
+        for list in [&mut trend.new, &mut trend.rising, &mut trend.falling] {
+            list.retain_mut(|movement| match labels.get(&movement.topic) {
+                Some(topic) => {
+                    movement.topic = topic.clone();
+                    true
+                }
+                None => false,
+            });
+        }
+        Ok(())
+    }
+
+    fn key(&self, brand: &str, day: NaiveDate) -> String {
+        format!("{}:{}:topics:{}", self.settings.redis_trend_prefix, brand, day.format("%Y-%m-%d"))
+    }
+
+    fn classify(&self, counts: HashMap<String, (f64, f64)>) -> TopicTrend {
+        let threshold = self.settings.topic_trend_change_threshold;
+        let mut trend = TopicTrend::default();
+        for (topic, (current, previous)) in counts {
+            if current.max(previous) < self.settings.topic_trend_min_count {
+                continue;
+            }
+            let change = (previous > 0.0).then(|| (current - previous) / previous);
+            let movement = TopicMovement {
+                topic,
+                current,
+                previous,
+                change,
+            };
+            match change {
+                None => trend.new.push(movement),
+                Some(change) if change >= threshold => trend.rising.push(movement),
+                Some(change) if change <= -threshold => trend.falling.push(movement),
+                Some(_) => {}
+            }
+        }
+        let change = |movement: &TopicMovement| movement.change.unwrap_or_default();
+        trend.new.sort_by(|a, b| b.current.total_cmp(&a.current));
+        trend.rising.sort_by(|a, b| change(b).total_cmp(&change(a)));
+        trend.falling.sort_by(|a, b| change(a).total_cmp(&change(b)));
+        for list in [&mut trend.new, &mut trend.rising, &mut trend.falling] {
+            list.truncate(self.settings.topic_trend_limit);
+        }
+        trend
+    }
+}
+
+fn topic_hash(topic: &str) -> String {
+    let digest = Sha256::digest(topic.as_bytes());
+    digest.iter().take(16).map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Each cluster's distinct topics weighted by its mention count.
+fn topic_weights(clusters: &[ClusterResult]) -> Vec<(String, f64)> {
+    let mut weights: HashMap<String, f64> = HashMap::new();
+    for cluster in clusters {
+        let mut seen = Vec::new();
+        for topic in cluster.topics.iter().flatten() {
+            let topic = topic.trim().to_lowercase();
+            if topic.is_empty() || topic.chars().count() > TOPIC_MAX_CHARS || seen.contains(&topic) {
+                continue;
+            }
+            *weights.entry(topic.clone()).or_default() += cluster.count as f64;
+            seen.push(topic);
+        }
+    }
+    weights.into_iter().collect()
+}
@@ -0,0 +1,82 @@
+use anyhow::{bail, Context};
+
+use crate::config::Settings;
+use crate::context::ProcessingContext;
+use crate::llm::InstrumentedLlmAdapter;
+use crate::types::Provenance;
+
+/// Insults and threats typical of pile-ons; ordinary complaints ("bad", "broken") are
+/// deliberately absent so negative sentiment alone doesn't read as abuse.
+const DEFAULT_LEXICON: [&str; 16] = [
+    "idiot",
+    "moron",
+    "stupid",
+    "scum",
+    "loser",
+    "pathetic",
+    "worthless",
+    "disgusting",
+    "shut up",
+    "go die",
+    "kill yourself",
+    "kys",
+    "hope you die",
+    "piece of trash",
+    "we know where you",
+    "dox",
+];
+
+/// Scores how abusive a cluster is, from 0 to 1, so harassment campaigns can be told apart
+/// from ordinary negative sentiment. `TOXICITY_SCORING=llm` asks the LLM provider and falls
+/// back to the lexicon; `lexicon` only uses the lexicon, which `TOXICITY_LEXICON_FILE`
+/// (one term per line) replaces.
+pub struct ToxicityScorer {
+    use_llm: bool,
+    lexicon: Vec<String>,
+}
+
+impl ToxicityScorer {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Option<Self>> {
+        let use_llm = match settings.toxicity_scoring.as_deref() {
+            None => return Ok(None),
+            Some("llm") => true,
+            Some("lexicon") => false,
+            Some(other) => bail!("unknown TOXICITY_SCORING mode: {other}"),
+        };
+        let lexicon = match &settings.toxicity_lexicon_file {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("read toxicity lexicon from {path}"))?
+                .lines()
+                .map(|term| term.trim().to_lowercase())
+                .filter(|term| !term.is_empty() && !term.starts_with('#'))
+                .collect(),
+            None => DEFAULT_LEXICON.iter().map(|term| term.to_string()).collect(),
+        };
+        Ok(Some(Self { use_llm, lexicon }))
+    }
+
+    /// Share of texts containing a lexicon term.
+    pub fn lexicon_score(&self, texts: &[String]) -> f32 {
+        let hits = texts
+            .iter()
+            .map(|text| text.to_lowercase())
+            .filter(|text| self.lexicon.iter().any(|term| text.contains(term.as_str())))
+            .count();
+        hits as f32 / texts.len().max(1) as f32
+    }
+
+    pub async fn score(
+        &self,
+        llm: &InstrumentedLlmAdapter,
+        job: &ProcessingContext,
+        texts: &[String],
+        provenance: &mut Provenance,
+    ) -> f32 {
+        if self.use_llm {
+            if let Some(score) = llm.toxicity(job, texts, provenance).await {
+                return score;
+            }
+        }
+        self.lexicon_score(texts)
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Settings;
+use crate::redis_client::RedisClient;
+use crate::types::SentimentTrend;
+
+pub struct SentimentTrendTracker {
+    redis: RedisClient,
+    settings: Arc<Settings>,
+}
+
+impl SentimentTrendTracker {
+    pub fn new(redis: RedisClient, settings: Arc<Settings>) -> Self {
+        Self { redis, settings }
+    }
+
+    pub async fn update(
+        &self,
+        brand: &str,
+        chunk_id: &str,
+        timestamp: i64,
+        score: f32,
+    ) -> anyhow::Result<SentimentTrend> {
+        let key = format!("{}:{}:sentiment", self.settings.redis_trend_prefix, brand);
+        let window_secs = i64::from(self.settings.sentiment_trend_window_days) * 86_400;
+        let window_start = timestamp - window_secs;
+
+        let history: Vec<f32> = self
+            .redis
+            .zrange_by_score(&key, window_start, timestamp)
+            .await?
+            .iter()
+            .filter_map(|member| member.rsplit(':').next()?.parse::<f32>().ok())
+            .collect();
+
+        let baseline_score = if history.is_empty() {
+            score
+        } else {
+            history.iter().sum::<f32>() / history.len() as f32
+        };
+        let delta = score - baseline_score;
+        let threshold = self.settings.sentiment_trend_flat_threshold;
+        let direction = if history.is_empty() || delta.abs() < threshold {
+            "flat"
+        } else if delta > 0.0 {
+            "up"
+        } else {
+            "down"
+        };
+
+        let ttl = Duration::from_secs((window_secs * 2) as u64);
+        self.redis
+            .zadd_with_ttl(&key, timestamp, &format!("{chunk_id}:{score}"), ttl)
+            .await?;
+        self.redis.zrem_range_by_score(&key, 0, window_start - 1).await?;
+
+        Ok(SentimentTrend {
+            window_days: self.settings.sentiment_trend_window_days,
+            samples: history.len(),
+            baseline_score,
+            delta,
+            direction: direction.to_string(),
+        })
+    }
+}
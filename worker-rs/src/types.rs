@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::model_overrides::ModelOverride;
+use crate::qa::QaArtifacts;
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Mention {
@@ -24,6 +27,22 @@ pub struct ChunkMeta {
     pub chunk_index: Option<i32>,
     #[serde(default)]
     pub total_chunks: Option<i32>,
+    #[serde(default)]
+    pub enqueued_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub attempt: Option<u32>,
+    /// When the result stops being useful to the caller, e.g. a live dashboard refresh.
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+    /// Free-form traffic class such as `realtime` or `batch`, echoed in the result.
+    #[serde(default)]
+    pub priority: Option<String>,
+    /// Caller's ID for logs about this chunk.
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    /// LLM provider and model for this chunk only.
+    #[serde(default)]
+    pub llm: Option<ModelOverride>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -49,6 +68,60 @@ pub struct ChunkMetrics {
     pub spike_detection_time_ms: f64,
     pub io_time_ms: f64,
     pub total_task_time_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_wait_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e2e_latency_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cross_chunk_duplicates: Option<usize>,
+    /// Mentions with media but no text, captioned or not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_mentions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub irrelevant_dropped: Option<usize>,
+    /// Mentions the WASM preprocessing hook dropped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_dropped: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub irrelevant_downweighted: Option<usize>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub stage_times_ms: BTreeMap<String, f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_stages: Vec<String>,
+}
+
+/// Which providers and algorithms produced a result, so output from degraded periods
+/// (hashed embeddings, heuristic sentiment) can be filtered out or re-run later.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub embedding_provider: Option<String>,
+    pub embedding_model: Option<String>,
+    pub clustering_algorithm: Option<String>,
+    pub clustering_params: Option<serde_json::Value>,
+    pub llm_provider: Option<String>,
+    pub llm_model: Option<String>,
+    /// Name of the `PIPELINE_ROUTES_FILE` route the chunk took; absent on the live pipeline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// `operation:reason` for every call served by a fallback, e.g. `embed:error`.
+    pub fallbacks: BTreeSet<String>,
+    /// Fallback calls recorded so far, repeats included, so a caller can tell whether the
+    /// calls it just made fell back.
+    #[serde(skip)]
+    pub fallback_calls: usize,
+}
+
+impl Provenance {
+    pub fn record_fallback(&mut self, operation: &str, reason: &str) {
+        self.fallbacks.insert(format!("{operation}:{reason}"));
+        self.fallback_calls += 1;
+    }
+
+    /// Whether any part of the result came from a fallback rather than the configured path.
+    pub fn degraded(&self) -> bool {
+        !self.fallbacks.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -62,6 +135,187 @@ pub struct ClusterResult {
     pub sentiment: HashMap<String, f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub topics: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurring_of: Option<ClusterReference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling_rate: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_event: Option<KnownEvent>,
+    /// Identifies the ongoing narrative across chunks; inherited from the recurring cluster.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stable_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// `premium` or `cheap` when LLM tiering is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_tier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emotions: Option<HashMap<String, f32>>,
+    /// Star ratings from review-site mentions, already blended into `sentiment`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<RatingSummary>,
+    /// 0–1 abuse score, present when `TOXICITY_SCORING` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toxicity: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<ClusterEntities>,
+    /// Mean probability that the cluster's mentions are about the brand.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<f32>,
+    /// One of `complaint`, `praise`, `question`, `churn_risk` or `other`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    /// Verbatim mentions the LLM picked as most representative of the cluster.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quotes: Option<Vec<String>>,
+    /// Present when `CONFIDENCE_MODE` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    /// 0–100 reputation risk, present when `SEVERITY_SCORING` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<u8>,
+    /// Present when any of the cluster's mentions carries engagement counts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engagement: Option<Engagement>,
+    /// Mentions per product, sub-brand or brand, present when the taxonomy stage ran.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub products: Option<HashMap<String, usize>>,
+    /// Present when most of the cluster's vocabulary is new for the brand.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emerging: Option<EmergingNarrative>,
+}
+
+/// A cluster whose top n-grams were mostly absent from the brand's recent history.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmergingNarrative {
+    /// Share of the cluster's top n-grams not seen in the novelty window.
+    pub novelty: f32,
+    pub terms: Vec<String>,
+}
+
+/// How far the LLM's summary and sentiment for a cluster can be trusted, each from 0 to 1.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Confidence {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f32>,
+}
+
+impl Confidence {
+    /// The lower of the two scores, when either is known.
+    pub fn min(&self) -> Option<f32> {
+        match (self.summary, self.sentiment) {
+            (Some(summary), Some(sentiment)) => Some(summary.min(sentiment)),
+            (summary, sentiment) => summary.or(sentiment),
+        }
+    }
+}
+
+/// Named entities the LLM found in a cluster's mentions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterEntities {
+    #[serde(default)]
+    pub people: Vec<String>,
+    #[serde(default)]
+    pub products: Vec<String>,
+    #[serde(default)]
+    pub competitors: Vec<String>,
+    #[serde(default)]
+    pub locations: Vec<String>,
+}
+
+impl ClusterEntities {
+    pub fn is_empty(&self) -> bool {
+        self.people.is_empty() && self.products.is_empty() && self.competitors.is_empty() && self.locations.is_empty()
+    }
+}
+
+/// Star ratings carried by review-site mentions.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingSummary {
+    pub average: f32,
+    pub count: usize,
+}
+
+impl RatingSummary {
+    pub fn from_ratings(ratings: &[f32]) -> Option<Self> {
+        (!ratings.is_empty()).then(|| Self {
+            average: ratings.iter().sum::<f32>() / ratings.len() as f32,
+            count: ratings.len(),
+        })
+    }
+
+    /// Count-weighted combination of several summaries.
+    pub fn combine<'a>(summaries: impl IntoIterator<Item = &'a RatingSummary>) -> Option<Self> {
+        let (total, count) = summaries
+            .into_iter()
+            .fold((0.0, 0), |(total, count), summary| {
+                (total + summary.average * summary.count as f32, count + summary.count)
+            });
+        (count > 0).then(|| Self {
+            average: total / count as f32,
+            count,
+        })
+    }
+}
+
+/// Likes, shares and comments summed over a cluster's mentions.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Engagement {
+    pub likes: u64,
+    pub shares: u64,
+    pub comments: u64,
+    pub total: u64,
+}
+
+impl Engagement {
+    pub fn new(likes: u64, shares: u64, comments: u64) -> Self {
+        Self {
+            likes,
+            shares,
+            comments,
+            total: likes.saturating_add(shares).saturating_add(comments),
+        }
+    }
+
+    /// Sum of several counts; `None` when there are none.
+    pub fn combine(counts: impl IntoIterator<Item = Engagement>) -> Option<Self> {
+        counts.into_iter().reduce(|sum, count| {
+            Self::new(
+                sum.likes.saturating_add(count.likes),
+                sum.shares.saturating_add(count.shares),
+                sum.comments.saturating_add(count.comments),
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownEvent {
+    pub name: String,
+    pub downgrade: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterReference {
+    pub chunk_id: String,
+    pub cluster_id: i32,
+    pub similarity: f32,
+}
+
+impl ClusterResult {
+    pub fn sentiment_score(&self) -> f32 {
+        self.sentiment.get("positive").copied().unwrap_or_default()
+            - self.sentiment.get("negative").copied().unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -72,14 +326,152 @@ pub struct ChunkResult {
     pub timestamp: i64,
     pub clusters: Vec<ClusterResult>,
     pub metrics: ChunkMetrics,
+    pub provenance: Provenance,
+    #[serde(skip)]
+    pub enqueued_at: Option<DateTime<Utc>>,
+    /// Set when the chunk fell in the `QA_SAMPLE_PERCENT` sample.
+    #[serde(skip)]
+    pub qa: Option<QaArtifacts>,
+    /// The chunk's deadline passed before processing finished.
+    pub expired: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
 }
 
+impl ChunkResult {
+    /// Mean sentiment score over the clusters that carry sentiment, the `sentiment.score`
+    /// of the orchestrator payload; `None` when no cluster does.
+    pub fn sentiment_score(&self) -> Option<f32> {
+        let scores: Vec<f32> = self
+            .clusters
+            .iter()
+            .filter(|cluster| !cluster.sentiment.is_empty())
+            .map(ClusterResult::sentiment_score)
+            .collect();
+        (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32)
+    }
+}
+
+/// Bumped whenever a field of [`FailureRecord`] is renamed, removed, or changes meaning.
+pub const FAILURE_RECORD_SCHEMA_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FailureRecord {
+    pub schema_version: u32,
     pub worker_id: String,
     pub brand: String,
     pub chunk_id: String,
     pub reason: String,
+    pub reason_code: String,
+    pub error_kind: String,
+    pub error_chain: Vec<String>,
+    pub stage: Option<String>,
+    /// Whether requeueing the payload could succeed; false for malformed payloads and bad
+    /// configuration.
+    pub retryable: bool,
+    pub attempt: u32,
+    pub failed_at: DateTime<Utc>,
+    pub payload_bytes: usize,
+    pub payload_preview: String,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillRequest {
+    pub brand: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
+/// Queue payload with `"type": "reprocess"`: re-runs an archived chunk instead of
+/// carrying mentions. `createdAt` narrows the archive lookup to one timestamp.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReprocessRequest {
+    #[serde(rename = "chunkId")]
+    pub chunk_id: String,
+    #[serde(default)]
+    pub brand: String,
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillReport {
+    pub brand: String,
+    pub archived: usize,
+    pub processed: usize,
+    pub failed: usize,
+    pub result_prefix: String,
+}
+
+/// What the worker would make of one queued payload, without consuming it.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadInspection {
+    /// Zero-based distance from the head of the queue.
+    pub position: usize,
     pub payload: String,
+    pub valid: bool,
+    /// Failure reason the payload would be recorded under, matching the failed list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// `chunk` or `reprocess`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mention_count: Option<usize>,
+    /// Problems that don't fail the payload but likely surprise its producer.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowComparison {
+    pub primary_cluster_count: usize,
+    pub shadow_cluster_count: usize,
+    pub cluster_count_delta: i64,
+    pub primary_sentiment_score: f32,
+    pub shadow_sentiment_score: f32,
+    pub sentiment_delta: f32,
+}
+
+/// A topic's mention-weighted count this week and the week before.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicMovement {
+    pub topic: String,
+    pub current: f64,
+    pub previous: f64,
+    /// Relative change from the previous week; `None` for a new topic.
+    pub change: Option<f64>,
+}
+
+/// Week-over-week topic movement for a brand, up to the chunk's day.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicTrend {
+    pub new: Vec<TopicMovement>,
+    pub rising: Vec<TopicMovement>,
+    pub falling: Vec<TopicMovement>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SentimentTrend {
+    pub window_days: u32,
+    pub samples: usize,
+    pub baseline_score: f32,
+    pub delta: f32,
+    pub direction: String,
 }
@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::clustering::DistanceMetric;
+use crate::config::Settings;
+use crate::error::{WorkerError, WorkerResult};
+use crate::http::HttpClient;
+
+/// One vector to upsert. `key` identifies it across chunks, so re-exporting the same
+/// mention or cluster overwrites the earlier point.
+pub struct VectorPoint {
+    pub key: String,
+    pub kind: &'static str,
+    pub vector: Vec<f32>,
+    pub payload: serde_json::Value,
+}
+
+/// Per-brand store of cluster centroids and mention embeddings, for analysts' semantic
+/// search over historical mentions.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    fn name(&self) -> &str;
+    async fn upsert(&self, brand: &str, points: &[VectorPoint]) -> WorkerResult<()>;
+}
+
+/// `None` when `VECTOR_STORE=off`.
+pub fn build_vector_store(settings: &Settings, http: &HttpClient) -> anyhow::Result<Option<Arc<dyn VectorStore>>> {
+    Ok(match settings.vector_store.as_str() {
+        "qdrant" => Some(Arc::new(QdrantStore::new(settings, http.clone())?)),
+        "pgvector" => Some(Arc::new(PgVectorStore::new(settings)?)),
+        _ => None,
+    })
+}
+
+/// `<prefix>_<brand>`, with anything but ASCII letters, digits and `_` replaced, so it is
+/// a valid Qdrant collection and Postgres table name.
+fn collection_name(prefix: &str, brand: &str) -> String {
+    format!("{prefix}_{brand}")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+/// Qdrant collections, created on first use with the dimension of the first vector
+/// written and the `DISTANCE_METRIC` distance. A collection keeps both, so switching
+/// embedding models or metrics needs a new `VECTOR_STORE_COLLECTION_PREFIX`.
+pub struct QdrantStore {
+    http: HttpClient,
+    base_url: String,
+    api_key: Option<String>,
+    prefix: String,
+    metric: DistanceMetric,
+    /// Collections known to exist.
+    ready: Mutex<HashSet<String>>,
+}
+
+#[derive(Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+}
+
+#[derive(Deserialize)]
+struct QdrantExists {
+    exists: bool,
+}
+
+impl QdrantStore {
+    pub fn new(settings: &Settings, http: HttpClient) -> anyhow::Result<Self> {
+        let base_url = settings
+            .vector_store_url
+            .clone()
+            .context("VECTOR_STORE_URL is required for VECTOR_STORE=qdrant")?;
+        Ok(Self {
+            http,
+            base_url,
+            api_key: settings.vector_store_api_key.clone(),
+            prefix: settings.vector_store_collection_prefix.clone(),
+            metric: DistanceMetric::from_settings(settings),
+            ready: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.header("api-key", key),
+            None => request,
+        }
+    }
+
+    async fn ensure_collection(&self, collection: &str, dimension: usize) -> anyhow::Result<()> {
+        let mut ready = self.ready.lock().await;
+        if ready.contains(collection) {
+            return Ok(());
+        }
+        let url = format!("{}/collections/{collection}", self.base_url);
+        let exists: QdrantResponse<QdrantExists> = self
+            .http
+            .send(self.authorize(self.http.get(&format!("{url}/exists"))), "Qdrant collection check")
+            .await?
+            .json()
+            .await
+            .context("decode Qdrant collection check response")?;
+        if !exists.result.exists {
+            let distance = match self.metric {
+                DistanceMetric::Cosine => "Cosine",
+                DistanceMetric::Euclidean => "Euclid",
+                DistanceMetric::Dot => "Dot",
+            };
+            let body = json!({ "vectors": { "size": dimension, "distance": distance } });
+            let request = self.authorize(self.http.put(&url).json(&body));
+            self.http.send(request, "Qdrant collection create").await?;
+            let index = json!({ "field_name": "kind", "field_schema": "keyword" });
+            let request = self.authorize(self.http.put(&format!("{url}/index")).json(&index));
+            self.http.send(request, "Qdrant payload index").await?;
+        }
+        ready.insert(collection.to_string());
+        Ok(())
+    }
+
+    async fn upsert_points(&self, brand: &str, points: &[VectorPoint]) -> anyhow::Result<()> {
+        let Some(dimension) = points.first().map(|point| point.vector.len()) else {
+            return Ok(());
+        };
+        let collection = collection_name(&self.prefix, brand);
+        self.ensure_collection(&collection, dimension).await?;
+        let points: Vec<serde_json::Value> = points
+            .iter()
+            .map(|point| {
+                let mut payload = point.payload.clone();
+                if let Some(fields) = payload.as_object_mut() {
+                    fields.insert("key".to_string(), json!(point.key));
+                    fields.insert("kind".to_string(), json!(point.kind));
+                }
+                json!({ "id": point_id(&point.key), "vector": point.vector, "payload": payload })
+            })
+            .collect();
+        let url = format!("{}/collections/{collection}/points?wait=true", self.base_url);
+        let request = self.authorize(self.http.put(&url).json(&json!({ "points": points })));
+        self.http.send(request, "Qdrant upsert").await?;
+        Ok(())
+    }
+}
+
+/// Qdrant point IDs must be integers or UUIDs; keys are hashed into a UUID.
+fn point_id(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut bytes = [0_u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Uuid::from_bytes(bytes).to_string()
+}
+
+#[async_trait]
+impl VectorStore for QdrantStore {
+    fn name(&self) -> &str {
+        "qdrant"
+    }
+
+    async fn upsert(&self, brand: &str, points: &[VectorPoint]) -> WorkerResult<()> {
+        self.upsert_points(brand, points).await.map_err(WorkerError::Storage)
+    }
+}
+
+/// Postgres tables with a pgvector `vector` column and an HNSW index for `DISTANCE_METRIC`,
+/// created on first use along with the `vector` extension. Needs the `pgvector` build
+/// feature. As with Qdrant, a table keeps the dimension of the first vector written.
+pub struct PgVectorStore {
+    #[cfg(feature = "pgvector")]
+    url: String,
+    #[cfg(feature = "pgvector")]
+    prefix: String,
+    #[cfg(feature = "pgvector")]
+    metric: DistanceMetric,
+    /// Reconnected on the next call once the connection drops.
+    #[cfg(feature = "pgvector")]
+    client: Mutex<Option<tokio_postgres::Client>>,
+    #[cfg(feature = "pgvector")]
+    ready: Mutex<HashSet<String>>,
+    #[cfg(not(feature = "pgvector"))]
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "pgvector")]
+impl PgVectorStore {
+    pub fn new(settings: &Settings) -> anyhow::Result<Self> {
+        let url = settings
+            .vector_store_url
+            .clone()
+            .context("VECTOR_STORE_URL is required for VECTOR_STORE=pgvector")?;
+        Ok(Self {
+            url,
+            prefix: settings.vector_store_collection_prefix.clone(),
+            metric: DistanceMetric::from_settings(settings),
+            client: Mutex::new(None),
+            ready: Mutex::new(HashSet::new()),
+        })
+    }
+
+    async fn client(&self) -> anyhow::Result<tokio::sync::MappedMutexGuard<'_, tokio_postgres::Client>> {
+        let mut client = self.client.lock().await;
+        if client.as_ref().is_none_or(|client| client.is_closed()) {
+            let (connected, connection) = tokio_postgres::connect(&self.url, tokio_postgres::NoTls)
+                .await
+                .context("connect to pgvector store")?;
+            tokio::spawn(async move {
+                if let Err(err) = connection.await {
+                    tracing::warn!(error = %err, "pgvector connection closed");
+                }
+            });
+            *client = Some(connected);
+        }
+        Ok(tokio::sync::MutexGuard::map(client, |client| {
+            client.as_mut().expect("pgvector client connected above")
+        }))
+    }
+
+    async fn ensure_table(&self, client: &tokio_postgres::Client, table: &str, dimension: usize) -> anyhow::Result<()> {
+        let mut ready = self.ready.lock().await;
+        if ready.contains(table) {
+            return Ok(());
+        }
+        let operators = match self.metric {
+            DistanceMetric::Cosine => "vector_cosine_ops",
+            DistanceMetric::Euclidean => "vector_l2_ops",
+            DistanceMetric::Dot => "vector_ip_ops",
+        };
+        let statements = format!(
+            "CREATE EXTENSION IF NOT EXISTS vector;
+             CREATE TABLE IF NOT EXISTS {table} (
+                 key TEXT PRIMARY KEY,
+                 kind TEXT NOT NULL,
+                 embedding vector({dimension}) NOT NULL,
+                 payload JSONB NOT NULL,
+                 updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+             );
+             CREATE INDEX IF NOT EXISTS {table}_embedding_idx ON {table} USING hnsw (embedding {operators});"
+        );
+        client
+            .batch_execute(&statements)
+            .await
+            .with_context(|| format!("create pgvector table {table}"))?;
+        ready.insert(table.to_string());
+        Ok(())
+    }
+
+    async fn upsert_points(&self, brand: &str, points: &[VectorPoint]) -> anyhow::Result<()> {
+        let Some(dimension) = points.first().map(|point| point.vector.len()) else {
+            return Ok(());
+        };
+        let table = collection_name(&self.prefix, brand);
+        let mut client = self.client().await?;
+        self.ensure_table(&client, &table, dimension).await?;
+        let transaction = client.transaction().await.context("begin pgvector upsert")?;
+        let statement = transaction
+            .prepare(&format!(
+                "INSERT INTO {table} (key, kind, embedding, payload) VALUES ($1, $2, $3::text::vector, $4::text::jsonb)
+                 ON CONFLICT (key) DO UPDATE
+                 SET kind = EXCLUDED.kind, embedding = EXCLUDED.embedding, payload = EXCLUDED.payload, updated_at = now()"
+            ))
+            .await
+            .context("prepare pgvector upsert")?;
+        for point in points {
+            let vector = vector_literal(&point.vector);
+            let payload = point.payload.to_string();
+            transaction
+                .execute(&statement, &[&point.key, &point.kind, &vector, &payload])
+                .await
+                .context("pgvector upsert")?;
+        }
+        transaction.commit().await.context("commit pgvector upsert")?;
+        Ok(())
+    }
+}
+
+/// pgvector's text form, `[x,y,...]`.
+#[cfg(feature = "pgvector")]
+fn vector_literal(vector: &[f32]) -> String {
+    let values: Vec<String> = vector.iter().map(f32::to_string).collect();
+    format!("[{}]", values.join(","))
+}
+
+#[cfg(feature = "pgvector")]
+#[async_trait]
+impl VectorStore for PgVectorStore {
+    fn name(&self) -> &str {
+        "pgvector"
+    }
+
+    async fn upsert(&self, brand: &str, points: &[VectorPoint]) -> WorkerResult<()> {
+        self.upsert_points(brand, points).await.map_err(WorkerError::Storage)
+    }
+}
+
+#[cfg(not(feature = "pgvector"))]
+impl PgVectorStore {
+    pub fn new(_settings: &Settings) -> anyhow::Result<Self> {
+        anyhow::bail!("VECTOR_STORE=pgvector needs a worker built with the pgvector feature")
+    }
+}
+
+#[cfg(not(feature = "pgvector"))]
+#[async_trait]
+impl VectorStore for PgVectorStore {
+    fn name(&self) -> &str {
+        match self.never {}
+    }
+
+    async fn upsert(&self, _brand: &str, _points: &[VectorPoint]) -> WorkerResult<()> {
+        match self.never {}
+    }
+}
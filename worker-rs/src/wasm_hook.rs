@@ -0,0 +1,145 @@
+use crate::config::Settings;
+use crate::types::Mention;
+
+/// Operator-supplied WASM module from `PREPROCESS_WASM_HOOK` that filters or rewrites
+/// mentions before cleaning, for customer-specific rules such as codename or regulatory
+/// redaction. Needs the `wasm-hooks` build feature.
+///
+/// The module imports nothing and exports `memory`, `alloc(len: i32) -> i32` and
+/// `transform(ptr: i32, len: i32) -> i64`. `transform` receives the mention as UTF-8 JSON
+/// (`brand`, `id`, `text`, `metadata`) and returns the replacement text packed as
+/// `(ptr << 32) | len`, or a negative value to drop the mention. Each call may spend at
+/// most `PREPROCESS_WASM_FUEL` units of fuel.
+pub struct PreprocessHook {
+    #[cfg(feature = "wasm-hooks")]
+    engine: wasmtime::Engine,
+    #[cfg(feature = "wasm-hooks")]
+    module: wasmtime::Module,
+    #[cfg(feature = "wasm-hooks")]
+    fuel: u64,
+    #[cfg(not(feature = "wasm-hooks"))]
+    never: std::convert::Infallible,
+}
+
+/// One hook instance, reused for every mention of a chunk.
+pub struct HookSession {
+    #[cfg(feature = "wasm-hooks")]
+    store: wasmtime::Store<()>,
+    #[cfg(feature = "wasm-hooks")]
+    memory: wasmtime::Memory,
+    #[cfg(feature = "wasm-hooks")]
+    alloc: wasmtime::TypedFunc<i32, i32>,
+    #[cfg(feature = "wasm-hooks")]
+    transform: wasmtime::TypedFunc<(i32, i32), i64>,
+    #[cfg(feature = "wasm-hooks")]
+    fuel: u64,
+    #[cfg(not(feature = "wasm-hooks"))]
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "wasm-hooks")]
+fn hook_input(brand: &str, mention: &Mention) -> String {
+    serde_json::json!({
+        "brand": brand,
+        "id": mention.id,
+        "text": mention.text,
+        "metadata": mention.metadata,
+    })
+    .to_string()
+}
+
+#[cfg(feature = "wasm-hooks")]
+impl PreprocessHook {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Option<Self>> {
+        use anyhow::Context;
+
+        let Some(path) = &settings.preprocess_wasm_hook else {
+            return Ok(None);
+        };
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = wasmtime::Engine::new(&config)?;
+        let module = wasmtime::Module::from_file(&engine, path)
+            .with_context(|| format!("load preprocessing hook from {path}"))?;
+        tracing::info!(worker_id = %settings.worker_id, path, "WASM preprocessing hook loaded");
+        Ok(Some(Self {
+            engine,
+            module,
+            fuel: settings.preprocess_wasm_fuel,
+        }))
+    }
+
+    pub fn session(&self) -> anyhow::Result<HookSession> {
+        use anyhow::Context;
+
+        let mut store = wasmtime::Store::new(&self.engine, ());
+        store.set_fuel(self.fuel)?;
+        let instance = wasmtime::Instance::new(&mut store, &self.module, &[]).context("instantiate preprocessing hook")?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("preprocessing hook exports no memory")?;
+        let alloc = instance.get_typed_func(&mut store, "alloc").context("preprocessing hook alloc export")?;
+        let transform = instance
+            .get_typed_func(&mut store, "transform")
+            .context("preprocessing hook transform export")?;
+        Ok(HookSession {
+            store,
+            memory,
+            alloc,
+            transform,
+            fuel: self.fuel,
+        })
+    }
+}
+
+#[cfg(feature = "wasm-hooks")]
+impl HookSession {
+    /// The rewritten mention text, or `None` when the hook drops the mention.
+    pub fn apply(&mut self, brand: &str, mention: &Mention) -> anyhow::Result<Option<String>> {
+        use anyhow::Context;
+
+        let input = hook_input(brand, mention);
+        let len = i32::try_from(input.len()).context("mention too large for preprocessing hook")?;
+        self.store.set_fuel(self.fuel)?;
+        let ptr = self.alloc.call(&mut self.store, len).context("preprocessing hook alloc")?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input.as_bytes())
+            .context("write mention into preprocessing hook memory")?;
+        let packed = self
+            .transform
+            .call(&mut self.store, (ptr, len))
+            .with_context(|| format!("preprocessing hook failed on mention {}", mention.id))?;
+        if packed < 0 {
+            return Ok(None);
+        }
+        let (out_ptr, out_len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+        let mut output = vec![0u8; out_len];
+        self.memory
+            .read(&self.store, out_ptr, &mut output)
+            .context("read preprocessing hook output")?;
+        String::from_utf8(output)
+            .map(Some)
+            .context("preprocessing hook returned invalid UTF-8")
+    }
+}
+
+#[cfg(not(feature = "wasm-hooks"))]
+impl PreprocessHook {
+    pub fn from_settings(settings: &Settings) -> anyhow::Result<Option<Self>> {
+        match &settings.preprocess_wasm_hook {
+            Some(path) => anyhow::bail!("PREPROCESS_WASM_HOOK={path} needs a worker built with the wasm-hooks feature"),
+            None => Ok(None),
+        }
+    }
+
+    pub fn session(&self) -> anyhow::Result<HookSession> {
+        match self.never {}
+    }
+}
+
+#[cfg(not(feature = "wasm-hooks"))]
+impl HookSession {
+    pub fn apply(&mut self, _brand: &str, _mention: &Mention) -> anyhow::Result<Option<String>> {
+        match self.never {}
+    }
+}